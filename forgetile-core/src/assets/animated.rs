@@ -0,0 +1,567 @@
+//! Animated images turned into spritesheets: the frames of a GIF, an Aseprite file or an
+//! Aseprite JSON sheet are laid out in a grid, and every tile of a frame becomes an
+//! animated tile cycling through the same cell of the following frames.
+
+use crate::assets::aseprite::AsepriteFile;
+use crate::assets::sidecar::{AnimationSpec, TilesetSidecar};
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, RgbaImage, imageops};
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Frames of an animated image laid out as one spritesheet.
+pub struct AnimatedSheet {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8 pixels of every frame, left to right and then top to bottom, wrapping before
+    /// the sheet gets wider than [`MAX_TEXTURE_SIZE`].
+    pub rgba: Vec<u8>,
+    /// The image's own `.tileset.json` completed with the tile size, tile names and one
+    /// `frames` animation per animated tile. Margin and spacing are not supported and are
+    /// cleared.
+    pub sidecar: TilesetSidecar,
+    /// Indices of the tiles shown in the palette, each the first frame of its animation.
+    pub tiles: Vec<usize>,
+}
+
+/// Widest and tallest sheet built, the texture size limit of common GPUs.
+pub const MAX_TEXTURE_SIZE: u32 = 16384;
+
+/// Most pixels of all frames together, the size of the largest sheet texture.
+pub(crate) const MAX_PIXELS: u64 = MAX_TEXTURE_SIZE as u64 * MAX_TEXTURE_SIZE as u64;
+
+/// One decoded frame and how long it is shown.
+pub(crate) struct Frame {
+    pub image: RgbaImage,
+    pub duration_ms: u32,
+}
+
+/// Named run of frames, e.g. an Aseprite tag, in playback order.
+pub(crate) struct FrameTag {
+    pub name: String,
+    pub frames: Vec<usize>,
+}
+
+/// Named region of the frames, e.g. an Aseprite slice, naming the tiles it covers.
+pub(crate) struct Slice {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AnimatedSheet {
+    /// Delay used for frames that declare none, as browsers do.
+    const DEFAULT_DURATION_MS: u32 = 100;
+
+    /// Returns `true` for a GIF, an Aseprite `.ase`/`.aseprite` file and the image of an
+    /// Aseprite JSON sheet, i.e. a PNG or JPEG with an Aseprite `<image_name>.json` next
+    /// to it.
+    pub fn is_animated(path: &Path) -> bool {
+        has_extension(path, &["gif", "ase", "aseprite"]) || read_aseprite_json(path).is_some()
+    }
+
+    /// Decodes the animated image at `path`, slicing its frames into tiles of the sidecar's
+    /// tile size, else `default_tile_size`; a frame smaller than that is one tile. Returns
+    /// `Ok(None)` when `path` is not an animated image.
+    pub fn load(path: &Path, default_tile_size: (usize, usize)) -> io::Result<Option<Self>> {
+        if has_extension(path, &["ase", "aseprite"]) {
+            let file = AsepriteFile::read(&fs::read(path)?)?;
+            return Self::from_frames(
+                path,
+                file.frames,
+                file.tags,
+                file.slices,
+                default_tile_size,
+            )
+            .map(Some);
+        }
+        if has_extension(path, &["gif"]) {
+            let file = BufReader::new(fs::File::open(path)?);
+            let frames = GifDecoder::new(file)
+                .and_then(|decoder| decoder.into_frames().collect_frames())
+                .map_err(io::Error::other)?
+                .into_iter()
+                .map(|frame| {
+                    let (numerator, denominator) = frame.delay().numer_denom_ms();
+                    Frame {
+                        duration_ms: numerator / denominator.max(1),
+                        image: frame.into_buffer(),
+                    }
+                })
+                .collect();
+            return Self::from_frames(path, frames, Vec::new(), Vec::new(), default_tile_size)
+                .map(Some);
+        }
+
+        let Some(json) = read_aseprite_json(path) else {
+            return Ok(None);
+        };
+        json.validate()?;
+        let sheet = image::open(path)
+            .map_err(io::Error::other)?
+            .into_rgba8();
+        let frames = json
+            .frames
+            .0
+            .iter()
+            .map(|frame| frame.crop(&sheet))
+            .collect();
+        let tags = json
+            .meta
+            .frame_tags
+            .iter()
+            .map(AsepriteTag::to_frame_tag)
+            .collect();
+        let slices = json
+            .meta
+            .slices
+            .iter()
+            .filter_map(AsepriteSlice::to_slice)
+            .collect();
+        Self::from_frames(path, frames, tags, slices, default_tile_size).map(Some)
+    }
+
+    /// Lays `frames` out into a sheet. Every tile of the first frame of each tag, or of the
+    /// first frame when there is no tag, cycles through its cell in the tag's frames. Tiles
+    /// lying inside one of `slices` are named after it.
+    pub(crate) fn from_frames(
+        path: &Path, frames: Vec<Frame>, mut tags: Vec<FrameTag>, slices: Vec<Slice>,
+        default_tile_size: (usize, usize),
+    ) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let first = frames
+            .first()
+            .ok_or_else(|| invalid("the image has no frame"))?;
+        let (frame_width, frame_height) = (first.image.width(), first.image.height());
+
+        let mut sidecar = TilesetSidecar::load_for(path);
+        let (mut tile_width, mut tile_height) = sidecar
+            .tile_size()
+            .unwrap_or(default_tile_size);
+        if tile_width == 0 || tile_height == 0 {
+            return Err(invalid("the tile size is zero"));
+        }
+        if (frame_width as usize) < tile_width || (frame_height as usize) < tile_height {
+            (tile_width, tile_height) = (frame_width as usize, frame_height as usize);
+        }
+        sidecar.tile_width = Some(tile_width);
+        sidecar.tile_height = Some(tile_height);
+        sidecar.margin = 0;
+        sidecar.spacing = 0;
+
+        let columns = frame_width as usize / tile_width;
+        let rows = frame_height as usize / tile_height;
+        if columns == 0 || rows == 0 {
+            return Err(invalid("the frames are smaller than one tile"));
+        }
+        let tiles_per_frame = columns * rows;
+        let grid = FrameGrid::new(frames.len(), columns, rows, (tile_width, tile_height))
+            .ok_or_else(|| invalid("too many frames to fit in one texture"))?;
+        let (used_width, used_height) =
+            ((columns * tile_width) as u32, (rows * tile_height) as u32);
+        let mut sheet = RgbaImage::new(grid.width, grid.height);
+        for (index, frame) in frames.iter().enumerate() {
+            let mut image = frame.image.clone();
+            if image.dimensions() != (used_width, used_height) {
+                image = imageops::crop_imm(&image, 0, 0, used_width, used_height).to_image();
+            }
+            let (x, y) = grid.frame_origin(index);
+            imageops::replace(&mut sheet, &image, i64::from(x), i64::from(y));
+        }
+
+        tags.retain_mut(|tag| {
+            tag.frames
+                .retain(|&frame| frame < frames.len());
+            !tag.frames.is_empty()
+        });
+        if tags.is_empty() {
+            tags.push(FrameTag {
+                name: String::new(),
+                frames: (0..frames.len()).collect(),
+            });
+        }
+
+        let stem = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let slice_names = slice_names(&slices, columns, rows, (tile_width, tile_height));
+        let mut tiles = Vec::new();
+        let mut taken = HashSet::new();
+        for tag in &tags {
+            if !taken.insert(tag.frames[0]) {
+                eprintln!(
+                    "[assets] Ignoring tag {:?} of {:?}: another tag starts on the same frame",
+                    tag.name, path
+                );
+                continue;
+            }
+            let total_ms: u32 = tag
+                .frames
+                .iter()
+                .map(|&frame| match frames[frame].duration_ms {
+                    0 => Self::DEFAULT_DURATION_MS,
+                    duration => duration,
+                })
+                .sum();
+            let fps = 1000.0 * tag.frames.len() as f32 / total_ms.max(1) as f32;
+            for (cell, slice_name) in slice_names.iter().enumerate() {
+                let index = grid.tile_index(tag.frames[0], cell);
+                tiles.push(index);
+                let key = index.to_string();
+                let name = match (slice_name, tag.name.as_str()) {
+                    (Some(slice), "") => Some(slice.clone()),
+                    (Some(slice), tag) => Some(format!("{slice}_{tag}")),
+                    (None, "") => None,
+                    (None, tag) if tiles_per_frame == 1 => Some(format!("{stem}_{tag}")),
+                    (None, tag) => Some(format!("{stem}_{tag}_{cell:02}")),
+                };
+                if let Some(name) = name {
+                    sidecar
+                        .names
+                        .entry(key.clone())
+                        .or_insert(name);
+                }
+                if tag.frames.len() > 1 {
+                    let frames = tag
+                        .frames
+                        .iter()
+                        .map(|&frame| grid.tile_index(frame, cell))
+                        .collect();
+                    sidecar
+                        .animations
+                        .entry(key)
+                        .or_insert(AnimationSpec::Frames { frames, fps });
+                }
+            }
+        }
+
+        Ok(Self {
+            width: sheet.width(),
+            height: sheet.height(),
+            rgba: sheet.into_raw(),
+            sidecar,
+            tiles,
+        })
+    }
+}
+
+/// Placement of the frames of an animated image in its sheet.
+struct FrameGrid {
+    /// Frames side by side in one row of the sheet.
+    frames_per_row: usize,
+    /// Tiles of one frame, per row and per column.
+    columns: usize,
+    rows: usize,
+    tile_size: (usize, usize),
+    width: u32,
+    height: u32,
+}
+
+impl FrameGrid {
+    /// Grid of `frame_count` frames of `columns` x `rows` tiles, or `None` when the sheet
+    /// would exceed [`MAX_TEXTURE_SIZE`].
+    fn new(
+        frame_count: usize, columns: usize, rows: usize, tile_size: (usize, usize),
+    ) -> Option<Self> {
+        let max = MAX_TEXTURE_SIZE as usize;
+        let frame_width = columns.checked_mul(tile_size.0)?;
+        let frame_height = rows.checked_mul(tile_size.1)?;
+        if frame_width > max || frame_height > max {
+            return None;
+        }
+        let frames_per_row = (max / frame_width)
+            .min(frame_count)
+            .max(1);
+        let width = frames_per_row.checked_mul(frame_width)?;
+        let height = frame_count
+            .div_ceil(frames_per_row)
+            .checked_mul(frame_height)?;
+        if height > max {
+            return None;
+        }
+        Some(Self {
+            frames_per_row,
+            columns,
+            rows,
+            tile_size,
+            width: u32::try_from(width).ok()?,
+            height: u32::try_from(height).ok()?,
+        })
+    }
+
+    /// Top-left pixel of `frame` in the sheet.
+    fn frame_origin(&self, frame: usize) -> (u32, u32) {
+        let x = (frame % self.frames_per_row) * self.columns * self.tile_size.0;
+        let y = (frame / self.frames_per_row) * self.rows * self.tile_size.1;
+        (x as u32, y as u32)
+    }
+
+    /// Index in the sheet of the tile at `cell` of `frame`.
+    fn tile_index(&self, frame: usize, cell: usize) -> usize {
+        let sheet_columns = self.frames_per_row * self.columns;
+        let row = (frame / self.frames_per_row) * self.rows + cell / self.columns;
+        let column = (frame % self.frames_per_row) * self.columns + cell % self.columns;
+        row * sheet_columns + column
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|extension| ext.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Name of every cell of a frame from the slice covering it whole, if any: the slice's
+/// name, followed by `_<n>` when the slice covers several cells. The first slice wins.
+fn slice_names(
+    slices: &[Slice], columns: usize, rows: usize, (tile_width, tile_height): (usize, usize),
+) -> Vec<Option<String>> {
+    let mut names = vec![None; columns * rows];
+    for slice in slices {
+        let (left, top) = (i64::from(slice.x), i64::from(slice.y));
+        let (right, bottom) = (left + i64::from(slice.width), top + i64::from(slice.height));
+        let covered: Vec<usize> = (0..columns * rows)
+            .filter(|&cell| {
+                let x = ((cell % columns) * tile_width) as i64;
+                let y = ((cell / columns) * tile_height) as i64;
+                x >= left
+                    && y >= top
+                    && x + tile_width as i64 <= right
+                    && y + tile_height as i64 <= bottom
+            })
+            .collect();
+        for (position, &cell) in covered.iter().enumerate() {
+            if names[cell].is_none() {
+                names[cell] = Some(match covered.len() {
+                    1 => slice.name.clone(),
+                    _ => format!("{}_{position:02}", slice.name),
+                });
+            }
+        }
+    }
+    names
+}
+
+/// Aseprite JSON data next to the image at `path`, if any.
+fn read_aseprite_json(path: &Path) -> Option<AsepriteJson> {
+    let data = fs::read_to_string(aseprite_json_path(path)?).ok()?;
+    serde_json::from_str::<AsepriteJson>(&data)
+        .ok()
+        .filter(|json| json.meta.app.contains("aseprite"))
+}
+
+/// `<image_name>.json`, where Aseprite writes the data of an exported sheet.
+fn aseprite_json_path(path: &Path) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let json = path.with_file_name(format!("{stem}.json"));
+    json.is_file().then_some(json)
+}
+
+/// Data file Aseprite writes along an exported sprite sheet.
+#[derive(Deserialize)]
+struct AsepriteJson {
+    frames: AsepriteFrames,
+    meta: AsepriteMeta,
+}
+
+impl AsepriteJson {
+    /// Checks the frame sizes and tag ranges before any frame is allocated, as the data
+    /// comes from an untrusted file.
+    fn validate(&self) -> io::Result<()> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let frames = &self.frames.0;
+        let mut pixels: u64 = 0;
+        for frame in frames {
+            let AsepriteSize { w, h } = frame.source_size;
+            if w > MAX_TEXTURE_SIZE || h > MAX_TEXTURE_SIZE {
+                return Err(invalid("a frame is larger than the texture size limit"));
+            }
+            pixels += u64::from(w.max(1)) * u64::from(h.max(1));
+            if pixels > MAX_PIXELS {
+                return Err(invalid("the sprite is too large"));
+            }
+        }
+        let in_range = |frame: usize| frame < frames.len();
+        if self
+            .meta
+            .frame_tags
+            .iter()
+            .any(|tag| !in_range(tag.from) || !in_range(tag.to))
+        {
+            return Err(invalid("a frame tag points past the last frame"));
+        }
+        Ok(())
+    }
+}
+
+/// Frames of the sheet in animation order, written either as an array or as an object
+/// keyed by frame file name.
+struct AsepriteFrames(Vec<AsepriteFrame>);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteFrame {
+    /// Region of the sheet holding the frame, without its trimmed transparent border.
+    frame: AsepriteRect,
+    /// Where that region goes inside the untrimmed frame.
+    sprite_source_size: AsepriteRect,
+    source_size: AsepriteSize,
+    #[serde(default)]
+    duration: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    #[serde(default)]
+    app: String,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+    #[serde(default)]
+    slices: Vec<AsepriteSlice>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSlice {
+    name: String,
+    #[serde(default)]
+    keys: Vec<AsepriteSliceKey>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSliceKey {
+    frame: usize,
+    bounds: AsepriteRect,
+}
+
+impl AsepriteFrame {
+    /// The untrimmed frame cut out of `sheet`.
+    fn crop(&self, sheet: &RgbaImage) -> Frame {
+        let mut image = RgbaImage::new(self.source_size.w.max(1), self.source_size.h.max(1));
+        let region =
+            imageops::crop_imm(sheet, self.frame.x, self.frame.y, self.frame.w, self.frame.h);
+        imageops::replace(
+            &mut image,
+            &*region,
+            i64::from(self.sprite_source_size.x),
+            i64::from(self.sprite_source_size.y),
+        );
+        Frame { image, duration_ms: self.duration }
+    }
+}
+
+impl AsepriteTag {
+    fn to_frame_tag(&self) -> FrameTag {
+        FrameTag {
+            name: self.name.clone(),
+            frames: playback_order(self.from, self.to, &self.direction),
+        }
+    }
+}
+
+impl AsepriteSlice {
+    /// The slice's bounds on the first frame it has a key for.
+    fn to_slice(&self) -> Option<Slice> {
+        let key = self
+            .keys
+            .iter()
+            .min_by_key(|key| key.frame)?;
+        Some(Slice {
+            name: self.name.clone(),
+            x: key.bounds.x as i32,
+            y: key.bounds.y as i32,
+            width: key.bounds.w,
+            height: key.bounds.h,
+        })
+    }
+}
+
+/// Frames `from..=to` in the order an Aseprite tag `direction` plays them.
+pub(crate) fn playback_order(from: usize, to: usize, direction: &str) -> Vec<usize> {
+    let forward: Vec<usize> = (from.min(to)..=from.max(to)).collect();
+    // Plays the frames, then walks back without repeating either end.
+    let bounce = |frames: Vec<usize>| {
+        let back: Vec<usize> = frames[1..frames.len().saturating_sub(1).max(1)]
+            .iter()
+            .rev()
+            .copied()
+            .collect();
+        [frames, back].concat()
+    };
+    match direction {
+        "reverse" => forward.into_iter().rev().collect(),
+        "pingpong" => bounce(forward),
+        "pingpong_reverse" => bounce(forward.into_iter().rev().collect()),
+        _ => forward,
+    }
+}
+
+impl<'de> Deserialize<'de> for AsepriteFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = AsepriteFrames;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an array or a map of Aseprite frames")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self, mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(frame) = seq.next_element::<AsepriteFrame>()? {
+                    frames.push(frame);
+                }
+                Ok(AsepriteFrames(frames))
+            }
+
+            // Keeps the order of the file, which a map type would sort by name.
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some((_, frame)) = map.next_entry::<String, AsepriteFrame>()? {
+                    frames.push(frame);
+                }
+                Ok(AsepriteFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
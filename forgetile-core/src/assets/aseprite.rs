@@ -0,0 +1,466 @@
+//! Reader of Aseprite's own `.ase`/`.aseprite` files, so sprites can be used without
+//! exporting a sheet first.
+//!
+//! Visible image layers are flattened into one image per frame. Every blend mode is drawn
+//! as normal, and tilemap layers are skipped. Frame tags and slices are kept for
+//! [`AnimatedSheet::from_frames`](crate::assets::animated::AnimatedSheet::from_frames).
+
+use crate::assets::animated::{Frame, FrameTag, MAX_PIXELS, Slice, playback_order};
+use flate2::read::ZlibDecoder;
+use image::{Rgba, RgbaImage};
+use std::io::{self, Read};
+
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const OLD_PALETTE_CHUNK: u16 = 0x0004;
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+const SLICE_CHUNK: u16 = 0x2022;
+
+/// Header flag telling that layer opacities are meaningful.
+const LAYER_OPACITY_VALID: u32 = 1;
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const LAYER_REFERENCE: u16 = 64;
+const IMAGE_LAYER: u16 = 0;
+
+/// Palette entries an indexed pixel can address.
+const MAX_PALETTE_SIZE: usize = 256;
+
+/// Flattened frames, tags and slices of an Aseprite file.
+pub(crate) struct AsepriteFile {
+    pub frames: Vec<Frame>,
+    pub tags: Vec<FrameTag>,
+    pub slices: Vec<Slice>,
+}
+
+/// Pixel format of the sprite.
+#[derive(Clone, Copy)]
+enum ColorDepth {
+    Rgba,
+    Grayscale,
+    /// Palette indices; the index is drawn transparent outside background layers.
+    Indexed {
+        transparent: u8,
+    },
+}
+
+struct Layer {
+    /// Visible itself and inside visible groups.
+    visible: bool,
+    image: bool,
+    background: bool,
+    opacity: u8,
+}
+
+#[derive(Clone)]
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    z_index: i16,
+    width: u32,
+    height: u32,
+    /// Pixels in the sprite's color depth, row by row.
+    pixels: Vec<u8>,
+}
+
+impl AsepriteFile {
+    /// Parses the content of an Aseprite file.
+    pub fn read(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader { bytes, position: 0 };
+        let _file_size = reader.u32()?;
+        if reader.u16()? != FILE_MAGIC {
+            return Err(invalid("not an Aseprite file"));
+        }
+        let frame_count = reader.u16()? as usize;
+        let width = u32::from(reader.u16()?);
+        let height = u32::from(reader.u16()?);
+        let depth = reader.u16()?;
+        let flags = reader.u32()?;
+        reader.take(10)?;
+        let transparent = reader.u8()?;
+        reader.take(128 - 29)?;
+        let depth = match depth {
+            32 => ColorDepth::Rgba,
+            16 => ColorDepth::Grayscale,
+            8 => ColorDepth::Indexed { transparent },
+            _ => return Err(invalid("unsupported color depth")),
+        };
+        if width == 0 || height == 0 {
+            return Err(invalid("the sprite is empty"));
+        }
+        if u64::from(width) * u64::from(height) * frame_count as u64 > MAX_PIXELS {
+            return Err(invalid("the sprite is too large"));
+        }
+
+        let mut layers = Vec::new();
+        // Visibility of the groups holding the next layer, outermost first.
+        let mut groups: Vec<bool> = Vec::new();
+        let mut palette: Vec<[u8; 4]> = Vec::new();
+        let mut frames: Vec<(u32, Vec<Cel>)> = Vec::with_capacity(frame_count);
+        let mut tags = Vec::new();
+        let mut slices = Vec::new();
+
+        for _ in 0..frame_count {
+            let frame_size = reader.u32()? as usize;
+            let mut frame = Reader {
+                bytes: reader.take(frame_size.saturating_sub(4))?,
+                position: 0,
+            };
+            if frame.u16()? != FRAME_MAGIC {
+                return Err(invalid("corrupted frame header"));
+            }
+            let old_chunk_count = u32::from(frame.u16()?);
+            let duration_ms = u32::from(frame.u16()?);
+            frame.take(2)?;
+            let chunk_count = match frame.u32()? {
+                0 => old_chunk_count,
+                count => count,
+            };
+
+            let mut cels = Vec::new();
+            for _ in 0..chunk_count {
+                let chunk_size = frame.u32()? as usize;
+                let kind = frame.u16()?;
+                let mut chunk = Reader {
+                    bytes: frame.take(chunk_size.saturating_sub(6))?,
+                    position: 0,
+                };
+                match kind {
+                    LAYER_CHUNK => {
+                        let layer_flags = chunk.u16()?;
+                        let layer_kind = chunk.u16()?;
+                        let level = chunk.u16()? as usize;
+                        chunk.take(6)?;
+                        let opacity = chunk.u8()?;
+                        groups.truncate(level);
+                        let visible = layer_flags & LAYER_VISIBLE != 0
+                            && layer_flags & LAYER_REFERENCE == 0
+                            && groups.iter().all(|&visible| visible);
+                        groups.push(visible);
+                        layers.push(Layer {
+                            visible,
+                            image: layer_kind == IMAGE_LAYER,
+                            background: layer_flags & LAYER_BACKGROUND != 0,
+                            opacity: match flags & LAYER_OPACITY_VALID {
+                                0 => u8::MAX,
+                                _ => opacity,
+                            },
+                        });
+                    }
+                    CEL_CHUNK => {
+                        if let Some(cel) = chunk.cel(depth, (width, height), &frames)? {
+                            cels.push(cel);
+                        }
+                    }
+                    OLD_PALETTE_CHUNK => chunk.old_palette(&mut palette)?,
+                    PALETTE_CHUNK => chunk.palette(&mut palette)?,
+                    TAGS_CHUNK => tags = chunk.tags()?,
+                    SLICE_CHUNK => slices.extend(chunk.slice()?),
+                    _ => {}
+                }
+            }
+            frames.push((duration_ms, cels));
+        }
+
+        let frames = frames
+            .into_iter()
+            .map(|(duration_ms, mut cels)| {
+                // Aseprite orders cels by layer plus z-index, then by z-index.
+                cels.sort_by_key(|cel| (cel.layer as i64 + i64::from(cel.z_index), cel.z_index));
+                let mut image = RgbaImage::new(width, height);
+                for cel in &cels {
+                    if let Some(layer) = layers
+                        .get(cel.layer)
+                        .filter(|layer| layer.visible && layer.image)
+                    {
+                        draw_cel(&mut image, cel, layer, depth, &palette);
+                    }
+                }
+                Frame { image, duration_ms }
+            })
+            .collect();
+        Ok(Self { frames, tags, slices })
+    }
+}
+
+/// Draws `cel` over `image` with normal blending.
+fn draw_cel(
+    image: &mut RgbaImage, cel: &Cel, layer: &Layer, depth: ColorDepth, palette: &[[u8; 4]],
+) {
+    let opacity = u32::from(cel.opacity) * u32::from(layer.opacity) / 255;
+    for row in 0..cel.height {
+        for column in 0..cel.width {
+            let (x, y) = (cel.x + column as i32, cel.y + row as i32);
+            if x < 0 || y < 0 || x as u32 >= image.width() || y as u32 >= image.height() {
+                continue;
+            }
+            let index = (row * cel.width + column) as usize;
+            let color = match depth {
+                ColorDepth::Rgba => {
+                    let pixel = &cel.pixels[index * 4..index * 4 + 4];
+                    [pixel[0], pixel[1], pixel[2], pixel[3]]
+                }
+                ColorDepth::Grayscale => {
+                    let (value, alpha) = (cel.pixels[index * 2], cel.pixels[index * 2 + 1]);
+                    [value, value, value, alpha]
+                }
+                ColorDepth::Indexed { transparent } => {
+                    let entry = cel.pixels[index];
+                    if entry == transparent && !layer.background {
+                        continue;
+                    }
+                    palette
+                        .get(usize::from(entry))
+                        .copied()
+                        .unwrap_or_default()
+                }
+            };
+            blend(image.get_pixel_mut(x as u32, y as u32), color, opacity);
+        }
+    }
+}
+
+/// Source-over compositing of `color`, faded by `opacity`, onto `pixel`.
+fn blend(pixel: &mut Rgba<u8>, color: [u8; 4], opacity: u32) {
+    let alpha = u32::from(color[3]) * opacity / 255;
+    if alpha == 0 {
+        return;
+    }
+    let below = u32::from(pixel[3]) * (255 - alpha) / 255;
+    let total = alpha + below;
+    for channel in 0..3 {
+        let value = u32::from(color[channel]) * alpha + u32::from(pixel[channel]) * below;
+        pixel[channel] = (value / total) as u8;
+    }
+    pixel[3] = total as u8;
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Little-endian cursor over the file, a frame or a chunk.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let length = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+
+    /// Cel of an image layer; `None` for tilemap cels and links to missing cels. A linked
+    /// cel is a copy of the cel it links to in `frames`. Cels larger than the `sprite`
+    /// size are rejected before their pixels are read.
+    fn cel(
+        &mut self, depth: ColorDepth, sprite: (u32, u32), frames: &[(u32, Vec<Cel>)],
+    ) -> io::Result<Option<Cel>> {
+        let layer = self.u16()? as usize;
+        let x = i32::from(self.i16()?);
+        let y = i32::from(self.i16()?);
+        let opacity = self.u8()?;
+        let kind = self.u16()?;
+        let z_index = self.i16()?;
+        self.take(5)?;
+
+        let bytes_per_pixel = match depth {
+            ColorDepth::Rgba => 4,
+            ColorDepth::Grayscale => 2,
+            ColorDepth::Indexed { .. } => 1,
+        };
+        let (width, height, pixels) = match kind {
+            0 | 2 => {
+                let width = u32::from(self.u16()?);
+                let height = u32::from(self.u16()?);
+                if width > sprite.0 || height > sprite.1 {
+                    return Err(invalid("cel larger than the sprite"));
+                }
+                let length = width as usize * height as usize * bytes_per_pixel;
+                let pixels = if kind == 0 {
+                    self.take(length)?.to_vec()
+                } else {
+                    let mut pixels = Vec::new();
+                    ZlibDecoder::new(&self.bytes[self.position..])
+                        .take(length as u64)
+                        .read_to_end(&mut pixels)?;
+                    pixels
+                };
+                if pixels.len() != length {
+                    return Err(invalid("truncated cel"));
+                }
+                (width, height, pixels)
+            }
+            1 => {
+                let linked = self.u16()? as usize;
+                return Ok(frames
+                    .get(linked)
+                    .and_then(|(_, cels)| {
+                        cels.iter()
+                            .find(|cel| cel.layer == layer)
+                    })
+                    .cloned());
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(Cel {
+            layer,
+            x,
+            y,
+            opacity,
+            z_index,
+            width,
+            height,
+            pixels,
+        }))
+    }
+
+    /// Applies a palette chunk to `palette`, growing it to the new size. Entries past
+    /// [`MAX_PALETTE_SIZE`], which no pixel can address, are read but not kept.
+    fn palette(&mut self, palette: &mut Vec<[u8; 4]>) -> io::Result<()> {
+        let size = self.u32()? as usize;
+        let first = self.u32()? as usize;
+        let last = self.u32()? as usize;
+        self.take(8)?;
+        if first > last || last >= size {
+            return Err(invalid("palette entries out of range"));
+        }
+        let size = size.min(MAX_PALETTE_SIZE);
+        if palette.len() < size {
+            palette.resize(size, [0, 0, 0, 255]);
+        }
+        for entry in first..=last {
+            let flags = self.u16()?;
+            let color = self.array::<4>()?;
+            if flags & 1 != 0 {
+                self.string()?;
+            }
+            if let Some(slot) = palette.get_mut(entry) {
+                *slot = color;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a palette chunk of files older than Aseprite 1.2, without alpha.
+    fn old_palette(&mut self, palette: &mut Vec<[u8; 4]>) -> io::Result<()> {
+        let mut entry = 0;
+        for _ in 0..self.u16()? {
+            entry += self.u8()? as usize;
+            let count = match self.u8()? {
+                0 => 256,
+                count => count as usize,
+            };
+            for _ in 0..count {
+                let [red, green, blue] = self.array()?;
+                if entry >= MAX_PALETTE_SIZE {
+                    continue;
+                }
+                if palette.len() <= entry {
+                    palette.resize(entry + 1, [0, 0, 0, 255]);
+                }
+                palette[entry] = [red, green, blue, 255];
+                entry += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn tags(&mut self) -> io::Result<Vec<FrameTag>> {
+        let count = self.u16()?;
+        self.take(8)?;
+        (0..count)
+            .map(|_| {
+                let from = self.u16()? as usize;
+                let to = self.u16()? as usize;
+                let direction = match self.u8()? {
+                    1 => "reverse",
+                    2 => "pingpong",
+                    3 => "pingpong_reverse",
+                    _ => "forward",
+                };
+                self.take(12)?;
+                let name = self.string()?;
+                Ok(FrameTag {
+                    name,
+                    frames: playback_order(from, to, direction),
+                })
+            })
+            .collect()
+    }
+
+    /// Bounds of a slice on the first frame it has a key for; `None` without keys.
+    fn slice(&mut self) -> io::Result<Option<Slice>> {
+        let key_count = self.u32()?;
+        let flags = self.u32()?;
+        self.take(4)?;
+        let name = self.string()?;
+        let mut first: Option<(u32, Slice)> = None;
+        for _ in 0..key_count {
+            let frame = self.u32()?;
+            let x = self.i32()?;
+            let y = self.i32()?;
+            let width = self.u32()?;
+            let height = self.u32()?;
+            // Nine-patch center and pivot, unused here.
+            if flags & 1 != 0 {
+                self.take(16)?;
+            }
+            if flags & 2 != 0 {
+                self.take(8)?;
+            }
+            if first
+                .as_ref()
+                .is_none_or(|(first_frame, _)| frame < *first_frame)
+            {
+                let slice = Slice { name: name.clone(), x, y, width, height };
+                first = Some((frame, slice));
+            }
+        }
+        Ok(first.map(|(_, slice)| slice))
+    }
+}
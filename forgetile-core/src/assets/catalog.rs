@@ -0,0 +1,267 @@
+//! Tile ids of an assets directory, computed from image sizes and sidecars without
+//! loading any texture.
+
+use crate::assets::animated::AnimatedSheet;
+use crate::assets::sidecar::{PixelRect, TilesetSidecar};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Spritesheet of the catalog and how the editor slices it. The sheet of a GIF or an
+/// Aseprite file is its frames laid out in a grid, see [`AnimatedSheet`].
+pub struct CatalogSheet {
+    /// Canonical path of the image.
+    pub path: PathBuf,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// Id of every tile, row-major: the sidecar id when it is unique, else the generated one.
+    pub ids: Vec<String>,
+    pub sidecar: TilesetSidecar,
+}
+
+impl CatalogSheet {
+    /// Pixel region of the tile at `index`.
+    pub fn tile_rect(&self, index: usize) -> PixelRect {
+        self.sidecar.tile_rect(
+            index,
+            self.columns as usize,
+            (self.tile_width as usize, self.tile_height as usize),
+        )
+    }
+
+    /// Pixel size of the sliced part of the image, including margin and spacing.
+    pub fn image_size(&self) -> (u32, u32) {
+        let extent = |count: u32, tile: u32| {
+            2 * self.margin + count * tile + count.saturating_sub(1) * self.spacing
+        };
+        (extent(self.columns, self.tile_width), extent(self.rows, self.tile_height))
+    }
+}
+
+/// Every spritesheet the editor would load from an assets directory.
+#[derive(Default)]
+pub struct Catalog {
+    pub sheets: Vec<CatalogSheet>,
+    /// Maps both the id and the generated `path::index` id of a tile to
+    /// `(sheet, tile index)`.
+    index: HashMap<String, (usize, usize)>,
+}
+
+impl Catalog {
+    /// Scans the images in `root`, its category folders and their sub-categories, mirroring
+    /// the editor's loader. Spritesheets are sliced at `default_tile_size` unless a
+    /// `.tileset.json` sidecar overrides it.
+    pub fn load(root: &Path, default_tile_size: (u32, u32)) -> Self {
+        Self::load_all(&[root.to_path_buf()], default_tile_size)
+    }
+
+    /// Scans several assets directories like [`Catalog::load`], in order: a sidecar id
+    /// already used by an earlier directory falls back to the generated id.
+    pub fn load_all(roots: &[PathBuf], default_tile_size: (u32, u32)) -> Self {
+        // Same order as the editor, so duplicate sidecar ids resolve to the same tile.
+        let mut directories = Vec::new();
+        for root in roots {
+            directories.push(root.clone());
+            for category in sub_directories(root) {
+                let sub_categories = nested_directories(&category);
+                directories.push(category);
+                directories.extend(sub_categories);
+            }
+        }
+
+        let mut catalog = Catalog::default();
+        let mut taken = HashSet::new();
+        for directory in directories {
+            let Ok(entries) = fs::read_dir(&directory) else {
+                continue;
+            };
+            for path in entries
+                .flatten()
+                .map(|entry| entry.path())
+            {
+                if path.is_file()
+                    && is_supported_image(&path)
+                    && let Some(sheet) = load_sheet(&path, default_tile_size, &mut taken)
+                {
+                    catalog.insert(sheet);
+                }
+            }
+        }
+        catalog
+    }
+
+    /// Number of distinct tiles.
+    pub fn tile_count(&self) -> usize {
+        self.sheets
+            .iter()
+            .map(|sheet| sheet.ids.len())
+            .sum()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.index.contains_key(id)
+    }
+
+    /// Returns the sheet holding the tile with `id` and the tile's index in it.
+    pub fn locate(&self, id: &str) -> Option<(&CatalogSheet, usize)> {
+        let &(sheet, index) = self.index.get(id)?;
+        Some((&self.sheets[sheet], index))
+    }
+
+    /// Position of the tile with `id` when counting the tiles of every sheet in order, as
+    /// used by CSV index grids.
+    pub fn tile_index(&self, id: &str) -> Option<usize> {
+        let &(sheet, index) = self.index.get(id)?;
+        let offset: usize = self.sheets[..sheet]
+            .iter()
+            .map(|sheet| sheet.ids.len())
+            .sum();
+        Some(offset + index)
+    }
+
+    /// Id of the tile at `index` when counting the tiles of every sheet in order; the
+    /// inverse of [`Catalog::tile_index`].
+    pub fn tile_id(&self, mut index: usize) -> Option<&str> {
+        for sheet in &self.sheets {
+            match sheet.ids.get(index) {
+                Some(id) => return Some(id),
+                None => index -= sheet.ids.len(),
+            }
+        }
+        None
+    }
+
+    /// Returns the sheet loaded from the canonical image path `path`.
+    pub fn sheet(&self, path: &Path) -> Option<&CatalogSheet> {
+        self.sheets
+            .iter()
+            .find(|sheet| sheet.path == path)
+    }
+
+    fn insert(&mut self, sheet: CatalogSheet) {
+        let position = self.sheets.len();
+        for (index, id) in sheet.ids.iter().enumerate() {
+            self.index
+                .insert(id.clone(), (position, index));
+            self.index
+                .insert(generated_tile_id(&sheet.path, index), (position, index));
+        }
+        self.sheets.push(sheet);
+    }
+}
+
+/// Id of a tile without a stable sidecar id: the sheet's canonical path and the tile index.
+pub fn generated_tile_id(sheet: &Path, index: usize) -> String {
+    format!("{}::{}", sheet.display(), index)
+}
+
+fn load_sheet(
+    path: &Path, default_tile_size: (u32, u32), taken: &mut HashSet<String>,
+) -> Option<CatalogSheet> {
+    let dimensions = if AnimatedSheet::is_animated(path) {
+        let tile_size = (default_tile_size.0 as usize, default_tile_size.1 as usize);
+        AnimatedSheet::load(path, tile_size).and_then(|sheet| {
+            sheet
+                .map(|sheet| ((sheet.width, sheet.height), sheet.sidecar))
+                .ok_or_else(|| io::Error::other("not an animated image"))
+        })
+    } else {
+        image::image_dimensions(path)
+            .map(|dimensions| (dimensions, TilesetSidecar::load_for(path)))
+            .map_err(io::Error::other)
+    };
+    let ((image_width, image_height), sidecar) = match dimensions {
+        Ok(dimensions) => dimensions,
+        Err(err) => {
+            eprintln!("[assets] Could not read {:?}: {err}", path);
+            return None;
+        }
+    };
+    let (tile_width, tile_height) = sidecar
+        .tile_size()
+        .unwrap_or((default_tile_size.0 as usize, default_tile_size.1 as usize));
+    let (columns, rows) =
+        sidecar.grid((image_width as usize, image_height as usize), (tile_width, tile_height));
+    if columns == 0 || rows == 0 {
+        return None;
+    }
+
+    let canonical_path = path
+        .canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf());
+    let ids = (0..columns * rows)
+        .map(|index| {
+            sidecar
+                .tile_id(index)
+                .filter(|id| taken.insert(id.to_string()))
+                .map(str::to_string)
+                .unwrap_or_else(|| generated_tile_id(&canonical_path, index))
+        })
+        .collect();
+
+    Some(CatalogSheet {
+        path: canonical_path,
+        tile_width: tile_width as u32,
+        tile_height: tile_height as u32,
+        columns: columns as u32,
+        rows: rows as u32,
+        margin: sidecar.margin as u32,
+        spacing: sidecar.spacing as u32,
+        ids,
+        sidecar,
+    })
+}
+
+/// Deepest sub-category level scanned below a category directory, which also keeps
+/// symbolic link loops from being followed forever.
+pub const MAX_CATEGORY_DEPTH: usize = 8;
+
+/// Every directory below the category directory `path`, up to [`MAX_CATEGORY_DEPTH`] levels
+/// deep. Siblings are sorted by name and each directory comes right before its own
+/// sub-directories, so a sub-tree is a contiguous run.
+pub fn nested_directories(path: &Path) -> Vec<PathBuf> {
+    let mut nested = Vec::new();
+    collect_nested_directories(path, 1, &mut nested);
+    nested
+}
+
+fn collect_nested_directories(path: &Path, depth: usize, nested: &mut Vec<PathBuf>) {
+    if depth > MAX_CATEGORY_DEPTH {
+        return;
+    }
+    let mut directories = sub_directories(path);
+    directories.sort();
+    for directory in directories {
+        nested.push(directory.clone());
+        collect_nested_directories(&directory, depth + 1, nested);
+    }
+}
+
+fn sub_directories(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns whether `path` has an image extension the editor loads.
+pub fn is_supported_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            [
+                "png", "jpg", "jpeg", "gif", "ase", "aseprite",
+            ]
+            .contains(&ext.to_ascii_lowercase().as_str())
+        })
+}
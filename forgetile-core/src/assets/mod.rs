@@ -0,0 +1,4 @@
+pub mod animated;
+mod aseprite;
+pub mod catalog;
+pub mod sidecar;
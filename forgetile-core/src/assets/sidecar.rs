@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Optional per-spritesheet settings read from `<image_name>.tileset.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TilesetSidecar {
+    /// Tile width in pixels, overriding the global tile size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_width: Option<usize>,
+    /// Tile height in pixels, overriding the global tile size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_height: Option<usize>,
+    /// Empty pixels around the edge of the spritesheet, before the first row and column.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub margin: usize,
+    /// Empty pixels between two neighbouring tiles.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub spacing: usize,
+    /// RGB color treated as transparent when slicing tiles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_key: Option<[u8; 3]>,
+    /// Maximum Manhattan distance in RGB space still matched by `color_key` (0 = exact).
+    pub color_key_tolerance: u8,
+    /// Leaves fully transparent tiles, after the color key, out of the palette.
+    #[serde(skip_serializing_if = "is_false")]
+    pub trim_transparent: bool,
+    /// Stable ids of individual tiles, keyed by tile index. Saved maps refer to tiles by
+    /// these ids, so they keep working when the image is moved or renamed.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub ids: BTreeMap<String, String>,
+    /// Display names of individual tiles, keyed by tile index.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, String>,
+    /// Key/value properties of individual tiles, keyed by tile index.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, BTreeMap<String, String>>,
+    /// Animations of individual tiles, keyed by tile index.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub animations: BTreeMap<String, AnimationSpec>,
+}
+
+/// Region of a spritesheet, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Animation declared for one tile of the spritesheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AnimationSpec {
+    /// Scroll across `columns` cells of the tile's row, starting at the tile itself.
+    UvScroll {
+        columns: usize,
+        fps: f32,
+    },
+    /// Cycle through the listed tile indices of the same spritesheet.
+    Frames {
+        frames: Vec<usize>,
+        fps: f32,
+    },
+}
+
+impl TilesetSidecar {
+    /// Loads the sidecar next to `image_path`, falling back to defaults when absent or invalid.
+    pub fn load_for(image_path: &Path) -> Self {
+        let Some(path) = sidecar_path(image_path) else {
+            return Self::default();
+        };
+        let Ok(data) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&data).unwrap_or_else(|err| {
+            eprintln!("[assets] Ignoring invalid sidecar {:?}: {err}", path);
+            Self::default()
+        })
+    }
+
+    /// Writes the sidecar next to `image_path`.
+    pub fn save_for(&self, image_path: &Path) -> Result<(), io::Error> {
+        let path = sidecar_path(image_path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "image has no file name")
+        })?;
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    /// Returns the properties stored for the tile at `index` of the spritesheet.
+    pub fn tile_metadata(&self, index: usize) -> BTreeMap<String, String> {
+        self.metadata
+            .get(&index.to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the stable id declared for the tile at `index`, ignoring blank ids.
+    pub fn tile_id(&self, index: usize) -> Option<&str> {
+        self.ids
+            .get(&index.to_string())
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Returns the display name declared for the tile at `index`.
+    pub fn tile_name(&self, index: usize) -> Option<&str> {
+        self.names
+            .get(&index.to_string())
+            .map(String::as_str)
+    }
+
+    /// Returns the tile size declared by the sidecar, if both dimensions are set and non-zero.
+    pub fn tile_size(&self) -> Option<(usize, usize)> {
+        match (self.tile_width, self.tile_height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+            _ => None,
+        }
+    }
+
+    /// Number of whole tiles of `tile_size` that fit in an image of `image_size` pixels,
+    /// as `(columns, rows)`, after the margin and spacing.
+    pub fn grid(&self, image_size: (usize, usize), tile_size: (usize, usize)) -> (usize, usize) {
+        let count = |extent: usize, tile: usize| {
+            (extent + self.spacing).saturating_sub(2 * self.margin) / (tile + self.spacing)
+        };
+        (count(image_size.0, tile_size.0), count(image_size.1, tile_size.1))
+    }
+
+    /// Pixel rectangle of the tile at `index` in a sheet of `columns` tiles per row.
+    pub fn tile_rect(&self, index: usize, columns: usize, tile_size: (usize, usize)) -> PixelRect {
+        let (width, height) = tile_size;
+        let (column, row) = (index % columns, index / columns);
+        PixelRect {
+            x: self.margin + column * (width + self.spacing),
+            y: self.margin + row * (height + self.spacing),
+            width,
+            height,
+        }
+    }
+
+    /// Makes every pixel of an RGBA8 buffer matching the color key fully transparent.
+    pub fn apply_color_key(&self, rgba: &mut [u8]) {
+        if self.color_key.is_none() {
+            return;
+        }
+        for pixel in rgba.chunks_exact_mut(4) {
+            if self.matches_color_key(pixel) {
+                pixel[3] = 0;
+            }
+        }
+    }
+
+    /// Returns `true` when every pixel of `rect` in an RGBA8 image `image_width` pixels
+    /// wide is fully transparent or matches the color key.
+    pub fn is_transparent(&self, rgba: &[u8], image_width: usize, rect: PixelRect) -> bool {
+        (rect.y..rect.y + rect.height).all(|y| {
+            let start = (y * image_width + rect.x) * 4;
+            rgba.get(start..start + rect.width * 4)
+                .is_none_or(|row| {
+                    row.chunks_exact(4)
+                        .all(|pixel| pixel[3] == 0 || self.matches_color_key(pixel))
+                })
+        })
+    }
+
+    fn matches_color_key(&self, pixel: &[u8]) -> bool {
+        let Some(key) = self.color_key else {
+            return false;
+        };
+        let distance: u32 = pixel[..3]
+            .iter()
+            .zip(key)
+            .map(|(&channel, key)| u32::from(channel.abs_diff(key)))
+            .sum();
+        distance <= u32::from(self.color_key_tolerance)
+    }
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn sidecar_path(image_path: &Path) -> Option<PathBuf> {
+    let stem = image_path
+        .file_stem()?
+        .to_string_lossy();
+    Some(image_path.with_file_name(format!("{stem}.tileset.json")))
+}
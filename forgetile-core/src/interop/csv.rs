@@ -0,0 +1,170 @@
+//! One map layer as a CSV grid: one line per row, one field per cell.
+//!
+//! Cells hold either tile ids, with empty fields for empty cells, or catalog tile indices
+//! for engines that expect numbers, with `-1` for empty cells (see [`CsvCells`]). Fields
+//! holding commas, quotes or line breaks are quoted with doubled quotes (RFC 4180).
+
+use crate::assets::catalog::Catalog;
+use crate::map::{LayerData, MapData};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// What the fields of a CSV grid hold.
+#[derive(Clone, Copy)]
+pub enum CsvCells<'a> {
+    /// Tile ids; empty fields are empty cells.
+    Ids,
+    /// Indices of the tiles in the catalog, counted across its spritesheets in order;
+    /// `-1` (or an empty field) is an empty cell.
+    Indices(&'a Catalog),
+}
+
+/// Writes the cells of `layer` as CSV.
+///
+/// Returns the number of painted cells written as empty because their tile is missing from
+/// the catalog; always 0 for [`CsvCells::Ids`].
+pub fn write(
+    map: &MapData, layer: &LayerData, cells: CsvCells, path: &Path,
+) -> Result<usize, io::Error> {
+    let mut csv = String::new();
+    let mut unresolved = 0;
+    for row in layer.tiles.chunks(map.width.max(1)) {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|tile| match (cells, tile.as_deref()) {
+                (CsvCells::Ids, tile) => tile.map(quote).unwrap_or_default(),
+                (CsvCells::Indices(_), None) => "-1".to_string(),
+                (CsvCells::Indices(catalog), Some(id)) => match catalog.tile_index(id) {
+                    Some(index) => index.to_string(),
+                    None => {
+                        unresolved += 1;
+                        "-1".to_string()
+                    }
+                },
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    fs::write(path, csv)?;
+    Ok(unresolved)
+}
+
+/// Reads a CSV grid as a single-layer map with tiles of `tile_size` pixels, named after the
+/// file.
+pub fn read(path: &Path, tile_size: (u32, u32), cells: CsvCells) -> Result<MapData, io::Error> {
+    let (width, height, tiles) = read_grid(path, cells)?;
+    let name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let mut map = MapData::new(width, height, (tile_size.0 as f32, tile_size.1 as f32));
+    map.layers
+        .push(LayerData::new(&name, tiles));
+    Ok(map)
+}
+
+/// Reads a CSV grid as its width, height and tile id of every cell, row by row.
+///
+/// Fails with [`io::ErrorKind::InvalidData`] on ragged rows and, for
+/// [`CsvCells::Indices`], on fields that are not indices of the catalog.
+pub fn read_grid(
+    path: &Path, cells: CsvCells,
+) -> Result<(usize, usize, Vec<Option<String>>), io::Error> {
+    let rows = parse(&fs::read_to_string(path)?)?;
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err(invalid_data("empty CSV grid"));
+    }
+    if let Some(row) = rows
+        .iter()
+        .position(|row| row.len() != width)
+    {
+        return Err(invalid_data(format!(
+            "row {} has {} cells, expected {width}",
+            row + 1,
+            rows[row].len()
+        )));
+    }
+
+    let height = rows.len();
+    let tiles = rows
+        .into_iter()
+        .flatten()
+        .map(|field| cell_tile(field, cells))
+        .collect::<Result<_, _>>()?;
+    Ok((width, height, tiles))
+}
+
+/// Tile id held by one field.
+fn cell_tile(field: String, cells: CsvCells) -> Result<Option<String>, io::Error> {
+    let CsvCells::Indices(catalog) = cells else {
+        return Ok((!field.is_empty()).then_some(field));
+    };
+    let field = field.trim();
+    if field.is_empty() || field == "-1" {
+        return Ok(None);
+    }
+    field
+        .parse()
+        .ok()
+        .and_then(|index| catalog.tile_id(index))
+        .map(|id| Some(id.to_string()))
+        .ok_or_else(|| invalid_data(format!("`{field}` is not a tile index of the catalog")))
+}
+
+/// `field` as a CSV field, quoted when it holds a comma, quote or line break.
+pub(crate) fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits CSV text into rows of unquoted fields, skipping blank lines.
+fn parse(text: &str) -> Result<Vec<Vec<String>>, io::Error> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match (quoted, char) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, char) => field.push(char),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                if !(row.len() == 1 && row[0].is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            (false, char) => field.push(char),
+        }
+    }
+    if quoted {
+        return Err(invalid_data("unterminated quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,942 @@
+//! Conversion between ForgeTile maps and [Tiled](https://www.mapeditor.org) maps
+//! (`.tmx` XML, `.tmj` JSON).
+//!
+//! Exports reference every used spritesheet as an external image tileset and store layer
+//! data as CSV (a plain array in JSON); object layers become object groups with string
+//! properties and image layers become Tiled image layers with their `parallaxx`/`parallaxy`
+//! factors. Layer opacity and tint color map to Tiled's `opacity` and `tintcolor`; per-tile
+//! tints, z-priorities, orientations and collision have no Tiled equivalent and are dropped. Imports accept orthogonal and isometric finite maps whose tileset images are
+//! spritesheets of the catalog; flip flags are ignored, image layers are skipped and only
+//! the name, type, bounds and properties of objects are kept.
+
+use crate::assets::catalog::{Catalog, CatalogSheet};
+use crate::map::object::MapObject;
+use crate::map::projection::MapProjection;
+use crate::map::{LayerData, LayerKind, MapData, MapLoadError};
+use roxmltree::Node;
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Map format version written to exported files.
+const TILED_FORMAT_VERSION: &str = "1.10";
+/// Bits of a global tile id holding its flip and rotation flags.
+const GID_FLAG_MASK: u32 = 0xF000_0000;
+
+/// Tiled map flavor, picked from the file extension.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TiledFile {
+    /// XML map (`.tmx`).
+    Tmx,
+    /// JSON map (`.tmj`).
+    Tmj,
+}
+
+impl TiledFile {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("tmx") {
+            Some(TiledFile::Tmx)
+        } else if extension.eq_ignore_ascii_case("tmj") {
+            Some(TiledFile::Tmj)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns whether `path` names a Tiled map (`.tmx` or `.tmj`).
+pub fn is_tiled_file(path: &Path) -> bool {
+    TiledFile::from_path(path).is_some()
+}
+
+/// Writes `map` as a Tiled map, XML for a `.tmx` path and JSON for a `.tmj` one.
+///
+/// Layer groups are flattened with [`MapData::flatten_groups`]. Returns the number of
+/// painted cells left empty because their tile is not in `catalog`.
+pub fn write(map: &MapData, catalog: &Catalog, path: &Path) -> Result<usize, io::Error> {
+    let mut flat = map.clone();
+    flat.flatten_groups();
+    let map = &flat;
+    let Some(file) = TiledFile::from_path(path) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Tiled exports need a .tmx or .tmj path, got {}", path.display()),
+        ));
+    };
+    let tile_size = (map.tile_width.round() as u32, map.tile_height.round() as u32);
+    let mut tilesets: Vec<(u32, &CatalogSheet)> = Vec::new();
+    let mut next_gid = 1;
+    let mut unresolved = 0;
+    let layers: Vec<Vec<u32>> = map
+        .layers
+        .iter()
+        .map(|layer| {
+            layer
+                .tiles
+                .iter()
+                .map(|tile| {
+                    let Some(id) = tile else {
+                        return 0;
+                    };
+                    let Some((sheet, index)) = catalog.locate(id) else {
+                        unresolved += 1;
+                        return 0;
+                    };
+                    let first_gid = match tilesets
+                        .iter()
+                        .find(|(_, used)| used.path == sheet.path)
+                    {
+                        Some((first_gid, _)) => *first_gid,
+                        None => {
+                            let first_gid = next_gid;
+                            next_gid += sheet.ids.len() as u32;
+                            tilesets.push((first_gid, sheet));
+                            first_gid
+                        }
+                    };
+                    first_gid + index as u32
+                })
+                .collect()
+        })
+        .collect();
+
+    let base_dir = parent_dir(path).canonicalize().ok();
+    let image = |sheet: &CatalogSheet| {
+        let relative = base_dir
+            .as_deref()
+            .and_then(|base| sheet.path.strip_prefix(base).ok());
+        relative
+            .unwrap_or(&sheet.path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    // Image layer paths are relative to the working directory, Tiled's to the map file.
+    let layer_image = |layer: &LayerData| {
+        let path = PathBuf::from(
+            layer
+                .image
+                .as_deref()
+                .unwrap_or_default(),
+        );
+        let relative = path
+            .canonicalize()
+            .ok()
+            .zip(base_dir.as_deref())
+            .and_then(|(path, base)| {
+                path.strip_prefix(base)
+                    .ok()
+                    .map(Path::to_path_buf)
+            });
+        relative
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    let name = |sheet: &CatalogSheet| {
+        sheet
+            .path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    let object_count = map
+        .layers
+        .iter()
+        .map(|layer| layer.objects.len())
+        .sum::<usize>();
+    // Tiled object ids are unique across the map.
+    let object_ids = map
+        .layers
+        .iter()
+        .scan(1, |next_id, layer| {
+            let first_id = *next_id;
+            *next_id += layer.objects.len();
+            Some(first_id)
+        })
+        .collect::<Vec<_>>();
+
+    let contents = if file == TiledFile::Tmx {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<map version=\"{TILED_FORMAT_VERSION}\" orientation=\"{}\" \
+             renderorder=\"right-down\" width=\"{}\" height=\"{}\" tilewidth=\"{}\" \
+             tileheight=\"{}\" infinite=\"0\" nextlayerid=\"{}\" nextobjectid=\"{}\">",
+            map.projection.name(),
+            map.width,
+            map.height,
+            tile_size.0,
+            tile_size.1,
+            map.layers.len() + 1,
+            object_count + 1
+        );
+        for (first_gid, sheet) in &tilesets {
+            let (image_width, image_height) = sheet.image_size();
+            let _ = writeln!(
+                xml,
+                " <tileset firstgid=\"{first_gid}\" name=\"{}\" tilewidth=\"{}\" \
+                 tileheight=\"{}\" spacing=\"{}\" margin=\"{}\" tilecount=\"{}\" columns=\"{}\">\n  \
+                 <image source=\"{}\" width=\"{image_width}\" height=\"{image_height}\"/>\n \
+                 </tileset>",
+                escape_xml(&name(sheet)),
+                sheet.tile_width,
+                sheet.tile_height,
+                sheet.spacing,
+                sheet.margin,
+                sheet.ids.len(),
+                sheet.columns,
+                escape_xml(&image(sheet))
+            );
+        }
+        for (index, (layer, data)) in map
+            .layers
+            .iter()
+            .zip(&layers)
+            .enumerate()
+        {
+            let flags = format!(
+                "{}{}{}{}",
+                if layer.visible {
+                    ""
+                } else {
+                    " visible=\"0\""
+                },
+                if layer.locked {
+                    " locked=\"1\""
+                } else {
+                    ""
+                },
+                if layer.opacity < 1.0 {
+                    format!(" opacity=\"{}\"", layer.opacity.max(0.0))
+                } else {
+                    String::new()
+                },
+                layer
+                    .tint_color
+                    .map(|tint| format!(" tintcolor=\"{}\"", tiled_color(tint)))
+                    .unwrap_or_default()
+            );
+            if layer.kind == LayerKind::Objects {
+                let _ = writeln!(
+                    xml,
+                    " <objectgroup id=\"{}\" name=\"{}\"{flags}>",
+                    index + 1,
+                    escape_xml(&layer.name)
+                );
+                for (object, id) in layer
+                    .objects
+                    .iter()
+                    .zip(object_ids[index]..)
+                {
+                    write_tmx_object(&mut xml, &to_tiled_object(map, object), id);
+                }
+                xml.push_str(" </objectgroup>\n");
+                continue;
+            }
+            if layer.kind == LayerKind::Image {
+                let _ = writeln!(
+                    xml,
+                    " <imagelayer id=\"{}\" name=\"{}\" parallaxx=\"{}\" parallaxy=\"{}\"{flags}>\n  \
+                     <image source=\"{}\"/>\n </imagelayer>",
+                    index + 1,
+                    escape_xml(&layer.name),
+                    layer.parallax[0],
+                    layer.parallax[1],
+                    escape_xml(&layer_image(layer))
+                );
+                continue;
+            }
+            let _ = writeln!(
+                xml,
+                " <layer id=\"{}\" name=\"{}\" width=\"{}\" height=\"{}\"{flags}>\n  \
+                 <data encoding=\"csv\">",
+                index + 1,
+                escape_xml(&layer.name),
+                map.width,
+                map.height
+            );
+            let rows: Vec<String> = data
+                .chunks(map.width.max(1))
+                .map(|row| {
+                    row.iter()
+                        .map(u32::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .collect();
+            xml.push_str(&rows.join(",\n"));
+            xml.push_str("\n  </data>\n </layer>\n");
+        }
+        xml.push_str("</map>\n");
+        xml
+    } else {
+        let tilesets: Vec<Value> = tilesets
+            .iter()
+            .map(|(first_gid, sheet)| {
+                let (image_width, image_height) = sheet.image_size();
+                json!({
+                    "firstgid": first_gid,
+                    "name": name(sheet),
+                    "tilewidth": sheet.tile_width,
+                    "tileheight": sheet.tile_height,
+                    "tilecount": sheet.ids.len(),
+                    "columns": sheet.columns,
+                    "image": image(sheet),
+                    "imagewidth": image_width,
+                    "imageheight": image_height,
+                    "margin": sheet.margin,
+                    "spacing": sheet.spacing,
+                })
+            })
+            .collect();
+        let layers: Vec<Value> = map
+            .layers
+            .iter()
+            .zip(&layers)
+            .enumerate()
+            .map(|(index, (layer, data))| {
+                let mut value = if layer.kind == LayerKind::Objects {
+                    json!({
+                        "type": "objectgroup",
+                        "id": index + 1,
+                        "name": layer.name,
+                        "x": 0,
+                        "y": 0,
+                        "opacity": layer.opacity.clamp(0.0, 1.0),
+                        "visible": layer.visible,
+                        "locked": layer.locked,
+                        "draworder": "index",
+                        "objects": layer
+                            .objects
+                            .iter()
+                            .zip(object_ids[index]..)
+                            .map(|(object, id)| tmj_object(&to_tiled_object(map, object), id))
+                            .collect::<Vec<_>>(),
+                    })
+                } else if layer.kind == LayerKind::Image {
+                    json!({
+                        "type": "imagelayer",
+                        "id": index + 1,
+                        "name": layer.name,
+                        "x": 0,
+                        "y": 0,
+                        "opacity": layer.opacity.clamp(0.0, 1.0),
+                        "visible": layer.visible,
+                        "locked": layer.locked,
+                        "image": layer_image(layer),
+                        "parallaxx": layer.parallax[0],
+                        "parallaxy": layer.parallax[1],
+                    })
+                } else {
+                    json!({
+                        "type": "tilelayer",
+                        "id": index + 1,
+                        "name": layer.name,
+                        "x": 0,
+                        "y": 0,
+                        "width": map.width,
+                        "height": map.height,
+                        "opacity": layer.opacity.clamp(0.0, 1.0),
+                        "visible": layer.visible,
+                        "locked": layer.locked,
+                        "data": data,
+                    })
+                };
+                if let Some(tint) = layer.tint_color {
+                    value["tintcolor"] = json!(tiled_color(tint));
+                }
+                value
+            })
+            .collect();
+        let document = json!({
+            "type": "map",
+            "version": TILED_FORMAT_VERSION,
+            "orientation": map.projection.name(),
+            "renderorder": "right-down",
+            "width": map.width,
+            "height": map.height,
+            "tilewidth": tile_size.0,
+            "tileheight": tile_size.1,
+            "infinite": false,
+            "nextlayerid": map.layers.len() + 1,
+            "nextobjectid": object_count + 1,
+            "tilesets": tilesets,
+            "layers": layers,
+        });
+        serde_json::to_string_pretty(&document).map_err(io::Error::other)?
+    };
+    fs::write(path, contents)?;
+    Ok(unresolved)
+}
+
+/// Tileset of a Tiled map: its first global id and canonical image path, `None` for
+/// image collection tilesets.
+type Tileset = (u32, Option<PathBuf>);
+
+/// Reads a Tiled map, resolving every tile against the spritesheets of `catalog`.
+///
+/// Tileset images are matched to catalog sheets by path and tile index, so they must be
+/// sliced with the same tile size. Tiles outside every known tileset and other features
+/// ForgeTile cannot represent fail with [`MapLoadError::UnsupportedFeature`].
+pub fn read(path: &Path, catalog: &Catalog) -> Result<MapData, MapLoadError> {
+    let (projection, map_size, tile_size, mut tilesets, layers) = match TiledFile::from_path(path)
+    {
+        Some(TiledFile::Tmx) => read_tmx(&fs::read_to_string(path)?, parent_dir(path))?,
+        Some(TiledFile::Tmj) => read_tmj(&fs::read_to_string(path)?, parent_dir(path))?,
+        None => return Err(MapLoadError::UnsupportedFormat(path.display().to_string())),
+    };
+    tilesets.sort_by_key(|(first_gid, _)| *first_gid);
+
+    let tile_id = |gid: u32| -> Result<Option<String>, MapLoadError> {
+        let gid = gid & !GID_FLAG_MASK;
+        if gid == 0 {
+            return Ok(None);
+        }
+        let (first_gid, image) = tilesets
+            .iter()
+            .rev()
+            .find(|(first_gid, _)| *first_gid <= gid)
+            .ok_or_else(|| unsupported(&format!("tile GID {gid} outside every tileset")))?;
+        let image = image.as_ref().ok_or_else(|| {
+            unsupported(&format!("tile GID {gid} from an image collection tileset"))
+        })?;
+        let index = (gid - first_gid) as usize;
+        catalog
+            .sheet(image)
+            .and_then(|sheet| sheet.ids.get(index))
+            .map(|id| Some(id.clone()))
+            .ok_or_else(|| {
+                unsupported(&format!(
+                    "tile GID {gid} ({}::{index}) missing from the asset catalog",
+                    image.display()
+                ))
+            })
+    };
+
+    let (width, height) = map_size;
+    let mut map = MapData::new(width, height, (tile_size.0 as f32, tile_size.1 as f32));
+    map.projection = projection;
+    for (header, content) in layers {
+        let data = match content {
+            RawContent::Tiles(data) => data,
+            RawContent::Objects(objects) => {
+                let objects = objects
+                    .into_iter()
+                    .map(|object| from_tiled_object(&map, object))
+                    .collect();
+                map.layers.push(LayerData {
+                    kind: LayerKind::Objects,
+                    objects,
+                    ..header
+                });
+                continue;
+            }
+        };
+        let expected = map
+            .cell_count()
+            .ok_or(MapLoadError::InvalidDimensions { width: map.width, height: map.height })?;
+        if data.len() != expected {
+            return Err(MapLoadError::TileCountMismatch { expected, found: data.len() });
+        }
+        let tiles = data
+            .into_iter()
+            .map(&tile_id)
+            .collect::<Result<_, _>>()?;
+        map.layers
+            .push(LayerData { tiles, ..header });
+    }
+    Ok(map)
+}
+
+/// Header (name, visibility, lock state, opacity and tint, no content) and content of a tile
+/// layer or object group.
+type RawLayer = (LayerData, RawContent);
+type RawMap = (MapProjection, (usize, usize), (u32, u32), Vec<Tileset>, Vec<RawLayer>);
+
+enum RawContent {
+    /// Global tile ids of a tile layer.
+    Tiles(Vec<u32>),
+    Objects(Vec<MapObject>),
+}
+fn read_tmx(text: &str, base_dir: &Path) -> Result<RawMap, MapLoadError> {
+    let document = roxmltree::Document::parse(text)?;
+    let root = document.root_element();
+    let projection =
+        check_map(root.attribute("orientation"), root.attribute("infinite") == Some("1"))?;
+
+    let mut tilesets = Vec::new();
+    let mut layers = Vec::new();
+    for node in root.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "tileset" => {
+                let image = match node.attribute("source") {
+                    Some(source) => external_tileset_image(&base_dir.join(source))?,
+                    None => tsx_image(node, base_dir),
+                };
+                tilesets.push((xml_number(node, "firstgid")?, image));
+            }
+            "layer" => {
+                layers.push((tmx_layer_header(node), RawContent::Tiles(tmx_layer_data(node)?)))
+            }
+            "objectgroup" => layers.push((
+                tmx_layer_header(node),
+                RawContent::Objects(
+                    node.children()
+                        .filter(|child| child.has_tag_name("object"))
+                        .map(tmx_object)
+                        .collect(),
+                ),
+            )),
+            "group" => return Err(unsupported("group layers")),
+            _ => {}
+        }
+    }
+
+    Ok((
+        projection,
+        (xml_number(root, "width")?, xml_number(root, "height")?),
+        (xml_number(root, "tilewidth")?, xml_number(root, "tileheight")?),
+        tilesets,
+        layers,
+    ))
+}
+
+fn read_tmj(text: &str, base_dir: &Path) -> Result<RawMap, MapLoadError> {
+    let map: Value = serde_json::from_str(text)?;
+    let projection = check_map(
+        map.get("orientation")
+            .and_then(Value::as_str),
+        map.get("infinite")
+            .and_then(Value::as_bool)
+            == Some(true),
+    )?;
+    let number = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid_data(format!("missing or invalid `{key}`")))
+    };
+    let array = |key: &str| {
+        map.get(key)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut tilesets = Vec::new();
+    for tileset in array("tilesets") {
+        let image = match tileset
+            .get("source")
+            .and_then(Value::as_str)
+        {
+            Some(source) => external_tileset_image(&base_dir.join(source))?,
+            None => tileset
+                .get("image")
+                .and_then(Value::as_str)
+                .map(|image| resolve_image(base_dir, image)),
+        };
+        tilesets.push((number(&tileset, "firstgid")? as u32, image));
+    }
+
+    let mut layers = Vec::new();
+    for layer in array("layers") {
+        match layer
+            .get("type")
+            .and_then(Value::as_str)
+        {
+            Some("tilelayer") => {
+                if let Some(encoding) = layer
+                    .get("encoding")
+                    .and_then(Value::as_str)
+                    && encoding != "csv"
+                {
+                    return Err(unsupported(&format!("{encoding} layer data")));
+                }
+                let data = layer
+                    .get("data")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid_data("tile layer without `data`"))?
+                    .iter()
+                    .map(|gid| {
+                        gid.as_u64()
+                            .and_then(|gid| u32::try_from(gid).ok())
+                            .ok_or_else(|| invalid_data(format!("invalid tile GID {gid}")))
+                    })
+                    .collect::<Result<_, _>>()?;
+                layers.push((tmj_layer_header(&layer), RawContent::Tiles(data)));
+            }
+            Some("objectgroup") => layers.push((
+                tmj_layer_header(&layer),
+                RawContent::Objects(
+                    layer
+                        .get("objects")
+                        .and_then(Value::as_array)
+                        .map(|objects| {
+                            objects
+                                .iter()
+                                .map(tmj_object_data)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                ),
+            )),
+            Some("group") => return Err(unsupported("group layers")),
+            _ => {}
+        }
+    }
+
+    Ok((
+        projection,
+        (number(&map, "width")? as usize, number(&map, "height")? as usize),
+        (number(&map, "tilewidth")? as u32, number(&map, "tileheight")? as u32),
+        tilesets,
+        layers,
+    ))
+}
+
+/// Empty layer carrying the name, visibility, lock state, opacity and tint of a TMX layer.
+fn tmx_layer_header(node: Node) -> LayerData {
+    LayerData {
+        visible: node.attribute("visible") != Some("0"),
+        locked: node.attribute("locked") == Some("1"),
+        opacity: node
+            .attribute("opacity")
+            .and_then(|opacity| opacity.parse().ok())
+            .unwrap_or(1.0),
+        tint_color: node
+            .attribute("tintcolor")
+            .and_then(parse_tiled_color),
+        ..LayerData::new(
+            node.attribute("name")
+                .unwrap_or_default(),
+            Vec::new(),
+        )
+    }
+}
+
+/// Empty layer carrying the name, visibility, lock state, opacity and tint of a TMJ layer.
+fn tmj_layer_header(layer: &Value) -> LayerData {
+    LayerData {
+        visible: layer
+            .get("visible")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        locked: layer
+            .get("locked")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        opacity: layer
+            .get("opacity")
+            .and_then(Value::as_f64)
+            .map_or(1.0, |opacity| opacity as f32),
+        tint_color: layer
+            .get("tintcolor")
+            .and_then(Value::as_str)
+            .and_then(parse_tiled_color),
+        ..LayerData::new(
+            layer
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default(),
+            Vec::new(),
+        )
+    }
+}
+
+/// Formats an RGB color as Tiled's `#rrggbb`.
+fn tiled_color(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Parses Tiled's `#rrggbb` or `#aarrggbb`, dropping the alpha.
+fn parse_tiled_color(text: &str) -> Option<[f32; 3]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    let rgb = match hex.len() {
+        6 => hex,
+        8 => hex.get(2..)?,
+        _ => return None,
+    };
+    let channel = |start: usize| {
+        u8::from_str_radix(rgb.get(start..start + 2)?, 16)
+            .ok()
+            .map(|value| f32::from(value) / 255.0)
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn write_tmx_object(xml: &mut String, object: &MapObject, id: usize) {
+    let _ = write!(xml, "  <object id=\"{id}\" name=\"{}\"", escape_xml(&object.name));
+    if !object.kind.is_empty() {
+        let _ = write!(xml, " type=\"{}\"", escape_xml(&object.kind));
+    }
+    let _ = write!(
+        xml,
+        " x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+        object.x, object.y, object.width, object.height
+    );
+    if object.properties.is_empty() {
+        xml.push_str("/>\n");
+        return;
+    }
+    xml.push_str(">\n   <properties>\n");
+    for (name, value) in &object.properties {
+        let _ = writeln!(
+            xml,
+            "    <property name=\"{}\" value=\"{}\"/>",
+            escape_xml(name),
+            escape_xml(value)
+        );
+    }
+    xml.push_str("   </properties>\n  </object>\n");
+}
+
+fn tmj_object(object: &MapObject, id: usize) -> Value {
+    let mut value = json!({
+        "id": id,
+        "name": object.name,
+        "type": object.kind,
+        "x": object.x,
+        "y": object.y,
+        "width": object.width,
+        "height": object.height,
+        "rotation": 0,
+        "visible": true,
+    });
+    if !object.properties.is_empty() {
+        value["properties"] = object
+            .properties
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "type": "string", "value": value }))
+            .collect();
+    }
+    value
+}
+
+/// Reads an `<object>` element. Shapes other than rectangles keep only their bounds.
+fn tmx_object(node: Node) -> MapObject {
+    let number = |attribute: &str| {
+        node.attribute(attribute)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0.0)
+    };
+    let mut object = MapObject::new(
+        node.attribute("name")
+            .unwrap_or_default(),
+        (number("x"), number("y")),
+        (number("width"), number("height")),
+    );
+    // Tiled 1.9 saved the type as `class`.
+    object.kind = node
+        .attribute("type")
+        .or_else(|| node.attribute("class"))
+        .unwrap_or_default()
+        .to_string();
+    object.properties = node
+        .children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|properties| properties.children())
+        .filter(|property| property.has_tag_name("property"))
+        .filter_map(|property| {
+            let value = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or_default();
+            Some((property.attribute("name")?.to_string(), value.to_string()))
+        })
+        .collect();
+    object
+}
+
+/// Reads an object of a TMJ object group, turning non-string properties into text.
+fn tmj_object_data(value: &Value) -> MapObject {
+    let text = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    };
+    let number = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0) as f32
+    };
+    let mut object = MapObject::new(
+        text("name"),
+        (number("x"), number("y")),
+        (number("width"), number("height")),
+    );
+    object.kind = match text("type") {
+        "" => text("class"),
+        kind => kind,
+    }
+    .to_string();
+    object.properties = value
+        .get("properties")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|property| {
+            let name = property.get("name")?.as_str()?;
+            let value = match property.get("value")? {
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            Some((name.to_string(), value))
+        })
+        .collect();
+    object
+}
+
+/// Reads the image of an external `.tsx` or `.tsj` tileset file.
+fn external_tileset_image(path: &Path) -> Result<Option<PathBuf>, MapLoadError> {
+    let text = fs::read_to_string(path)?;
+    let base_dir = parent_dir(path);
+    if is_tsx(path) {
+        let document = roxmltree::Document::parse(&text)?;
+        return Ok(tsx_image(document.root_element(), base_dir));
+    }
+    let tileset: Value = serde_json::from_str(&text)?;
+    Ok(tileset
+        .get("image")
+        .and_then(Value::as_str)
+        .map(|image| resolve_image(base_dir, image)))
+}
+
+fn is_tsx(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("tsx"))
+}
+
+/// Image of a `<tileset>` element; `None` when its tiles carry their own images.
+fn tsx_image(tileset: Node, base_dir: &Path) -> Option<PathBuf> {
+    tileset
+        .children()
+        .find(|child| child.has_tag_name("image"))
+        .and_then(|image| image.attribute("source"))
+        .map(|source| resolve_image(base_dir, source))
+}
+
+/// Global tile ids of a TMX `<layer>`, stored as CSV or as `<tile gid>` elements.
+fn tmx_layer_data(layer: Node) -> Result<Vec<u32>, MapLoadError> {
+    let data = layer
+        .children()
+        .find(|child| child.has_tag_name("data"))
+        .ok_or_else(|| invalid_data("layer without <data>"))?;
+    match data.attribute("encoding") {
+        Some("csv") => data
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| invalid_data(format!("invalid tile GID {value:?}")))
+            })
+            .collect(),
+        Some(encoding) => Err(unsupported(&format!("{encoding} layer data"))),
+        None => data
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| match tile.attribute("gid") {
+                Some(_) => xml_number(tile, "gid"),
+                None => Ok(0),
+            })
+            .collect(),
+    }
+}
+
+fn xml_number<T: std::str::FromStr>(node: Node, attribute: &str) -> Result<T, MapLoadError> {
+    node.attribute(attribute)
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| {
+            invalid_data(format!(
+                "missing or invalid `{attribute}` on <{}>",
+                node.tag_name().name()
+            ))
+        })
+}
+
+/// Checks that the map can be represented, returning its projection.
+fn check_map(orientation: Option<&str>, infinite: bool) -> Result<MapProjection, MapLoadError> {
+    if infinite {
+        return Err(unsupported("infinite maps"));
+    }
+    match orientation {
+        None => Ok(MapProjection::Orthogonal),
+        Some(name) => MapProjection::from_name(name)
+            .ok_or_else(|| unsupported(&format!("{name} orientation"))),
+    }
+}
+
+/// Tiled places the objects of isometric maps in grid coordinates scaled by the tile
+/// height instead of world pixels. Only the position is converted; the size stays in
+/// pixels along Tiled's isometric axes.
+fn to_tiled_object(map: &MapData, object: &MapObject) -> MapObject {
+    if map.projection.is_orthogonal() {
+        return object.clone();
+    }
+    let (x, y) = map.projection.grid_coords(
+        (object.x, object.y),
+        (map.width, map.height),
+        (map.tile_width, map.tile_height),
+    );
+    MapObject {
+        x: x * map.tile_height,
+        y: y * map.tile_height,
+        ..object.clone()
+    }
+}
+
+/// Inverse of [`to_tiled_object`].
+fn from_tiled_object(map: &MapData, object: MapObject) -> MapObject {
+    if map.projection.is_orthogonal() {
+        return object;
+    }
+    let (x, y) = map.projection.grid_point(
+        (object.x / map.tile_height, object.y / map.tile_height),
+        (map.width, map.height),
+        (map.tile_width, map.tile_height),
+    );
+    MapObject { x, y, ..object }
+}
+
+/// Resolves a path written in a Tiled file against the file's directory, in the
+/// canonical form used by catalog tile ids.
+fn resolve_image(base_dir: &Path, source: &str) -> PathBuf {
+    let path = base_dir.join(source);
+    path.canonicalize().unwrap_or(path)
+}
+
+/// Directory holding `path`, `.` for bare file names.
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unsupported(feature: &str) -> MapLoadError {
+    MapLoadError::UnsupportedFeature(feature.to_string())
+}
+
+/// Error for a Tiled file that parses but is not a well-formed map.
+fn invalid_data(message: impl Into<String>) -> MapLoadError {
+    MapLoadError::Io(io::Error::new(io::ErrorKind::InvalidData, message.into()))
+}
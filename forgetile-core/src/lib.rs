@@ -0,0 +1,16 @@
+//! Map files, tileset catalog and Tiled/CSV interop of the ForgeTile editor, without any
+//! graphics dependency.
+//!
+//! Games and tools can depend on this crate to read maps saved by the editor:
+//! [`map::MapData::read`] loads a `.fgt`/`.json`/`.ron` file, [`assets::catalog::Catalog`]
+//! resolves its tile ids to spritesheet regions, [`interop::tiled`] converts maps to
+//! and from Tiled files and [`interop::csv`] reads and writes single layers as CSV grids.
+//!
+//! With the `macroquad` feature, [`runtime::MapRuntime`] also loads the spritesheets of a
+//! map and offers tile iteration, collision queries, coordinate conversion and drawing.
+
+pub mod assets;
+pub mod interop;
+pub mod map;
+#[cfg(feature = "macroquad")]
+pub mod runtime;
@@ -0,0 +1,233 @@
+//! Compact binary map layout (`.fgtl`).
+//!
+//! Everything but the bulky per-cell grids is stored as compact JSON; tile ids and the
+//! collision grid follow as run-length encoded sections, so mostly empty maps stay small
+//! and load without parsing one JSON string per cell:
+//!
+//! ```text
+//! "FGTL" | version: u16 LE | header length: u32 LE | header: JSON map without tiles or collision
+//! per tile layer, in order:  palette size, palette ids (length + UTF-8), run count, runs
+//! collision:                 run count, run lengths alternating blocked/walkable
+//! ```
+//!
+//! Tile layers nested in groups follow in [`MapData::content_layers`] order. Every number
+//! after the header is an unsigned LEB128 varint. A tile run is its length
+//! followed by its palette symbol: `0` for empty cells, `n` for the `n`-th palette id.
+
+use crate::map::{LayerKind, MapData, MapLoadError};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"FGTL";
+const VERSION: u16 = 1;
+
+/// Encodes `map` in the binary layout.
+pub(crate) fn encode(map: &MapData) -> Result<Vec<u8>, serde_json::Error> {
+    let mut header = map.clone();
+    let mut grids = Vec::new();
+    for layer in header.content_layers_mut() {
+        if layer.kind == LayerKind::Tiles {
+            grids.push(std::mem::take(&mut layer.tiles));
+        }
+    }
+    let collision = std::mem::take(&mut header.collision);
+    let header = serde_json::to_vec(&header)?;
+
+    let mut bytes = Vec::with_capacity(header.len() + 64);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&header);
+    for tiles in &grids {
+        write_tiles(&mut bytes, tiles);
+    }
+    write_collision(&mut bytes, &collision);
+    Ok(bytes)
+}
+
+/// Decodes a map written by [`encode`].
+pub(crate) fn decode(bytes: &[u8]) -> Result<MapData, MapLoadError> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid("not a ForgeTile binary map"));
+    }
+    let version = u16::from_le_bytes(reader.array()?);
+    if version != VERSION {
+        return Err(MapLoadError::UnsupportedFormat(format!("binary map version {version}")));
+    }
+    let header_length = u32::from_le_bytes(reader.array()?) as usize;
+    let mut map = MapData::from_json(reader.take(header_length)?)?;
+
+    let cells = map
+        .cell_count()
+        .ok_or(MapLoadError::InvalidDimensions { width: map.width, height: map.height })?;
+    for layer in map.content_layers_mut() {
+        if layer.kind == LayerKind::Tiles {
+            layer.tiles = reader.tiles(cells)?;
+        }
+    }
+    map.collision = reader.collision(cells)?;
+    if reader.position != bytes.len() {
+        return Err(invalid("trailing bytes after the collision grid"));
+    }
+    Ok(map)
+}
+
+fn write_tiles(bytes: &mut Vec<u8>, tiles: &[Option<String>]) {
+    let mut palette: Vec<&str> = Vec::new();
+    let mut symbols: HashMap<&str, u64> = HashMap::new();
+    let mut runs: Vec<(u64, u64)> = Vec::new();
+    for tile in tiles {
+        let symbol = match tile.as_deref() {
+            None => 0,
+            Some(id) => *symbols.entry(id).or_insert_with(|| {
+                palette.push(id);
+                palette.len() as u64
+            }),
+        };
+        match runs.last_mut() {
+            Some((length, last)) if *last == symbol => *length += 1,
+            _ => runs.push((1, symbol)),
+        }
+    }
+
+    write_varint(bytes, palette.len() as u64);
+    for id in palette {
+        write_varint(bytes, id.len() as u64);
+        bytes.extend_from_slice(id.as_bytes());
+    }
+    write_varint(bytes, runs.len() as u64);
+    for (length, symbol) in runs {
+        write_varint(bytes, length);
+        write_varint(bytes, symbol);
+    }
+}
+
+fn write_collision(bytes: &mut Vec<u8>, collision: &[bool]) {
+    let mut runs: Vec<u64> = Vec::new();
+    let mut current = false;
+    let mut length = 0;
+    for &walkable in collision {
+        if walkable != current {
+            runs.push(length);
+            current = walkable;
+            length = 0;
+        }
+        length += 1;
+    }
+    if !collision.is_empty() {
+        runs.push(length);
+    }
+
+    write_varint(bytes, runs.len() as u64);
+    for length in runs {
+        write_varint(bytes, length);
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], MapLoadError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], MapLoadError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn varint(&mut self) -> Result<u64, MapLoadError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let [byte] = self.array()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint longer than 64 bits"))
+    }
+
+    /// A varint bounded by `limit`, e.g. the cells left to fill.
+    fn count(&mut self, limit: usize) -> Result<usize, MapLoadError> {
+        usize::try_from(self.varint()?)
+            .ok()
+            .filter(|&count| count <= limit)
+            .ok_or_else(|| invalid("count larger than the map"))
+    }
+
+    /// A tile layer: its palette, then runs covering every cell.
+    fn tiles(&mut self, cells: usize) -> Result<Vec<Option<String>>, MapLoadError> {
+        let palette_size = self.count(cells)?;
+        let mut palette = Vec::new();
+        for _ in 0..palette_size {
+            let length = self.count(self.bytes.len())?;
+            let id = std::str::from_utf8(self.take(length)?)
+                .map_err(|_| invalid("tile id is not UTF-8"))?;
+            palette.push(id.to_string());
+        }
+
+        // Runs are checked to cover the grid exactly before any cell is allocated.
+        let mut runs = Vec::new();
+        let mut covered = 0;
+        for _ in 0..self.count(cells)? {
+            let length = self.count(cells - covered)?;
+            let symbol = self.count(palette.len())?;
+            covered += length;
+            runs.push((length, symbol));
+        }
+        if covered != cells {
+            return Err(invalid("tile runs do not cover the map"));
+        }
+        let mut tiles = Vec::with_capacity(cells);
+        for (length, symbol) in runs {
+            let tile = symbol
+                .checked_sub(1)
+                .map(|symbol| palette[symbol].clone());
+            tiles.resize(tiles.len() + length, tile);
+        }
+        Ok(tiles)
+    }
+
+    /// The collision grid: empty, or runs covering every cell.
+    fn collision(&mut self, cells: usize) -> Result<Vec<bool>, MapLoadError> {
+        let mut runs = Vec::new();
+        let mut covered = 0;
+        for _ in 0..self.count(cells + 1)? {
+            let length = self.count(cells - covered)?;
+            covered += length;
+            runs.push(length);
+        }
+        if covered != 0 && covered != cells {
+            return Err(invalid("collision runs do not cover the map"));
+        }
+        let mut collision = Vec::with_capacity(covered);
+        for (run, length) in runs.into_iter().enumerate() {
+            collision.resize(collision.len() + length, run % 2 == 1);
+        }
+        Ok(collision)
+    }
+}
+
+fn invalid(message: &str) -> MapLoadError {
+    MapLoadError::BinaryParse(message.to_string())
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Encoding of a saved map file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapFormat {
+    /// Pretty-printed JSON (`.fgt`, `.json`).
+    Json,
+    /// Compact binary layout (`.fgtl`).
+    Binary,
+    /// Rusty Object Notation (`.ron`).
+    Ron,
+}
+
+impl MapFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            MapFormat::Json => "JSON",
+            MapFormat::Binary => "binary",
+            MapFormat::Ron => "RON",
+        }
+    }
+}
+
+/// Layout of the tile arrays in JSON map files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TileEncoding {
+    /// One entry per cell.
+    #[default]
+    Plain,
+    /// Runs of identical cells collapsed into `[length, tile id]` pairs, marked with
+    /// `"encoding": "rle"` in the file.
+    Rle,
+}
+
+/// Maps file extensions to the format used to read and write them.
+#[derive(Debug, Clone)]
+pub struct FormatRegistry {
+    extension_to_format: HashMap<String, MapFormat>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        let mut registry = Self { extension_to_format: HashMap::new() };
+        registry.register("fgt", MapFormat::Json);
+        registry.register("json", MapFormat::Json);
+        registry.register("fgtl", MapFormat::Binary);
+        registry.register("ron", MapFormat::Ron);
+        registry
+    }
+}
+
+impl FormatRegistry {
+    /// Associates `extension` (without the dot, case-insensitive) with `format`.
+    pub fn register(&mut self, extension: &str, format: MapFormat) {
+        self.extension_to_format.insert(
+            extension
+                .trim_start_matches('.')
+                .to_ascii_lowercase(),
+            format,
+        );
+    }
+
+    /// Returns the format registered for the extension of `path`.
+    pub fn format_for(&self, path: &Path) -> Option<MapFormat> {
+        let extension = path
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+        self.extension_to_format
+            .get(&extension)
+            .copied()
+    }
+}
@@ -0,0 +1,642 @@
+mod binary;
+pub mod format;
+pub mod object;
+pub mod projection;
+pub mod property;
+pub mod reference;
+mod rle;
+pub mod stats;
+pub mod tile;
+
+use crate::map::format::{FormatRegistry, MapFormat, TileEncoding};
+use crate::map::object::MapObject;
+use crate::map::projection::MapProjection;
+use crate::map::property::Properties;
+use crate::map::reference::ReferenceImage;
+use crate::map::tile::TileTransform;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Contents of a saved map file: the grid size and every layer's tile ids and cell data.
+///
+/// Tiles are referenced by id; resolve them against an
+/// [`assets::catalog::Catalog`](crate::assets::catalog::Catalog) to find their spritesheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapData {
+    /// Save format revision the file was written with; see [`MapData::VERSION`].
+    #[serde(default = "unversioned")]
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    pub tile_width: f32,
+    pub tile_height: f32,
+    /// Layout of the cells; omitted for orthogonal maps.
+    #[serde(default, skip_serializing_if = "MapProjection::is_orthogonal")]
+    pub projection: MapProjection,
+    /// Single-layer grid of maps saved before layers existed; moved into `layers` on read.
+    #[serde(default, rename = "tiles", skip_serializing_if = "Vec::is_empty")]
+    legacy_tiles: Vec<Option<String>>,
+    #[serde(default, rename = "tints", skip_serializing_if = "Vec::is_empty")]
+    legacy_tints: Vec<Option<[f32; 4]>>,
+    #[serde(default, rename = "z_priorities", skip_serializing_if = "Vec::is_empty")]
+    legacy_z_priorities: Vec<i32>,
+    /// Layer stack, bottom layer first. Group layers nest their children; see
+    /// [`MapData::content_layers`] and [`MapData::flatten_groups`] for a flat view.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<LayerData>,
+    /// Per-cell walkability, row-major; empty when every cell is walkable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collision: Vec<bool>,
+    /// Custom properties of the whole map.
+    #[serde(default, skip_serializing_if = "Properties::is_empty")]
+    pub properties: Properties,
+    /// Tracing image shown by the editor; never part of the map's content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<ReferenceImage>,
+    /// Layout of the tile arrays when written as JSON; read from the file's `encoding`.
+    #[serde(default, skip_serializing)]
+    pub encoding: TileEncoding,
+}
+
+/// One layer of a [`MapData`]. Every per-cell vector is row-major and parallel to `tiles`;
+/// the optional ones are empty when no cell uses them.
+///
+/// A group layer holds no cells of its own: its visibility, lock and opacity apply to every
+/// layer nested in `layers`. An image layer holds no cells either and draws `image` behind
+/// the tile layers, scrolling by `parallax`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerData {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "LayerKind::is_tiles")]
+    pub kind: LayerKind,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub locked: bool,
+    /// Opacity of the whole layer, `0.0..=1.0`; omitted when fully opaque.
+    #[serde(default = "default_opacity", skip_serializing_if = "is_opaque")]
+    pub opacity: f32,
+    /// RGB color multiplied into every tile of the layer; omitted when untinted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tint_color: Option<[f32; 3]>,
+    /// Tile id of every cell; empty for object layers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "rle::deserialize"
+    )]
+    pub tiles: Vec<Option<String>>,
+    /// Per-tile RGBA tint; omitted when no tile is tinted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tints: Vec<Option<[f32; 4]>>,
+    /// Per-tile render priority; omitted when every tile uses 0.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub z_priorities: Vec<i32>,
+    /// Per-tile rotation and flips; omitted when no tile is transformed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<TileTransform>,
+    /// Objects of an object layer, drawn in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<MapObject>,
+    /// Custom properties of the layer.
+    #[serde(default, skip_serializing_if = "Properties::is_empty")]
+    pub properties: Properties,
+    /// Per-tile custom properties; omitted when no tile has any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cell_properties: Vec<Properties>,
+    /// Children of a group layer, bottom layer first; empty for other kinds.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<LayerData>,
+    /// Image file drawn by an image layer, as typed in the editor; relative paths start at
+    /// the working directory, like the assets directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Horizontal and vertical scroll factors relative to the camera: `1.0` moves with the
+    /// map, `0.0` stays fixed on screen and values in between scroll slower, for distant
+    /// backgrounds. Omitted when both are `1.0`.
+    #[serde(default = "default_parallax", skip_serializing_if = "is_default_parallax")]
+    pub parallax: [f32; 2],
+    /// Whether the editor folds the children of a group layer away in its layers panel;
+    /// they are still drawn. Omitted when unfolded.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub collapsed: bool,
+}
+
+/// What a layer holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerKind {
+    /// A grid of painted tiles.
+    #[default]
+    Tiles,
+    /// Free-positioned [`MapObject`]s.
+    Objects,
+    /// A folder of other layers.
+    Group,
+    /// A background image scrolling at its own parallax factor.
+    Image,
+}
+
+impl LayerKind {
+    fn is_tiles(&self) -> bool {
+        *self == LayerKind::Tiles
+    }
+}
+
+/// Version of files saved before the field existed.
+fn unversioned() -> u32 {
+    1
+}
+
+/// Just the version of a map file, read when the full map fails to parse.
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default = "unversioned")]
+    version: u32,
+}
+
+/// Just the size of a map file, read first to bound its run-length encoded tile arrays.
+#[derive(Deserialize)]
+struct SizeProbe {
+    width: usize,
+    height: usize,
+}
+
+impl SizeProbe {
+    /// Like [`MapData::cell_count`], reporting an unusable size as an error.
+    fn cell_count(&self) -> Result<usize, MapLoadError> {
+        self.width
+            .checked_mul(self.height)
+            .filter(|&cells| cells <= MapData::MAX_CELLS)
+            .ok_or(MapLoadError::InvalidDimensions { width: self.width, height: self.height })
+    }
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn is_opaque(opacity: &f32) -> bool {
+    *opacity >= 1.0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn default_parallax() -> [f32; 2] {
+    [1.0; 2]
+}
+
+fn is_default_parallax(parallax: &[f32; 2]) -> bool {
+    *parallax == default_parallax()
+}
+
+impl MapData {
+    /// Save format revision written by this version of the crate.
+    ///
+    /// 1. Files without a `version`; the oldest keep a single grid in top-level `tiles`,
+    ///    `tints` and `z_priorities`.
+    /// 2. Every grid lives in `layers`.
+    /// 3. Layers can be grouped: `"kind": "group"` layers nest their children in `layers`.
+    /// 4. `"kind": "image"` layers draw a background `image` scrolled by `parallax`.
+    pub const VERSION: u32 = 4;
+
+    /// Cells a map file may describe, 16 times the largest map the editor creates, so a
+    /// corrupt size or run length cannot exhaust memory.
+    pub const MAX_CELLS: usize = 1 << 24;
+
+    /// Creates a map of `width` x `height` tiles of `tile_size` pixels without any layer.
+    pub fn new(width: usize, height: usize, tile_size: (f32, f32)) -> Self {
+        Self {
+            version: Self::VERSION,
+            width,
+            height,
+            tile_width: tile_size.0,
+            tile_height: tile_size.1,
+            projection: MapProjection::Orthogonal,
+            legacy_tiles: Vec::new(),
+            legacy_tints: Vec::new(),
+            legacy_z_priorities: Vec::new(),
+            layers: Vec::new(),
+            collision: Vec::new(),
+            properties: Properties::new(),
+            reference: None,
+            encoding: TileEncoding::Plain,
+        }
+    }
+
+    /// Reads a map file, picking the decoder from its extension through `formats`.
+    ///
+    /// Files written by older versions are upgraded to [`MapData::VERSION`] (maps saved
+    /// before layers existed come back as a single layer), files from newer versions fail
+    /// with [`MapLoadError::UnsupportedVersion`], and every layer and the collision grid are
+    /// checked against the map size.
+    pub fn read(path: &Path, formats: &FormatRegistry) -> Result<Self, MapLoadError> {
+        let mut data: MapData = match formats.format_for(path) {
+            Some(MapFormat::Json) => Self::from_json(fs::read_to_string(path)?.as_bytes())?,
+            Some(MapFormat::Ron) => {
+                let text = fs::read_to_string(path)?;
+                let cells = match ron::from_str::<SizeProbe>(&text) {
+                    Ok(probe) => probe.cell_count()?,
+                    // The full parse below reports what is wrong with the file.
+                    Err(_) => 0,
+                };
+                rle::with_cell_limit(cells, || ron::from_str(&text)).map_err(|err| {
+                    ron::from_str::<VersionProbe>(&text)
+                        .ok()
+                        .and_then(|probe| newer_version(probe.version))
+                        .unwrap_or(MapLoadError::from(err))
+                })?
+            }
+            Some(MapFormat::Binary) => binary::decode(&fs::read(path)?)?,
+            None => {
+                let extension = path
+                    .extension()
+                    .map(|ext| format!(".{}", ext.to_string_lossy()))
+                    .unwrap_or_else(|| "(none)".to_string());
+                return Err(MapLoadError::UnsupportedFormat(extension));
+            }
+        };
+
+        data.migrate()?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Parses a JSON map, reporting files from newer versions as
+    /// [`MapLoadError::UnsupportedVersion`] rather than as parse errors.
+    pub(crate) fn from_json(json: &[u8]) -> Result<Self, MapLoadError> {
+        let cells = match serde_json::from_slice::<SizeProbe>(json) {
+            Ok(probe) => probe.cell_count()?,
+            // The full parse below reports what is wrong with the file.
+            Err(_) => 0,
+        };
+        rle::with_cell_limit(cells, || serde_json::from_slice(json)).map_err(|err| {
+            serde_json::from_slice::<VersionProbe>(json)
+                .ok()
+                .and_then(|probe| newer_version(probe.version))
+                .unwrap_or(MapLoadError::from(err))
+        })
+    }
+
+    /// Upgrades a map read from a file to [`MapData::VERSION`], one version at a time.
+    fn migrate(&mut self) -> Result<(), MapLoadError> {
+        if let Some(err) = newer_version(self.version) {
+            return Err(err);
+        }
+        while self.version < Self::VERSION {
+            if self.version <= 1 && self.layers.is_empty() {
+                self.layers = vec![LayerData {
+                    tints: std::mem::take(&mut self.legacy_tints),
+                    z_priorities: std::mem::take(&mut self.legacy_z_priorities),
+                    ..LayerData::new("Layer 1", std::mem::take(&mut self.legacy_tiles))
+                }];
+            }
+            self.version += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes the map, picking the encoder from the extension of `path` through `formats`.
+    pub fn write(&self, path: &Path, formats: &FormatRegistry) -> Result<(), io::Error> {
+        let data = match formats.format_for(path) {
+            Some(MapFormat::Json) => match self.encoding {
+                TileEncoding::Plain => serde_json::to_vec_pretty(self),
+                TileEncoding::Rle => serde_json::to_vec_pretty(&self.rle_json()?),
+            }
+            .map_err(io::Error::other)?,
+            Some(MapFormat::Ron) => ron::ser::to_string_pretty(self, Default::default())
+                .map_err(io::Error::other)?
+                .into_bytes(),
+            Some(MapFormat::Binary) => binary::encode(self).map_err(io::Error::other)?,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no map format registered for {}", path.display()),
+                ));
+            }
+        };
+        fs::write(path, data)
+    }
+
+    /// The map as JSON with `"encoding": "rle"` and run-length encoded tile arrays.
+    fn rle_json(&self) -> Result<serde_json::Value, io::Error> {
+        let mut value = serde_json::to_value(self).map_err(io::Error::other)?;
+        value["encoding"] = serde_json::to_value(TileEncoding::Rle).map_err(io::Error::other)?;
+        if let Some(layers) = value
+            .get_mut("layers")
+            .and_then(serde_json::Value::as_array_mut)
+        {
+            rle_encode_layers(layers, &self.layers);
+        }
+        Ok(value)
+    }
+
+    /// Number of cells of every layer, or `None` when `width * height` overflows or exceeds
+    /// [`MapData::MAX_CELLS`].
+    pub fn cell_count(&self) -> Option<usize> {
+        self.width
+            .checked_mul(self.height)
+            .filter(|&cells| cells <= Self::MAX_CELLS)
+    }
+
+    /// Tile, object and image layers in stack order, bottom first, with groups opened up.
+    ///
+    /// The layers keep their own visibility, lock and opacity; use
+    /// [`MapData::flatten_groups`] to fold in those of their groups.
+    pub fn content_layers(&self) -> Vec<&LayerData> {
+        fn collect<'a>(layers: &'a [LayerData], out: &mut Vec<&'a LayerData>) {
+            for layer in layers {
+                match layer.kind {
+                    LayerKind::Group => collect(&layer.layers, out),
+                    _ => out.push(layer),
+                }
+            }
+        }
+        let mut layers = Vec::new();
+        collect(&self.layers, &mut layers);
+        layers
+    }
+
+    /// Mutable [`MapData::content_layers`].
+    pub fn content_layers_mut(&mut self) -> Vec<&mut LayerData> {
+        fn collect<'a>(layers: &'a mut [LayerData], out: &mut Vec<&'a mut LayerData>) {
+            for layer in layers {
+                match layer.kind {
+                    LayerKind::Group => collect(&mut layer.layers, out),
+                    _ => out.push(layer),
+                }
+            }
+        }
+        let mut layers = Vec::new();
+        collect(&mut self.layers, &mut layers);
+        layers
+    }
+
+    /// Replaces every group by its children, for readers that only know flat layer stacks.
+    ///
+    /// See [`LayerData::flatten`] for how group settings carry over to the children.
+    pub fn flatten_groups(&mut self) {
+        self.layers = std::mem::take(&mut self.layers)
+            .into_iter()
+            .flat_map(LayerData::flatten)
+            .collect();
+    }
+
+    /// Checks that the map size is representable and that every tile layer and the
+    /// collision grid hold one entry per cell.
+    pub fn validate(&self) -> Result<(), MapLoadError> {
+        let expected = self
+            .cell_count()
+            .ok_or(MapLoadError::InvalidDimensions { width: self.width, height: self.height })?;
+        let mismatch = self
+            .content_layers()
+            .into_iter()
+            .filter(|layer| layer.kind == LayerKind::Tiles)
+            .map(|layer| layer.tiles.len())
+            .chain((!self.collision.is_empty()).then_some(self.collision.len()))
+            .find(|&found| found != expected);
+        match mismatch {
+            Some(found) => Err(MapLoadError::TileCountMismatch { expected, found }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Replaces the `tiles` of every tile layer in `json` with their run-length encoding,
+/// descending into groups.
+fn rle_encode_layers(json: &mut [serde_json::Value], layers: &[LayerData]) {
+    for (json, layer) in json.iter_mut().zip(layers) {
+        if !layer.tiles.is_empty() {
+            json["tiles"] = rle::encode(&layer.tiles);
+        }
+        if let Some(children) = json
+            .get_mut("layers")
+            .and_then(serde_json::Value::as_array_mut)
+        {
+            rle_encode_layers(children, &layer.layers);
+        }
+    }
+}
+
+/// The error for a file written with `version` when it is newer than [`MapData::VERSION`].
+fn newer_version(version: u32) -> Option<MapLoadError> {
+    (version > MapData::VERSION).then_some(MapLoadError::UnsupportedVersion {
+        found: version,
+        supported: MapData::VERSION,
+    })
+}
+
+impl LayerData {
+    /// Creates a visible, unlocked layer holding `tiles` without tints or transforms.
+    pub fn new(name: &str, tiles: Vec<Option<String>>) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: LayerKind::Tiles,
+            visible: true,
+            locked: false,
+            opacity: 1.0,
+            tint_color: None,
+            tiles,
+            tints: Vec::new(),
+            z_priorities: Vec::new(),
+            transforms: Vec::new(),
+            objects: Vec::new(),
+            properties: Properties::new(),
+            cell_properties: Vec::new(),
+            layers: Vec::new(),
+            image: None,
+            parallax: default_parallax(),
+            collapsed: false,
+        }
+    }
+
+    /// Creates a visible, unlocked group holding `layers`, bottom layer first.
+    pub fn group(name: &str, layers: Vec<LayerData>) -> Self {
+        Self {
+            kind: LayerKind::Group,
+            layers,
+            ..Self::new(name, Vec::new())
+        }
+    }
+
+    /// The layers of a group with nested groups opened up, or the layer itself for other
+    /// kinds.
+    ///
+    /// A child ends up hidden when its group is hidden, locked when its group is locked, and
+    /// with its opacity multiplied by the group's.
+    pub fn flatten(self) -> Vec<LayerData> {
+        if self.kind != LayerKind::Group {
+            return vec![self];
+        }
+        self.layers
+            .into_iter()
+            .flat_map(LayerData::flatten)
+            .map(|child| LayerData {
+                visible: child.visible && self.visible,
+                locked: child.locked || self.locked,
+                opacity: child.opacity * self.opacity,
+                ..child
+            })
+            .collect()
+    }
+
+    /// Creates a visible, unlocked image layer drawing `image` with the given `parallax`
+    /// factors.
+    pub fn image_layer(name: &str, image: &str, parallax: [f32; 2]) -> Self {
+        Self {
+            kind: LayerKind::Image,
+            image: Some(image.to_string()),
+            parallax,
+            ..Self::new(name, Vec::new())
+        }
+    }
+
+    /// World position of the top-left corner of an image layer while the camera is
+    /// centered on `camera_center`, with the map origin as parallax origin.
+    pub fn parallax_origin(&self, camera_center: [f32; 2]) -> [f32; 2] {
+        let [x, y] = camera_center;
+        [
+            x * (1.0 - self.parallax[0]),
+            y * (1.0 - self.parallax[1]),
+        ]
+    }
+
+    /// Creates a visible, unlocked object layer holding `objects`.
+    pub fn object_layer(name: &str, objects: Vec<MapObject>) -> Self {
+        Self {
+            kind: LayerKind::Objects,
+            objects,
+            ..Self::new(name, Vec::new())
+        }
+    }
+
+    /// RGBA color every tile of the layer is multiplied by: the tint color, or white, with
+    /// the opacity as alpha.
+    pub fn modulation(&self) -> [f32; 4] {
+        let [r, g, b] = self.tint_color.unwrap_or([1.0; 3]);
+        [r, g, b, self.opacity.clamp(0.0, 1.0)]
+    }
+
+    /// RGBA tint of the cell at `index`, if it has one.
+    pub fn tint(&self, index: usize) -> Option<[f32; 4]> {
+        self.tints.get(index).copied().flatten()
+    }
+
+    /// Render priority of the cell at `index`.
+    pub fn z_priority(&self, index: usize) -> i32 {
+        self.z_priorities
+            .get(index)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Custom properties of the cell at `index`; `None` when it has none.
+    pub fn cell_properties(&self, index: usize) -> Option<&Properties> {
+        self.cell_properties
+            .get(index)
+            .filter(|properties| !properties.is_empty())
+    }
+
+    /// Orientation of the cell at `index`, snapped to a multiple of 90 degrees.
+    pub fn transform(&self, index: usize) -> TileTransform {
+        self.transforms
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+            .normalized()
+    }
+}
+
+/// Possible failures when loading a map from disk.
+#[derive(Debug)]
+pub enum MapLoadError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    RonParse(ron::error::SpannedError),
+    XmlParse(roxmltree::Error),
+    /// Malformed binary (`.fgtl`) map.
+    BinaryParse(String),
+    UnsupportedFormat(String),
+    /// The file uses something the editor cannot represent, such as a tile outside every
+    /// known tileset.
+    UnsupportedFeature(String),
+    TileCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The map has more cells than [`MapData::MAX_CELLS`] or than can be represented.
+    InvalidDimensions {
+        width: usize,
+        height: usize,
+    },
+    UnknownTile(String),
+    /// The file was written by a newer version of the save format.
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl From<io::Error> for MapLoadError {
+    fn from(value: io::Error) -> Self {
+        MapLoadError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for MapLoadError {
+    fn from(value: serde_json::Error) -> Self {
+        MapLoadError::Parse(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for MapLoadError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        MapLoadError::RonParse(value)
+    }
+}
+
+impl From<roxmltree::Error> for MapLoadError {
+    fn from(value: roxmltree::Error) -> Self {
+        MapLoadError::XmlParse(value)
+    }
+}
+
+impl std::fmt::Display for MapLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapLoadError::Io(err) => write!(f, "IO error: {err}"),
+            MapLoadError::Parse(err) => write!(f, "JSON parse error: {err}"),
+            MapLoadError::RonParse(err) => write!(f, "RON parse error: {err}"),
+            MapLoadError::XmlParse(err) => write!(f, "XML parse error: {err}"),
+            MapLoadError::BinaryParse(message) => write!(f, "Binary map error: {message}"),
+            MapLoadError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported map format: {format}")
+            }
+            MapLoadError::UnsupportedFeature(feature) => {
+                write!(f, "Unsupported map feature: {feature}")
+            }
+            MapLoadError::TileCountMismatch { expected, found } => {
+                write!(f, "Tile count mismatch. Expected {expected}, found {found}")
+            }
+            MapLoadError::InvalidDimensions { width, height } => {
+                write!(f, "Invalid map dimensions {width}x{height}")
+            }
+            MapLoadError::UnknownTile(id) => write!(f, "Unknown tile id: {id}"),
+            MapLoadError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Map file version {found} is newer than the supported version {supported}; \
+                 update the editor to open it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MapLoadError {}
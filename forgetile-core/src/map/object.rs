@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Free-positioned marker of an object layer, such as a spawn point, trigger or NPC.
+///
+/// Position and size are in pixels from the top-left corner of the map, independent of the
+/// tile grid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapObject {
+    pub name: String,
+    /// Game-defined category, e.g. `spawn` or `door`.
+    #[serde(default, rename = "type")]
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Free-form key/value properties read by the game.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+impl MapObject {
+    /// Creates an object without type or properties.
+    pub fn new(name: &str, position: (f32, f32), size: (f32, f32)) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: String::new(),
+            x: position.0,
+            y: position.1,
+            width: size.0,
+            height: size.1,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Returns whether the pixel position `(x, y)` lies inside the object.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+/// How grid cells are laid out in world space.
+///
+/// Positions are in world units (pixels at zoom 1) and map sizes in cells. Grid points are
+/// cell corners: `(x, y)` is the top-left corner of cell `(x, y)` on orthogonal maps and its
+/// top corner on isometric ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapProjection {
+    /// Rectangular cells in rows and columns.
+    #[default]
+    Orthogonal,
+    /// Diamond-shaped cells of `tile_width` x `tile_height`, with the x axis running down
+    /// to the right and the y axis down to the left from the top corner of the map, like
+    /// Tiled's isometric orientation.
+    Isometric,
+}
+
+impl MapProjection {
+    /// Every projection, in the order shown by the new map dialog.
+    pub const ALL: [MapProjection; 2] = [
+        MapProjection::Orthogonal,
+        MapProjection::Isometric,
+    ];
+
+    /// Stable name used for display and by Tiled's `orientation` attribute.
+    pub fn name(self) -> &'static str {
+        match self {
+            MapProjection::Orthogonal => "orthogonal",
+            MapProjection::Isometric => "isometric",
+        }
+    }
+
+    /// Parses a name produced by [`MapProjection::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|projection| projection.name() == name)
+    }
+
+    pub fn is_orthogonal(&self) -> bool {
+        *self == MapProjection::Orthogonal
+    }
+
+    /// Size of the area covered by a map of `map_size` cells.
+    pub fn world_size(self, map_size: (usize, usize), tile_size: (f32, f32)) -> (f32, f32) {
+        let (width, height) = (map_size.0 as f32, map_size.1 as f32);
+        match self {
+            MapProjection::Orthogonal => (width * tile_size.0, height * tile_size.1),
+            MapProjection::Isometric => {
+                ((width + height) * tile_size.0 / 2.0, (width + height) * tile_size.1 / 2.0)
+            }
+        }
+    }
+
+    /// World position of the grid point `point`, which may have fractional or out-of-map
+    /// coordinates.
+    pub fn grid_point(
+        self, point: (f32, f32), map_size: (usize, usize), tile_size: (f32, f32),
+    ) -> (f32, f32) {
+        match self {
+            MapProjection::Orthogonal => (point.0 * tile_size.0, point.1 * tile_size.1),
+            MapProjection::Isometric => {
+                let origin_x = map_size.1 as f32 * tile_size.0 / 2.0;
+                (
+                    origin_x + (point.0 - point.1) * tile_size.0 / 2.0,
+                    (point.0 + point.1) * tile_size.1 / 2.0,
+                )
+            }
+        }
+    }
+
+    /// Grid coordinates of the world position `position`, the inverse of
+    /// [`MapProjection::grid_point`]. The integer parts name the cell.
+    pub fn grid_coords(
+        self, position: (f32, f32), map_size: (usize, usize), tile_size: (f32, f32),
+    ) -> (f32, f32) {
+        match self {
+            MapProjection::Orthogonal => (position.0 / tile_size.0, position.1 / tile_size.1),
+            MapProjection::Isometric => {
+                let origin_x = map_size.1 as f32 * tile_size.0 / 2.0;
+                let across = (position.0 - origin_x) / tile_size.0;
+                let down = position.1 / tile_size.1;
+                (down + across, down - across)
+            }
+        }
+    }
+
+    /// Cell containing the world position `position`, or `None` outside the map.
+    pub fn cell_at(
+        self, position: (f32, f32), map_size: (usize, usize), tile_size: (f32, f32),
+    ) -> Option<(usize, usize)> {
+        let (x, y) = self.grid_coords(position, map_size, tile_size);
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (x, y) = (x.floor() as usize, y.floor() as usize);
+        (x < map_size.0 && y < map_size.1).then_some((x, y))
+    }
+
+    /// Top-left corner of the `tile_size` box drawing cell `cell`.
+    pub fn cell_origin(
+        self, cell: (usize, usize), map_size: (usize, usize), tile_size: (f32, f32),
+    ) -> (f32, f32) {
+        let (x, y) = (cell.0 as f32, cell.1 as f32);
+        let (left, _) = self.grid_point((x, y + 1.0), map_size, tile_size);
+        let (_, top) = self.grid_point((x, y), map_size, tile_size);
+        (left, top)
+    }
+
+    /// Outline of the block of `size` cells starting at grid point `origin`, clockwise from
+    /// that point.
+    pub fn block_corners(
+        self, origin: (usize, usize), size: (usize, usize), map_size: (usize, usize),
+        tile_size: (f32, f32),
+    ) -> [(f32, f32); 4] {
+        let (x0, y0) = (origin.0 as f32, origin.1 as f32);
+        let (x1, y1) = (x0 + size.0 as f32, y0 + size.1 as f32);
+        [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+            .map(|point| self.grid_point(point, map_size, tile_size))
+    }
+}
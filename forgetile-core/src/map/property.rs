@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Custom properties attached to a map, a layer or a painted cell, keyed by name.
+pub type Properties = BTreeMap<String, PropertyValue>;
+
+/// Value of a custom property, stored as the matching JSON or RON literal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// Type of a [`PropertyValue`], as offered by the properties editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropertyType {
+    #[default]
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl PropertyType {
+    /// Every type, in the order shown by the editor.
+    pub const ALL: [PropertyType; 4] = [
+        PropertyType::String,
+        PropertyType::Int,
+        PropertyType::Float,
+        PropertyType::Bool,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PropertyType::String => "string",
+            PropertyType::Int => "int",
+            PropertyType::Float => "float",
+            PropertyType::Bool => "bool",
+        }
+    }
+
+    /// Parses `text` as a value of this type; `None` when it does not fit.
+    ///
+    /// Booleans accept `true`/`false` and `1`/`0`.
+    pub fn parse(self, text: &str) -> Option<PropertyValue> {
+        let trimmed = text.trim();
+        match self {
+            PropertyType::String => Some(PropertyValue::String(text.to_string())),
+            PropertyType::Int => trimmed
+                .parse()
+                .ok()
+                .map(PropertyValue::Int),
+            PropertyType::Float => trimmed
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(PropertyValue::Float),
+            PropertyType::Bool => match trimmed {
+                "true" | "1" => Some(PropertyValue::Bool(true)),
+                "false" | "0" => Some(PropertyValue::Bool(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl PropertyValue {
+    pub fn kind(&self) -> PropertyType {
+        match self {
+            PropertyValue::Bool(_) => PropertyType::Bool,
+            PropertyValue::Int(_) => PropertyType::Int,
+            PropertyValue::Float(_) => PropertyType::Float,
+            PropertyValue::String(_) => PropertyType::String,
+        }
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Bool(value) => write!(f, "{value}"),
+            PropertyValue::Int(value) => write!(f, "{value}"),
+            PropertyValue::Float(value) => write!(f, "{value}"),
+            PropertyValue::String(value) => f.write_str(value),
+        }
+    }
+}
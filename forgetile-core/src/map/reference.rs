@@ -0,0 +1,51 @@
+//! Tracing image shown over a map while editing it.
+
+use serde::{Deserialize, Serialize};
+
+/// Image (concept art, a scanned sketch) drawn over the map in the editor so a layout can be
+/// traced. Only its settings are saved; it is never painted into a layer and the runtime
+/// ignores it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceImage {
+    /// Image file, as typed by the user.
+    pub path: String,
+    /// Position of the image's top-left corner, in tiles from the map origin.
+    #[serde(default)]
+    pub offset: [f32; 2],
+    /// Size multiplier applied to the image's pixel size.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Opacity of the overlay, `0.0..=1.0`.
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+}
+
+impl ReferenceImage {
+    /// Smallest and largest scale offered by the editor.
+    pub const SCALE_RANGE: (f32, f32) = (0.1, 8.0);
+
+    /// Shows `path` at the map origin, at its pixel size and half transparent.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            offset: [0.0; 2],
+            scale: default_scale(),
+            opacity: default_opacity(),
+            visible: true,
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_opacity() -> f32 {
+    0.5
+}
+
+fn default_visible() -> bool {
+    true
+}
@@ -0,0 +1,143 @@
+//! Run-length encoded tile arrays of JSON maps (`"encoding": "rle"`).
+//!
+//! An encoded array mixes plain entries (a tile id or `null`) for single cells with
+//! `[run length, tile id or null]` pairs for runs of two or more identical cells. The
+//! decoder accepts both, so plain and encoded maps load through the same path.
+
+use crate::map::MapData;
+use serde::Deserializer;
+use serde::de::{Error, SeqAccess, Visitor};
+use serde_json::{Value, json};
+use std::cell::Cell;
+use std::fmt;
+
+thread_local! {
+    /// Cells a tile array may expand to: the size of the map being read while
+    /// [`with_cell_limit`] runs, otherwise [`MapData::MAX_CELLS`].
+    static CELL_LIMIT: Cell<usize> = const { Cell::new(MapData::MAX_CELLS) };
+}
+
+/// Runs `read` with the tile arrays it decodes bounded by `cells`, the cell count of the
+/// map being read, so corrupt run lengths cannot expand past the map.
+pub(crate) fn with_cell_limit<T>(cells: usize, read: impl FnOnce() -> T) -> T {
+    let previous = CELL_LIMIT.replace(cells.min(MapData::MAX_CELLS));
+    let result = read();
+    CELL_LIMIT.set(previous);
+    result
+}
+
+/// Encodes `tiles` as plain entries and `[length, id]` runs.
+pub(crate) fn encode(tiles: &[Option<String>]) -> Value {
+    let mut entries = Vec::new();
+    let mut index = 0;
+    while index < tiles.len() {
+        let tile = &tiles[index];
+        let length = tiles[index..]
+            .iter()
+            .take_while(|other| *other == tile)
+            .count();
+        entries.push(match length {
+            1 => json!(tile),
+            _ => json!([length, tile]),
+        });
+        index += length;
+    }
+    Value::Array(entries)
+}
+
+/// Deserializes a tile array written plain or by [`encode`].
+pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(TilesVisitor)
+}
+
+struct TilesVisitor;
+
+impl<'de> Visitor<'de> for TilesVisitor {
+    type Value = Vec<Option<String>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of tile ids, nulls and [length, tile id] runs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut tiles = Vec::new();
+        while let Some(entry) = seq.next_element::<Entry>()? {
+            match entry {
+                Entry::Tile(tile) => tiles.push(tile),
+                Entry::Run(length, tile) => {
+                    let limit = CELL_LIMIT.get();
+                    let expanded = tiles
+                        .len()
+                        .checked_add(length)
+                        .filter(|&expanded| expanded <= limit)
+                        .ok_or_else(|| {
+                            Error::custom(format!("tile runs expand past the map's {limit} cells"))
+                        })?;
+                    tiles.resize(expanded, tile);
+                }
+            }
+        }
+        Ok(tiles)
+    }
+}
+
+/// One element of a tile array.
+enum Entry {
+    Tile(Option<String>),
+    Run(usize, Option<String>),
+}
+
+impl<'de> serde::Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+struct EntryVisitor;
+
+impl<'de> Visitor<'de> for EntryVisitor {
+    type Value = Entry;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tile id, null or a [length, tile id] run")
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Entry, E> {
+        Ok(Entry::Tile(None))
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Entry, E> {
+        Ok(Entry::Tile(None))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Entry, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_str<E: Error>(self, id: &str) -> Result<Entry, E> {
+        Ok(Entry::Tile(Some(id.to_string())))
+    }
+
+    fn visit_string<E: Error>(self, id: String) -> Result<Entry, E> {
+        Ok(Entry::Tile(Some(id)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Entry, A::Error> {
+        let length: usize = seq
+            .next_element()?
+            .ok_or_else(|| Error::invalid_length(0, &self))?;
+        let tile: Option<String> = seq
+            .next_element()?
+            .ok_or_else(|| Error::invalid_length(1, &self))?;
+        if seq
+            .next_element::<serde::de::IgnoredAny>()?
+            .is_some()
+        {
+            return Err(Error::invalid_length(3, &self));
+        }
+        Ok(Entry::Run(length, tile))
+    }
+}
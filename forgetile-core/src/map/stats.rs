@@ -0,0 +1,161 @@
+//! Tile usage report of a map, for trimming tilesets before shipping.
+
+use crate::assets::catalog::Catalog;
+use crate::interop::csv::quote;
+use crate::map::{LayerKind, MapData};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+
+/// Painted cells, tile usage and memory estimates of one map.
+#[derive(Debug, Clone, Default)]
+pub struct MapStats {
+    /// Cells per layer, `width * height`.
+    pub cell_count: usize,
+    /// Tile, object and image layers, bottom first, with groups opened up.
+    pub layers: Vec<LayerStats>,
+    /// Painted cells per tile id across every layer, most used first.
+    pub tile_usage: Vec<(String, usize)>,
+    /// Catalog tiles painted nowhere, in catalog order; empty until
+    /// [`MapStats::add_catalog`].
+    pub unused_tiles: Vec<String>,
+    /// Estimated bytes held by each part of the map, e.g. `("Map cells", 40960)`.
+    pub memory: Vec<(String, usize)>,
+}
+
+/// Content of one layer.
+#[derive(Debug, Clone)]
+pub struct LayerStats {
+    pub name: String,
+    pub kind: LayerKind,
+    /// Painted cells; 0 for object and image layers.
+    pub painted: usize,
+    /// Objects of an object layer.
+    pub objects: usize,
+}
+
+impl MapStats {
+    /// Counts the painted cells and tile ids of `map`.
+    pub fn new(map: &MapData) -> Self {
+        let mut usage: HashMap<&str, usize> = HashMap::new();
+        let mut cell_bytes = 0;
+        let layers = map
+            .content_layers()
+            .into_iter()
+            .map(|layer| {
+                cell_bytes += layer.tiles.len() * mem::size_of::<Option<String>>();
+                let painted = layer
+                    .tiles
+                    .iter()
+                    .flatten()
+                    .inspect(|id| {
+                        cell_bytes += id.len();
+                        *usage.entry(id.as_str()).or_default() += 1;
+                    })
+                    .count();
+                LayerStats {
+                    name: layer.name.clone(),
+                    kind: layer.kind,
+                    painted,
+                    objects: layer.objects.len(),
+                }
+            })
+            .collect();
+
+        let mut tile_usage: Vec<(String, usize)> = usage
+            .into_iter()
+            .map(|(id, count)| (id.to_string(), count))
+            .collect();
+        tile_usage.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Self {
+            cell_count: map.cell_count().unwrap_or_default(),
+            layers,
+            tile_usage,
+            unused_tiles: Vec::new(),
+            memory: vec![("Map cells".to_string(), cell_bytes)],
+        }
+    }
+
+    /// Lists the tiles of `catalog` the map does not use and estimates the memory of its
+    /// spritesheets as RGBA textures.
+    pub fn add_catalog(&mut self, catalog: &Catalog) {
+        let used: HashSet<&str> = self
+            .tile_usage
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect();
+        self.unused_tiles = catalog
+            .sheets
+            .iter()
+            .flat_map(|sheet| &sheet.ids)
+            .filter(|id| !used.contains(id.as_str()))
+            .cloned()
+            .collect();
+        let texture_bytes = catalog
+            .sheets
+            .iter()
+            .map(|sheet| {
+                let (width, height) = sheet.image_size();
+                width as usize * height as usize * 4
+            })
+            .sum();
+        self.memory
+            .push(("Spritesheets".to_string(), texture_bytes));
+    }
+
+    /// Painted share of the cells of `layer`, in `0.0..=1.0`.
+    pub fn fill(&self, layer: &LayerStats) -> f32 {
+        layer.painted as f32 / self.cell_count.max(1) as f32
+    }
+
+    /// Sum of every memory estimate, in bytes.
+    pub fn total_memory(&self) -> usize {
+        self.memory
+            .iter()
+            .map(|(_, bytes)| bytes)
+            .sum()
+    }
+
+    /// The report as CSV with the columns `section,name,count,percent`: one `layer` row per
+    /// tile layer (painted cells and fill), one `tile` row per used tile (cells and share of
+    /// all painted cells), one `unused` row per unused catalog tile and one `memory` row per
+    /// estimate (bytes).
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("section,name,count,percent\n");
+        let mut row = |section: &str, name: &str, count: usize, percent: Option<f32>| {
+            let percent = percent
+                .map(|percent| format!("{percent:.2}"))
+                .unwrap_or_default();
+            csv.push_str(&format!("{section},{},{count},{percent}\n", quote(name)));
+        };
+        for layer in self
+            .layers
+            .iter()
+            .filter(|layer| layer.kind == LayerKind::Tiles)
+        {
+            row("layer", &layer.name, layer.painted, Some(self.fill(layer) * 100.0));
+        }
+        let painted: usize = self
+            .tile_usage
+            .iter()
+            .map(|(_, count)| count)
+            .sum();
+        for (id, count) in &self.tile_usage {
+            row("tile", id, *count, Some(*count as f32 * 100.0 / painted.max(1) as f32));
+        }
+        for id in &self.unused_tiles {
+            row("unused", id, 0, None);
+        }
+        for (name, bytes) in &self.memory {
+            row("memory", name, *bytes, None);
+        }
+        csv
+    }
+
+    /// Writes [`MapStats::to_csv`] to `path`.
+    pub fn write_csv(&self, path: &Path) -> Result<(), io::Error> {
+        fs::write(path, self.to_csv())
+    }
+}
@@ -0,0 +1,105 @@
+// Serializable tile primitives that are not fully wired into the editor yet.
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileTexture {
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl From<Size> for (f32, f32) {
+    fn from(size: Size) -> Self {
+        (size.width, size.height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Collider {
+    pub is_walkable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tile {
+    dimensions: Size,
+    texture: TileTexture,
+    position: Position,
+    collider: Collider,
+}
+
+/// Orientation of a painted tile: the texture is mirrored first, then rotated clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileTransform {
+    /// Clockwise rotation in degrees: 0, 90, 180 or 270.
+    pub rotation: u16,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl TileTransform {
+    /// Returns the orientation turned a further 90 degrees clockwise.
+    pub fn rotated_clockwise(self) -> Self {
+        Self {
+            rotation: (self.normalized().rotation + 90) % 360,
+            ..self
+        }
+    }
+
+    /// Returns the orientation mirrored along the screen's horizontal axis, whatever the rotation.
+    pub fn flipped_horizontally(self) -> Self {
+        if self.rotation.is_multiple_of(180) {
+            Self { flip_x: !self.flip_x, ..self }
+        } else {
+            Self { flip_y: !self.flip_y, ..self }
+        }
+    }
+
+    /// Returns the orientation mirrored along the screen's vertical axis, whatever the rotation.
+    pub fn flipped_vertically(self) -> Self {
+        if self.rotation.is_multiple_of(180) {
+            Self { flip_y: !self.flip_y, ..self }
+        } else {
+            Self { flip_x: !self.flip_x, ..self }
+        }
+    }
+
+    /// Snaps the rotation down to a multiple of 90 degrees, for values read from files.
+    pub fn normalized(self) -> Self {
+        Self {
+            rotation: self.rotation % 360 / 90 * 90,
+            ..self
+        }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self == Self::default()
+    }
+
+    pub fn radians(self) -> f32 {
+        f32::from(self.rotation).to_radians()
+    }
+
+    /// Short description such as `90° H`, used by the tools panel and inspector.
+    pub fn label(self) -> String {
+        let mut label = format!("{}°", self.rotation);
+        if self.flip_x {
+            label.push_str(" H");
+        }
+        if self.flip_y {
+            label.push_str(" V");
+        }
+        label
+    }
+}
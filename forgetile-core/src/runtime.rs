@@ -0,0 +1,350 @@
+//! Maps ready to query and draw in macroquad games, enabled by the `macroquad` feature.
+//!
+//! [`MapRuntime::load`] reads a saved map, resolves its tile ids against an assets
+//! directory and uploads the spritesheets it uses. Games then iterate the layers and tiles,
+//! ask [`MapRuntime::is_walkable`] for collision and convert between world and tile
+//! coordinates. On orthogonal maps tile `(0, 0)` starts at world position `(0, 0)`; isometric
+//! maps follow [`MapProjection`](crate::map::projection::MapProjection), with the top corner
+//! of the map at the top of the world area. Image layers are drawn separately by
+//! [`MapRuntime::draw_images`], since their parallax depends on the camera.
+
+use crate::assets::animated::AnimatedSheet;
+use crate::assets::catalog::{Catalog, CatalogSheet};
+use crate::map::format::FormatRegistry;
+use crate::map::object::MapObject;
+use crate::map::property::Properties;
+use crate::map::tile::TileTransform;
+use crate::map::{LayerData, LayerKind, MapData, MapLoadError};
+use macroquad::color::{Color, WHITE};
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::texture::{
+    DrawTextureParams, FilterMode, Image, Texture2D, draw_texture_ex, load_image,
+};
+use std::io;
+use std::path::Path;
+
+/// Draw order distance between two layers, leaving room for per-tile z-priorities.
+const LAYER_Z_STRIDE: i64 = 10_000;
+
+/// A loaded map with the textures of every spritesheet it uses.
+pub struct MapRuntime {
+    data: MapData,
+    /// Resolved cells of every layer, parallel to `data.layers`.
+    cells: Vec<Vec<Option<Cell>>>,
+    /// One texture per used spritesheet, color-keyed like in the editor.
+    textures: Vec<Texture2D>,
+    /// Image of every image layer, parallel to `data.layers`.
+    images: Vec<Option<Texture2D>>,
+}
+
+/// Spritesheet region a painted cell draws.
+#[derive(Clone, Copy)]
+struct Cell {
+    texture: usize,
+    source: Rect,
+}
+
+/// A painted cell of one layer.
+pub struct RuntimeTile<'a> {
+    pub x: usize,
+    pub y: usize,
+    /// Tile id as saved in the map.
+    pub id: &'a str,
+    pub texture: &'a Texture2D,
+    /// Region of `texture` holding the tile.
+    pub source: Rect,
+    /// Tile tint multiplied by the layer's tint color and opacity.
+    pub tint: Color,
+    /// Draw order within the layer; higher values draw on top.
+    pub z_priority: i32,
+    pub transform: TileTransform,
+    /// Custom properties of the cell, such as `damage` or `door_target`.
+    pub properties: Option<&'a Properties>,
+}
+
+impl MapRuntime {
+    /// Loads the map at `map_path` and the spritesheets of `assets_dir` it uses.
+    ///
+    /// The assets directory is scanned like the editor does, with the map's tile size as
+    /// the default for spritesheets without a sidecar. Fails with
+    /// [`MapLoadError::UnknownTile`] when a tile id is not found there.
+    pub async fn load(map_path: &Path, assets_dir: &Path) -> Result<Self, MapLoadError> {
+        let data = MapData::read(map_path, &FormatRegistry::default())?;
+        let tile_size = (data.tile_width.round() as u32, data.tile_height.round() as u32);
+        let catalog = Catalog::load(assets_dir, tile_size);
+        Self::from_data(data, &catalog).await
+    }
+
+    /// Builds a runtime map from already loaded map data, taking tiles from `catalog`.
+    ///
+    /// Layer groups are flattened with [`MapData::flatten_groups`], so hidden or translucent
+    /// groups hide or fade their layers.
+    pub async fn from_data(mut data: MapData, catalog: &Catalog) -> Result<Self, MapLoadError> {
+        data.validate()?;
+        data.flatten_groups();
+        let mut sheets: Vec<&CatalogSheet> = Vec::new();
+        let cells = data
+            .layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .tiles
+                    .iter()
+                    .map(|id| {
+                        let Some(id) = id else {
+                            return Ok(None);
+                        };
+                        let (sheet, index) = catalog
+                            .locate(id)
+                            .ok_or_else(|| MapLoadError::UnknownTile(id.clone()))?;
+                        let texture = match sheets
+                            .iter()
+                            .position(|used| used.path == sheet.path)
+                        {
+                            Some(position) => position,
+                            None => {
+                                sheets.push(sheet);
+                                sheets.len() - 1
+                            }
+                        };
+                        let rect = sheet.tile_rect(index);
+                        Ok(Some(Cell {
+                            texture,
+                            source: Rect::new(
+                                rect.x as f32,
+                                rect.y as f32,
+                                rect.width as f32,
+                                rect.height as f32,
+                            ),
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, MapLoadError>>()
+            })
+            .collect::<Result<Vec<_>, MapLoadError>>()?;
+
+        let mut textures = Vec::with_capacity(sheets.len());
+        for sheet in sheets {
+            textures.push(load_sheet_texture(sheet).await?);
+        }
+        let mut images = Vec::with_capacity(data.layers.len());
+        for layer in &data.layers {
+            images.push(match (&layer.kind, &layer.image) {
+                (LayerKind::Image, Some(path)) => Some(load_layer_image(path).await?),
+                _ => None,
+            });
+        }
+        Ok(Self { data, cells, textures, images })
+    }
+
+    /// Map size in tiles.
+    pub fn size(&self) -> (usize, usize) {
+        (self.data.width, self.data.height)
+    }
+
+    /// Size of one tile in world units (pixels at zoom 1).
+    pub fn tile_size(&self) -> Vec2 {
+        vec2(self.data.tile_width, self.data.tile_height)
+    }
+
+    /// Size of the whole map in world units.
+    pub fn world_size(&self) -> Vec2 {
+        self.data
+            .projection
+            .world_size(self.size(), self.tile_size().into())
+            .into()
+    }
+
+    /// The saved map, for anything this API does not cover.
+    pub fn data(&self) -> &MapData {
+        &self.data
+    }
+
+    /// Layers from bottom to top, with their names, visibility and raw cell data.
+    pub fn layers(&self) -> &[LayerData] {
+        &self.data.layers
+    }
+
+    /// Index of the first layer called `name`.
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.data
+            .layers
+            .iter()
+            .position(|layer| layer.name == name)
+    }
+
+    /// Objects of every object layer, bottom layer first, e.g. to find spawn points by
+    /// [`MapObject::kind`]. Positions are in world units.
+    pub fn objects(&self) -> impl Iterator<Item = &MapObject> {
+        self.data
+            .layers
+            .iter()
+            .flat_map(|layer| &layer.objects)
+    }
+
+    /// Painted cells of `layer`, row by row. Yields nothing for an unknown layer.
+    pub fn tiles(&self, layer: usize) -> impl Iterator<Item = RuntimeTile<'_>> {
+        let width = self.data.width.max(1);
+        (0..self
+            .cells
+            .get(layer)
+            .map_or(0, Vec::len))
+            .filter_map(move |index| self.tile(layer, index % width, index / width))
+    }
+
+    /// The tile painted at `(x, y)` on `layer`, if any.
+    pub fn tile(&self, layer: usize, x: usize, y: usize) -> Option<RuntimeTile<'_>> {
+        if x >= self.data.width || y >= self.data.height {
+            return None;
+        }
+        let index = y * self.data.width + x;
+        let cell = (*self.cells.get(layer)?.get(index)?)?;
+        let data = &self.data.layers[layer];
+        Some(RuntimeTile {
+            x,
+            y,
+            id: data.tiles[index].as_deref()?,
+            texture: &self.textures[cell.texture],
+            source: cell.source,
+            tint: modulate(
+                data.tint(index)
+                    .map(Color::from)
+                    .unwrap_or(WHITE),
+                data.modulation(),
+            ),
+            z_priority: data.z_priority(index),
+            transform: data.transform(index),
+            properties: data.cell_properties(index),
+        })
+    }
+
+    /// Returns whether the cell at `(x, y)` can be walked on.
+    ///
+    /// Cells outside the map are blocked, so callers can probe neighbours of edge cells.
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return false;
+        };
+        if x >= self.data.width || y >= self.data.height {
+            return false;
+        }
+        self.data
+            .collision
+            .get(y * self.data.width + x)
+            .copied()
+            .unwrap_or(true)
+    }
+
+    /// Tile containing the world position `position`, or `None` outside the map.
+    pub fn world_to_tile(&self, position: Vec2) -> Option<(usize, usize)> {
+        self.data
+            .projection
+            .cell_at(position.into(), self.size(), self.tile_size().into())
+    }
+
+    /// World position of the top-left corner of the box tile `(x, y)` is drawn in.
+    pub fn tile_to_world(&self, x: usize, y: usize) -> Vec2 {
+        self.data
+            .projection
+            .cell_origin((x, y), self.size(), self.tile_size().into())
+            .into()
+    }
+
+    /// World position of the center of tile `(x, y)`.
+    pub fn tile_center(&self, x: usize, y: usize) -> Vec2 {
+        self.tile_to_world(x, y) + self.tile_size() / 2.0
+    }
+
+    /// Draws every visible layer with the current camera, in the editor's layer and
+    /// z-priority order. Animated tiles draw their first frame.
+    pub fn draw(&self) {
+        let mut draw_list: Vec<(i64, RuntimeTile)> = self
+            .data
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.visible)
+            .flat_map(|(index, _)| {
+                self.tiles(index).map(move |tile| {
+                    (index as i64 * LAYER_Z_STRIDE + i64::from(tile.z_priority), tile)
+                })
+            })
+            .collect();
+        draw_list.sort_by_key(|(order, _)| *order);
+        for (_, tile) in &draw_list {
+            self.draw_tile(tile);
+        }
+    }
+
+    /// Draws every visible image layer, bottom first, for a camera centered on
+    /// `camera_center`. Call it before [`MapRuntime::draw`] so the images stay behind the
+    /// tiles.
+    pub fn draw_images(&self, camera_center: Vec2) {
+        for (layer, image) in self
+            .data
+            .layers
+            .iter()
+            .zip(&self.images)
+            .filter(|(layer, _)| layer.visible)
+        {
+            let Some(image) = image else {
+                continue;
+            };
+            let [x, y] = layer.parallax_origin(camera_center.into());
+            draw_texture_ex(image, x, y, modulate(WHITE, layer.modulation()), Default::default());
+        }
+    }
+
+    /// Draws one tile over its cell. The rotation pivots on the cell center, like in the
+    /// editor.
+    pub fn draw_tile(&self, tile: &RuntimeTile) {
+        let position = self.tile_to_world(tile.x, tile.y);
+        draw_texture_ex(
+            tile.texture,
+            position.x,
+            position.y,
+            tile.tint,
+            DrawTextureParams {
+                dest_size: Some(self.tile_size()),
+                source: Some(tile.source),
+                rotation: tile.transform.radians(),
+                flip_x: tile.transform.flip_x,
+                flip_y: tile.transform.flip_y,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Multiplies `color` component-wise by the RGBA `by`.
+fn modulate(color: Color, [r, g, b, a]: [f32; 4]) -> Color {
+    Color::new(color.r * r, color.g * g, color.b * b, color.a * a)
+}
+
+/// Uploads the image of an image layer.
+async fn load_layer_image(path: &str) -> Result<Texture2D, MapLoadError> {
+    let image = load_image(path)
+        .await
+        .map_err(|err| io::Error::other(format!("{path}: {err}")))?;
+    Ok(Texture2D::from_image(&image))
+}
+
+/// Uploads a spritesheet with its sidecar's color key applied.
+async fn load_sheet_texture(sheet: &CatalogSheet) -> Result<Texture2D, MapLoadError> {
+    let tile_size = (sheet.tile_width as usize, sheet.tile_height as usize);
+    let mut image = match AnimatedSheet::load(&sheet.path, tile_size)? {
+        Some(animated) => Image {
+            bytes: animated.rgba,
+            width: animated.width as u16,
+            height: animated.height as u16,
+        },
+        None => load_image(&sheet.path.to_string_lossy())
+            .await
+            .map_err(|err| io::Error::other(format!("{}: {err}", sheet.path.display())))?,
+    };
+    sheet
+        .sidecar
+        .apply_color_key(&mut image.bytes);
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    Ok(texture)
+}
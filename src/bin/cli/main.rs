@@ -0,0 +1,469 @@
+//! Headless companion tool for batch operations on ForgeTile map files.
+//!
+//! Reads maps through `forgetile-core` so it never initializes the graphics stack.
+
+use forgetile_core::assets::catalog::Catalog;
+use forgetile_core::interop::csv::{self, CsvCells};
+use forgetile_core::interop::tiled;
+use forgetile_core::map::format::FormatRegistry;
+use forgetile_core::map::stats::MapStats;
+use forgetile_core::map::{LayerData, LayerKind, MapData, MapLoadError};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Tile size used for spritesheets without a sidecar and for CSV input, in pixels.
+const DEFAULT_TILE_SIZE: (u32, u32) = (32, 32);
+
+const USAGE: &str = "\
+Usage:
+  forgetile-cli replace-tile --old-id <ID> --new-id <ID> <MAP>...
+  forgetile-cli validate --catalog-path <ASSETS_DIR> <MAP>...
+  forgetile-cli convert [--catalog-path <ASSETS_DIR>] [--layer <INDEX|NAME>] [--tile-size <WxH>] [--indices] <INPUT> <OUTPUT>
+  forgetile-cli stats [--catalog-path <ASSETS_DIR>] [--csv] <MAP>...
+
+convert picks the formats from the extensions: .json/.fgt/.ron/.fgtl (ForgeTile), .tmx/.tmj (Tiled,
+needs --catalog-path) and .csv (one layer of tile ids; --layer picks it, --tile-size sets
+the tile size when reading, --indices uses catalog tile indices instead of ids and needs
+--catalog-path).
+
+stats lists the painted cells of every layer and the most used tile ids; --catalog-path also
+counts the catalog tiles the map never uses and --csv writes the full report next to each map
+as <MAP>_stats.csv.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("replace-tile") => replace_tile_command(&args[1..]),
+        Some("validate") => validate_command(&args[1..]),
+        Some("convert") => convert_command(&args[1..]),
+        Some("stats") => stats_command(&args[1..]),
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(format!("Unknown or missing command.\n{USAGE}")),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn replace_tile_command(args: &[String]) -> Result<ExitCode, String> {
+    let mut old_id = None;
+    let mut new_id = None;
+    let mut paths = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--old-id" => old_id = iter.next().cloned(),
+            "--new-id" => new_id = iter.next().cloned(),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let (Some(old_id), Some(new_id)) = (old_id, new_id) else {
+        return Err(format!("replace-tile requires --old-id and --new-id.\n{USAGE}"));
+    };
+    if paths.is_empty() {
+        return Err(format!("replace-tile requires at least one map file.\n{USAGE}"));
+    }
+
+    let mut updated_maps = 0;
+    let mut replaced_total = 0;
+    let mut failed = false;
+
+    for path in &paths {
+        match replace_tile_in_file(path, &old_id, &new_id) {
+            Ok(0) => {}
+            Ok(replaced) => {
+                updated_maps += 1;
+                replaced_total += replaced;
+            }
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                failed = true;
+            }
+        }
+    }
+
+    println!("Updated {updated_maps} maps, {replaced_total} tile instances total");
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Rewrites every cell holding `old_id`, saving the file in its own format and tile
+/// encoding only when something changed.
+fn replace_tile_in_file(path: &Path, old_id: &str, new_id: &str) -> Result<usize, MapLoadError> {
+    let formats = FormatRegistry::default();
+    let mut map = MapData::read(path, &formats)?;
+
+    let mut replaced = 0;
+    for tile in map
+        .content_layers_mut()
+        .into_iter()
+        .filter(|layer| layer.kind == LayerKind::Tiles)
+        .flat_map(|layer| layer.tiles.iter_mut().flatten())
+    {
+        if tile == old_id {
+            *tile = new_id.to_string();
+            replaced += 1;
+        }
+    }
+
+    if replaced > 0 {
+        map.write(path, &formats)?;
+    }
+    Ok(replaced)
+}
+
+fn validate_command(args: &[String]) -> Result<ExitCode, String> {
+    let mut catalog_path = None;
+    let mut paths = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--catalog-path" => catalog_path = iter.next().map(PathBuf::from),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let Some(catalog_path) = catalog_path else {
+        return Err(format!("validate requires --catalog-path.\n{USAGE}"));
+    };
+    if paths.is_empty() {
+        return Err(format!("validate requires at least one map file.\n{USAGE}"));
+    }
+    if !catalog_path.is_dir() {
+        return Err(format!("{} is not a directory.", catalog_path.display()));
+    }
+
+    let catalog = Catalog::load(&catalog_path, DEFAULT_TILE_SIZE);
+    println!("Catalog {}: {} tiles", catalog_path.display(), catalog.tile_count());
+
+    let mut failed_maps = 0;
+    for path in &paths {
+        match validate_map_file(path, &catalog) {
+            Ok(problems) if problems.is_empty() => println!("OK    {}", path.display()),
+            Ok(problems) => {
+                failed_maps += 1;
+                println!("FAIL  {}", path.display());
+                for problem in problems {
+                    println!("      {problem}");
+                }
+            }
+            Err(err) => {
+                failed_maps += 1;
+                println!("FAIL  {}", path.display());
+                println!("      malformed file: {err}");
+            }
+        }
+    }
+
+    println!("{} of {} maps passed", paths.len() - failed_maps, paths.len());
+    Ok(if failed_maps > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Reads a saved map, which checks its tile counts, and returns one line per tile id
+/// missing from `catalog`.
+fn validate_map_file(path: &Path, catalog: &Catalog) -> Result<Vec<String>, MapLoadError> {
+    let map = MapData::read(path, &FormatRegistry::default())?;
+    let mut missing: Vec<&str> = Vec::new();
+    for id in map
+        .content_layers()
+        .into_iter()
+        .filter(|layer| layer.kind == LayerKind::Tiles)
+        .flat_map(|layer| layer.tiles.iter().flatten())
+    {
+        if !catalog.contains(id) && !missing.contains(&id.as_str()) {
+            missing.push(id);
+        }
+    }
+    Ok(missing
+        .into_iter()
+        .map(|id| format!("missing tile id: {id}"))
+        .collect())
+}
+
+/// Map file format, picked from the file extension.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MapFile {
+    /// ForgeTile map (`.json`, `.fgt`, `.ron`, binary `.fgtl`).
+    Native,
+    /// Tiled map (`.tmx`, `.tmj`).
+    Tiled,
+    /// One layer of tile ids (`.csv`).
+    Csv,
+}
+
+impl MapFile {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" | "fgt" | "ron" | "fgtl" => Ok(MapFile::Native),
+            "tmx" | "tmj" => Ok(MapFile::Tiled),
+            "csv" => Ok(MapFile::Csv),
+            _ => Err(format!("{}: unsupported map format.\n{USAGE}", path.display())),
+        }
+    }
+}
+
+fn convert_command(args: &[String]) -> Result<ExitCode, String> {
+    let mut catalog_path = None;
+    let mut layer = None;
+    let mut tile_size = DEFAULT_TILE_SIZE;
+    let mut indices = false;
+    let mut paths = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--catalog-path" => catalog_path = iter.next().map(PathBuf::from),
+            "--layer" => layer = iter.next().cloned(),
+            "--indices" => indices = true,
+            "--tile-size" => {
+                tile_size = iter
+                    .next()
+                    .and_then(|value| parse_tile_size(value))
+                    .ok_or_else(|| format!("--tile-size expects WIDTHxHEIGHT.\n{USAGE}"))?;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [input, output] = paths.as_slice() else {
+        return Err(format!("convert requires an input and an output file.\n{USAGE}"));
+    };
+    let (input_format, output_format) = (MapFile::from_path(input)?, MapFile::from_path(output)?);
+    let catalog = if input_format == MapFile::Tiled || output_format == MapFile::Tiled || indices {
+        let Some(catalog_path) = catalog_path.filter(|path| path.is_dir()) else {
+            return Err(format!(
+                "Tiled maps and --indices require --catalog-path <ASSETS_DIR>.\n{USAGE}"
+            ));
+        };
+        Some(Catalog::load(&catalog_path, DEFAULT_TILE_SIZE))
+    } else {
+        None
+    };
+
+    let cells = match (indices, &catalog) {
+        (true, Some(catalog)) => CsvCells::Indices(catalog),
+        _ => CsvCells::Ids,
+    };
+    let formats = FormatRegistry::default();
+    let map = match (input_format, &catalog) {
+        (MapFile::Native, _) => MapData::read(input, &formats),
+        (MapFile::Tiled, Some(catalog)) => tiled::read(input, catalog),
+        (MapFile::Csv, _) => csv::read(input, tile_size, cells).map_err(MapLoadError::from),
+        (MapFile::Tiled, None) => unreachable!("catalog is loaded for Tiled maps"),
+    }
+    .map_err(|err| format!("{}: {err}", input.display()))?;
+
+    let written = match (output_format, &catalog) {
+        (MapFile::Native, _) => map.write(output, &formats),
+        (MapFile::Tiled, Some(catalog)) => tiled::write(&map, catalog, output).map(|unresolved| {
+            if unresolved > 0 {
+                eprintln!("{unresolved} tiles are missing from the catalog and were left empty");
+            }
+        }),
+        (MapFile::Csv, _) => {
+            let layer = pick_layer(&map, layer.as_deref())?;
+            csv::write(&map, layer, cells, output).map(|unresolved| {
+                if unresolved > 0 {
+                    eprintln!(
+                        "{unresolved} tiles are missing from the catalog and were left empty"
+                    );
+                }
+            })
+        }
+        (MapFile::Tiled, None) => unreachable!("catalog is loaded for Tiled maps"),
+    };
+    written.map_err(|err| format!("{}: {err}", output.display()))?;
+
+    println!(
+        "Converted {} -> {} ({}x{} tiles, {} layers)",
+        input.display(),
+        output.display(),
+        map.width,
+        map.height,
+        map.content_layers().len()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Parses a `WIDTHxHEIGHT` tile size in pixels.
+fn parse_tile_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    let (width, height) = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Tile layer selected by index or name, the bottom one by default. Indices count the
+/// layers of groups, not the groups themselves.
+fn pick_layer<'a>(map: &'a MapData, selector: Option<&str>) -> Result<&'a LayerData, String> {
+    let layers = map.content_layers();
+    let found = match selector {
+        None => layers
+            .into_iter()
+            .find(|layer| layer.kind == LayerKind::Tiles),
+        Some(selector) => selector
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| layers.get(index).copied())
+            .or_else(|| {
+                layers
+                    .into_iter()
+                    .find(|layer| layer.name == selector)
+            }),
+    };
+    match found {
+        Some(layer) if layer.kind != LayerKind::Tiles => {
+            Err(format!("Layer {:?} is not a tile layer.", layer.name))
+        }
+        Some(layer) => Ok(layer),
+        None => Err(format!("No tile layer {:?} in the map.", selector.unwrap_or("0"))),
+    }
+}
+
+fn stats_command(args: &[String]) -> Result<ExitCode, String> {
+    let mut catalog_path = None;
+    let mut write_csv = false;
+    let mut paths = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--catalog-path" => catalog_path = iter.next().map(PathBuf::from),
+            "--csv" => write_csv = true,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        return Err(format!("stats requires at least one map file.\n{USAGE}"));
+    }
+    let catalog = match catalog_path {
+        Some(path) if !path.is_dir() => {
+            return Err(format!("{} is not a directory.", path.display()));
+        }
+        Some(path) => Some(Catalog::load(&path, DEFAULT_TILE_SIZE)),
+        None => None,
+    };
+
+    let formats = FormatRegistry::default();
+    let mut failed = false;
+    for path in paths {
+        let map = match MapData::read(&path, &formats) {
+            Ok(map) => map,
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        let mut stats = MapStats::new(&map);
+        if let Some(catalog) = &catalog {
+            stats.add_catalog(catalog);
+        }
+        print_stats(&path, &map, &stats);
+        if write_csv {
+            let csv_path = path.with_file_name(format!(
+                "{}_stats.csv",
+                path.file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ));
+            match stats.write_csv(&csv_path) {
+                Ok(()) => println!("  report written to {}", csv_path.display()),
+                Err(err) => {
+                    eprintln!("{}: {err}", csv_path.display());
+                    failed = true;
+                }
+            }
+        }
+    }
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Number of tile ids listed as the most used ones.
+const TOP_TILE_COUNT: usize = 5;
+
+fn print_stats(path: &Path, map: &MapData, stats: &MapStats) {
+    println!(
+        "{}: {}x{} {} tiles of {}x{} px, {} layers",
+        path.display(),
+        map.width,
+        map.height,
+        map.projection.name(),
+        map.tile_width,
+        map.tile_height,
+        stats.layers.len()
+    );
+    for (index, (layer, data)) in stats
+        .layers
+        .iter()
+        .zip(map.content_layers())
+        .enumerate()
+    {
+        match layer.kind {
+            LayerKind::Objects => {
+                println!("  layer {index} {:?}: {} objects", layer.name, layer.objects);
+            }
+            LayerKind::Image => println!(
+                "  layer {index} {:?}: image {:?}, parallax {}x{}",
+                layer.name,
+                data.image
+                    .as_deref()
+                    .unwrap_or_default(),
+                data.parallax[0],
+                data.parallax[1]
+            ),
+            _ => println!(
+                "  layer {index} {:?}: {} painted cells ({:.1}%)",
+                layer.name,
+                layer.painted,
+                stats.fill(layer) * 100.0
+            ),
+        }
+    }
+
+    println!("  {} distinct tile ids", stats.tile_usage.len());
+    for (id, count) in stats
+        .tile_usage
+        .iter()
+        .take(TOP_TILE_COUNT)
+    {
+        println!("    {count:>6}  {id}");
+    }
+    if !stats.unused_tiles.is_empty() {
+        println!("  {} catalog tiles unused", stats.unused_tiles.len());
+    }
+    let blocked = map
+        .collision
+        .iter()
+        .filter(|&&is_walkable| !is_walkable)
+        .count();
+    println!("  {blocked} blocked collision cells");
+    println!("  ~{} KiB in memory", stats.total_memory().div_ceil(1024));
+}
@@ -0,0 +1,58 @@
+use macroquad::math::Rect;
+use macroquad::texture::Texture2D;
+
+/// How a tile changes over time when drawn on the map.
+#[derive(Clone, Default)]
+pub enum AnimationMode {
+    /// Always shows the sprite's own region of the atlas.
+    #[default]
+    Static,
+    /// Cycles through separately sliced frames of the spritesheet atlas.
+    FrameList {
+        frames: Vec<Rect>,
+        fps: f32,
+    },
+    /// Samples `cols` consecutive cells of a spritesheet row starting at `origin`,
+    /// advancing the source rect by `stride` pixels (one column) per frame.
+    UVScroll {
+        origin: Rect,
+        cols: usize,
+        stride: f32,
+        fps: f32,
+    },
+}
+
+impl AnimationMode {
+    /// Returns the texture and source rect to draw at `time` seconds.
+    ///
+    /// `atlas` and `source` are the tile's own region; without a source rect the animation
+    /// is not played.
+    pub fn frame<'a>(
+        &self, atlas: &'a Texture2D, source: Option<Rect>, time: f64,
+    ) -> (&'a Texture2D, Option<Rect>) {
+        if source.is_none() {
+            return (atlas, None);
+        }
+        match self {
+            AnimationMode::Static => (atlas, source),
+            AnimationMode::FrameList { frames, fps } => {
+                let frame = frames
+                    .get(frame_index(time, *fps, frames.len()))
+                    .copied();
+                (atlas, frame.or(source))
+            }
+            AnimationMode::UVScroll { origin, cols, stride, fps } => {
+                let column = frame_index(time, *fps, *cols) as f32;
+                let frame = Rect::new(origin.x + column * stride, origin.y, origin.w, origin.h);
+                (atlas, Some(frame))
+            }
+        }
+    }
+}
+
+fn frame_index(time: f64, fps: f32, frame_count: usize) -> usize {
+    if frame_count == 0 || fps <= 0.0 {
+        return 0;
+    }
+    (time * f64::from(fps)) as usize % frame_count
+}
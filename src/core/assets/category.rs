@@ -0,0 +1,31 @@
+use crate::core::assets::TextureFilter;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Optional per-category settings read from `category.json` inside the category folder.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategoryManifest {
+    /// Texture filter for every tile of the category, overriding the global default.
+    pub filter_mode: Option<TextureFilter>,
+    /// Marks the category as a terrain set for the autotile brush; each tile declares the
+    /// neighbors it is drawn for in its `terrain_mask` property.
+    pub terrain: bool,
+}
+
+impl CategoryManifest {
+    const FILE_NAME: &'static str = "category.json";
+
+    /// Loads the manifest of `directory`, falling back to defaults when absent or invalid.
+    pub fn load_for(directory: &Path) -> Self {
+        let path = directory.join(Self::FILE_NAME);
+        let Ok(data) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&data).unwrap_or_else(|err| {
+            eprintln!("[assets] Ignoring invalid category manifest {:?}: {err}", path);
+            Self::default()
+        })
+    }
+}
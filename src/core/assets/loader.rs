@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::texture::FilterMode;
+use macroquad::time::get_time;
+
+use crate::core::assets::watcher::AssetWatcher;
+use crate::core::assets::{
+    AssetCatalog, CategoryPlan, TextureFilter, resolve_assets_root, sheet_tile_size,
+};
+use crate::core::map::tile::Size;
+use forgetile_core::assets::catalog::Catalog;
+
+/// Builds an [`AssetCatalog`] a few spritesheets at a time, so the editor keeps drawing
+/// frames while large asset directories load.
+///
+/// The directories are scanned up front, which only lists files; [`AssetLoader::step`] then
+/// slices images until its time budget runs out and [`AssetLoader::finish`] turns the result
+/// into the catalog.
+pub struct AssetLoader {
+    /// Directories the catalog was asked to load, in priority order, including missing ones.
+    directories: Vec<PathBuf>,
+    /// Existing directories of `directories`.
+    roots: Vec<PathBuf>,
+    plans: Vec<CategoryPlan>,
+    /// Category of `plans` and image inside it sliced next.
+    next: (usize, usize),
+    loaded_images: usize,
+    total_images: usize,
+    tile_size: Size,
+    default_filter: FilterMode,
+}
+
+impl AssetLoader {
+    /// Lists the spritesheets of `directories`, in order; categories of later directories
+    /// follow those of earlier ones. An empty list probes for an `assets` directory next to
+    /// the executable, then in the working directory.
+    pub fn new(directories: &[PathBuf], tile_size: Size, default_filter: TextureFilter) -> Self {
+        let directories = if directories.is_empty() {
+            resolve_assets_root()
+                .into_iter()
+                .collect()
+        } else {
+            directories.to_vec()
+        };
+        let default_filter = FilterMode::from(default_filter);
+        let roots: Vec<PathBuf> = directories
+            .iter()
+            .filter(|directory| directory.is_dir())
+            .cloned()
+            .collect();
+        if roots.is_empty() {
+            eprintln!(
+                "[assets] Unable to locate an assets directory. Place your assets next to \
+                 the final binary in an `assets` folder, or add asset directories in the \
+                 Project menu."
+            );
+        }
+
+        let mut plans = Vec::new();
+        for (position, root) in roots.iter().enumerate() {
+            plans.extend(CategoryPlan::root(root, position, default_filter));
+            if let Ok(entries) = fs::read_dir(root) {
+                plans.extend(
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter_map(|path| CategoryPlan::named(&path, default_filter)),
+                );
+            }
+        }
+        let total_images = plans
+            .iter()
+            .map(|plan| plan.images.len())
+            .sum();
+
+        Self {
+            directories,
+            roots,
+            plans,
+            next: (0, 0),
+            loaded_images: 0,
+            total_images,
+            tile_size,
+            default_filter,
+        }
+    }
+
+    /// Slices images until `budget` seconds have passed, at least one per call. Returns
+    /// `true` once every image is loaded.
+    pub async fn step(&mut self, budget: f64) -> bool {
+        let start = get_time();
+        while let Some(plan) = self.plans.get_mut(self.next.0) {
+            let Some((sub_category, path)) = plan.images.get(self.next.1).cloned() else {
+                self.next = (self.next.0 + 1, 0);
+                continue;
+            };
+            plan.load_image(sub_category, &path, self.tile_size)
+                .await;
+            self.next.1 += 1;
+            self.loaded_images += 1;
+            if get_time() - start >= budget {
+                break;
+            }
+        }
+        self.is_done()
+    }
+
+    /// Returns `true` once every image is loaded.
+    pub fn is_done(&self) -> bool {
+        self.loaded_images >= self.total_images
+    }
+
+    /// Images loaded so far and in total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.loaded_images, self.total_images)
+    }
+
+    /// Image sliced by the next [`AssetLoader::step`], if any.
+    pub fn current(&self) -> Option<&Path> {
+        self.plans
+            .get(self.next.0)
+            .and_then(|plan| plan.images.get(self.next.1))
+            .or_else(|| {
+                self.plans
+                    .get(self.next.0 + 1..)?
+                    .iter()
+                    .find_map(|plan| plan.images.first())
+            })
+            .map(|(_, path)| path.as_path())
+    }
+
+    /// The catalog of every image loaded so far, without its empty categories.
+    pub fn finish(self) -> AssetCatalog {
+        let mut catalog = AssetCatalog {
+            categories: self
+                .plans
+                .into_iter()
+                .filter_map(CategoryPlan::finish)
+                .collect(),
+            watchers: self
+                .roots
+                .iter()
+                .map(|root| AssetWatcher::new(root.clone(), get_time()))
+                .collect(),
+            sheets: Catalog::load_all(&self.roots, sheet_tile_size(self.tile_size)),
+            index: HashMap::new(),
+            directories: self.directories,
+            tile_size: self.tile_size,
+            default_filter: self.default_filter,
+        };
+        catalog.dedupe_ids();
+        catalog.rebuild_index();
+        catalog
+    }
+}
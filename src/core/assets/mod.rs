@@ -1,58 +1,406 @@
-use std::borrow::Cow;
+pub mod animation;
+pub mod category;
+pub mod loader;
+pub mod watcher;
+
+pub use forgetile_core::assets::sidecar;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::io;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use macroquad::math::Rect;
 use macroquad::texture::FilterMode;
-use macroquad::texture::{Texture2D, load_image};
+use macroquad::texture::{Image, Texture2D, load_image};
+use serde::{Deserialize, Serialize};
 
+use crate::core::assets::animation::AnimationMode;
+use crate::core::assets::category::CategoryManifest;
+use crate::core::assets::sidecar::{AnimationSpec, PixelRect, TilesetSidecar};
+use crate::core::assets::watcher::AssetWatcher;
 use crate::core::map::tile::Size;
+use forgetile_core::assets::animated::AnimatedSheet;
+use forgetile_core::assets::catalog::{
+    Catalog, generated_tile_id, is_supported_image, nested_directories,
+};
+
+/// Sampling used when tile textures are scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureFilter {
+    /// Crisp pixels, suited to pixel art.
+    #[default]
+    Nearest,
+    /// Smooth interpolation, suited to high-resolution tiles.
+    Linear,
+}
+
+impl TextureFilter {
+    pub const ALL: [TextureFilter; 2] = [
+        TextureFilter::Nearest,
+        TextureFilter::Linear,
+    ];
+
+    /// Label shown in the preferences dialog.
+    pub fn name(self) -> &'static str {
+        match self {
+            TextureFilter::Nearest => "Nearest",
+            TextureFilter::Linear => "Linear",
+        }
+    }
+}
+
+impl From<TextureFilter> for FilterMode {
+    fn from(filter: TextureFilter) -> Self {
+        match filter {
+            TextureFilter::Nearest => FilterMode::Nearest,
+            TextureFilter::Linear => FilterMode::Linear,
+        }
+    }
+}
+
+/// Metadata key holding whether a tile type can be walked on (`"true"` / `"false"`).
+pub const WALKABLE_KEY: &str = "is_walkable";
 
 #[derive(Clone)]
 pub struct TileSprite {
     pub id: String,
     pub name: String,
+    /// Standalone texture of the tile, for UI widgets that cannot draw a texture region.
     pub texture: Texture2D,
+    /// Color-keyed spritesheet shared by every tile of the sheet; the map draws
+    /// `source.rect` from it so tiles of one sheet batch into few draw calls.
+    atlas: Texture2D,
+    pub filter_mode: FilterMode,
+    /// Free-form key/value properties of the tile type.
+    pub metadata: BTreeMap<String, String>,
+    pub animation_mode: AnimationMode,
+    source: TileSource,
 }
 
+/// Where a sprite was sliced from.
+#[derive(Clone)]
+struct TileSource {
+    /// Canonical path of the spritesheet.
+    path: PathBuf,
+    /// Position of the tile inside its spritesheet, row-major.
+    index: usize,
+    rect: Rect,
+    /// Columns the spritesheet was sliced into.
+    columns: usize,
+}
+
+/// Location of a sprite inside its source spritesheet.
+pub struct SheetSlot<'a> {
+    pub path: &'a Path,
+    /// Position of the tile inside the spritesheet, row-major.
+    pub index: usize,
+    pub columns: usize,
+}
+
+/// Top-level palette group, loaded from a directory under `assets/`.
+///
+/// Tiles are addressed by a flat index: the category's own tiles first, followed by
+/// the tiles of each sub-category in order.
 pub struct AssetCategory {
     pub name: String,
+    /// Groups loaded from the category's nested sub-directories, in tree order: each
+    /// sub-category comes right before the ones nested in it.
+    pub sub_categories: Vec<SubCategory>,
+    /// Tiles stored directly in the category directory.
+    pub direct_tiles: Vec<TileSprite>,
+    /// Filter forced by the category's `category.json`, ignoring the global default.
+    filter_override: Option<FilterMode>,
+    /// Set by `category.json` when the tiles form an autotile terrain set.
+    pub terrain: bool,
+    /// Directory the category was loaded from, used to reload it when its files change.
+    source_dir: Option<PathBuf>,
+}
+
+/// Palette group nested inside an [`AssetCategory`], loaded from one of its sub-directories.
+pub struct SubCategory {
+    pub name: String,
+    /// Nesting level: 1 for a directory right inside the category, 2 for one inside that,
+    /// and so on.
+    pub depth: usize,
     pub tiles: Vec<TileSprite>,
 }
 
 pub struct AssetCatalog {
     categories: Vec<AssetCategory>,
+    /// Maps a sprite id to its `(category, tile)` indices.
+    index: HashMap<String, (usize, usize)>,
+    /// Directories the catalog was asked to load, in priority order, including missing ones.
+    directories: Vec<PathBuf>,
+    /// Watches each existing directory of `directories` for [`AssetCatalog::hot_reload`].
+    watchers: Vec<AssetWatcher>,
+    tile_size: Size,
+    default_filter: FilterMode,
+    /// Texture-free view of the same spritesheets, for map interop.
+    sheets: Catalog,
+}
+
+/// How one asset directory of the catalog loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetDirStatus {
+    /// The directory does not exist or is not a directory.
+    Missing,
+    /// Palette categories and tiles loaded from the directory; both are 0 when it holds no
+    /// usable image.
+    Loaded {
+        categories: usize,
+        tiles: usize,
+    },
 }
 
 impl AssetCatalog {
-    pub async fn load(tile_size: Size) -> Self {
-        let mut categories = Vec::new();
-        if let Some(root) = resolve_assets_root() {
-            if let Some(mut root_files) = load_category_from_path(&root, tile_size).await {
-                root_files.name = "General".to_string();
-                if !root_files.tiles.is_empty() {
-                    categories.push(root_files);
+    /// Catalog without tiles, shown while an [`AssetLoader`] slices the real one.
+    pub fn empty(tile_size: Size, default_filter: TextureFilter) -> Self {
+        Self {
+            categories: Vec::new(),
+            index: HashMap::new(),
+            directories: Vec::new(),
+            watchers: Vec::new(),
+            tile_size,
+            default_filter: FilterMode::from(default_filter),
+            sheets: Catalog::default(),
+        }
+    }
+
+    /// Existing directories the catalog loaded, in priority order.
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        self.watchers
+            .iter()
+            .map(AssetWatcher::root)
+    }
+
+    /// Every directory the catalog was asked to load, with how it loaded.
+    pub fn directory_status(&self) -> Vec<(&Path, AssetDirStatus)> {
+        self.directories
+            .iter()
+            .map(|directory| {
+                if !self
+                    .roots()
+                    .any(|root| root == directory)
+                {
+                    return (directory.as_path(), AssetDirStatus::Missing);
                 }
+                let loaded: Vec<&AssetCategory> = self
+                    .categories
+                    .iter()
+                    .filter(|category| self.root_of(category) == Some(directory.as_path()))
+                    .collect();
+                let tiles = loaded
+                    .iter()
+                    .map(|category| category.tile_count())
+                    .sum();
+                (directory.as_path(), AssetDirStatus::Loaded { categories: loaded.len(), tiles })
+            })
+            .collect()
+    }
+
+    /// Asset directory `category` was loaded from.
+    fn root_of(&self, category: &AssetCategory) -> Option<&Path> {
+        let source = category.source_dir.as_deref()?;
+        self.roots()
+            .find(|root| source == *root || source.parent() == Some(*root))
+    }
+
+    /// Reloads the categories whose images, sidecars or manifests changed on disk.
+    ///
+    /// The asset directories are polled about once a second. Returns `true` when categories
+    /// were reloaded; sprites then have new textures and palette indices may have shifted,
+    /// so painted tiles and selections should be looked up again by id.
+    pub async fn hot_reload(&mut self, now: f64) -> bool {
+        let mut directories = BTreeSet::new();
+        for (position, watcher) in self.watchers.iter_mut().enumerate() {
+            let root = watcher.root().to_path_buf();
+            for path in watcher.poll(now) {
+                let Ok(relative) = path.strip_prefix(&root) else {
+                    continue;
+                };
+                let mut components = relative.components();
+                let Some(first) = components.next() else {
+                    continue;
+                };
+                let directory = match components.next() {
+                    Some(_) => root.join(first),
+                    None => root.clone(),
+                };
+                directories.insert((position, directory));
             }
+        }
+        if directories.is_empty() {
+            return false;
+        }
 
-            if let Ok(entries) = fs::read_dir(&root) {
-                for entry in entries.flatten() {
-                    if entry.path().is_dir() {
-                        if let Some(category) = load_named_category(&entry.path(), tile_size).await
-                        {
-                            categories.push(category);
-                        }
-                    }
+        for (position, directory) in &directories {
+            let is_root = *directory == self.watchers[*position].root();
+            let reloaded = if is_root {
+                load_root_category(directory, *position, self.tile_size, self.default_filter).await
+            } else if directory.is_dir() {
+                load_named_category(directory, self.tile_size, self.default_filter).await
+            } else {
+                None
+            };
+            let index = self
+                .categories
+                .iter()
+                .position(|category| category.source_dir.as_ref() == Some(directory));
+            match (index, reloaded) {
+                (Some(index), Some(category)) => self.categories[index] = category,
+                (Some(index), None) => {
+                    self.categories.remove(index);
                 }
+                (None, Some(category)) if is_root && *position == 0 => {
+                    self.categories.insert(0, category);
+                }
+                (None, Some(category)) => self.categories.push(category),
+                (None, None) => {}
+            }
+            println!("[assets] Reloaded {:?}", directory);
+        }
+
+        let roots: Vec<PathBuf> = self
+            .roots()
+            .map(Path::to_path_buf)
+            .collect();
+        self.sheets = Catalog::load_all(&roots, sheet_tile_size(self.tile_size));
+        self.dedupe_ids();
+        self.rebuild_index();
+        true
+    }
+
+    /// Falls back to the generated id for sprites whose sidecar id is already taken.
+    fn dedupe_ids(&mut self) {
+        let mut seen = HashSet::new();
+        for sprite in self
+            .categories
+            .iter_mut()
+            .flat_map(|category| category.tiles_mut())
+        {
+            if !seen.insert(sprite.id.clone()) {
+                let generated = generated_tile_id(&sprite.source.path, sprite.source.index);
+                eprintln!(
+                    "[assets] Duplicate tile id {:?}; {:?} keeps its generated id",
+                    sprite.id, generated
+                );
+                seen.insert(generated.clone());
+                sprite.id = generated;
+            }
+        }
+    }
+
+    /// Drops every sprite whose id is not in `used_ids`, returning how many were freed.
+    ///
+    /// Textures are released once the last handle to them is dropped. Categories and
+    /// sub-categories left without tiles are removed.
+    pub fn compact(&mut self, used_ids: &HashSet<String>) -> usize {
+        let mut freed = 0;
+        for category in &mut self.categories {
+            category.retain(|sprite| {
+                let keep = used_ids.contains(&sprite.id);
+                if !keep {
+                    freed += 1;
+                }
+                keep
+            });
+        }
+        self.categories
+            .retain(|category| !category.is_empty());
+        self.rebuild_index();
+        freed
+    }
+
+    /// Indexes sprites by id and by generated `<sheet>::<index>` id, so maps saved before
+    /// a tile got a stable id still resolve.
+    fn rebuild_index(&mut self) {
+        self.index = self
+            .categories
+            .iter()
+            .enumerate()
+            .flat_map(|(category_index, category)| {
+                category
+                    .tiles()
+                    .enumerate()
+                    .map(move |(tile_index, tile)| (tile.id.clone(), (category_index, tile_index)))
+            })
+            .collect();
+        for (category_index, category) in self.categories.iter().enumerate() {
+            for (tile_index, tile) in category.tiles().enumerate() {
+                self.index
+                    .entry(generated_tile_id(&tile.source.path, tile.source.index))
+                    .or_insert((category_index, tile_index));
             }
-        } else {
-            eprintln!(
-                "[assets] Unable to locate an assets directory near the executable. \
-                 Place your assets next to the final binary in an `assets` folder."
-            );
         }
+    }
 
-        Self { categories }
+    /// Applies a new default filter to every category without its own override.
+    pub fn set_default_filter_mode(&mut self, filter: TextureFilter) {
+        let filter = FilterMode::from(filter);
+        self.default_filter = filter;
+        for category in &mut self.categories {
+            if category.filter_override.is_some() {
+                continue;
+            }
+            for sprite in category.tiles_mut() {
+                sprite.filter_mode = filter;
+                sprite.texture.set_filter(filter);
+                sprite.atlas.set_filter(filter);
+            }
+        }
+    }
+
+    /// Replaces the properties of the tile type `id`.
+    pub fn set_tile_metadata(&mut self, id: &str, metadata: BTreeMap<String, String>) {
+        if let Some(sprite) = self.sprite_by_id_mut(id) {
+            sprite.metadata = metadata;
+        }
+    }
+
+    /// Marks every sprite as walkable or solid from the brightness of its center.
+    ///
+    /// Dark tiles (average luminance of the center 3x3 pixels below `threshold`) become
+    /// solid. The result is stored in each sprite's metadata under [`WALKABLE_KEY`].
+    /// Returns `(walkable, solid)` counts.
+    pub fn auto_detect_collision(&mut self, threshold: u8) -> (usize, usize) {
+        let (mut walkable, mut solid) = (0, 0);
+        for sprite in self
+            .categories
+            .iter_mut()
+            .flat_map(|category| category.tiles_mut())
+        {
+            let is_walkable = center_brightness(&sprite.texture.get_texture_data()) >= threshold;
+            if is_walkable {
+                walkable += 1;
+            } else {
+                solid += 1;
+            }
+            sprite
+                .metadata
+                .insert(WALKABLE_KEY.to_string(), is_walkable.to_string());
+        }
+        (walkable, solid)
+    }
+
+    /// Stores the properties of `id` in its spritesheet's `.tileset.json` sidecar.
+    pub fn save_tile_metadata(&self, id: &str) -> Result<(), io::Error> {
+        let sprite = self.sprite_by_id(id).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("unknown tile {id}"))
+        })?;
+        let path = &sprite.source.path;
+        let mut sidecar = TilesetSidecar::load_for(path);
+        let key = sprite.source.index.to_string();
+        if sprite.metadata.is_empty() {
+            sidecar.metadata.remove(&key);
+        } else {
+            sidecar
+                .metadata
+                .insert(key, sprite.metadata.clone());
+        }
+        sidecar.save_for(path)
     }
 
     pub fn categories(&self) -> &[AssetCategory] {
@@ -67,85 +415,357 @@ impl AssetCatalog {
         self.categories.is_empty()
     }
 
+    /// Looks a sprite up by its id, or by the generated id of its sheet slot.
     pub fn sprite_by_id(&self, id: &str) -> Option<&TileSprite> {
-        self.categories
-            .iter()
-            .flat_map(|category| category.tiles.iter())
-            .find(|sprite| sprite.id == id)
+        let &(category, tile) = self.index.get(id)?;
+        self.categories[category].tile(tile)
+    }
+
+    /// Spritesheets of the assets directory without textures, as read by
+    /// [`forgetile_core::interop::tiled`].
+    pub fn sheets(&self) -> &Catalog {
+        &self.sheets
+    }
+
+    /// Returns the tile at `index` of the spritesheet with canonical path `sheet`.
+    pub fn sprite_at(&self, sheet: &Path, index: usize) -> Option<&TileSprite> {
+        self.sprite_by_id(&generated_tile_id(sheet, index))
+    }
+
+    fn sprite_by_id_mut(&mut self, id: &str) -> Option<&mut TileSprite> {
+        let &(category, tile) = self.index.get(id)?;
+        self.categories[category].tile_mut(tile)
+    }
+}
+
+impl TileSprite {
+    /// Returns where the sprite was sliced from.
+    pub fn sheet_slot(&self) -> SheetSlot<'_> {
+        SheetSlot {
+            path: &self.source.path,
+            index: self.source.index,
+            columns: self.source.columns,
+        }
+    }
+
+    /// Texture and source rect the map draws this tile with.
+    pub fn atlas_region(&self) -> (&Texture2D, Option<Rect>) {
+        (&self.atlas, Some(self.source.rect))
     }
 }
 
 impl AssetCategory {
     pub fn new(name: impl Into<String>, tiles: Vec<TileSprite>) -> Self {
-        Self { name: name.into(), tiles }
+        Self {
+            name: name.into(),
+            sub_categories: Vec::new(),
+            direct_tiles: tiles,
+            filter_override: None,
+            terrain: false,
+            source_dir: None,
+        }
     }
-}
 
-async fn load_named_category(path: &Path, tile_size: Size) -> Option<AssetCategory> {
-    let tiles = load_tiles_from_directory(path, tile_size).await;
-    if tiles.is_empty() {
-        return None;
+    /// Iterates every tile in flat index order.
+    pub fn tiles(&self) -> impl Iterator<Item = &TileSprite> {
+        self.direct_tiles.iter().chain(
+            self.sub_categories
+                .iter()
+                .flat_map(|sub| sub.tiles.iter()),
+        )
     }
 
-    let name = path
-        .file_name()
-        .map(|s| s.to_string_lossy())
-        .unwrap_or(Cow::Borrowed("Assets"));
-    Some(AssetCategory::new(name.into_owned(), tiles))
-}
+    fn tiles_mut(&mut self) -> impl Iterator<Item = &mut TileSprite> {
+        self.direct_tiles.iter_mut().chain(
+            self.sub_categories
+                .iter_mut()
+                .flat_map(|sub| sub.tiles.iter_mut()),
+        )
+    }
 
-async fn load_category_from_path(path: &Path, tile_size: Size) -> Option<AssetCategory> {
-    if !path.is_dir() {
-        return None;
+    /// Returns the tile at a flat index.
+    pub fn tile(&self, index: usize) -> Option<&TileSprite> {
+        if let Some(tile) = self.direct_tiles.get(index) {
+            return Some(tile);
+        }
+        let mut index = index - self.direct_tiles.len();
+        for sub in &self.sub_categories {
+            if index < sub.tiles.len() {
+                return sub.tiles.get(index);
+            }
+            index -= sub.tiles.len();
+        }
+        None
     }
 
-    let tiles = load_tiles_from_directory(path, tile_size).await;
-    Some(AssetCategory::new(
-        path.file_name()
-            .map(|s| s.to_string_lossy().into_owned())
-            .unwrap_or_else(|| "Assets".to_string()),
-        tiles,
-    ))
-}
+    fn tile_mut(&mut self, index: usize) -> Option<&mut TileSprite> {
+        if index < self.direct_tiles.len() {
+            return self.direct_tiles.get_mut(index);
+        }
+        let mut index = index - self.direct_tiles.len();
+        for sub in &mut self.sub_categories {
+            if index < sub.tiles.len() {
+                return sub.tiles.get_mut(index);
+            }
+            index -= sub.tiles.len();
+        }
+        None
+    }
 
-async fn load_tiles_from_directory(path: &Path, tile_size: Size) -> Vec<TileSprite> {
-    let mut tiles = Vec::new();
+    pub fn tile_count(&self) -> usize {
+        self.direct_tiles.len()
+            + self
+                .sub_categories
+                .iter()
+                .map(|sub| sub.tiles.len())
+                .sum::<usize>()
+    }
 
-    let Ok(entries) = fs::read_dir(path) else {
-        eprintln!("[assets] Failed to read directory {:?}", path);
-        return tiles;
-    };
+    pub fn is_empty(&self) -> bool {
+        self.tile_count() == 0
+    }
 
-    for entry in entries.flatten() {
-        let entry_path = entry.path();
-        if entry_path.is_file() && is_supported_image(&entry_path) {
-            match load_tiles_from_image(&entry_path, tile_size).await {
-                Some(mut sprite_tiles) => tiles.append(&mut sprite_tiles),
-                None => {
-                    eprintln!("[assets] Could not process {:?}", entry_path);
-                }
+    /// Flat index range covered by the sub-category at `sub_index`.
+    pub fn sub_category_range(&self, sub_index: usize) -> Range<usize> {
+        let start = self.direct_tiles.len()
+            + self.sub_categories[..sub_index.min(self.sub_categories.len())]
+                .iter()
+                .map(|sub| sub.tiles.len())
+                .sum::<usize>();
+        let len = self
+            .sub_categories
+            .get(sub_index)
+            .map_or(0, |sub| sub.tiles.len());
+        start..start + len
+    }
+
+    /// End of the sub-tree rooted at `sub_index`: the index of the next sub-category that
+    /// is not nested in it.
+    fn sub_tree_end(&self, sub_index: usize) -> usize {
+        let Some(root) = self.sub_categories.get(sub_index) else {
+            return self.sub_categories.len();
+        };
+        self.sub_categories[sub_index + 1..]
+            .iter()
+            .position(|sub| sub.depth <= root.depth)
+            .map_or(self.sub_categories.len(), |offset| sub_index + 1 + offset)
+    }
+
+    /// Returns `true` when other sub-categories are nested in the one at `sub_index`.
+    pub fn has_nested(&self, sub_index: usize) -> bool {
+        self.sub_tree_end(sub_index) > sub_index + 1
+    }
+
+    /// Flat index range covered by the sub-category at `sub_index` and the ones nested
+    /// in it.
+    pub fn sub_tree_range(&self, sub_index: usize) -> Range<usize> {
+        let start = self.sub_category_range(sub_index).start;
+        let end = self
+            .sub_category_range(self.sub_tree_end(sub_index))
+            .start;
+        start..end
+    }
+
+    /// Names from the category down to the sub-category at `sub_index`, e.g. `Trees/Oak`
+    /// for `<category>/Trees/Oak`.
+    pub fn sub_category_path(&self, sub_index: usize) -> String {
+        let Some(sub) = self.sub_categories.get(sub_index) else {
+            return String::new();
+        };
+        let mut names = vec![sub.name.as_str()];
+        let mut depth = sub.depth;
+        for ancestor in self.sub_categories[..sub_index]
+            .iter()
+            .rev()
+        {
+            if ancestor.depth < depth {
+                names.push(&ancestor.name);
+                depth = ancestor.depth;
             }
         }
+        names.reverse();
+        names.join("/")
+    }
+
+    /// Keeps only the tiles matching `keep`, dropping sub-trees left empty.
+    fn retain(&mut self, mut keep: impl FnMut(&TileSprite) -> bool) {
+        self.direct_tiles.retain(&mut keep);
+        for sub in &mut self.sub_categories {
+            sub.tiles.retain(&mut keep);
+        }
+        self.prune_sub_categories();
+    }
+
+    /// Drops the sub-categories without tiles, keeping empty ones that group others.
+    fn prune_sub_categories(&mut self) {
+        let keep: Vec<bool> = (0..self.sub_categories.len())
+            .map(|sub_index| {
+                !self
+                    .sub_tree_range(sub_index)
+                    .is_empty()
+            })
+            .collect();
+        let mut keep = keep.into_iter();
+        self.sub_categories
+            .retain(|_| keep.next().unwrap_or(false));
+    }
+}
+
+/// Category whose name, groups and settings are known but whose images are not sliced yet.
+struct CategoryPlan {
+    category: AssetCategory,
+    filter: FilterMode,
+    /// Images to slice, with the sub-category receiving their tiles; `None` for the
+    /// category's own tiles.
+    images: Vec<(Option<usize>, PathBuf)>,
+}
+
+impl CategoryPlan {
+    /// Plans the category of `path` without its sub-directories, honoring its
+    /// `category.json`. Returns `None` when `path` is not a directory.
+    fn new(path: &Path, default_filter: FilterMode) -> Option<Self> {
+        if !path.is_dir() {
+            return None;
+        }
+
+        let manifest = CategoryManifest::load_for(path);
+        let filter_override = manifest
+            .filter_mode
+            .map(FilterMode::from);
+        let mut category = AssetCategory::new(
+            path.file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Assets".to_string()),
+            Vec::new(),
+        );
+        category.filter_override = filter_override;
+        category.terrain = manifest.terrain;
+        category.source_dir = Some(path.to_path_buf());
+        Some(Self {
+            category,
+            filter: filter_override.unwrap_or(default_filter),
+            images: list_images(path)
+                .into_iter()
+                .map(|image| (None, image))
+                .collect(),
+        })
+    }
+
+    /// Plans a category directory, grouping the images of each nested sub-directory into
+    /// a [`SubCategory`].
+    fn named(path: &Path, default_filter: FilterMode) -> Option<Self> {
+        let mut plan = Self::new(path, default_filter)?;
+        for sub_path in nested_directories(path) {
+            let sub_index = plan.category.sub_categories.len();
+            plan.category
+                .sub_categories
+                .push(SubCategory {
+                    name: sub_path
+                        .file_name()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    depth: sub_path
+                        .strip_prefix(path)
+                        .map_or(1, |relative| relative.components().count()),
+                    tiles: Vec::new(),
+                });
+            plan.images.extend(
+                list_images(&sub_path)
+                    .into_iter()
+                    .map(|image| (Some(sub_index), image)),
+            );
+        }
+        Some(plan)
+    }
+
+    /// Plans the images placed directly in an asset directory: "General" for the first
+    /// directory, named after the directory for the others.
+    fn root(root: &Path, position: usize, default_filter: FilterMode) -> Option<Self> {
+        let mut plan = Self::new(root, default_filter)?;
+        if position == 0 {
+            plan.category.name = "General".to_string();
+        }
+        Some(plan)
+    }
+
+    /// Slices the image `path` and adds its tiles to `sub_category`, or to the category's
+    /// own tiles for `None`.
+    async fn load_image(&mut self, sub_category: Option<usize>, path: &Path, tile_size: Size) {
+        let Some(mut tiles) = load_tiles_from_image(path, tile_size, self.filter).await else {
+            eprintln!("[assets] Could not process {:?}", path);
+            return;
+        };
+        match sub_category.and_then(|index| {
+            self.category
+                .sub_categories
+                .get_mut(index)
+        }) {
+            Some(sub) => sub.tiles.append(&mut tiles),
+            None => self
+                .category
+                .direct_tiles
+                .append(&mut tiles),
+        }
+    }
+
+    /// Slices every planned image at once.
+    async fn load(mut self, tile_size: Size) -> Option<AssetCategory> {
+        for (sub_category, path) in std::mem::take(&mut self.images) {
+            self.load_image(sub_category, &path, tile_size)
+                .await;
+        }
+        self.finish()
     }
 
-    tiles
+    /// The loaded category without its empty sub-trees; `None` when it holds no tile.
+    fn finish(mut self) -> Option<AssetCategory> {
+        self.category.prune_sub_categories();
+        (!self.category.is_empty()).then_some(self.category)
+    }
+}
+
+/// Reloads a category directory with its sub-categories.
+async fn load_named_category(
+    path: &Path, tile_size: Size, default_filter: FilterMode,
+) -> Option<AssetCategory> {
+    CategoryPlan::named(path, default_filter)?
+        .load(tile_size)
+        .await
 }
 
-fn is_supported_image(path: &Path) -> bool {
-    matches!(
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_ascii_lowercase()),
-        Some(ref ext) if ["png", "jpg", "jpeg"].contains(&ext.as_str())
-    )
+/// Reloads the images placed directly in an asset directory.
+async fn load_root_category(
+    root: &Path, position: usize, tile_size: Size, default_filter: FilterMode,
+) -> Option<AssetCategory> {
+    CategoryPlan::root(root, position, default_filter)?
+        .load(tile_size)
+        .await
 }
 
-async fn load_tiles_from_image(path: &Path, tile_size: Size) -> Option<Vec<TileSprite>> {
-    let image = load_image(path.to_str()?).await.ok()?;
-    let (tile_width, tile_height) = size_to_pixels(tile_size)?;
+/// Supported images directly inside `path`.
+fn list_images(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(path) else {
+        eprintln!("[assets] Failed to read directory {:?}", path);
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|entry_path| entry_path.is_file() && is_supported_image(entry_path))
+        .collect()
+}
 
-    let columns = image.width() / tile_width;
-    let rows = image.height() / tile_height;
+async fn load_tiles_from_image(
+    path: &Path, tile_size: Size, filter: FilterMode,
+) -> Option<Vec<TileSprite>> {
+    let SheetImage { image, sidecar, shown } =
+        read_sheet(path, size_to_pixels(tile_size)?).await?;
+    let (tile_width, tile_height) = sidecar
+        .tile_size()
+        .or_else(|| size_to_pixels(tile_size))?;
+
+    let tile_size = (tile_width, tile_height);
+    let (columns, rows) = sidecar.grid((image.width(), image.height()), tile_size);
     if columns == 0 || rows == 0 {
         return None;
     }
@@ -154,32 +774,217 @@ async fn load_tiles_from_image(path: &Path, tile_size: Size) -> Option<Vec<TileS
         .file_stem()
         .unwrap_or_default()
         .to_string_lossy();
+    let canonical_path = path
+        .canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf());
+    let atlas = build_atlas(&image, &sidecar, filter);
     let mut sprites = Vec::with_capacity(columns * rows);
 
+    let tile_rect = |index: usize| to_rect(sidecar.tile_rect(index, columns, tile_size));
+
     for row in 0..rows {
         for col in 0..columns {
-            let rect = Rect::new(
-                (col * tile_width) as f32,
-                (row * tile_height) as f32,
-                tile_width as f32,
-                tile_height as f32,
-            );
-            let tile_image = image.sub_image(rect);
-            let texture = Texture2D::from_image(&tile_image);
-            texture.set_filter(FilterMode::Nearest);
-
-            let label = format!("{}_{:02}", file_stem, row * columns + col);
-            let canonical_path = path
-                .canonicalize()
-                .unwrap_or_else(|_| path.to_path_buf());
-            let id = format!("{}::{}", canonical_path.display(), row * columns + col);
-            sprites.push(TileSprite { id, name: label, texture });
+            let index = row * columns + col;
+            if shown
+                .as_ref()
+                .is_some_and(|shown| !shown.contains(&index))
+            {
+                continue;
+            }
+            if sidecar.trim_transparent
+                && is_blank_tile(&image, &sidecar, index, columns, tile_size)
+            {
+                continue;
+            }
+            let rect = tile_rect(index);
+            let texture = slice_tile(&image, rect, &sidecar, filter);
+
+            let label = sidecar
+                .tile_name(index)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{}_{:02}", file_stem, index));
+            let id = sidecar
+                .tile_id(index)
+                .map(str::to_string)
+                .unwrap_or_else(|| generated_tile_id(&canonical_path, index));
+            let source = TileSource {
+                path: canonical_path.clone(),
+                index,
+                rect,
+                columns,
+            };
+            sprites.push(TileSprite {
+                id,
+                name: label,
+                texture,
+                atlas: atlas.clone(),
+                filter_mode: filter,
+                metadata: sidecar.tile_metadata(index),
+                animation_mode: AnimationMode::Static,
+                source,
+            });
         }
     }
 
+    for (key, spec) in &sidecar.animations {
+        let Some(sprite) = key
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| {
+                sprites
+                    .iter_mut()
+                    .find(|sprite| sprite.source.index == index)
+            })
+        else {
+            eprintln!("[assets] Ignoring animation for unknown tile {key} in {:?}", path);
+            continue;
+        };
+        let index = sprite.source.index;
+
+        sprite.animation_mode = match spec {
+            AnimationSpec::UvScroll { columns: cols, fps } => {
+                let cols = (*cols).min(columns - index % columns);
+                if cols < 2 {
+                    continue;
+                }
+                AnimationMode::UVScroll {
+                    origin: tile_rect(index),
+                    cols,
+                    stride: (tile_width + sidecar.spacing) as f32,
+                    fps: *fps,
+                }
+            }
+            AnimationSpec::Frames { frames, fps } => {
+                let frames: Vec<Rect> = frames
+                    .iter()
+                    .filter(|&&frame| frame < columns * rows)
+                    .map(|&frame| tile_rect(frame))
+                    .collect();
+                if frames.is_empty() {
+                    continue;
+                }
+                AnimationMode::FrameList { frames, fps: *fps }
+            }
+        };
+    }
+
     Some(sprites)
 }
 
+/// Returns `true` when the tile at `index` is fully transparent after the color key, and
+/// so are the frames of its `frames` animation, if any.
+fn is_blank_tile(
+    image: &Image, sidecar: &TilesetSidecar, index: usize, columns: usize,
+    tile_size: (usize, usize),
+) -> bool {
+    let frames = match sidecar
+        .animations
+        .get(&index.to_string())
+    {
+        Some(AnimationSpec::Frames { frames, .. }) => frames.as_slice(),
+        _ => &[],
+    };
+    std::iter::once(&index)
+        .chain(frames)
+        .all(|&frame| {
+            let rect = sidecar.tile_rect(frame, columns, tile_size);
+            sidecar.is_transparent(&image.bytes, usize::from(image.width), rect)
+        })
+}
+
+/// Decoded spritesheet and the sidecar it is sliced with.
+struct SheetImage {
+    image: Image,
+    sidecar: TilesetSidecar,
+    /// Tile indices shown in the palette; `None` shows every tile.
+    shown: Option<Vec<usize>>,
+}
+
+/// Reads the spritesheet at `path`. Animated images become their frames laid out in a
+/// grid, sliced by a generated sidecar whose animations cycle through the frames.
+async fn read_sheet(path: &Path, tile_size: (usize, usize)) -> Option<SheetImage> {
+    if !AnimatedSheet::is_animated(path) {
+        return Some(SheetImage {
+            image: load_image(path.to_str()?).await.ok()?,
+            sidecar: TilesetSidecar::load_for(path),
+            shown: None,
+        });
+    }
+    let sheet = match AnimatedSheet::load(path, tile_size) {
+        Ok(sheet) => sheet?,
+        Err(err) => {
+            eprintln!("[assets] Could not decode {:?}: {err}", path);
+            return None;
+        }
+    };
+    let shown = Some(sheet.tiles.clone());
+    let sidecar = sheet.sidecar.clone();
+    Some(SheetImage {
+        image: sheet_image(sheet)?,
+        sidecar,
+        shown,
+    })
+}
+
+/// Image of the frames of `sheet`, whose size [`AnimatedSheet`] keeps within
+/// [`MAX_TEXTURE_SIZE`](forgetile_core::assets::animated::MAX_TEXTURE_SIZE).
+fn sheet_image(sheet: AnimatedSheet) -> Option<Image> {
+    Some(Image {
+        bytes: sheet.rgba,
+        width: u16::try_from(sheet.width).ok()?,
+        height: u16::try_from(sheet.height).ok()?,
+    })
+}
+
+/// Uploads the color-keyed spritesheet as one texture shared by all of its tiles.
+fn build_atlas(image: &Image, sidecar: &TilesetSidecar, filter: FilterMode) -> Texture2D {
+    let mut keyed = image.clone();
+    sidecar.apply_color_key(&mut keyed.bytes);
+    let texture = Texture2D::from_image(&keyed);
+    texture.set_filter(filter);
+    texture
+}
+
+fn slice_tile(
+    image: &Image, rect: Rect, sidecar: &TilesetSidecar, filter: FilterMode,
+) -> Texture2D {
+    let mut tile_image = image.sub_image(rect);
+    sidecar.apply_color_key(&mut tile_image.bytes);
+    let texture = Texture2D::from_image(&tile_image);
+    texture.set_filter(filter);
+    texture
+}
+
+/// Average luminance of the 3x3 block at the center of `image`.
+fn center_brightness(image: &Image) -> u8 {
+    let (width, height) = (usize::from(image.width), usize::from(image.height));
+    if width == 0 || height == 0 {
+        return 0;
+    }
+    let (center_x, center_y) = (width / 2, height / 2);
+
+    let mut total = 0.0;
+    let mut samples = 0.0;
+    for y in center_y.saturating_sub(1)..(center_y + 2).min(height) {
+        for x in center_x.saturating_sub(1)..(center_x + 2).min(width) {
+            let offset = (y * width + x) * 4;
+            let [r, g, b] = [0, 1, 2].map(|channel| f32::from(image.bytes[offset + channel]));
+            total += 0.299 * r + 0.587 * g + 0.114 * b;
+            samples += 1.0;
+        }
+    }
+    (total / samples).round() as u8
+}
+
+fn to_rect(rect: PixelRect) -> Rect {
+    Rect::new(rect.x as f32, rect.y as f32, rect.width as f32, rect.height as f32)
+}
+
+/// Default tile size handed to the [`Catalog`], matching [`size_to_pixels`].
+fn sheet_tile_size(size: Size) -> (u32, u32) {
+    (size.width.round() as u32, size.height.round() as u32)
+}
+
 fn size_to_pixels(size: Size) -> Option<(usize, usize)> {
     let width = size.width.round() as usize;
     let height = size.height.round() as usize;
@@ -192,10 +997,10 @@ fn size_to_pixels(size: Size) -> Option<(usize, usize)> {
 fn resolve_assets_root() -> Option<PathBuf> {
     let mut candidates = Vec::new();
 
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(dir) = exe_path.parent() {
-            candidates.push(dir.join("assets"));
-        }
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(dir) = exe_path.parent()
+    {
+        candidates.push(dir.join("assets"));
     }
 
     if let Ok(current_dir) = std::env::current_dir() {
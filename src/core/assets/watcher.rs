@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use forgetile_core::assets::catalog::MAX_CATEGORY_DEPTH;
+
+/// Polls the assets directory for added, removed or modified asset files.
+///
+/// Images, tileset sidecars, Aseprite sheet data and `category.json` files are watched in the root, the
+/// category folders and their nested sub-category folders, mirroring what the catalog loads.
+pub struct AssetWatcher {
+    root: PathBuf,
+    snapshot: BTreeMap<PathBuf, (SystemTime, u64)>,
+    last_poll: f64,
+}
+
+impl AssetWatcher {
+    /// Seconds between two scans of the assets directory.
+    const POLL_INTERVAL: f64 = 1.0;
+
+    /// Starts watching `root`, taking the files currently present as the baseline.
+    pub fn new(root: PathBuf, now: f64) -> Self {
+        let snapshot = scan(&root);
+        Self { root, snapshot, last_poll: now }
+    }
+
+    /// Rescans the directory once the poll interval has elapsed, returning the files that
+    /// changed since the previous scan.
+    pub fn poll(&mut self, now: f64) -> Vec<PathBuf> {
+        if now - self.last_poll < Self::POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = now;
+
+        let snapshot = scan(&self.root);
+        let mut changed: Vec<PathBuf> = snapshot
+            .iter()
+            .filter(|(path, stamp)| self.snapshot.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.snapshot
+                .keys()
+                .filter(|path| !snapshot.contains_key(*path))
+                .cloned(),
+        );
+        self.snapshot = snapshot;
+        changed
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+/// Modification time and size of every watched file under `root`, down to the deepest
+/// sub-category level the catalog loads.
+fn scan(root: &Path) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+    let mut snapshot = BTreeMap::new();
+    let mut directories = vec![(root.to_path_buf(), 0)];
+    while let Some((directory, depth)) = directories.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if depth <= MAX_CATEGORY_DEPTH {
+                    directories.push((path, depth + 1));
+                }
+            } else if is_watched_file(&path) {
+                let modified = metadata
+                    .modified()
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                snapshot.insert(path, (modified, metadata.len()));
+            }
+        }
+    }
+    snapshot
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    // Covers `category.json`, `.tileset.json` sidecars and Aseprite sheet data.
+    [
+        ".json",
+        ".png",
+        ".jpg",
+        ".jpeg",
+        ".gif",
+        ".ase",
+        ".aseprite",
+    ]
+    .iter()
+    .any(|extension| name.ends_with(extension))
+}
@@ -1,9 +1,13 @@
 use macroquad::camera::Camera2D;
-use macroquad::input::{KeyCode, is_key_down};
+use macroquad::input::{
+    KeyCode, MouseButton, TouchPhase, is_key_down, is_mouse_button_down, mouse_position,
+    mouse_wheel, touches,
+};
 use macroquad::math::{Rect, Vec2, vec2};
 use macroquad::prelude::screen_width;
 use macroquad::time::get_frame_time;
 use macroquad::window::screen_height;
+use serde::{Deserialize, Serialize};
 
 /// 2D position expressed as horizontal (`x`) and vertical (`y`) components.
 #[derive(Debug, Clone)]
@@ -24,6 +28,79 @@ impl From<AxisPosition> for Vec2 {
     }
 }
 
+/// Restricts camera panning to a single axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PanAxisLock {
+    /// Pan freely on both axes.
+    #[default]
+    None,
+    /// Ignore horizontal pan input.
+    LockX,
+    /// Ignore vertical pan input.
+    LockY,
+}
+
+impl PanAxisLock {
+    /// Zeroes the components of `delta` blocked by the lock.
+    fn apply(self, delta: Vec2) -> Vec2 {
+        match self {
+            PanAxisLock::None => delta,
+            PanAxisLock::LockX => vec2(0.0, delta.y),
+            PanAxisLock::LockY => vec2(delta.x, 0.0),
+        }
+    }
+}
+
+/// Path followed by the camera while playback mode scrolls across the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackDirection {
+    /// Sweep rows left to right, moving down one screen per row.
+    #[default]
+    LeftToRight,
+    /// Sweep columns top to bottom, moving right one screen per column.
+    TopToBottom,
+    /// Spiral outwards from the map center.
+    Spiral,
+}
+
+impl PlaybackDirection {
+    pub const ALL: [PlaybackDirection; 3] = [
+        PlaybackDirection::LeftToRight,
+        PlaybackDirection::TopToBottom,
+        PlaybackDirection::Spiral,
+    ];
+
+    /// Label shown in the tools panel.
+    pub fn name(self) -> &'static str {
+        match self {
+            PlaybackDirection::LeftToRight => "Left to right",
+            PlaybackDirection::TopToBottom => "Top to bottom",
+            PlaybackDirection::Spiral => "Spiral",
+        }
+    }
+}
+
+/// Zoom step or jump being eased in over [`CameraController::ANIMATION_SECS`].
+#[derive(Debug, Clone)]
+struct CameraAnimation {
+    from_center: Vec2,
+    from_zoom: f32,
+    to_center: Vec2,
+    to_zoom: f32,
+    /// Screen point and the world point kept under it while zooming toward the cursor
+    anchor: Option<(Vec2, Vec2)>,
+    elapsed: f32,
+}
+
+/// Two-finger touch gesture as seen during the previous frame.
+#[derive(Debug, Clone, Copy)]
+struct PinchGesture {
+    /// Screen distance between the two fingers
+    distance: f32,
+    /// Screen point halfway between the two fingers
+    midpoint: Vec2,
+}
+
 /// Encapsulates zoom, panning and viewport conversion logic for the editor camera.
 #[derive(Debug, Clone)]
 pub struct CameraController {
@@ -31,6 +108,27 @@ pub struct CameraController {
     pub screen_center: AxisPosition,
     /// Current zoom level (1.0 = normal, >1.0 = zoomed in, <1.0 = zoomed out)
     pub zoom_level: f32,
+    /// Axis on which pan input is ignored
+    pub pan_axis_lock: PanAxisLock,
+    /// Whether pan and zoom keys are read (disabled while text is being typed)
+    pub keyboard_input_enabled: bool,
+    /// Whether the mouse wheel and pinch gestures move the camera (disabled while the cursor
+    /// is over a panel)
+    pub mouse_wheel_enabled: bool,
+    /// Multiplier of the zoom step made by one mouse wheel notch
+    pub wheel_zoom_sensitivity: f32,
+    /// Multiplier of the distance panned by one mouse wheel notch
+    pub wheel_pan_sensitivity: f32,
+    /// Whether zoom steps and jumps animate instead of snapping
+    pub smooth_motion: bool,
+    /// Zoom step or jump in progress
+    animation: Option<CameraAnimation>,
+    /// Screen position of the cursor during the previous frame of a right-button drag
+    drag_anchor: Option<Vec2>,
+    /// Axis locked by a `Shift` snap-drag, chosen from the drag's first movement
+    drag_snap_lock: Option<PanAxisLock>,
+    /// Two-finger touch gesture in progress
+    pinch: Option<PinchGesture>,
 }
 
 impl CameraController {
@@ -42,32 +140,141 @@ impl CameraController {
     const MAX_ZOOM: f32 = 8.0;
     /// Minimum allowed zoom level (lower bound).
     const MIN_ZOOM: f32 = 0.1;
+    /// Duration of a smooth zoom step or jump, in seconds.
+    const ANIMATION_SECS: f32 = 0.15;
 
-    const PAN_SPEED: f32 = 1.0;
+    /// Keyboard pan speed in screen pixels per second, so panning feels the same at any zoom.
+    const PAN_SPEED: f32 = 600.0;
+    /// Screen pixels panned per mouse wheel notch at a pan sensitivity of `1.0`.
+    const WHEEL_PAN_PIXELS: f32 = 60.0;
+    /// Number of turns made by the spiral playback path.
+    const SPIRAL_TURNS: f32 = 3.0;
+    /// Points sampled per spiral turn.
+    const SPIRAL_SEGMENTS_PER_TURN: usize = 64;
 
     /// Creates a controller with the camera centered at the given world position.
     pub fn new(screen_center: AxisPosition) -> Self {
         Self {
             screen_center,
             zoom_level: Self::DEFAULT_ZOOM,
+            pan_axis_lock: PanAxisLock::default(),
+            keyboard_input_enabled: true,
+            mouse_wheel_enabled: true,
+            wheel_zoom_sensitivity: 1.0,
+            wheel_pan_sensitivity: 1.0,
+            smooth_motion: true,
+            animation: None,
+            drag_anchor: None,
+            drag_snap_lock: None,
+            pinch: None,
         }
     }
 
     /// Resets the zoom level to the default value (`1.0`).
     pub fn reset_zoom_level(&mut self) {
-        self.zoom_level = Self::DEFAULT_ZOOM;
+        self.animate_to(self.target_center(), Self::DEFAULT_ZOOM, None);
     }
 
     /// Decreases the zoom level (zoom out) while respecting the minimum threshold.
     pub fn decrease_zoom_level(&mut self) {
-        let zoom_after_decrease: f32 = self.zoom_level / Self::ZOOM_MULTIPLIER;
-        self.zoom_level = zoom_after_decrease.max(Self::MIN_ZOOM);
+        let zoom_after_decrease: f32 = self.target_zoom() / Self::ZOOM_MULTIPLIER;
+        self.animate_to(self.target_center(), zoom_after_decrease.max(Self::MIN_ZOOM), None);
     }
 
     /// Increases the zoom level (zoom in) while respecting the maximum threshold.
     pub fn increase_zoom_level(&mut self) {
-        let zoom_after_increase: f32 = self.zoom_level * Self::ZOOM_MULTIPLIER;
-        self.zoom_level = zoom_after_increase.min(Self::MAX_ZOOM);
+        let zoom_after_increase: f32 = self.target_zoom() * Self::ZOOM_MULTIPLIER;
+        self.animate_to(self.target_center(), zoom_after_increase.min(Self::MAX_ZOOM), None);
+    }
+
+    /// Multiplies the zoom level by `factor` around `screen_point`, keeping the world point
+    /// under it fixed on screen.
+    pub fn zoom_toward(&mut self, screen_point: Vec2, factor: f32) {
+        let world_point = self.screen_to_world(screen_point);
+        let zoom = (self.target_zoom() * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        let center = Self::anchored_center(screen_point, world_point, zoom);
+        self.animate_to(center, zoom, Some((screen_point, world_point)));
+    }
+
+    /// Centers the camera on `center` at `zoom` right away, clamped to the allowed zoom range.
+    pub fn set_view(&mut self, center: Vec2, zoom: f32) {
+        self.animation = None;
+        self.screen_center = center.into();
+        self.zoom_level = zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+    }
+
+    /// Centers the camera on `center` right away, keeping the zoom level.
+    pub fn set_center(&mut self, center: Vec2) {
+        self.set_view(center, self.target_zoom());
+    }
+
+    /// Moves the camera to `center`, animated unless smooth motion is off.
+    pub fn jump_to(&mut self, center: Vec2) {
+        self.animate_to(center, self.target_zoom(), None);
+    }
+
+    /// Zoom level the camera is heading to, or the current one when it is not animating.
+    fn target_zoom(&self) -> f32 {
+        self.animation
+            .as_ref()
+            .map_or(self.zoom_level, |animation| animation.to_zoom)
+    }
+
+    /// Center the camera is heading to, or the current one when it is not animating.
+    fn target_center(&self) -> Vec2 {
+        self.animation
+            .as_ref()
+            .map_or(self.screen_center.clone().into(), |animation| animation.to_center)
+    }
+
+    /// Camera center that shows `world_point` at `screen_point` when zoomed to `zoom`.
+    fn anchored_center(screen_point: Vec2, world_point: Vec2, zoom: f32) -> Vec2 {
+        let screen_half = vec2(screen_width(), screen_height()) / 2.0;
+        world_point - (screen_point - screen_half) / zoom
+    }
+
+    /// Starts easing toward `center` and `zoom`, or goes there at once if smooth motion is off.
+    fn animate_to(&mut self, center: Vec2, zoom: f32, anchor: Option<(Vec2, Vec2)>) {
+        if !self.smooth_motion {
+            self.set_view(center, zoom);
+            return;
+        }
+        self.animation = Some(CameraAnimation {
+            from_center: self.screen_center.clone().into(),
+            from_zoom: self.zoom_level,
+            to_center: center,
+            to_zoom: zoom,
+            anchor,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advances a running zoom step or jump by one frame.
+    pub fn animate(&mut self) {
+        let Some(animation) = &mut self.animation else {
+            return;
+        };
+        animation.elapsed += get_frame_time();
+        let progress = (animation.elapsed / Self::ANIMATION_SECS).min(1.0);
+        let eased = 1.0 - (1.0 - progress).powi(3);
+
+        // Interpolating the logarithm makes every zoom step feel equally fast.
+        let zoom = (animation.from_zoom.ln()
+            + (animation.to_zoom.ln() - animation.from_zoom.ln()) * eased)
+            .exp();
+        let center = match animation.anchor {
+            Some((screen_point, world_point)) => {
+                Self::anchored_center(screen_point, world_point, zoom)
+            }
+            None => animation
+                .from_center
+                .lerp(animation.to_center, eased),
+        };
+        self.screen_center = center.into();
+        self.zoom_level = zoom;
+        if progress >= 1.0 {
+            self.animation = None;
+        }
     }
 
     /// Returns the current zoom level.
@@ -77,13 +284,17 @@ impl CameraController {
 
     /// Processes keyboard input to update camera position.
     pub fn update_keyboard_pan(&mut self) {
+        if !self.keyboard_input_enabled {
+            return;
+        }
         let mut direction = Vec2::ZERO;
 
+        // World y grows downwards, like screen y.
         if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
-            direction.y += 1.0;
+            direction.y -= 1.0;
         }
         if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
-            direction.y -= 1.0;
+            direction.y += 1.0;
         }
         if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
             direction.x -= 1.0;
@@ -93,9 +304,57 @@ impl CameraController {
         }
 
         if direction.length_squared() > 0.0 {
-            let delta = direction.normalize() * Self::PAN_SPEED * get_frame_time();
-            self.screen_center.x += delta.x;
-            self.screen_center.y += delta.y;
+            let delta =
+                direction.normalize() * Self::PAN_SPEED / self.zoom_level * get_frame_time();
+            self.pan_by(delta);
+        }
+    }
+
+    /// Processes right-button drags, moving the grid along with the cursor.
+    ///
+    /// Holding `Shift` snaps the drag to whichever axis dominated its first movement.
+    pub fn update_mouse_pan(&mut self) {
+        if !is_mouse_button_down(MouseButton::Right) {
+            self.drag_anchor = None;
+            self.drag_snap_lock = None;
+            return;
+        }
+        let snapping = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        if !snapping {
+            self.drag_snap_lock = None;
+        }
+
+        let mouse = Vec2::from(mouse_position());
+        if let Some(anchor) = self.drag_anchor {
+            let mut delta = (anchor - mouse) / self.zoom_level;
+            if snapping {
+                if self.drag_snap_lock.is_none() && delta != Vec2::ZERO {
+                    self.drag_snap_lock = Some(if delta.x.abs() >= delta.y.abs() {
+                        PanAxisLock::LockY
+                    } else {
+                        PanAxisLock::LockX
+                    });
+                }
+                if let Some(lock) = self.drag_snap_lock {
+                    delta = lock.apply(delta);
+                }
+            }
+            self.pan_by(delta);
+        }
+        self.drag_anchor = Some(mouse);
+    }
+
+    /// Moves the camera center by a world-space delta, honoring the axis lock.
+    fn pan_by(&mut self, delta: Vec2) {
+        let delta = self.pan_axis_lock.apply(delta);
+        self.screen_center.x += delta.x;
+        self.screen_center.y += delta.y;
+        if let Some(animation) = &mut self.animation {
+            animation.from_center += delta;
+            animation.to_center += delta;
+            if let Some((_, world_point)) = &mut animation.anchor {
+                *world_point += delta;
+            }
         }
     }
 
@@ -107,6 +366,22 @@ impl CameraController {
         vec2(visible_width, visible_height)
     }
 
+    /// Converts a screen position to world coordinates.
+    pub fn screen_to_world(&self, screen_point: Vec2) -> Vec2 {
+        self.to_camera2d()
+            .screen_to_world(screen_point)
+    }
+
+    /// World position under the mouse cursor.
+    pub fn cursor_world_position(&self) -> Vec2 {
+        self.screen_to_world(Vec2::from(mouse_position()))
+    }
+
+    /// Width of one screen pixel in world units.
+    pub fn world_units_per_pixel(&self) -> f32 {
+        1.0 / self.zoom_level
+    }
+
     /// Calculates the boundary position for camera viewport.
     fn calculate_view_bound(center: f32, view_size: f32) -> f32 {
         center - view_size / 2.0
@@ -130,6 +405,10 @@ impl CameraController {
     pub fn update_zoom_from_input(&mut self) {
         use macroquad::input::is_key_pressed;
 
+        if !self.keyboard_input_enabled {
+            return;
+        }
+
         if is_key_pressed(KeyCode::Equal) {
             self.increase_zoom_level();
         }
@@ -143,13 +422,158 @@ impl CameraController {
         }
     }
 
+    /// Zooms one step toward the cursor per mouse wheel notch, or pans while a modifier is held.
+    ///
+    /// `Shift` + wheel pans horizontally and `Ctrl` + wheel vertically. A horizontal wheel, like
+    /// a sideways trackpad swipe, always pans horizontally. Only the scroll direction is used,
+    /// since wheel deltas differ between platforms.
+    pub fn update_wheel(&mut self) {
+        if !self.mouse_wheel_enabled {
+            return;
+        }
+        let (wheel_x, wheel_y) = mouse_wheel();
+        let (wheel_x, wheel_y) = (wheel_direction(wheel_x), wheel_direction(wheel_y));
+        if wheel_x == 0.0 && wheel_y == 0.0 {
+            return;
+        }
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+
+        // Scrolling up or left shows what lies above or left of the view.
+        let step = Self::WHEEL_PAN_PIXELS * self.wheel_pan_sensitivity / self.zoom_level;
+        let mut pan = vec2(-wheel_x, 0.0);
+        if shift {
+            pan.x -= wheel_y;
+        } else if ctrl {
+            pan.y -= wheel_y;
+        } else if wheel_y != 0.0 {
+            let factor = Self::ZOOM_MULTIPLIER.powf(wheel_y * self.wheel_zoom_sensitivity);
+            self.zoom_toward(Vec2::from(mouse_position()), factor);
+        }
+        if pan != Vec2::ZERO {
+            self.pan_by(pan * step);
+        }
+    }
+
+    /// Zooms around the fingers and pans along with them during a two-finger touch gesture.
+    ///
+    /// Only touch screens report the fingers; trackpad pinches are not exposed by miniquad.
+    pub fn update_pinch(&mut self) {
+        let fingers: Vec<Vec2> = touches()
+            .iter()
+            .filter(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|touch| touch.position)
+            .collect();
+        let [first, second] = fingers[..] else {
+            self.pinch = None;
+            return;
+        };
+        let gesture = PinchGesture {
+            distance: first.distance(second),
+            midpoint: (first + second) / 2.0,
+        };
+        if self.mouse_wheel_enabled
+            && let Some(previous) = self.pinch
+            && previous.distance > f32::EPSILON
+        {
+            self.pan_by((previous.midpoint - gesture.midpoint) / self.zoom_level);
+            let world_point = self.screen_to_world(gesture.midpoint);
+            let zoom = (self.zoom_level * gesture.distance / previous.distance)
+                .clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+            self.set_view(Self::anchored_center(gesture.midpoint, world_point, zoom), zoom);
+        }
+        self.pinch = Some(gesture);
+    }
+
     /// Updates the camera state based on the current grid size.
     pub fn update(&mut self, grid_size: Vec2) {
         self.update_zoom_from_input();
+        self.update_wheel();
+        self.update_pinch();
         self.update_keyboard_pan();
+        self.update_mouse_pan();
+        self.animate();
         self.clamp_to_bounds(grid_size);
     }
 
+    /// Places the camera `distance` world units along the looping playback path.
+    pub fn follow_playback(
+        &mut self, grid_size: Vec2, direction: PlaybackDirection, distance: f32,
+    ) {
+        let waypoints = self.playback_waypoints(grid_size, direction);
+        let segment_lengths: Vec<f32> = waypoints
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .collect();
+        let total: f32 = segment_lengths.iter().sum();
+        if total <= f32::EPSILON {
+            self.clamp_to_bounds(grid_size);
+            return;
+        }
+
+        let mut remaining = distance.rem_euclid(total);
+        for (pair, length) in waypoints
+            .windows(2)
+            .zip(segment_lengths)
+        {
+            if remaining <= length {
+                let point = pair[0].lerp(pair[1], remaining / length.max(f32::EPSILON));
+                self.screen_center = point.into();
+                return;
+            }
+            remaining -= length;
+        }
+    }
+
+    /// Builds the polyline of camera centers visited during playback.
+    fn playback_waypoints(&self, grid_size: Vec2, direction: PlaybackDirection) -> Vec<Vec2> {
+        let view_size = self.get_view_size();
+        let min = vec2(
+            clamp_component(f32::MIN, grid_size.x, view_size.x),
+            clamp_component(f32::MIN, grid_size.y, view_size.y),
+        );
+        let max = vec2(
+            clamp_component(f32::MAX, grid_size.x, view_size.x),
+            clamp_component(f32::MAX, grid_size.y, view_size.y),
+        );
+
+        match direction {
+            PlaybackDirection::LeftToRight => {
+                serpentine(min.y, max.y, view_size.y, |row, forward| {
+                    let (start, end) = if forward {
+                        (min.x, max.x)
+                    } else {
+                        (max.x, min.x)
+                    };
+                    [vec2(start, row), vec2(end, row)]
+                })
+            }
+            PlaybackDirection::TopToBottom => {
+                serpentine(min.x, max.x, view_size.x, |column, forward| {
+                    let (start, end) = if forward {
+                        (min.y, max.y)
+                    } else {
+                        (max.y, min.y)
+                    };
+                    [vec2(column, start), vec2(column, end)]
+                })
+            }
+            PlaybackDirection::Spiral => {
+                let center = (min + max) / 2.0;
+                let radius = (max - min) / 2.0;
+                let samples =
+                    (Self::SPIRAL_TURNS * Self::SPIRAL_SEGMENTS_PER_TURN as f32) as usize;
+                (0..=samples)
+                    .map(|step| {
+                        let t = step as f32 / samples as f32;
+                        let angle = t * Self::SPIRAL_TURNS * std::f32::consts::TAU;
+                        center + radius * t * vec2(angle.cos(), angle.sin())
+                    })
+                    .collect()
+            }
+        }
+    }
+
     /// Clamps the camera position to the bounds of the grid.
     fn clamp_to_bounds(&mut self, grid_size: Vec2) {
         let view_size = self.get_view_size();
@@ -158,6 +582,33 @@ impl CameraController {
     }
 }
 
+/// Visits bands from `start` to `end` spaced one view apart, alternating sweep direction.
+fn serpentine(
+    start: f32, end: f32, step: f32, sweep: impl Fn(f32, bool) -> [Vec2; 2],
+) -> Vec<Vec2> {
+    let mut waypoints = Vec::new();
+    let mut band = start;
+    let mut forward = true;
+    loop {
+        waypoints.extend(sweep(band, forward));
+        if band >= end || step <= 0.0 {
+            break;
+        }
+        band = (band + step).min(end);
+        forward = !forward;
+    }
+    waypoints
+}
+
+/// Scroll direction of a wheel delta: `1.0`, `-1.0` or `0.0` when it did not move.
+fn wheel_direction(delta: f32) -> f32 {
+    if delta == 0.0 {
+        0.0
+    } else {
+        delta.signum()
+    }
+}
+
 /// Clamps a component of the camera position to the bounds of the grid.
 fn clamp_component(center: f32, grid_extent: f32, view_extent: f32) -> f32 {
     let half_view = view_extent / 2.0;
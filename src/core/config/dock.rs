@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+/// Window edge a docked panel sticks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DockSide {
+    Left,
+    Right,
+}
+
+impl DockSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            DockSide::Left => DockSide::Right,
+            DockSide::Right => DockSide::Left,
+        }
+    }
+}
+
+/// Panels arranged by the dock, in their stacking order within a side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockPanel {
+    Palette,
+    Layers,
+    Properties,
+    History,
+    Minimap,
+}
+
+impl DockPanel {
+    pub const ALL: [DockPanel; 5] = [
+        DockPanel::Palette,
+        DockPanel::Layers,
+        DockPanel::Properties,
+        DockPanel::History,
+        DockPanel::Minimap,
+    ];
+}
+
+/// Side, size and collapsed state of one docked panel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PanelPlacement {
+    pub side: DockSide,
+    /// Width of the panel body, in screen pixels.
+    pub width: f32,
+    /// Preferred height of the panel body; shrunk when the side runs out of room.
+    pub height: f32,
+    /// Only the header is shown.
+    pub collapsed: bool,
+}
+
+impl PanelPlacement {
+    const fn new(side: DockSide, width: f32, height: f32) -> Self {
+        Self { side, width, height, collapsed: false }
+    }
+}
+
+/// Placement of every docked panel, persisted between sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockLayout {
+    pub palette: PanelPlacement,
+    pub layers: PanelPlacement,
+    pub properties: PanelPlacement,
+    pub history: PanelPlacement,
+    pub minimap: PanelPlacement,
+}
+
+impl Default for DockLayout {
+    fn default() -> Self {
+        Self {
+            palette: PanelPlacement::new(DockSide::Left, 280.0, 620.0),
+            layers: PanelPlacement::new(DockSide::Right, 260.0, 330.0),
+            properties: PanelPlacement::new(DockSide::Right, 380.0, 300.0),
+            history: PanelPlacement::new(DockSide::Right, 260.0, 220.0),
+            minimap: PanelPlacement::new(DockSide::Right, 180.0, 180.0),
+        }
+    }
+}
+
+impl DockLayout {
+    pub fn placement(&self, panel: DockPanel) -> &PanelPlacement {
+        match panel {
+            DockPanel::Palette => &self.palette,
+            DockPanel::Layers => &self.layers,
+            DockPanel::Properties => &self.properties,
+            DockPanel::History => &self.history,
+            DockPanel::Minimap => &self.minimap,
+        }
+    }
+
+    pub fn placement_mut(&mut self, panel: DockPanel) -> &mut PanelPlacement {
+        match panel {
+            DockPanel::Palette => &mut self.palette,
+            DockPanel::Layers => &mut self.layers,
+            DockPanel::Properties => &mut self.properties,
+            DockPanel::History => &mut self.history,
+            DockPanel::Minimap => &mut self.minimap,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Language of the editor's labels and messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "pt-BR")]
+    BrazilianPortuguese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [
+        Language::English,
+        Language::BrazilianPortuguese,
+    ];
+
+    /// Name of the language in itself, as listed in the Preferences dialog.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::BrazilianPortuguese => "Português (Brasil)",
+        }
+    }
+}
@@ -0,0 +1,199 @@
+pub mod dock;
+pub mod language;
+pub mod theme;
+
+use crate::core::assets::TextureFilter;
+use crate::core::camera::PanAxisLock;
+use crate::core::config::dock::DockLayout;
+use crate::core::config::language::Language;
+use crate::core::config::theme::UiTheme;
+use crate::core::map::format::{MapFormat, TileEncoding};
+use crate::core::map::grid::GridStyle;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the working directory) holding editor state files.
+pub const STATE_DIR: &str = ".forgetile";
+
+/// User preferences persisted between editor sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Short WAV/OGG clip played whenever a tile is painted (`None` = silent).
+    pub paint_sound_path: Option<PathBuf>,
+    /// TTF font used for overlay text (`None` = macroquad's built-in font).
+    pub overlay_font_path: Option<PathBuf>,
+    /// Base pixel size for overlay text.
+    pub overlay_font_size: u16,
+    /// Language of the editor's labels.
+    pub language: Language,
+    /// Color scheme of the editor windows.
+    pub ui_theme: UiTheme,
+    /// Size multiplier for the text, spacing and docked panels of the editor, e.g. `1.5` on
+    /// high-DPI displays.
+    pub ui_scale: f32,
+    /// TTF font used by the editor windows (`None` = macroquad's built-in font).
+    pub ui_font_path: Option<PathBuf>,
+    /// Paint with the secondary brush on a short right click (right drags still pan).
+    pub secondary_brush_enabled: bool,
+    /// Minimum cursor travel, in tile widths, between placements while drag-painting.
+    pub paint_cooldown_tiles: f32,
+    /// Axis on which camera panning is disabled.
+    pub pan_axis_lock: PanAxisLock,
+    /// Animate zoom steps and camera jumps instead of snapping to the new view.
+    pub smooth_camera: bool,
+    /// Multiplier of the zoom step made by one mouse wheel notch.
+    pub wheel_zoom_sensitivity: f32,
+    /// Multiplier of the distance panned by one mouse wheel notch (`Shift` or `Ctrl` held).
+    pub wheel_pan_sensitivity: f32,
+    /// Texture filter for tiles whose category has no `category.json` override.
+    pub default_filter_mode: TextureFilter,
+    /// Spritesheet directories loaded when no project is open, in order (empty = the
+    /// `assets` folder next to the executable or in the working directory).
+    pub asset_dirs: Vec<PathBuf>,
+    /// Seconds between autosaves of unsaved edits to `.forgetile/autosave.json` (`0` = off).
+    pub autosave_interval_secs: u32,
+    /// Extra map file extensions (without the dot) and the format they are read as.
+    pub map_extensions: BTreeMap<String, MapFormat>,
+    /// Save JSON maps with run-length encoded tile arrays (`"encoding": "rle"`).
+    pub rle_json_tiles: bool,
+    /// Grid display options set in the View settings panel.
+    pub grid: GridStyle,
+    /// Side, size and collapsed state of the docked panels.
+    pub dock: DockLayout,
+    /// Name of the tool active when the previous session ended.
+    pub last_paint_mode: String,
+    /// Palette tile selected when the previous session ended.
+    pub last_selected_tile_id: Option<String>,
+    /// Palette category selected when the previous session ended.
+    pub last_selected_category_index: usize,
+    /// Brush tint (RGBA) active when the previous session ended.
+    pub last_tint: [f32; 4],
+    /// Ids of the most recently painted tiles, most recent first.
+    pub recent_tiles: Vec<String>,
+    /// Number of paint actions that used each tile id, for the palette's frequent tiles.
+    pub tile_paint_counts: BTreeMap<String, u32>,
+    /// Recently opened or saved map files, most recent first.
+    pub recent_files: Vec<PathBuf>,
+    /// Reopen the previous session's map, camera position and zoom on startup.
+    pub reopen_last_session: bool,
+    /// Map file open when the previous session ended (`None` = an unsaved map).
+    pub last_map_path: Option<PathBuf>,
+    /// Project open when the previous session ended.
+    pub last_project_path: Option<PathBuf>,
+    /// Camera center, in world coordinates, when the previous session ended.
+    pub last_camera_center: [f32; 2],
+    /// Zoom level when the previous session ended.
+    pub last_zoom: f32,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            paint_sound_path: None,
+            overlay_font_path: None,
+            overlay_font_size: 12,
+            language: Language::English,
+            ui_theme: UiTheme::Light,
+            ui_scale: 1.0,
+            ui_font_path: None,
+            secondary_brush_enabled: false,
+            paint_cooldown_tiles: 0.5,
+            pan_axis_lock: PanAxisLock::None,
+            smooth_camera: true,
+            wheel_zoom_sensitivity: 1.0,
+            wheel_pan_sensitivity: 1.0,
+            default_filter_mode: TextureFilter::Nearest,
+            asset_dirs: Vec::new(),
+            autosave_interval_secs: 60,
+            map_extensions: BTreeMap::new(),
+            rle_json_tiles: false,
+            grid: GridStyle::default(),
+            dock: DockLayout::default(),
+            last_paint_mode: "Paint".to_string(),
+            last_selected_tile_id: None,
+            last_selected_category_index: 0,
+            last_tint: [1.0; 4],
+            recent_tiles: Vec::new(),
+            tile_paint_counts: BTreeMap::new(),
+            recent_files: Vec::new(),
+            reopen_last_session: true,
+            last_map_path: None,
+            last_project_path: None,
+            last_camera_center: [0.0; 2],
+            last_zoom: 1.0,
+        }
+    }
+}
+
+impl AppConfig {
+    const FILE_NAME: &'static str = "config.json";
+    /// Number of entries kept in [`AppConfig::recent_files`].
+    pub const MAX_RECENT_FILES: usize = 10;
+    /// Smallest accepted [`AppConfig::ui_scale`].
+    pub const MIN_UI_SCALE: f32 = 0.75;
+    /// Largest accepted [`AppConfig::ui_scale`].
+    pub const MAX_UI_SCALE: f32 = 2.5;
+
+    /// UI scale clamped to the supported range, so a hand-edited config cannot break layout.
+    pub fn ui_scale(&self) -> f32 {
+        if self.ui_scale.is_finite() {
+            self.ui_scale
+                .clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    /// Layout of the tile arrays in saved JSON maps.
+    pub fn tile_encoding(&self) -> TileEncoding {
+        if self.rle_json_tiles {
+            TileEncoding::Rle
+        } else {
+            TileEncoding::Plain
+        }
+    }
+
+    /// Moves `path` to the front of the recent files, dropping the oldest entry when full.
+    pub fn add_recent_file(&mut self, path: &Path) {
+        self.recent_files
+            .retain(|recent| recent != path);
+        self.recent_files
+            .insert(0, path.to_path_buf());
+        self.recent_files
+            .truncate(Self::MAX_RECENT_FILES);
+    }
+
+    /// Loads the configuration from disk, falling back to defaults when missing or invalid.
+    pub fn load() -> Self {
+        let path = Self::path();
+        match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|err| {
+                eprintln!("[config] Ignoring invalid {:?}: {err}", path);
+                Self::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                eprintln!("[config] Failed to read {:?}: {err}", path);
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the configuration to disk, creating the state directory if needed.
+    pub fn save(&self) -> Result<(), io::Error> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    fn path() -> PathBuf {
+        Path::new(STATE_DIR).join(Self::FILE_NAME)
+    }
+}
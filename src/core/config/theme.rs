@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Color scheme of the editor windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UiTheme {
+    /// Macroquad's own light gray look.
+    #[default]
+    Light,
+    Dark,
+}
+
+impl UiTheme {
+    pub const ALL: [UiTheme; 2] = [UiTheme::Light, UiTheme::Dark];
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+
+/// Change of a single map cell, stored with its value before and after the edit.
+#[derive(Clone)]
+pub struct CellChange<T> {
+    pub layer: usize,
+    pub index: usize,
+    pub before: T,
+    pub after: T,
+}
+
+/// Kind of edit that produced an undo step, shown in the History panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// Paint stroke, including sequence, random and terrain brushes.
+    Paint,
+    /// Stroke that only cleared cells.
+    Erase,
+    Stamp,
+    /// Rectangle or line.
+    Shape,
+    Paste,
+    EraseSelection,
+    /// Global fill replacing every matching tile.
+    ReplaceTiles,
+    /// Flood fill of a connected region.
+    Fill,
+    FillEmpty,
+    /// Layer replaced by an imported CSV grid.
+    ImportLayer,
+    /// Layer filled by a procedural generator.
+    Generate,
+    MigrateIds,
+    /// Grid resized from the Resize map dialog.
+    Resize,
+    /// Grid cropped to its painted and blocked cells.
+    Crop,
+    /// Custom properties of one painted cell edited.
+    CellProperties,
+}
+
+/// Kind and size of one undo step.
+#[derive(Debug, Clone, Copy)]
+pub struct StepSummary {
+    pub kind: EditKind,
+    /// Number of cells changed by the step.
+    pub cells: usize,
+}
+
+/// Changes of an undone or redone step.
+pub enum StepChanges<'a, T, G> {
+    /// Cells edited in place.
+    Cells(&'a [CellChange<T>]),
+    /// Whole grid replaced, e.g. by a resize, with what undoing or redoing it needs.
+    Grid(&'a G),
+}
+
+enum StepContent<T, G> {
+    Cells(Vec<CellChange<T>>),
+    /// Grid replacement and the number of cells of the new grid.
+    Grid(G, usize),
+}
+
+struct HistoryStep<T, G> {
+    kind: EditKind,
+    content: StepContent<T, G>,
+}
+
+impl<T, G> HistoryStep<T, G> {
+    fn summary(&self) -> StepSummary {
+        let cells = match &self.content {
+            StepContent::Cells(changes) => changes.len(),
+            StepContent::Grid(_, cells) => *cells,
+        };
+        StepSummary { kind: self.kind, cells }
+    }
+
+    fn changes(&self) -> StepChanges<'_, T, G> {
+        match &self.content {
+            StepContent::Cells(changes) => StepChanges::Cells(changes),
+            StepContent::Grid(grid, _) => StepChanges::Grid(grid),
+        }
+    }
+}
+
+/// Undo/redo stacks of map edits.
+///
+/// Changes are collected with [`EditHistory::record`] and grouped into one undo step by
+/// [`EditHistory::commit`], so a whole drag stroke or fill is undone at once. Edits that
+/// replace the whole grid are recorded as a single `G` with [`EditHistory::commit_grid`].
+pub struct EditHistory<T, G> {
+    pending: Vec<CellChange<T>>,
+    undo_stack: VecDeque<HistoryStep<T, G>>,
+    redo_stack: Vec<HistoryStep<T, G>>,
+    capacity: usize,
+}
+
+impl<T: Clone, G> EditHistory<T, G> {
+    /// Number of undo steps kept by default.
+    pub const DEFAULT_CAPACITY: usize = 200;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            pending: Vec::new(),
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a cell change to the step being built.
+    pub fn record(&mut self, layer: usize, index: usize, before: T, after: T) {
+        self.pending
+            .push(CellChange { layer, index, before, after });
+    }
+
+    /// Changes recorded since the last commit.
+    pub fn pending(&self) -> &[CellChange<T>] {
+        &self.pending
+    }
+
+    /// Closes the step being built as a `kind` edit; does nothing when no change was recorded.
+    ///
+    /// A new step discards the redo stack, dropping the oldest step when full.
+    pub fn commit(&mut self, kind: EditKind) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let changes = std::mem::take(&mut self.pending);
+        self.push(kind, StepContent::Cells(changes));
+    }
+
+    /// Records a `kind` edit that replaced the whole grid with one of `cells` cells.
+    ///
+    /// Pending changes must be committed first.
+    pub fn commit_grid(&mut self, kind: EditKind, grid: G, cells: usize) {
+        self.push(kind, StepContent::Grid(grid, cells));
+    }
+
+    fn push(&mut self, kind: EditKind, content: StepContent<T, G>) {
+        self.redo_stack.clear();
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack
+            .push_back(HistoryStep { kind, content });
+    }
+
+    /// Returns the changes of the last step; cell changes are reverted in reverse order.
+    ///
+    /// Pending changes must be committed first.
+    pub fn undo(&mut self) -> Option<StepChanges<'_, T, G>> {
+        let step = self.undo_stack.pop_back()?;
+        self.redo_stack.push(step);
+        self.redo_stack
+            .last()
+            .map(HistoryStep::changes)
+    }
+
+    /// Returns the changes of the last undone step; cell changes are reapplied in order.
+    ///
+    /// Pending changes must be committed first.
+    pub fn redo(&mut self) -> Option<StepChanges<'_, T, G>> {
+        let step = self.redo_stack.pop()?;
+        self.undo_stack.push_back(step);
+        self.undo_stack
+            .back()
+            .map(HistoryStep::changes)
+    }
+
+    /// Every kept step from oldest to newest, undone steps last.
+    pub fn steps(&self) -> Vec<StepSummary> {
+        self.undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .map(HistoryStep::summary)
+            .collect()
+    }
+
+    /// Number of steps of [`EditHistory::steps`] currently applied.
+    pub fn position(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Forgets every step, e.g. after a new map was loaded or the layer stack changed.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl<T: Clone, G> Default for EditHistory<T, G> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::core::assets::AssetCatalog;
+use crate::core::map::map::{Map, MapLoadError};
+use forgetile_core::interop::csv::{self, CsvCells};
+use forgetile_core::map::LayerKind;
+use std::io;
+use std::path::{Path, PathBuf};
+
+impl Map {
+    /// Writes every tile layer as its own CSV grid next to `map_path`, named
+    /// `<map name>_<layer index>_<layer name>.csv`.
+    ///
+    /// Cells hold tile ids, or catalog tile indices (`-1` for empty cells) when `indices` is
+    /// set. Returns the written files and the number of cells left empty because their tile
+    /// is no longer in the catalog.
+    pub fn export_csv_layers<P: AsRef<Path>>(
+        &self, map_path: P, catalog: &AssetCatalog, indices: bool,
+    ) -> Result<(Vec<PathBuf>, usize), io::Error> {
+        let map_path = map_path.as_ref();
+        let stem = map_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let cells = csv_cells(catalog, indices);
+        let data = self.export();
+        let mut written = Vec::new();
+        let mut unresolved = 0;
+        for (index, layer) in data
+            .content_layers()
+            .into_iter()
+            .enumerate()
+        {
+            if layer.kind != LayerKind::Tiles {
+                continue;
+            }
+            let path =
+                map_path.with_file_name(format!("{stem}_{index}_{}.csv", file_safe(&layer.name)));
+            unresolved += csv::write(&data, layer, cells, &path)?;
+            written.push(path);
+        }
+        Ok((written, unresolved))
+    }
+
+    /// Replaces the cells of the active tile layer with a CSV grid of the map's size, as one
+    /// undo step. See [`Map::set_active_layer_tiles`] for when nothing changes.
+    pub fn import_csv_layer<P: AsRef<Path>>(
+        &mut self, path: P, catalog: &AssetCatalog, indices: bool,
+    ) -> Result<bool, MapLoadError> {
+        let (width, height, ids) = csv::read_grid(path.as_ref(), csv_cells(catalog, indices))?;
+        let (map_width, map_height) = self.dimensions();
+        if (width, height) != (map_width, map_height) {
+            return Err(MapLoadError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the grid is {width}x{height} tiles, the map {map_width}x{map_height}"),
+            )));
+        }
+        self.set_active_layer_tiles(&ids, catalog)
+    }
+}
+
+fn csv_cells(catalog: &AssetCatalog, indices: bool) -> CsvCells<'_> {
+    if indices {
+        CsvCells::Indices(catalog.sheets())
+    } else {
+        CsvCells::Ids
+    }
+}
+
+/// `name` with everything but ASCII letters, digits, `-` and `_` replaced by `_`.
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() || char == '-' {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
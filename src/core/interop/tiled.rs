@@ -0,0 +1,38 @@
+use crate::core::assets::AssetCatalog;
+use crate::core::map::map::{Map, MapLoadError};
+use forgetile_core::interop::tiled;
+use std::io;
+use std::path::Path;
+
+pub use forgetile_core::interop::tiled::is_tiled_file;
+
+impl Map {
+    /// Exports the map for the [Tiled](https://www.mapeditor.org) editor and the engines
+    /// that read its files.
+    ///
+    /// A `.tmx` path writes XML and a `.tmj` path JSON; both store tile layer data as CSV
+    /// (a plain array in JSON), write object layers as object groups and reference every used spritesheet as an external image
+    /// tileset. Tints and z-priorities have no Tiled equivalent and are dropped.
+    ///
+    /// Returns the number of painted cells left empty because their tile is no longer in
+    /// the catalog.
+    pub fn export_tiled<P: AsRef<Path>>(
+        &self, path: P, catalog: &AssetCatalog,
+    ) -> Result<usize, io::Error> {
+        tiled::write(&self.export(), catalog.sheets(), path.as_ref())
+    }
+
+    /// Replaces the map with the tile and object layers of a Tiled `.tmx` or `.tmj` file.
+    ///
+    /// Tileset images are matched to catalog sprites by path and tile index, so their
+    /// spritesheets must be loaded from `assets/` with the same tile size. Flip and rotation
+    /// flags are ignored and image layers are skipped; tiles outside every known
+    /// tileset and other features the editor cannot represent fail with
+    /// [`MapLoadError::UnsupportedFeature`].
+    pub fn import_tiled<P: AsRef<Path>>(
+        &mut self, path: P, catalog: &AssetCatalog,
+    ) -> Result<(), MapLoadError> {
+        let data = tiled::read(path.as_ref(), catalog.sheets())?;
+        self.load_data(data, catalog)
+    }
+}
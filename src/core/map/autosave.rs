@@ -0,0 +1,162 @@
+use crate::core::assets::AssetCatalog;
+use crate::core::config::STATE_DIR;
+use crate::core::map::map::{Map, MapLoadError};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Periodically writes the unsaved edits of every tab to `.forgetile/autosave-<tab>.json`
+/// so they survive a crash.
+///
+/// The path of each map is kept next to its autosave in `autosave-<tab>.source`, so a
+/// restored map saves back to its original file.
+pub struct Autosave {
+    /// Seconds between two autosaves; `0.0` disables autosaving.
+    interval: f64,
+    last_write: f64,
+}
+
+/// Autosave found at startup that is newer than the last explicit save.
+pub struct Recovery {
+    /// Map file the autosaved edits belong to; `None` for a map that was never saved.
+    pub source: Option<PathBuf>,
+    /// Autosave holding the edits.
+    path: PathBuf,
+}
+
+impl Autosave {
+    /// Start of every autosave file name, including the single `autosave.json` written by
+    /// earlier versions.
+    const FILE_PREFIX: &'static str = "autosave";
+
+    /// Creates the service, autosaving every `interval_secs` seconds (`0` = never).
+    pub fn new(interval_secs: u32, now: f64) -> Self {
+        Self {
+            interval: interval_secs as f64,
+            last_write: now,
+        }
+    }
+
+    /// Writes the dirty maps once the interval has elapsed since the last autosave.
+    pub fn update(&mut self, maps: &[Map], now: f64) {
+        if self.interval <= 0.0 || now - self.last_write < self.interval {
+            return;
+        }
+        self.last_write = now;
+        self.write(maps);
+    }
+
+    /// Writes every dirty map immediately to the autosave of its tab and deletes the
+    /// autosaves of the other tabs, logging failures.
+    pub fn write(&self, maps: &[Map]) {
+        let mut kept = HashSet::new();
+        for (tab, map) in maps
+            .iter()
+            .enumerate()
+            .filter(|(_, map)| map.is_dirty())
+        {
+            let path = Self::path(tab);
+            if let Err(err) = Self::write_files(map, &path) {
+                eprintln!("[autosave] Could not write {:?}: {err}", path);
+            }
+            kept.insert(path);
+        }
+        for path in Self::files() {
+            if !kept.contains(&path) {
+                Self::remove_files(&path);
+            }
+        }
+    }
+
+    /// Returns the autosaves left by the previous session that hold edits newer than the
+    /// file they belong to.
+    pub fn pending_recovery() -> Vec<Recovery> {
+        Self::files()
+            .into_iter()
+            .filter_map(|path| {
+                let saved_at = modified(&path)?;
+                let source = fs::read_to_string(source_path(&path))
+                    .ok()
+                    .map(|text| text.trim().to_string())
+                    .filter(|text| !text.is_empty())
+                    .map(PathBuf::from);
+                let newer = source
+                    .as_deref()
+                    .and_then(modified)
+                    .is_none_or(|source_saved_at| saved_at > source_saved_at);
+                newer.then_some(Recovery { source, path })
+            })
+            .collect()
+    }
+
+    /// Loads the autosaved map as an unsaved edit of its original file.
+    pub fn restore(
+        recovery: Recovery, map: &mut Map, catalog: &AssetCatalog,
+    ) -> Result<(), MapLoadError> {
+        map.load_from_file(&recovery.path, catalog)?;
+        map.mark_recovered(recovery.source);
+        Ok(())
+    }
+
+    /// Deletes the autosave of `recovery`, e.g. when recovery is declined.
+    pub fn discard(recovery: &Recovery) {
+        Self::remove_files(&recovery.path);
+    }
+
+    fn write_files(map: &Map, path: &Path) -> Result<(), io::Error> {
+        fs::create_dir_all(STATE_DIR)?;
+        map.write_snapshot(path)?;
+        let source = map
+            .file_path()
+            .and_then(|path| std::path::absolute(path).ok())
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        fs::write(source_path(path), source)
+    }
+
+    fn remove_files(path: &Path) {
+        for path in [path.to_path_buf(), source_path(path)] {
+            if let Err(err) = fs::remove_file(&path)
+                && err.kind() != io::ErrorKind::NotFound
+            {
+                eprintln!("[autosave] Could not remove {:?}: {err}", path);
+            }
+        }
+    }
+
+    fn path(tab: usize) -> PathBuf {
+        Path::new(STATE_DIR).join(format!("{}-{}.json", Self::FILE_PREFIX, tab + 1))
+    }
+
+    /// Autosaves currently on disk, sorted by name.
+    fn files() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(STATE_DIR) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension() == Some("json".as_ref())
+                    && path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(|stem| stem.starts_with(Self::FILE_PREFIX))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+/// File next to the autosave at `path` naming the map it belongs to.
+fn source_path(path: &Path) -> PathBuf {
+    path.with_extension("source")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,37 @@
+use macroquad::color::Color;
+use serde::{Deserialize, Serialize};
+
+/// How the tile grid is drawn over the map.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridStyle {
+    pub visible: bool,
+    /// RGBA color of the lines; the alpha is their opacity.
+    pub color: [f32; 4],
+    /// Tiles between the thicker major lines (`0` = no major lines).
+    pub major_every: u32,
+    /// Label every cell with its tile coordinates when zoomed in far enough to read them.
+    pub show_coordinates: bool,
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            color: [0.51, 0.51, 0.51, 1.0],
+            major_every: 0,
+            show_coordinates: false,
+        }
+    }
+}
+
+impl GridStyle {
+    pub fn color(&self) -> Color {
+        Color::from(self.color)
+    }
+
+    /// Returns `true` when grid line `index` (a column or row boundary) is a major line.
+    pub fn is_major(&self, index: usize) -> bool {
+        self.major_every > 0 && index.is_multiple_of(self.major_every as usize)
+    }
+}
@@ -1,38 +1,339 @@
+use crate::core::assets::animation::AnimationMode;
 use crate::core::assets::{AssetCatalog, TileSprite};
-use crate::core::camera::{AxisPosition, CameraController};
-use crate::core::map::tile::Size;
+use crate::core::camera::{AxisPosition, CameraController, PlaybackDirection};
+use crate::core::history::{EditHistory, EditKind, StepChanges, StepSummary};
+use crate::core::interop::tiled;
+use crate::core::map::format::{FormatRegistry, MapFormat, TileEncoding};
+use crate::core::map::grid::GridStyle;
+use crate::core::map::object::MapObject;
+use crate::core::map::projection::MapProjection;
+use crate::core::map::property::Properties;
+use crate::core::map::reference::ReferenceImage;
+use crate::core::map::stats::MapStats;
+use crate::core::map::tile::{Collider, Size, TileTransform};
+use crate::core::tools::autotile::{NEIGHBOR_OFFSETS, TerrainSet};
+use crate::core::tools::noise::NoiseStamp;
+use crate::core::tools::{
+    ApplyTo, FillScope, PaintMode, RandomBrush, ReplaceScope, Selection, TileClipboard,
+    TileSequence, line_cells,
+};
+use crate::ui::overlay::OverlayText;
+use forgetile_core::map::{LayerData, LayerKind, MapData};
+use macroquad::audio::{PlaySoundParams, Sound, play_sound};
 use macroquad::camera::{Camera2D, set_camera, set_default_camera};
-use macroquad::color::{Color, GRAY, WHITE};
-use macroquad::input::mouse_position;
-use macroquad::math::{Vec2, vec2};
-use macroquad::shapes::{draw_line, draw_rectangle};
-use macroquad::texture::{DrawTextureParams, Texture2D, draw_texture_ex};
-use serde::{Deserialize, Serialize};
+use macroquad::color::{BLANK, Color, WHITE, YELLOW};
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::shapes::{draw_line, draw_rectangle, draw_rectangle_lines, draw_triangle};
+use macroquad::texture::{
+    DrawTextureParams, Image, RenderTarget, Texture2D, draw_texture_ex, render_target,
+};
+use macroquad::time::{get_frame_time, get_time};
+use macroquad::window::clear_background;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+pub use forgetile_core::map::MapLoadError;
 
 /// Runtime representation of the editable tile map.
 pub struct Map {
     camera_controller: CameraController,
-    tiles: Vec<Option<PaintedTile>>,
+    /// Layer stack, drawn from index 0 (bottom) upwards.
+    layers: Vec<Layer>,
+    /// Folders referenced by [`Layer::group`], possibly nested in one another; the layers
+    /// of a group and of the groups nested in it are always adjacent.
+    groups: Vec<LayerGroup>,
+    active_layer: usize,
+    history: EditHistory<Option<PaintedTile>, Reframe>,
+    /// Walkability of every cell, shared by all layers.
+    colliders: Vec<Collider>,
+    /// Walkability applied by the collision stroke in progress.
+    collision_stroke: Option<bool>,
     map_height_tiles: usize,
     map_width_tiles: usize,
     tile_dimensions: Size,
+    /// Layout of the cells in world space.
+    projection: MapProjection,
+    /// File the map was last loaded from or saved to; `None` for new and imported maps.
+    file_path: Option<PathBuf>,
+    /// Set by every edit since the map was last loaded or saved.
+    dirty: bool,
+    /// Bumped whenever the map content changes, so cached renders know when to refresh.
+    revision: u64,
+    on_paint_sound: Option<Sound>,
+    /// Ids of the brush tiles painted since [`Map::take_painted_ids`] was last called.
+    painted_ids: Vec<String>,
+    show_tile_outlines: bool,
+    paint_mode: PaintMode,
+    fill_scope: FillScope,
+    /// Whether the magic wand selects the connected region or every matching cell.
+    wand_scope: FillScope,
+    /// Layers edited by the erase and fill tools.
+    apply_to: ApplyTo,
+    brush_tint: Color,
+    brush_z_priority: i32,
+    brush_transform: TileTransform,
+    selection: Option<Selection>,
+    /// Corner where the marquee drag started.
+    marquee_anchor: Option<(usize, usize)>,
+    /// Block copied with [`Map::copy_selection`].
+    clipboard: Option<TileClipboard<PaintedTile>>,
+    /// Top-left cell of the clipboard block following the mouse while pasting.
+    paste_origin: Option<(usize, usize)>,
+    /// Every cell of a procedural generator's output while its dialog is open; empty
+    /// otherwise.
+    generated_preview: Vec<Option<PaintedTile>>,
+    /// Rectangle or line being dragged, painted when the mouse button is released.
+    shape_drag: Option<ShapeDrag>,
+    multi_selection_cells: HashSet<(usize, usize)>,
+    /// Object picked on the active object layer, edited by the object panel.
+    selected_object: Option<usize>,
+    /// Offset from the cursor to the top-left corner of the object being dragged.
+    object_drag: Option<Vec2>,
+    tile_sequence: TileSequence,
+    random_brush: RandomBrush,
+    noise_stamp: NoiseStamp,
+    /// Cell last painted by the sequence or noise brush during the current stroke.
+    last_stroke_cell: Option<(usize, usize)>,
+    /// Cell last reached by a paint or erase drag, where the next interpolated segment of
+    /// the stroke starts.
+    drag_cell: Option<(usize, usize)>,
+    /// Minimum cursor travel, in tile widths, between two placements of the same stroke.
+    paint_cooldown_tiles: f32,
+    last_paint_world_pos: Option<Vec2>,
+    playback_active: bool,
+    /// Camera scroll speed in world units per second while playback is active.
+    playback_speed: f32,
+    playback_direction: PlaybackDirection,
+    /// Distance already travelled along the playback path.
+    playback_distance: f32,
+    /// Color of the outline drawn around painted tiles when outlines are enabled.
+    pub tile_outline_color: Color,
+    grid_style: GridStyle,
+    formats: FormatRegistry,
+    /// Layout of the tile arrays in saved JSON maps.
+    tile_encoding: TileEncoding,
+    /// Custom properties of the whole map.
+    properties: Properties,
+    /// Tracing image drawn over the tiles; saved with the map but never painted into it.
+    reference: Option<ReferenceImage>,
+    /// Texture of `reference`; `None` while it cannot be read.
+    reference_texture: Option<Texture2D>,
+}
+
+/// Grid replaced by [`Map::resize`] or [`Map::crop_to_content`], kept in the undo history.
+#[derive(Clone)]
+struct Reframe {
+    /// Old cell that became the new top-left cell.
+    offset: (isize, isize),
+    size: (usize, usize),
+    /// World-space distance objects and the view moved back by.
+    shift: Vec2,
+    old_size: (usize, usize),
+    /// Cells of every layer before the reframe.
+    old_tiles: Vec<Vec<Option<PaintedTile>>>,
+    old_colliders: Vec<Collider>,
+}
+
+/// In-progress rectangle or line drag.
+struct ShapeDrag {
+    mode: PaintMode,
+    start: (usize, usize),
+    end: (usize, usize),
+    tile: PaintedTile,
 }
 
 #[derive(Clone)]
 struct PaintedTile {
+    /// Spritesheet atlas the tile is drawn from.
     texture: Texture2D,
+    /// Region of `texture` holding the tile; `None` draws the whole texture.
+    source: Option<Rect>,
     tile_id: String,
+    display_name: String,
+    tint: Color,
+    z_priority: i32,
+    transform: TileTransform,
+    animation: AnimationMode,
+    /// Custom properties of the cell, kept until the tile is erased or painted over.
+    properties: Properties,
+}
+
+/// Cell of the old grid that stays in place when the map is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeAnchor {
+    /// Rows and columns are added or removed at the right and bottom edges.
+    #[default]
+    TopLeft,
+    /// Rows and columns are added or removed evenly on every side.
+    Center,
+}
+
+/// Named grid of painted cells, or set of free-positioned objects, drawn above the layers
+/// before it; or a background image drawn behind every tile layer.
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    /// Locked layers ignore every editing tool.
+    pub locked: bool,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+    /// Alpha multiplied into every tile of the layer, `0.0..=1.0`.
+    pub opacity: f32,
+    /// Color multiplied into every tile of the layer, e.g. dark blue for a night overlay.
+    pub tint_color: Option<Color>,
+    kind: LayerKind,
+    /// Index of the innermost [`LayerGroup`] holding the layer.
+    group: Option<usize>,
+    /// Scroll factors of an image layer relative to the camera; see [`LayerData::parallax`].
+    pub parallax: Vec2,
+    /// Image file of an image layer, as saved in the map.
+    image_path: Option<String>,
+    /// Texture of `image_path`; `None` while it cannot be read.
+    image: Option<Texture2D>,
+    /// Painted cells; always empty on object layers.
+    tiles: Vec<Option<PaintedTile>>,
+    objects: Vec<MapObject>,
+}
+
+/// Folder of adjacent layers and of other groups. Its settings cascade to the layers of
+/// every group nested in it without changing them: a hidden group hides them, a locked one
+/// locks them, and its opacity multiplies theirs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerGroup {
+    pub name: String,
+    pub visible: bool,
+    pub locked: bool,
+    /// Alpha multiplied into every layer of the group, `0.0..=1.0`.
+    pub opacity: f32,
+    /// Whether the layers panel hides the group's layers; they are still drawn.
+    pub collapsed: bool,
+    /// Custom properties of the group, kept when saving.
+    pub properties: Properties,
+    /// Index of the group this one is nested in.
+    parent: Option<usize>,
+}
+
+impl LayerGroup {
+    fn new(name: impl Into<String>, parent: Option<usize>) -> Self {
+        Self {
+            name: name.into(),
+            visible: true,
+            locked: false,
+            opacity: 1.0,
+            collapsed: false,
+            properties: Properties::new(),
+            parent,
+        }
+    }
+
+    /// Index in [`Map::groups`] of the group this one is nested in.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+impl Layer {
+    fn new(name: impl Into<String>, cell_count: usize) -> Self {
+        Self {
+            name: name.into(),
+            visible: true,
+            locked: false,
+            properties: Properties::new(),
+            opacity: 1.0,
+            tint_color: None,
+            kind: LayerKind::Tiles,
+            group: None,
+            parallax: Vec2::ONE,
+            image_path: None,
+            image: None,
+            tiles: vec![None; cell_count],
+            objects: Vec::new(),
+        }
+    }
+
+    fn new_objects(name: impl Into<String>, cell_count: usize) -> Self {
+        Self {
+            kind: LayerKind::Objects,
+            ..Self::new(name, cell_count)
+        }
+    }
+
+    fn new_image(name: impl Into<String>, cell_count: usize) -> Self {
+        Self {
+            kind: LayerKind::Image,
+            parallax: vec2(0.5, 0.5),
+            ..Self::new(name, cell_count)
+        }
+    }
+
+    /// Color every tile of the layer is multiplied by when its groups have a combined
+    /// opacity of `group_opacity`.
+    fn modulation(&self, group_opacity: f32) -> Color {
+        let tint = self.tint_color.unwrap_or(WHITE);
+        let opacity = self.opacity * group_opacity;
+        Color::new(tint.r, tint.g, tint.b, opacity.clamp(0.0, 1.0))
+    }
+
+    /// Returns `true` for layers holding objects instead of tiles.
+    pub fn is_object_layer(&self) -> bool {
+        self.kind == LayerKind::Objects
+    }
+
+    /// Returns `true` for background image layers.
+    pub fn is_image_layer(&self) -> bool {
+        self.kind == LayerKind::Image
+    }
+
+    /// Image file of an image layer.
+    pub fn image_path(&self) -> Option<&str> {
+        self.image_path.as_deref()
+    }
+
+    /// Index in [`Map::groups`] of the innermost group holding the layer.
+    pub fn group(&self) -> Option<usize> {
+        self.group
+    }
+}
+
+/// Snapshot of everything known about a painted cell, used by the tile inspector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileInfo {
+    pub tile_id: String,
+    pub display_name: String,
+    pub layer: usize,
+    pub position: (usize, usize),
+    pub tint: Color,
+    pub alpha: f32,
+    pub z_priority: i32,
+    pub transform: TileTransform,
+    /// Walkability of the cell from the collision grid.
+    pub walkable: bool,
+    /// Custom properties of the painted cell.
+    pub properties: Properties,
+    /// Properties of the tile type in the catalog, empty when the tile is not in it.
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl Map {
+    /// Gain used for the paint click so rapid drag-painting stays unobtrusive.
+    const PAINT_SOUND_VOLUME: f32 = 0.15;
+    /// Draw-order distance between layers; z-priorities are expected to stay well below it.
+    const LAYER_Z_STRIDE: i64 = 10_000;
+    /// Largest width or height of an exported image, within the texture limit of most GPUs.
+    pub const MAX_IMAGE_SIDE: u32 = 8192;
+    /// Default paint cooldown: place at most one tile per half tile of cursor movement.
+    const DEFAULT_PAINT_COOLDOWN_TILES: f32 = 0.5;
+    /// Side, in tiles, of the square chunks culled against the camera view.
+    const CHUNK_TILES: usize = 32;
+
     /// Creates a map with the provided pixel dimensions and tile size.
     pub fn new(map_dimension: Size, tile_size: Size) -> Self {
         let map_width_tiles: usize = dimension_to_tiles(map_dimension.width);
         let map_height_tiles: usize = dimension_to_tiles(map_dimension.height);
-        let tiles: Vec<Option<PaintedTile>> = vec![None; map_width_tiles * map_height_tiles];
+        let cell_count = map_width_tiles * map_height_tiles;
+        let layer = Layer::new("Layer 1", cell_count);
 
         let grid_size: Vec2 = vec2(
             map_width_tiles as f32 * tile_size.width,
@@ -44,10 +345,332 @@ impl Map {
         Self {
             camera_controller: CameraController::new(camera_center),
             tile_dimensions: tile_size,
+            projection: MapProjection::default(),
+            file_path: None,
+            dirty: false,
+            revision: 0,
             map_width_tiles,
             map_height_tiles,
-            tiles,
+            layers: vec![layer],
+            groups: Vec::new(),
+            active_layer: 0,
+            history: EditHistory::default(),
+            on_paint_sound: None,
+            painted_ids: Vec::new(),
+            show_tile_outlines: false,
+            paint_mode: PaintMode::default(),
+            fill_scope: FillScope::default(),
+            wand_scope: FillScope::default(),
+            apply_to: ApplyTo::default(),
+            brush_tint: WHITE,
+            brush_z_priority: 0,
+            brush_transform: TileTransform::default(),
+            selection: None,
+            marquee_anchor: None,
+            clipboard: None,
+            paste_origin: None,
+            generated_preview: Vec::new(),
+            shape_drag: None,
+            multi_selection_cells: HashSet::new(),
+            selected_object: None,
+            object_drag: None,
+            colliders: vec![Collider { is_walkable: true }; cell_count],
+            collision_stroke: None,
+            tile_sequence: TileSequence::default(),
+            random_brush: RandomBrush::default(),
+            noise_stamp: NoiseStamp::default(),
+            last_stroke_cell: None,
+            drag_cell: None,
+            paint_cooldown_tiles: Self::DEFAULT_PAINT_COOLDOWN_TILES,
+            last_paint_world_pos: None,
+            playback_active: false,
+            playback_speed: 0.0,
+            playback_direction: PlaybackDirection::default(),
+            playback_distance: 0.0,
+            tile_outline_color: Color { r: 1.0, g: 1.0, b: 1.0, a: 0.6 },
+            grid_style: GridStyle::default(),
+            formats: FormatRegistry::default(),
+            tile_encoding: TileEncoding::Plain,
+            properties: Properties::new(),
+            reference: None,
+            reference_texture: None,
+        }
+    }
+
+    /// Replaces the content with an empty single-layer map of `map_dimension` tiles of
+    /// `tile_size` pixels laid out with `projection`, keeping the editor settings (tools,
+    /// brush, sound, formats).
+    pub fn reset(&mut self, map_dimension: Size, tile_size: Size, projection: MapProjection) {
+        let fresh = Map::new(map_dimension, tile_size);
+        self.layers = fresh.layers;
+        self.groups = fresh.groups;
+        self.colliders = fresh.colliders;
+        self.properties = fresh.properties;
+        self.reference = None;
+        self.reference_texture = None;
+        self.active_layer = 0;
+        self.map_width_tiles = fresh.map_width_tiles;
+        self.map_height_tiles = fresh.map_height_tiles;
+        self.tile_dimensions = fresh.tile_dimensions;
+        self.projection = projection;
+        self.file_path = None;
+        self.dirty = false;
+        self.revision += 1;
+        self.history.clear();
+        self.clear_selection();
+        self.selected_object = None;
+        self.shape_drag = None;
+        self.paste_origin = None;
+        self.end_stroke();
+        self.stop_playback();
+        self.camera_controller
+            .set_center(self.grid_size());
+    }
+
+    /// Creates an empty map for a new tab with this map's size, tile size and projection,
+    /// carrying over the editor settings (tools, brush, clipboard, sound, formats).
+    pub fn new_tab(&self) -> Map {
+        let dimensions = Size {
+            width: self.map_width_tiles as f32,
+            height: self.map_height_tiles as f32,
+        };
+        let mut map = Map::new(dimensions, self.tile_dimensions);
+        map.reset(dimensions, self.tile_dimensions, self.projection);
+        map.on_paint_sound = self.on_paint_sound.clone();
+        map.paint_cooldown_tiles = self.paint_cooldown_tiles;
+        map.tile_outline_color = self.tile_outline_color;
+        map.grid_style = self.grid_style;
+        map.formats = self.formats.clone();
+        map.tile_encoding = self.tile_encoding;
+        map.camera_controller.pan_axis_lock = self.camera_controller.pan_axis_lock;
+        map.camera_controller.smooth_motion = self.camera_controller.smooth_motion;
+        map.camera_controller
+            .wheel_zoom_sensitivity = self
+            .camera_controller
+            .wheel_zoom_sensitivity;
+        map.camera_controller
+            .wheel_pan_sensitivity = self
+            .camera_controller
+            .wheel_pan_sensitivity;
+        map.carry_tools_from(self);
+        map
+    }
+
+    /// Takes over the tool and brush state of `other`, so switching tabs keeps the current
+    /// tool, brush and clipboard.
+    pub fn carry_tools_from(&mut self, other: &Map) {
+        self.cancel_shape();
+        self.cancel_paste();
+        self.end_stroke();
+        self.paint_mode = other.paint_mode;
+        self.fill_scope = other.fill_scope;
+        self.wand_scope = other.wand_scope;
+        self.apply_to = other.apply_to;
+        self.brush_tint = other.brush_tint;
+        self.brush_z_priority = other.brush_z_priority;
+        self.brush_transform = other.brush_transform;
+        self.clipboard = other.clipboard.clone();
+        self.tile_sequence = other.tile_sequence.clone();
+        self.random_brush = other.random_brush.clone();
+        self.noise_stamp = other.noise_stamp.clone();
+        self.show_tile_outlines = other.show_tile_outlines;
+    }
+
+    /// Grows or shrinks the grid to `width` x `height` tiles, keeping the tiles and collision
+    /// cells that still fit at the same position relative to `anchor`.
+    ///
+    /// Recorded as one undo step that restores the old grid.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: ResizeAnchor) {
+        let (offset_x, offset_y) = match anchor {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Center => (
+                (self.map_width_tiles as isize - width as isize) / 2,
+                (self.map_height_tiles as isize - height as isize) / 2,
+            ),
+        };
+        self.reframe(offset_x, offset_y, width, height, EditKind::Resize);
+    }
+
+    /// Shrinks the grid to the smallest rectangle holding every painted tile and blocked
+    /// collision cell. Returns the kept region in old tile coordinates, or `None` when the
+    /// map is empty or already tight.
+    pub fn crop_to_content(&mut self) -> Option<Selection> {
+        let width = self.map_width_tiles;
+        let occupied = (0..width * self.map_height_tiles).filter(|&index| {
+            !self.colliders[index].is_walkable
+                || self
+                    .layers
+                    .iter()
+                    .any(|layer| layer.tiles[index].is_some())
+        });
+        let mut bounds: Option<((usize, usize), (usize, usize))> = None;
+        for index in occupied {
+            let cell = (index % width, index / width);
+            bounds = Some(match bounds {
+                None => (cell, cell),
+                Some((min, max)) => (
+                    (min.0.min(cell.0), min.1.min(cell.1)),
+                    (max.0.max(cell.0), max.1.max(cell.1)),
+                ),
+            });
+        }
+        let region = bounds.map(|(min, max)| Selection::from_corners(min, max))?;
+        if (region.width, region.height) == self.dimensions() {
+            return None;
         }
+        self.reframe(
+            region.x as isize,
+            region.y as isize,
+            region.width,
+            region.height,
+            EditKind::Crop,
+        );
+        Some(region)
+    }
+
+    /// Rebuilds every per-cell grid at `width` x `height`, where new cell `(x, y)` takes the
+    /// content of old cell `(x + offset_x, y + offset_y)`, as one `kind` undo step.
+    fn reframe(
+        &mut self, offset_x: isize, offset_y: isize, width: usize, height: usize, kind: EditKind,
+    ) {
+        if (offset_x, offset_y) == (0, 0) && (width, height) == self.dimensions() {
+            return;
+        }
+        self.end_stroke();
+        let old_size = self.dimensions();
+        let old_tiles = self
+            .layers
+            .iter()
+            .map(|layer| layer.tiles.clone())
+            .collect();
+        let old_colliders = self.colliders.clone();
+        let shift = self.apply_reframe(offset_x, offset_y, width, height);
+        let reframe = Reframe {
+            offset: (offset_x, offset_y),
+            size: (width, height),
+            shift,
+            old_size,
+            old_tiles,
+            old_colliders,
+        };
+        self.history
+            .commit_grid(kind, reframe, width * height);
+    }
+
+    /// Rebuilds the grids for [`Map::reframe`] and returns how far objects and the view
+    /// moved back.
+    fn apply_reframe(
+        &mut self, offset_x: isize, offset_y: isize, width: usize, height: usize,
+    ) -> Vec2 {
+        let (old_width, old_height) = self.dimensions();
+        let old_origin = self.grid_point(offset_x as f32, offset_y as f32);
+        let old_index = |index: usize| {
+            let x = (index % width) as isize + offset_x;
+            let y = (index / width) as isize + offset_y;
+            (x >= 0 && y >= 0 && (x as usize) < old_width && (y as usize) < old_height)
+                .then(|| y as usize * old_width + x as usize)
+        };
+
+        for layer in &mut self.layers {
+            let mut tiles = vec![None; width * height];
+            for (index, cell) in tiles.iter_mut().enumerate() {
+                if let Some(old) = old_index(index) {
+                    *cell = layer.tiles[old].take();
+                }
+            }
+            layer.tiles = tiles;
+        }
+        self.colliders = (0..width * height)
+            .map(|index| {
+                old_index(index)
+                    .map(|old| self.colliders[old])
+                    .unwrap_or(Collider { is_walkable: true })
+            })
+            .collect();
+
+        self.map_width_tiles = width;
+        self.map_height_tiles = height;
+        // Keep objects and the view on the same content.
+        let shift = old_origin - self.grid_point(0.0, 0.0);
+        self.finish_reframe(shift);
+        shift
+    }
+
+    /// Puts back the grid a [`Reframe`] replaced.
+    fn undo_reframe(&mut self, reframe: Reframe) {
+        for (layer, tiles) in self
+            .layers
+            .iter_mut()
+            .zip(reframe.old_tiles)
+        {
+            layer.tiles = tiles;
+        }
+        self.colliders = reframe.old_colliders;
+        (self.map_width_tiles, self.map_height_tiles) = reframe.old_size;
+        self.finish_reframe(-reframe.shift);
+    }
+
+    /// Moves objects and the view back by `shift` and drops the state tied to old cells.
+    fn finish_reframe(&mut self, shift: Vec2) {
+        self.clear_selection();
+        self.shape_drag = None;
+        self.paste_origin = None;
+        for object in self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| &mut layer.objects)
+        {
+            object.x -= shift.x;
+            object.y -= shift.y;
+        }
+        let center = Vec2::from(
+            self.camera_controller
+                .screen_center
+                .clone(),
+        ) - shift;
+        self.camera_controller
+            .set_center(center);
+        self.mark_edited();
+    }
+
+    /// Map size in tiles, as `(width, height)`.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.map_width_tiles, self.map_height_tiles)
+    }
+
+    /// Pixel size of one grid cell.
+    pub fn tile_size(&self) -> Size {
+        self.tile_dimensions
+    }
+
+    /// Layout of the cells in world space.
+    pub fn projection(&self) -> MapProjection {
+        self.projection
+    }
+
+    /// File the map was last loaded from or saved to, if any.
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file_path.as_deref()
+    }
+
+    /// Returns `true` when the map has unsaved edits.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Counter that changes whenever the map content does.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn mark_edited(&mut self) {
+        self.dirty = true;
+        self.revision += 1;
+    }
+
+    /// Sets the clip played whenever a tile is painted, or `None` to paint silently.
+    pub fn set_paint_sound(&mut self, sound: Option<Sound>) {
+        self.on_paint_sound = sound;
     }
 
     /// Returns an immutable reference to the camera controller.
@@ -60,246 +683,2938 @@ impl Map {
         &mut self.camera_controller
     }
 
-    /// Draws the map contents and returns the active camera used for the draw call.
-    pub fn draw(&mut self) -> Camera2D {
+    /// Updates the camera and draws the map contents.
+    pub fn draw(&mut self, overlay: &OverlayText) {
         let grid_size = self.grid_size();
 
-        self.camera_controller.update(grid_size);
+        if self.playback_active {
+            self.playback_distance += self.playback_speed * get_frame_time();
+            self.camera_controller
+                .update_zoom_from_input();
+            self.camera_controller.animate();
+            self.camera_controller.follow_playback(
+                grid_size,
+                self.playback_direction,
+                self.playback_distance,
+            );
+        } else {
+            self.camera_controller.update(grid_size);
+        }
 
-        let camera = self.camera_controller.to_camera2d();
-        set_camera(&camera);
+        set_camera(&self.camera_controller.to_camera2d());
 
-        self.draw_tiles();
-        self.setup_grid();
-        self.highlight_hovered_tile(&camera);
+        let visible = self.visible_region();
+        self.draw_images();
+        self.draw_tiles(visible);
+        if self.show_tile_outlines {
+            self.draw_tile_outlines(visible);
+        }
+        self.draw_objects(overlay);
+        self.draw_reference_image();
+        self.draw_shape_preview();
+        self.draw_paste_preview();
+        self.draw_generated_preview(visible);
+        self.setup_grid(visible, overlay);
+        self.draw_selection();
+        if self.paint_mode == PaintMode::Collision {
+            self.draw_collision_overlay(visible);
+        }
+        self.highlight_hovered_tile();
 
         set_default_camera();
-        camera
     }
 
-    fn setup_grid(&self) {
-        let Size { width: tile_width, height: tile_height } = self.tile_dimensions;
+    /// Chunk-aligned block of cells intersecting the camera view, clamped to the map.
+    fn visible_region(&self) -> Selection {
+        let center = Vec2::from(
+            self.camera_controller
+                .screen_center
+                .clone(),
+        );
+        let half_view = self.camera_controller.get_view_size() / 2.0;
+        // Grid coordinates of the view corners; isometric views cover a rotated block.
+        let corners = [
+            vec2(-1.0, -1.0),
+            vec2(1.0, -1.0),
+            vec2(1.0, 1.0),
+            vec2(-1.0, 1.0),
+        ]
+        .map(|corner| self.grid_coords(center + corner * half_view));
+        let min = corners
+            .into_iter()
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let max = corners
+            .into_iter()
+            .reduce(Vec2::max)
+            .unwrap_or_default();
+
+        let chunk_range = |min: f32, max: f32, tiles: usize| {
+            let first = min.floor().max(0.0) as usize;
+            let last = (max.ceil().max(0.0) as usize).min(tiles);
+            let start = (first / Self::CHUNK_TILES * Self::CHUNK_TILES).min(tiles);
+            let end = last
+                .div_ceil(Self::CHUNK_TILES)
+                .saturating_mul(Self::CHUNK_TILES)
+                .min(tiles);
+            (start, end.max(start))
+        };
+        let (x0, x1) = chunk_range(min.x, max.x, self.map_width_tiles);
+        let (y0, y1) = chunk_range(min.y, max.y, self.map_height_tiles);
+        Selection::new(x0, y0, x1 - x0, y1 - y0)
+    }
 
-        let grid_width = self.map_width_tiles as f32 * tile_width;
-        let grid_height = self.map_height_tiles as f32 * tile_height;
-        let thickness = 0.0;
+    /// Row-major indices of the cells inside `region`.
+    fn region_indices(&self, region: Selection) -> impl Iterator<Item = usize> + '_ {
+        (region.y..region.y + region.height).flat_map(move |tile_y| {
+            (region.x..region.x + region.width)
+                .map(move |tile_x| tile_y * self.map_width_tiles + tile_x)
+        })
+    }
+
+    fn setup_grid(&self, region: Selection, overlay: &OverlayText) {
+        let style = self.grid_style;
+        if style.show_coordinates {
+            self.draw_cell_coordinates(region, overlay);
+        }
+        if !style.visible {
+            return;
+        }
+        let (left, top) = (region.x as f32, region.y as f32);
+        let right = (region.x + region.width) as f32;
+        let bottom = (region.y + region.height) as f32;
+        let pixel = self
+            .camera_controller
+            .world_units_per_pixel();
+        let color = style.color();
+        let thickness = |line: usize| {
+            if style.is_major(line) {
+                2.0 * pixel
+            } else {
+                pixel
+            }
+        };
 
-        for col in 0..=self.map_width_tiles {
-            let x = col as f32 * tile_width;
-            draw_line(x, 0.0, x, grid_height, thickness, GRAY);
+        for col in region.x..=region.x + region.width {
+            let start = self.grid_point(col as f32, top);
+            let end = self.grid_point(col as f32, bottom);
+            draw_line(start.x, start.y, end.x, end.y, thickness(col), color);
         }
 
-        for row in 0..=self.map_height_tiles {
-            let y = row as f32 * tile_height;
-            draw_line(0.0, y, grid_width, y, thickness, GRAY);
+        for row in region.y..=region.y + region.height {
+            let start = self.grid_point(left, row as f32);
+            let end = self.grid_point(right, row as f32);
+            draw_line(start.x, start.y, end.x, end.y, thickness(row), color);
         }
     }
 
-    fn highlight_hovered_tile(&self, camera: &Camera2D) {
-        if let Some((tile_x, tile_y)) = self.hovered_tile(camera) {
-            let Size { width: tile_width, height: tile_height } = self.tile_dimensions;
-            let tile_origin_x = tile_x as f32 * tile_width;
-            let tile_origin_y = tile_y as f32 * tile_height;
+    /// Labels the cells of `region` with their `x,y` tile coordinates, unless the cells are
+    /// too small on screen to fit them.
+    fn draw_cell_coordinates(&self, region: Selection, overlay: &OverlayText) {
+        const MIN_CELL_PIXELS: f32 = 28.0;
+        let pixel = self
+            .camera_controller
+            .world_units_per_pixel();
+        if self.tile_dimensions.width < MIN_CELL_PIXELS * pixel {
+            return;
+        }
+        // Kept at a constant on-screen size, like the grid lines.
+        let scale = 0.8 * pixel;
+        let color = Color { a: 0.8, ..self.grid_style.color() };
+        for (tile_x, tile_y) in region.cells() {
+            let origin = self.cell_origin(tile_x, tile_y);
+            overlay.draw_world(
+                &format!("{tile_x},{tile_y}"),
+                origin.x + 2.0 * pixel,
+                origin.y + overlay.world_height(scale),
+                scale,
+                color,
+            );
+        }
+    }
 
-            let highlight_color = Color { r: 0.1, g: 0.9, b: 0.2, a: 0.35 };
-            draw_rectangle(tile_origin_x, tile_origin_y, tile_width, tile_height, highlight_color);
-        }
-    }
-
-    fn draw_tiles(&self) {
-        let tile_width = self.tile_dimensions.width;
-        let tile_height = self.tile_dimensions.height;
-
-        for (idx, tile) in self.tiles.iter().enumerate() {
-            if let Some(painted) = tile {
-                let x = (idx % self.map_width_tiles) as f32 * tile_width;
-                let y = (idx / self.map_width_tiles) as f32 * tile_height;
-                draw_texture_ex(
-                    &painted.texture,
-                    x,
-                    y,
-                    WHITE,
-                    DrawTextureParams {
-                        dest_size: Some(vec2(tile_width, tile_height)),
-                        ..Default::default()
-                    },
-                );
+    /// World position of the grid point `(x, y)`; see [`MapProjection::grid_point`].
+    fn grid_point(&self, x: f32, y: f32) -> Vec2 {
+        self.projection
+            .grid_point((x, y), self.dimensions(), self.tile_dimensions.into())
+            .into()
+    }
+
+    /// Grid coordinates of a world position; see [`MapProjection::grid_coords`].
+    fn grid_coords(&self, position: Vec2) -> Vec2 {
+        self.projection
+            .grid_coords(position.into(), self.dimensions(), self.tile_dimensions.into())
+            .into()
+    }
+
+    /// Top-left corner of the box a tile of cell `(tile_x, tile_y)` is drawn in.
+    fn cell_origin(&self, tile_x: usize, tile_y: usize) -> Vec2 {
+        self.projection
+            .cell_origin((tile_x, tile_y), self.dimensions(), self.tile_dimensions.into())
+            .into()
+    }
+
+    /// Fills the area of the cells in `region`: a rectangle, or a parallelogram on isometric
+    /// maps.
+    fn fill_block(&self, region: Selection, color: Color) {
+        let [a, b, c, d] = self.block_corners(region);
+        match self.projection {
+            MapProjection::Orthogonal => draw_rectangle(a.x, a.y, c.x - a.x, c.y - a.y, color),
+            MapProjection::Isometric => {
+                draw_triangle(a, b, c, color);
+                draw_triangle(a, c, d, color);
             }
         }
     }
 
-    /// Returns the `(x, y)` tile coordinates currently under the mouse cursor.
-    pub fn hovered_tile(&self, camera: &Camera2D) -> Option<(usize, usize)> {
-        let (mouse_x, mouse_y) = mouse_position();
-        let mouse_screen = vec2(mouse_x, mouse_y);
-        let world_pos = camera.screen_to_world(mouse_screen);
+    /// Outlines the area of the cells in `region`.
+    fn outline_block(&self, region: Selection, thickness: f32, color: Color) {
+        let corners = self.block_corners(region);
+        match self.projection {
+            MapProjection::Orthogonal => {
+                let [a, _, c, _] = corners;
+                draw_rectangle_lines(a.x, a.y, c.x - a.x, c.y - a.y, thickness, color);
+            }
+            MapProjection::Isometric => {
+                for (start, end) in corners
+                    .iter()
+                    .zip(corners.iter().cycle().skip(1))
+                {
+                    draw_line(start.x, start.y, end.x, end.y, thickness, color);
+                }
+            }
+        }
+    }
 
-        let tile_width = self.tile_dimensions.width;
-        let tile_height = self.tile_dimensions.height;
-        let grid_size = self.grid_size();
-        let grid_width = grid_size.x;
-        let grid_height = grid_size.y;
+    fn block_corners(&self, region: Selection) -> [Vec2; 4] {
+        self.projection
+            .block_corners(
+                (region.x, region.y),
+                (region.width, region.height),
+                self.dimensions(),
+                self.tile_dimensions.into(),
+            )
+            .map(Vec2::from)
+    }
 
-        if world_pos.x < 0.0
-            || world_pos.y < 0.0
-            || world_pos.x >= grid_width
-            || world_pos.y >= grid_height
+    /// Draws the image of every visible image layer at its natural size, offset from the map
+    /// origin by its parallax.
+    fn draw_images(&self) {
+        let center = Vec2::from(
+            self.camera_controller
+                .screen_center
+                .clone(),
+        );
+        for layer in self
+            .layers
+            .iter()
+            .filter(|layer| self.is_shown(layer))
         {
-            return None;
+            let Some(image) = &layer.image else {
+                continue;
+            };
+            let origin = center * (Vec2::ONE - layer.parallax);
+            draw_texture_ex(
+                image,
+                origin.x,
+                origin.y,
+                layer.modulation(self.group_opacity(layer)),
+                DrawTextureParams::default(),
+            );
         }
-
-        let tile_x = (world_pos.x / tile_width).floor() as usize;
-        let tile_y = (world_pos.y / tile_height).floor() as usize;
-
-        Some((tile_x, tile_y))
     }
 
-    /// Paints a tile slot with the sprite, replacing any previous texture.
-    pub fn paint_tile(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) {
-        if let Some(index) = self.tile_index(tile_x, tile_y) {
-            self.tiles[index] = Some(PaintedTile {
-                texture: sprite.texture.clone(),
-                tile_id: sprite.id.clone(),
-            });
+    /// Draws the reference image over the tiles, offset in whole or fractional tiles.
+    fn draw_reference_image(&self) {
+        let (Some(reference), Some(texture)) = (&self.reference, &self.reference_texture) else {
+            return;
+        };
+        if !reference.visible {
+            return;
         }
+        let origin = vec2(
+            reference.offset[0] * self.tile_dimensions.width,
+            reference.offset[1] * self.tile_dimensions.height,
+        );
+        draw_texture_ex(
+            texture,
+            origin.x,
+            origin.y,
+            Color::new(1.0, 1.0, 1.0, reference.opacity),
+            DrawTextureParams {
+                dest_size: Some(texture.size() * reference.scale),
+                ..Default::default()
+            },
+        );
     }
 
-    fn tile_index(&self, tile_x: usize, tile_y: usize) -> Option<usize> {
-        if tile_x >= self.map_width_tiles || tile_y >= self.map_height_tiles {
-            return None;
+    /// Draws the objects of every visible object layer as labeled rectangles.
+    fn draw_objects(&self, overlay: &OverlayText) {
+        let fill = Color { r: 0.2, g: 0.6, b: 1.0, a: 0.25 };
+        let outline = Color { r: 0.2, g: 0.6, b: 1.0, a: 0.9 };
+        let label_height = overlay.world_height(1.0);
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            if !self.is_shown(layer) || !layer.is_object_layer() {
+                continue;
+            }
+            for (index, object) in layer.objects.iter().enumerate() {
+                let selected =
+                    layer_index == self.active_layer && self.selected_object == Some(index);
+                draw_rectangle(object.x, object.y, object.width, object.height, fill);
+                draw_rectangle_lines(
+                    object.x,
+                    object.y,
+                    object.width,
+                    object.height,
+                    if selected {
+                        2.0
+                    } else {
+                        1.0
+                    },
+                    if selected {
+                        YELLOW
+                    } else {
+                        outline
+                    },
+                );
+                let label = if object.kind.is_empty() {
+                    object.name.clone()
+                } else {
+                    format!("{} ({})", object.name, object.kind)
+                };
+                overlay.draw_world(&label, object.x + 2.0, object.y + label_height, 1.0, WHITE);
+            }
         }
+    }
 
-        Some(tile_y * self.map_width_tiles + tile_x)
+    /// Shades every blocked cell red.
+    fn draw_collision_overlay(&self, region: Selection) {
+        let blocked_color = Color { r: 0.9, g: 0.1, b: 0.1, a: 0.4 };
+        for index in self
+            .region_indices(region)
+            .filter(|&index| !self.colliders[index].is_walkable)
+        {
+            let cell =
+                Selection::new(index % self.map_width_tiles, index / self.map_width_tiles, 1, 1);
+            self.fill_block(cell, blocked_color);
+        }
     }
 
-    fn grid_size(&self) -> Vec2 {
-        vec2(
-            self.map_width_tiles as f32 * self.tile_dimensions.width,
-            self.map_height_tiles as f32 * self.tile_dimensions.height,
-        )
+    /// Returns `false` for cells marked as blocked; cells outside the map are not walkable.
+    pub fn is_walkable(&self, tile_x: usize, tile_y: usize) -> bool {
+        self.tile_index(tile_x, tile_y)
+            .is_some_and(|index| self.colliders[index].is_walkable)
     }
 
-    /// Writes the current map state to disk in JSON format.
-    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
-        let export = self.export();
-        let json = serde_json::to_string_pretty(&export)
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-        fs::write(path, json)
+    /// Applies the collision stroke to `(tile_x, tile_y)`.
+    ///
+    /// The first cell of a stroke flips its walkability and every cell dragged over after it
+    /// gets the same state. Collision edits are not recorded in the undo history.
+    pub fn paint_collision(&mut self, tile_x: usize, tile_y: usize) {
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return;
+        };
+        let walkable = *self
+            .collision_stroke
+            .get_or_insert(!self.colliders[index].is_walkable);
+        if self.colliders[index].is_walkable != walkable {
+            self.colliders[index].is_walkable = walkable;
+            self.mark_edited();
+        }
     }
 
-    /// Loads map data from disk and rebuilds the internal tile buffers.
-    pub fn load_from_file<P: AsRef<Path>>(
-        &mut self, path: P, catalog: &AssetCatalog,
-    ) -> Result<(), MapLoadError> {
-        let data = fs::read_to_string(path)?;
-        let export: MapExport = serde_json::from_str(&data)?;
+    fn draw_selection(&self) {
+        let selection_color = Color { r: 0.2, g: 0.5, b: 1.0, a: 0.3 };
 
-        let expected_tile_count = export.width * export.height;
-        if export.tiles.len() != expected_tile_count {
-            return Err(MapLoadError::TileCountMismatch {
-                expected: expected_tile_count,
-                found: export.tiles.len(),
-            });
+        if let Some(selection) = self.selection {
+            self.fill_block(selection, selection_color);
+        }
+        for &(tile_x, tile_y) in &self.multi_selection_cells {
+            self.fill_block(Selection::new(tile_x, tile_y, 1, 1), selection_color);
         }
+    }
 
-        self.map_width_tiles = export.width;
-        self.map_height_tiles = export.height;
-        self.tile_dimensions = Size {
-            width: export.tile_width,
-            height: export.tile_height,
-        };
+    fn highlight_hovered_tile(&self) {
+        if let Some((tile_x, tile_y)) = self.hovered_tile() {
+            let highlight_color = Color { r: 0.1, g: 0.9, b: 0.2, a: 0.35 };
+            self.fill_block(Selection::new(tile_x, tile_y, 1, 1), highlight_color);
+        }
+    }
 
-        self.tiles = export
-            .tiles
-            .into_iter()
-            .map(|maybe_id| match maybe_id {
-                Some(id) => {
-                    let sprite = catalog
-                        .sprite_by_id(&id)
-                        .ok_or_else(|| MapLoadError::UnknownTile(id.clone()))?;
-                    Ok(Some(PaintedTile {
-                        texture: sprite.texture.clone(),
-                        tile_id: sprite.id.clone(),
-                    }))
-                }
-                None => Ok(None),
+    /// Draws the painted cells of `region` on every shown layer, ordered by
+    /// `layer * LAYER_Z_STRIDE + z_priority`.
+    fn draw_tiles(&self, region: Selection) {
+        let time = get_time();
+
+        let mut draw_list: Vec<(i64, usize, &PaintedTile, Color)> = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| self.is_shown(layer))
+            .flat_map(|(layer_index, layer)| {
+                let modulation = layer.modulation(self.group_opacity(layer));
+                self.region_indices(region)
+                    .filter_map(move |idx| {
+                        layer.tiles[idx]
+                            .as_ref()
+                            .map(|painted| {
+                                let order = layer_index as i64 * Self::LAYER_Z_STRIDE
+                                    + i64::from(painted.z_priority);
+                                (order, idx, painted, modulation)
+                            })
+                    })
             })
-            .collect::<Result<Vec<_>, MapLoadError>>()?;
+            .collect();
+        draw_list.sort_by_key(|&(order, _, _, _)| order);
 
-        self.camera_controller.screen_center = self.grid_size().into();
+        for (_, idx, painted, modulation) in draw_list {
+            let Vec2 { x, y } =
+                self.cell_origin(idx % self.map_width_tiles, idx / self.map_width_tiles);
+            let (texture, source) =
+                painted
+                    .animation
+                    .frame(&painted.texture, painted.source, time);
+            let tint = painted.tint;
+            draw_texture_ex(
+                texture,
+                x,
+                y,
+                Color::new(
+                    tint.r * modulation.r,
+                    tint.g * modulation.g,
+                    tint.b * modulation.b,
+                    tint.a * modulation.a,
+                ),
+                self.cell_draw_params(painted.transform, source),
+            );
+        }
+    }
 
-        Ok(())
+    /// Draws every visible layer, scaled to fill `target`, for overview renders such as the
+    /// minimap. Leaves the default camera active.
+    pub fn draw_overview(&self, target: &RenderTarget) {
+        let mut camera = Camera2D::from_display_rect(Rect::new(
+            0.0,
+            0.0,
+            self.grid_size().x,
+            self.grid_size().y,
+        ));
+        camera.render_target = Some(target.clone());
+        set_camera(&camera);
+        clear_background(BLANK);
+        self.draw_tiles(Selection::new(0, 0, self.map_width_tiles, self.map_height_tiles));
+        set_default_camera();
     }
 
-    fn export(&self) -> MapExport {
-        let tiles = self
-            .tiles
-            .iter()
-            .map(|tile| {
-                tile.as_ref()
-                    .map(|painted| painted.tile_id.clone())
-            })
+    /// Renders every visible tile layer of the whole map, `scale` image pixels per map
+    /// pixel, and writes it to `path` as a PNG with a transparent background.
+    ///
+    /// Object layers are editor markers and are left out. Returns the image size; fails
+    /// with [`io::ErrorKind::InvalidInput`] when a side would exceed [`Self::MAX_IMAGE_SIDE`].
+    pub fn export_image<P: AsRef<Path>>(
+        &self, path: P, scale: u32,
+    ) -> Result<(u32, u32), io::Error> {
+        let size = self.grid_size() * scale.max(1) as f32;
+        let (width, height) = (size.x.ceil() as u32, size.y.ceil() as u32);
+        if width == 0
+            || height == 0
+            || width > Self::MAX_IMAGE_SIDE
+            || height > Self::MAX_IMAGE_SIDE
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "a {width}x{height} image is outside 1..={} pixels per side; lower the scale",
+                    Self::MAX_IMAGE_SIDE
+                ),
+            ));
+        }
+
+        let target = render_target(width, height);
+        self.draw_overview(&target);
+        let pixels = target.texture.get_texture_data();
+        // Render targets are stored bottom row first.
+        let row = width as usize * 4;
+        let flipped: Vec<u8> = pixels
+            .bytes
+            .chunks_exact(row)
+            .rev()
+            .flatten()
+            .copied()
             .collect();
+        image::save_buffer(path, &flipped, width, height, image::ColorType::Rgba8)
+            .map_err(io::Error::other)?;
+        Ok((width, height))
+    }
 
-        MapExport {
-            width: self.map_width_tiles,
-            height: self.map_height_tiles,
-            tile_width: self.tile_dimensions.width,
-            tile_height: self.tile_dimensions.height,
-            tiles,
+    /// Returns the tool applied by left clicks on the grid.
+    pub fn paint_mode(&self) -> PaintMode {
+        self.paint_mode
+    }
+
+    /// Selects the tool applied by left clicks on the grid, restarting the tile sequence.
+    pub fn set_paint_mode(&mut self, mode: PaintMode) {
+        if mode != self.paint_mode {
+            self.tile_sequence.reset();
+            self.shape_drag = None;
+            self.marquee_anchor = None;
         }
+        self.paint_mode = mode;
     }
-}
 
-/// Converts a raw dimension into an integral number of tiles.
-fn dimension_to_tiles(value: f32) -> usize {
-    value.max(1.0).round() as usize
-}
+    /// Starts a rectangle or line drag of the active tool at `(tile_x, tile_y)`.
+    pub fn begin_shape(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) {
+        if matches!(self.paint_mode, PaintMode::Rectangle | PaintMode::Line)
+            && self
+                .tile_index(tile_x, tile_y)
+                .is_some()
+        {
+            self.shape_drag = Some(ShapeDrag {
+                mode: self.paint_mode,
+                start: (tile_x, tile_y),
+                end: (tile_x, tile_y),
+                tile: self.brush_tile(sprite),
+            });
+        }
+    }
 
-/// Possible failures when loading a map from disk.
-#[derive(Debug)]
-pub enum MapLoadError {
-    Io(io::Error),
-    Parse(serde_json::Error),
-    TileCountMismatch {
-        expected: usize,
-        found: usize,
-    },
-    UnknownTile(String),
-}
+    /// Moves the free end of the shape being dragged.
+    pub fn update_shape(&mut self, tile_x: usize, tile_y: usize) {
+        if let Some(drag) = self.shape_drag.as_mut() {
+            drag.end = (tile_x, tile_y);
+        }
+    }
 
-impl From<io::Error> for MapLoadError {
-    fn from(value: io::Error) -> Self {
-        MapLoadError::Io(value)
+    /// Paints the dragged shape on the active layer as a single undo step.
+    pub fn finish_shape(&mut self) {
+        let Some(drag) = self.shape_drag.take() else {
+            return;
+        };
+        let mut painted = false;
+        for (tile_x, tile_y) in shape_cells(&drag) {
+            if let Some(index) = self.tile_index(tile_x, tile_y)
+                && self.is_editable(tile_x, tile_y)
+            {
+                self.set_cell(index, Some(drag.tile.clone()));
+                painted = true;
+            }
+        }
+        self.history.commit(EditKind::Shape);
+        if painted {
+            self.note_painted(&drag.tile.tile_id);
+            self.play_paint_sound();
+        }
     }
-}
 
-impl From<serde_json::Error> for MapLoadError {
-    fn from(value: serde_json::Error) -> Self {
-        MapLoadError::Parse(value)
+    /// Drops the shape being dragged without painting it.
+    pub fn cancel_shape(&mut self) {
+        self.shape_drag = None;
     }
-}
 
-impl std::fmt::Display for MapLoadError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            MapLoadError::Io(err) => write!(f, "IO error: {err}"),
-            MapLoadError::Parse(err) => write!(f, "JSON parse error: {err}"),
-            MapLoadError::TileCountMismatch { expected, found } => {
-                write!(f, "Tile count mismatch. Expected {expected}, found {found}")
-            }
-            MapLoadError::UnknownTile(id) => write!(f, "Unknown tile id: {id}"),
+    /// Draws the dragged shape with translucent copies of its tile.
+    fn draw_shape_preview(&self) {
+        let Some(drag) = &self.shape_drag else {
+            return;
+        };
+        for (tile_x, tile_y) in shape_cells(drag) {
+            self.draw_ghost_tile(&drag.tile, tile_x, tile_y);
+        }
+    }
+
+    /// Draws a translucent copy of `tile` on a cell, for previews of pending edits.
+    fn draw_ghost_tile(&self, tile: &PaintedTile, tile_x: usize, tile_y: usize) {
+        let tint = Color { a: tile.tint.a * 0.5, ..tile.tint };
+        let origin = self.cell_origin(tile_x, tile_y);
+        draw_texture_ex(
+            &tile.texture,
+            origin.x,
+            origin.y,
+            tint,
+            self.cell_draw_params(tile.transform, tile.source),
+        );
+    }
+
+    /// Parameters drawing a texture region over one grid cell in the given orientation.
+    ///
+    /// The rotation pivots on the cell center, so 90 and 270 degree turns of non-square
+    /// cells overflow them.
+    fn cell_draw_params(
+        &self, transform: TileTransform, source: Option<Rect>,
+    ) -> DrawTextureParams {
+        DrawTextureParams {
+            dest_size: Some(vec2(self.tile_dimensions.width, self.tile_dimensions.height)),
+            source,
+            rotation: transform.radians(),
+            flip_x: transform.flip_x,
+            flip_y: transform.flip_y,
+            ..Default::default()
+        }
+    }
+
+    /// Starts a marquee selection at `(tile_x, tile_y)`, replacing the current selection.
+    pub fn begin_marquee(&mut self, tile_x: usize, tile_y: usize) {
+        if self
+            .tile_index(tile_x, tile_y)
+            .is_some()
+        {
+            self.clear_selection();
+            self.marquee_anchor = Some((tile_x, tile_y));
+            self.selection = Some(Selection::new(tile_x, tile_y, 1, 1));
+        }
+    }
+
+    /// Stretches the marquee selection to `(tile_x, tile_y)`.
+    pub fn update_marquee(&mut self, tile_x: usize, tile_y: usize) {
+        if let Some(anchor) = self.marquee_anchor {
+            self.selection = Some(Selection::from_corners(anchor, (tile_x, tile_y)));
         }
     }
-}
 
-impl std::error::Error for MapLoadError {}
+    /// Copies the selected cells of the active layer into the clipboard, as the block
+    /// bounding the selection.
+    ///
+    /// Returns the number of painted cells copied; the clipboard is kept when it is `0`.
+    pub fn copy_selection(&mut self) -> usize {
+        let Some(bounds) = self.selection_bounds() else {
+            return 0;
+        };
+        let cells: Vec<Option<PaintedTile>> = bounds
+            .cells()
+            .map(|(tile_x, tile_y)| {
+                self.tile_index(tile_x, tile_y)
+                    .filter(|_| self.is_selected(tile_x, tile_y))
+                    .and_then(|index| self.tiles()[index].clone())
+            })
+            .collect();
+        let copied = cells.iter().flatten().count();
+        if copied > 0 {
+            self.clipboard = Some(TileClipboard::new(bounds.width, cells));
+        }
+        copied
+    }
 
-#[derive(Serialize, Deserialize)]
-struct MapExport {
-    width: usize,
-    height: usize,
-    tile_width: f32,
-    tile_height: f32,
-    tiles: Vec<Option<String>>,
+    /// Smallest rectangle containing every selected cell.
+    fn selection_bounds(&self) -> Option<Selection> {
+        let corners = self
+            .selection
+            .into_iter()
+            .flat_map(|selection| {
+                [
+                    (selection.x, selection.y),
+                    (selection.x + selection.width - 1, selection.y + selection.height - 1),
+                ]
+            })
+            .chain(
+                self.multi_selection_cells
+                    .iter()
+                    .copied(),
+            );
+        corners.fold(None, |bounds: Option<Selection>, cell| {
+            Some(match bounds {
+                Some(bounds) => Selection::from_corners(
+                    (bounds.x.min(cell.0), bounds.y.min(cell.1)),
+                    (
+                        (bounds.x + bounds.width - 1).max(cell.0),
+                        (bounds.y + bounds.height - 1).max(cell.1),
+                    ),
+                ),
+                None => Selection::new(cell.0, cell.1, 1, 1),
+            })
+        })
+    }
+
+    /// Attaches the clipboard block to the cursor with its top-left corner at
+    /// `(tile_x, tile_y)`, clearing the selection. Returns `false` when nothing was copied.
+    pub fn start_paste(&mut self, tile_x: usize, tile_y: usize) -> bool {
+        if self.clipboard.is_none() {
+            return false;
+        }
+        self.clear_selection();
+        self.shape_drag = None;
+        self.paste_origin = Some((tile_x, tile_y));
+        true
+    }
+
+    /// Returns `true` while a clipboard block follows the cursor.
+    pub fn is_pasting(&self) -> bool {
+        self.paste_origin.is_some()
+    }
+
+    /// Moves the pasted block so its top-left corner sits on `(tile_x, tile_y)`.
+    pub fn move_paste(&mut self, tile_x: usize, tile_y: usize) {
+        if let Some(origin) = self.paste_origin.as_mut() {
+            *origin = (tile_x, tile_y);
+        }
+    }
+
+    /// Writes the pasted block into the active layer as a single undo step.
+    ///
+    /// Cells left out of the copy and cells falling outside the map are skipped.
+    pub fn commit_paste(&mut self) {
+        let (Some((origin_x, origin_y)), Some(clipboard)) =
+            (self.paste_origin.take(), self.clipboard.clone())
+        else {
+            return;
+        };
+        let mut pasted = false;
+        for (dx, dy, tile) in clipboard.cells() {
+            let (tile_x, tile_y) = (origin_x + dx, origin_y + dy);
+            if let Some(index) = self.tile_index(tile_x, tile_y)
+                && self.is_editable(tile_x, tile_y)
+            {
+                self.set_cell(index, Some(tile.clone()));
+                pasted = true;
+            }
+        }
+        self.history.commit(EditKind::Paste);
+        if pasted {
+            self.play_paint_sound();
+        }
+    }
+
+    /// Drops the pasted block without writing it.
+    pub fn cancel_paste(&mut self) {
+        self.paste_origin = None;
+    }
+
+    /// Draws the pasted block translucently under the cursor.
+    fn draw_paste_preview(&self) {
+        let (Some((origin_x, origin_y)), Some(clipboard)) = (self.paste_origin, &self.clipboard)
+        else {
+            return;
+        };
+        for (dx, dy, tile) in clipboard.cells() {
+            let (tile_x, tile_y) = (origin_x + dx, origin_y + dy);
+            if tile_x < self.map_width_tiles && tile_y < self.map_height_tiles {
+                self.draw_ghost_tile(tile, tile_x, tile_y);
+            }
+        }
+    }
+
+    fn draw_generated_preview(&self, region: Selection) {
+        for index in self.region_indices(region) {
+            if let Some(Some(tile)) = self.generated_preview.get(index) {
+                self.draw_ghost_tile(
+                    tile,
+                    index % self.map_width_tiles,
+                    index / self.map_width_tiles,
+                );
+            }
+        }
+    }
+
+    /// Returns the sequence painted by [`PaintMode::Sequence`] for editing.
+    pub fn tile_sequence_mut(&mut self) -> &mut TileSequence {
+        &mut self.tile_sequence
+    }
+
+    /// Paints the next sprite of the tile sequence, once per entered cell.
+    pub fn paint_sequence_tile(&mut self, tile_x: usize, tile_y: usize) {
+        if self.last_stroke_cell == Some((tile_x, tile_y)) || self.paint_cooling_down() {
+            return;
+        }
+        if let Some(sprite) = self.tile_sequence.next_sprite() {
+            self.last_stroke_cell = Some((tile_x, tile_y));
+            self.paint_tile(tile_x, tile_y, &sprite);
+        }
+    }
+
+    /// Returns the weighted set painted by [`PaintMode::Random`] for editing.
+    pub fn random_brush_mut(&mut self) -> &mut RandomBrush {
+        &mut self.random_brush
+    }
+
+    /// Paints a random sprite of the random brush, once per entered cell.
+    pub fn paint_random_tile(&mut self, tile_x: usize, tile_y: usize) {
+        if self.last_stroke_cell == Some((tile_x, tile_y)) || self.paint_cooling_down() {
+            return;
+        }
+        if let Some(sprite) = self.random_brush.pick().cloned() {
+            self.last_stroke_cell = Some((tile_x, tile_y));
+            self.paint_tile(tile_x, tile_y, &sprite);
+        }
+    }
+
+    pub fn noise_stamp(&self) -> &NoiseStamp {
+        &self.noise_stamp
+    }
+
+    /// Replaces the decision table used by [`PaintMode::NoiseStamp`].
+    pub fn set_noise_stamp(&mut self, noise_stamp: NoiseStamp) {
+        self.noise_stamp = noise_stamp;
+    }
+
+    /// Paints the tile picked by the noise stamp, once per entered cell.
+    pub fn paint_noise_tile(&mut self, tile_x: usize, tile_y: usize, catalog: &AssetCatalog) {
+        if self.last_stroke_cell == Some((tile_x, tile_y)) || self.paint_cooling_down() {
+            return;
+        }
+        if let Some(sprite) = self
+            .noise_stamp
+            .pick(tile_x, tile_y, self, catalog)
+        {
+            self.last_stroke_cell = Some((tile_x, tile_y));
+            self.paint_tile(tile_x, tile_y, sprite);
+        }
+    }
+
+    /// Paints terrain on `(tile_x, tile_y)`, once per entered cell, then re-picks the tile of
+    /// that cell and of every terrain cell around it from their eight neighbors.
+    ///
+    /// Cells outside the map count as terrain, so terrain reaching the map edge gets no border.
+    pub fn paint_terrain(&mut self, tile_x: usize, tile_y: usize, terrain: &TerrainSet) {
+        if self.last_stroke_cell == Some((tile_x, tile_y)) || !self.is_editable(tile_x, tile_y) {
+            return;
+        }
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return;
+        };
+        self.last_stroke_cell = Some((tile_x, tile_y));
+
+        let mut changed = false;
+        if !self
+            .tile_id(tile_x, tile_y)
+            .is_some_and(|id| terrain.contains(id))
+        {
+            self.set_cell(index, Some(self.terrain_tile(terrain.variant(0))));
+            changed = true;
+        }
+        for (x, y) in self.terrain_neighborhood(tile_x, tile_y) {
+            if !self.is_editable(x, y)
+                || !self
+                    .tile_id(x, y)
+                    .is_some_and(|id| terrain.contains(id))
+            {
+                continue;
+            }
+            let variant = terrain.variant(self.terrain_mask(x, y, terrain));
+            if self.tile_id(x, y) != Some(variant.id.as_str())
+                && let Some(index) = self.tile_index(x, y)
+            {
+                self.set_cell(index, Some(self.terrain_tile(variant)));
+                changed = true;
+            }
+        }
+        if changed {
+            self.play_paint_sound();
+        }
+    }
+
+    /// The cell and its eight neighbors that lie inside the map.
+    fn terrain_neighborhood(&self, tile_x: usize, tile_y: usize) -> Vec<(usize, usize)> {
+        std::iter::once((0, 0))
+            .chain(NEIGHBOR_OFFSETS)
+            .filter_map(|(dx, dy)| {
+                let x = tile_x.checked_add_signed(dx)?;
+                let y = tile_y.checked_add_signed(dy)?;
+                self.tile_index(x, y).map(|_| (x, y))
+            })
+            .collect()
+    }
+
+    /// Bit mask of the neighbors of `(tile_x, tile_y)` holding `terrain` (see [`NEIGHBOR_OFFSETS`]).
+    fn terrain_mask(&self, tile_x: usize, tile_y: usize, terrain: &TerrainSet) -> u8 {
+        NEIGHBOR_OFFSETS
+            .iter()
+            .enumerate()
+            .filter(|(_, (dx, dy))| {
+                let x = tile_x.checked_add_signed(*dx);
+                let y = tile_y.checked_add_signed(*dy);
+                match (x, y) {
+                    (Some(x), Some(y)) if self.tile_index(x, y).is_some() => self
+                        .tile_id(x, y)
+                        .is_some_and(|id| terrain.contains(id)),
+                    _ => true,
+                }
+            })
+            .fold(0, |mask, (bit, _)| mask | 1 << bit)
+    }
+
+    /// Brush tile for a terrain variant; terrain tiles are never rotated or flipped.
+    fn terrain_tile(&self, sprite: &TileSprite) -> PaintedTile {
+        PaintedTile {
+            transform: TileTransform::default(),
+            ..self.brush_tile(sprite)
+        }
+    }
+
+    /// Marks the end of a mouse stroke so the next click starts fresh.
+    ///
+    /// The edits made during the stroke become a single undo step.
+    pub fn end_stroke(&mut self) {
+        self.last_stroke_cell = None;
+        self.drag_cell = None;
+        self.collision_stroke = None;
+        self.marquee_anchor = None;
+        self.last_paint_world_pos = None;
+        self.object_drag = None;
+        self.commit_stroke();
+    }
+
+    /// Closes the undo step of the current stroke, as an erase when it only cleared cells.
+    fn commit_stroke(&mut self) {
+        let kind = if self
+            .history
+            .pending()
+            .iter()
+            .all(|change| change.after.is_none())
+        {
+            EditKind::Erase
+        } else {
+            EditKind::Paint
+        };
+        self.history.commit(kind);
+    }
+
+    /// Reverts the last edit step, returning `false` when there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.commit_stroke();
+        let reframe = match self.history.undo() {
+            None => return false,
+            Some(StepChanges::Cells(changes)) => {
+                for change in changes.iter().rev() {
+                    self.layers[change.layer].tiles[change.index] = change.before.clone();
+                }
+                None
+            }
+            Some(StepChanges::Grid(reframe)) => Some(reframe.clone()),
+        };
+        if let Some(reframe) = reframe {
+            self.undo_reframe(reframe);
+        }
+        self.mark_edited();
+        true
+    }
+
+    /// Reapplies the last undone edit step, returning `false` when there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.commit_stroke();
+        let reframe = match self.history.redo() {
+            None => return false,
+            Some(StepChanges::Cells(changes)) => {
+                for change in changes {
+                    self.layers[change.layer].tiles[change.index] = change.after.clone();
+                }
+                None
+            }
+            Some(StepChanges::Grid(reframe)) => Some((reframe.offset, reframe.size)),
+        };
+        if let Some(((offset_x, offset_y), (width, height))) = reframe {
+            self.apply_reframe(offset_x, offset_y, width, height);
+        }
+        self.mark_edited();
+        true
+    }
+
+    /// Kept undo steps from oldest to newest, undone steps last.
+    pub fn history_steps(&self) -> Vec<StepSummary> {
+        self.history.steps()
+    }
+
+    /// Number of [`Map::history_steps`] currently applied.
+    pub fn history_position(&self) -> usize {
+        self.history.position()
+    }
+
+    /// Undoes or redoes steps until exactly `position` of [`Map::history_steps`] are applied.
+    pub fn jump_to_history(&mut self, position: usize) {
+        while self.history_position() > position && self.undo() {}
+        while self.history_position() < position && self.redo() {}
+    }
+
+    /// Writes a cell of the active layer, recording the change for undo.
+    fn set_cell(&mut self, index: usize, tile: Option<PaintedTile>) {
+        self.set_layer_cell(self.active_layer, index, tile);
+    }
+
+    fn set_layer_cell(&mut self, layer: usize, index: usize, tile: Option<PaintedTile>) {
+        let before = std::mem::replace(&mut self.layers[layer].tiles[index], tile.clone());
+        if before.is_some() || tile.is_some() {
+            self.history
+                .record(layer, index, before, tile);
+            self.mark_edited();
+        }
+    }
+
+    /// Cells of the active layer.
+    fn tiles(&self) -> &[Option<PaintedTile>] {
+        &self.layers[self.active_layer].tiles
+    }
+
+    /// Custom properties of the whole map.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Replaces the map's custom properties, marking the map as edited when they changed.
+    pub fn set_properties(&mut self, properties: Properties) {
+        if self.properties != properties {
+            self.properties = properties;
+            self.mark_edited();
+        }
+    }
+
+    /// Custom properties of the tile painted at `(tile_x, tile_y)` on the active layer;
+    /// `None` for empty cells.
+    pub fn cell_properties(&self, tile_x: usize, tile_y: usize) -> Option<&Properties> {
+        let index = self.tile_index(tile_x, tile_y)?;
+        self.tiles()[index]
+            .as_ref()
+            .map(|painted| &painted.properties)
+    }
+
+    /// Replaces the custom properties of the tile painted at `(tile_x, tile_y)` on the
+    /// active layer, recording the change for undo.
+    ///
+    /// Returns `false` when the cell is empty or the active layer cannot be edited.
+    pub fn set_cell_properties(
+        &mut self, tile_x: usize, tile_y: usize, properties: Properties,
+    ) -> bool {
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return false;
+        };
+        let Some(painted) = self.tiles()[index].clone() else {
+            return false;
+        };
+        if !self.active_layer_editable() {
+            return false;
+        }
+        if painted.properties != properties {
+            self.commit_stroke();
+            self.set_cell(index, Some(PaintedTile { properties, ..painted }));
+            self.history
+                .commit(EditKind::CellProperties);
+        }
+        true
+    }
+
+    /// Returns the layer stack, bottom layer first.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns a layer for renaming or toggling its visibility and lock; marks the map as edited.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer> {
+        if index >= self.layers.len() {
+            return None;
+        }
+        self.mark_edited();
+        self.layers.get_mut(index)
+    }
+
+    /// Returns the layer groups referenced by [`Layer::group`].
+    pub fn groups(&self) -> &[LayerGroup] {
+        &self.groups
+    }
+
+    /// Returns a group for renaming or changing its visibility, lock and opacity; marks the
+    /// map as edited.
+    pub fn group_mut(&mut self, index: usize) -> Option<&mut LayerGroup> {
+        if index >= self.groups.len() {
+            return None;
+        }
+        self.mark_edited();
+        self.groups.get_mut(index)
+    }
+
+    /// Puts the active layer in a new group of its own, nested in the group it was in.
+    pub fn group_active_layer(&mut self) {
+        let parent = self.layers[self.active_layer].group;
+        let name = format!("Group {}", self.groups.len() + 1);
+        self.groups
+            .push(LayerGroup::new(name, parent));
+        self.layers[self.active_layer].group = Some(self.groups.len() - 1);
+        self.mark_edited();
+    }
+
+    /// Dissolves the innermost group of the active layer, leaving its layers and nested
+    /// groups in place, in its parent group, and drops the group.
+    pub fn ungroup_active_layer(&mut self) {
+        let Some(group) = self.layers[self.active_layer].group else {
+            return;
+        };
+        let parent = self.groups[group].parent;
+        for layer in &mut self.layers {
+            if layer.group == Some(group) {
+                layer.group = parent;
+            }
+        }
+        for nested in &mut self.groups {
+            if nested.parent == Some(group) {
+                nested.parent = parent;
+            }
+        }
+        self.remove_empty_groups();
+        self.mark_edited();
+    }
+
+    /// Moves the layer at `layer_index` into the group at `group_index`, right above the
+    /// group's top layer so the group stays adjacent. The moved layer stays active when it
+    /// was.
+    pub fn add_layer_to_group(&mut self, layer_index: usize, group_index: usize) {
+        if layer_index >= self.layers.len()
+            || group_index >= self.groups.len()
+            || self.layers[layer_index].group == Some(group_index)
+        {
+            return;
+        }
+        let was_active = self.active_layer == layer_index;
+        let layer = self.layers.remove(layer_index);
+        let target = self
+            .layers
+            .iter()
+            .rposition(|member| self.is_nested_in(member.group, Some(group_index)))
+            .map_or(layer_index.min(self.layers.len()), |top| top + 1);
+        self.layers
+            .insert(target, Layer { group: Some(group_index), ..layer });
+        if was_active {
+            self.active_layer = target;
+        } else {
+            if self.active_layer > layer_index {
+                self.active_layer -= 1;
+            }
+            if self.active_layer >= target {
+                self.active_layer += 1;
+            }
+        }
+        self.remove_empty_groups();
+        self.selected_object = None;
+        self.history.clear();
+        self.mark_edited();
+    }
+
+    /// Drops the groups holding no layer anymore, even through nested groups, and
+    /// renumbers the remaining ones.
+    fn remove_empty_groups(&mut self) {
+        let mut used = HashSet::new();
+        for layer in &self.layers {
+            used.extend(self.group_chain(layer.group));
+        }
+        let mut renumbered = Vec::with_capacity(self.groups.len());
+        let mut next = 0;
+        for index in 0..self.groups.len() {
+            renumbered.push(used.contains(&index).then(|| {
+                next += 1;
+                next - 1
+            }));
+        }
+        let mut keep = renumbered.iter();
+        self.groups
+            .retain(|_| keep.next().is_some_and(Option::is_some));
+        let renumber = |group: Option<usize>| group.and_then(|group| renumbered[group]);
+        for layer in &mut self.layers {
+            layer.group = renumber(layer.group);
+        }
+        for group in &mut self.groups {
+            group.parent = renumber(group.parent);
+        }
+    }
+
+    /// `group` followed by the groups it is nested in, innermost first.
+    fn group_chain(&self, group: Option<usize>) -> Vec<usize> {
+        let mut chain = Vec::new();
+        let mut next = group;
+        // Bounded by the group count so a parent loop cannot hang the editor.
+        while let Some(group) = next.filter(|_| chain.len() < self.groups.len()) {
+            chain.push(group);
+            next = self
+                .groups
+                .get(group)
+                .and_then(|group| group.parent);
+        }
+        chain
+    }
+
+    /// Returns `true` when `group` is `outer` or nested in it; everything is inside `None`,
+    /// the top level.
+    fn is_nested_in(&self, group: Option<usize>, outer: Option<usize>) -> bool {
+        outer.is_none_or(|outer| self.group_chain(group).contains(&outer))
+    }
+
+    /// Index of the layer edited by the tools.
+    pub fn active_layer(&self) -> usize {
+        self.active_layer
+    }
+
+    pub fn set_active_layer(&mut self, index: usize) {
+        if index < self.layers.len() && index != self.active_layer {
+            self.active_layer = index;
+            self.selected_object = None;
+        }
+    }
+
+    /// Inserts an empty layer above the active one, in the same group, and makes it active.
+    pub fn add_layer(&mut self) {
+        let name = format!("Layer {}", self.layers.len() + 1);
+        let cell_count = self.map_width_tiles * self.map_height_tiles;
+        self.insert_layer(Layer::new(name, cell_count));
+    }
+
+    /// Inserts an empty object layer above the active one, in the same group, and makes it
+    /// active.
+    pub fn add_object_layer(&mut self) {
+        let name = format!("Objects {}", self.layers.len() + 1);
+        let cell_count = self.map_width_tiles * self.map_height_tiles;
+        self.insert_layer(Layer::new_objects(name, cell_count));
+    }
+
+    /// Inserts an image layer without image above the active one, in the same group, and
+    /// makes it active. Its parallax starts at half the camera speed.
+    pub fn add_image_layer(&mut self) {
+        let name = format!("Background {}", self.layers.len() + 1);
+        let cell_count = self.map_width_tiles * self.map_height_tiles;
+        self.insert_layer(Layer::new_image(name, cell_count));
+    }
+
+    fn insert_layer(&mut self, layer: Layer) {
+        let group = self.layers[self.active_layer].group;
+        self.active_layer += 1;
+        self.layers
+            .insert(self.active_layer, Layer { group, ..layer });
+        self.selected_object = None;
+        self.history.clear();
+        self.mark_edited();
+    }
+
+    /// Points an image layer at the image file `path`, loading it right away.
+    ///
+    /// Fails without changes when the file cannot be read or decoded, or with
+    /// [`io::ErrorKind::InvalidInput`] when the layer is not an image layer.
+    pub fn set_layer_image(&mut self, index: usize, path: &str) -> Result<(), io::Error> {
+        if !self
+            .layers
+            .get(index)
+            .is_some_and(Layer::is_image_layer)
+        {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "not an image layer"));
+        }
+        let image = load_layer_image(path)?;
+        let layer = &mut self.layers[index];
+        layer.image_path = Some(path.to_string());
+        layer.image = Some(image);
+        self.mark_edited();
+        Ok(())
+    }
+
+    /// Tracing image shown over the map, if any.
+    pub fn reference_image(&self) -> Option<&ReferenceImage> {
+        self.reference.as_ref()
+    }
+
+    /// Whether the image of [`Map::reference_image`] was read and can be drawn.
+    pub fn reference_image_loaded(&self) -> bool {
+        self.reference_texture.is_some()
+    }
+
+    /// Shows the image file `path` as the reference image, loading it right away. The
+    /// offset, scale and opacity of the previous reference image are kept.
+    ///
+    /// Fails without changes when the file cannot be read or decoded.
+    pub fn load_reference_image(&mut self, path: &str) -> Result<(), io::Error> {
+        let texture = load_layer_image(path)?;
+        let reference = match self.reference.take() {
+            Some(previous) => ReferenceImage { path: path.to_string(), ..previous },
+            None => ReferenceImage::new(path),
+        };
+        self.reference = Some(reference);
+        self.reference_texture = Some(texture);
+        self.mark_edited();
+        Ok(())
+    }
+
+    /// Updates the offset, scale, opacity or visibility of the reference image, marking the
+    /// map as edited when they changed. The image itself is changed with
+    /// [`Map::load_reference_image`].
+    pub fn set_reference_image_settings(&mut self, settings: ReferenceImage) {
+        if let Some(reference) = &mut self.reference
+            && *reference != settings
+        {
+            *reference = ReferenceImage { path: reference.path.clone(), ..settings };
+            self.mark_edited();
+        }
+    }
+
+    /// Stops showing the reference image.
+    pub fn clear_reference_image(&mut self) {
+        if self.reference.take().is_some() {
+            self.reference_texture = None;
+            self.mark_edited();
+        }
+    }
+
+    /// Removes a layer; the last remaining layer cannot be removed.
+    pub fn remove_layer(&mut self, index: usize) {
+        if self.layers.len() <= 1 || index >= self.layers.len() {
+            return;
+        }
+        self.layers.remove(index);
+        self.remove_empty_groups();
+        if self.active_layer >= index && self.active_layer > 0 {
+            self.active_layer -= 1;
+        }
+        self.selected_object = None;
+        self.history.clear();
+        self.mark_edited();
+    }
+
+    /// Swaps a layer with its neighbor, `up` moving it towards the top of the stack.
+    ///
+    /// At the edge of a group the layer first leaves its group, one level at a time, then
+    /// enters the groups of the neighbor, so groups stay adjacent.
+    pub fn move_layer(&mut self, index: usize, up: bool) {
+        let Some(target) = (if up {
+            index.checked_add(1)
+        } else {
+            index.checked_sub(1)
+        }) else {
+            return;
+        };
+        if index >= self.layers.len() || target >= self.layers.len() {
+            return;
+        }
+        let (group, neighbor_group) = (self.layers[index].group, self.layers[target].group);
+        if group != neighbor_group {
+            self.layers[index].group = if self.is_nested_in(neighbor_group, group) {
+                // The neighbor is in a group nested in ours: enter its outermost one.
+                self.group_chain(neighbor_group)
+                    .into_iter()
+                    .find(|&nested| self.groups[nested].parent == group)
+            } else {
+                group.and_then(|group| self.groups[group].parent)
+            };
+            self.remove_empty_groups();
+            self.mark_edited();
+            return;
+        }
+        self.layers.swap(index, target);
+        if self.active_layer == index {
+            self.active_layer = target;
+        } else if self.active_layer == target {
+            self.active_layer = index;
+        }
+        self.selected_object = None;
+        self.history.clear();
+        self.mark_edited();
+    }
+
+    /// Starts an object tool click: picks the topmost object under the cursor on the active
+    /// object layer, or places a new tile-sized object on the hovered cell. Either way the
+    /// object follows the cursor until the stroke ends.
+    ///
+    /// Does nothing unless the active layer is a visible, unlocked object layer. Object edits
+    /// are not recorded in the undo history.
+    pub fn pick_or_place_object(&mut self) {
+        if !self.object_layer_editable() {
+            return;
+        }
+        let cursor = self.cursor_world_pos();
+        let Size { width: tile_width, height: tile_height } = self.tile_dimensions;
+        let index = match self.layers[self.active_layer]
+            .objects
+            .iter()
+            .rposition(|object| object.contains(cursor.x, cursor.y))
+        {
+            Some(index) => index,
+            None => {
+                let Some((tile_x, tile_y)) = self.hovered_tile() else {
+                    return;
+                };
+                let position = self.cell_origin(tile_x, tile_y).into();
+                let layer = &mut self.layers[self.active_layer];
+                let name = format!("Object {}", layer.objects.len() + 1);
+                layer
+                    .objects
+                    .push(MapObject::new(&name, position, (tile_width, tile_height)));
+                let index = layer.objects.len() - 1;
+                self.mark_edited();
+                index
+            }
+        };
+        let object = &self.layers[self.active_layer].objects[index];
+        self.object_drag = Some(vec2(object.x, object.y) - cursor);
+        self.selected_object = Some(index);
+    }
+
+    /// Moves the object grabbed by [`Map::pick_or_place_object`] with the cursor.
+    pub fn drag_object(&mut self) {
+        let (Some(offset), Some(index)) = (self.object_drag, self.selected_object) else {
+            return;
+        };
+        let position = self.cursor_world_pos() + offset;
+        let object = &mut self.layers[self.active_layer].objects[index];
+        if (object.x, object.y) != (position.x, position.y) {
+            (object.x, object.y) = (position.x, position.y);
+            self.mark_edited();
+        }
+    }
+
+    /// The selected object of the active object layer, if any.
+    pub fn selected_object(&self) -> Option<&MapObject> {
+        self.layers[self.active_layer]
+            .objects
+            .get(self.selected_object?)
+    }
+
+    /// Replaces the selected object, marking the map as edited when it changed.
+    pub fn set_selected_object(&mut self, object: MapObject) {
+        let Some(index) = self.selected_object else {
+            return;
+        };
+        let Some(slot) = self.layers[self.active_layer]
+            .objects
+            .get_mut(index)
+        else {
+            return;
+        };
+        if *slot != object {
+            *slot = object;
+            self.mark_edited();
+        }
+    }
+
+    /// Deletes the selected object, returning `false` when none was selected.
+    pub fn remove_selected_object(&mut self) -> bool {
+        if !self.object_layer_editable() {
+            return false;
+        }
+        let Some(index) = self.selected_object.take() else {
+            return false;
+        };
+        self.object_drag = None;
+        self.layers[self.active_layer]
+            .objects
+            .remove(index);
+        self.mark_edited();
+        true
+    }
+
+    /// Sets how far, in tile widths, the cursor must travel between placements while dragging.
+    ///
+    /// `0.0` disables the cooldown so every frame of a stroke may paint.
+    pub fn set_paint_cooldown(&mut self, min_distance_tiles: f32) {
+        self.paint_cooldown_tiles = min_distance_tiles.max(0.0);
+    }
+
+    /// Returns true while the cursor is still within the cooldown distance of the last placement.
+    fn paint_cooling_down(&self) -> bool {
+        let Some(last) = self.last_paint_world_pos else {
+            return false;
+        };
+        let min_distance = self.paint_cooldown_tiles * self.tile_dimensions.width;
+        self.cursor_world_pos().distance(last) < min_distance
+    }
+
+    fn cursor_world_pos(&self) -> Vec2 {
+        self.camera_controller
+            .cursor_world_position()
+    }
+
+    /// Starts scrolling the camera across the whole map at `speed` world units per second.
+    ///
+    /// The camera loops back to the start of the path once it reaches the end.
+    pub fn start_playback(&mut self, speed: f32) {
+        self.playback_active = true;
+        self.playback_speed = speed;
+        self.playback_distance = 0.0;
+    }
+
+    /// Stops playback, leaving the camera where it currently is.
+    pub fn stop_playback(&mut self) {
+        self.playback_active = false;
+    }
+
+    pub fn is_playback_active(&self) -> bool {
+        self.playback_active
+    }
+
+    pub fn playback_direction(&self) -> PlaybackDirection {
+        self.playback_direction
+    }
+
+    /// Sets the path followed by playback; takes effect immediately when already playing.
+    pub fn set_playback_direction(&mut self, direction: PlaybackDirection) {
+        self.playback_direction = direction;
+    }
+
+    /// Returns the color newly painted tiles are modulated with.
+    pub fn brush_tint(&self) -> Color {
+        self.brush_tint
+    }
+
+    /// Sets the color newly painted tiles are modulated with (`WHITE` = untinted).
+    pub fn set_brush_tint(&mut self, tint: Color) {
+        self.brush_tint = tint;
+    }
+
+    /// Returns the render priority given to newly painted tiles.
+    pub fn brush_z_priority(&self) -> i32 {
+        self.brush_z_priority
+    }
+
+    /// Sets the render priority given to newly painted tiles (higher draws on top).
+    pub fn set_brush_z_priority(&mut self, z_priority: i32) {
+        self.brush_z_priority = z_priority;
+    }
+
+    /// Returns the orientation given to newly painted tiles.
+    pub fn brush_transform(&self) -> TileTransform {
+        self.brush_transform
+    }
+
+    /// Sets the orientation given to newly painted tiles.
+    pub fn set_brush_transform(&mut self, transform: TileTransform) {
+        self.brush_transform = transform;
+    }
+
+    pub fn grid_style(&self) -> GridStyle {
+        self.grid_style
+    }
+
+    pub fn set_grid_style(&mut self, style: GridStyle) {
+        self.grid_style = style;
+    }
+
+    /// Toggles the per-tile outline overlay.
+    pub fn toggle_tile_outlines(&mut self) {
+        self.show_tile_outlines = !self.show_tile_outlines;
+    }
+
+    /// Draws a one screen-pixel outline around every cell painted on a shown layer.
+    pub fn draw_tile_outlines(&self, region: Selection) {
+        let thickness = self
+            .camera_controller
+            .world_units_per_pixel();
+        let shown: Vec<&Layer> = self
+            .layers
+            .iter()
+            .filter(|layer| self.is_shown(layer))
+            .collect();
+        for idx in self.region_indices(region) {
+            if shown
+                .iter()
+                .any(|layer| layer.tiles[idx].is_some())
+            {
+                let cell =
+                    Selection::new(idx % self.map_width_tiles, idx / self.map_width_tiles, 1, 1);
+                self.outline_block(cell, thickness, self.tile_outline_color);
+            }
+        }
+    }
+
+    /// Returns the `(x, y)` tile coordinates currently under the mouse cursor.
+    pub fn hovered_tile(&self) -> Option<(usize, usize)> {
+        self.projection.cell_at(
+            self.cursor_world_pos().into(),
+            self.dimensions(),
+            self.tile_dimensions.into(),
+        )
+    }
+
+    /// Paints a tile slot with the sprite, replacing any previous texture.
+    ///
+    /// Repainting a slot with the tile it already holds is a no-op, so holding the mouse
+    /// over a single cell does not retrigger the paint sound every frame. Within a stroke,
+    /// placements closer together than the paint cooldown are skipped.
+    pub fn paint_tile(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) {
+        if self
+            .tile_index(tile_x, tile_y)
+            .is_none()
+            || !self.is_editable(tile_x, tile_y)
+            || self.paint_cooling_down()
+        {
+            return;
+        }
+        self.last_paint_world_pos = Some(self.cursor_world_pos());
+        if self.place_tile(tile_x, tile_y, sprite) {
+            self.play_paint_sound();
+        }
+    }
+
+    /// Paints like [`Map::paint_tile`] during a drag, also covering the cells the cursor
+    /// skipped since the previous placement of the stroke, so fast drags leave no gaps.
+    ///
+    /// A paint cooldown of a tile or more asks for spaced placements, so those strokes are
+    /// not filled in.
+    pub fn paint_stroke(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) {
+        if self.paint_cooldown_tiles >= 1.0 {
+            self.paint_tile(tile_x, tile_y, sprite);
+            return;
+        }
+        if self.paint_cooling_down() {
+            return;
+        }
+        self.last_paint_world_pos = Some(self.cursor_world_pos());
+        let mut painted = false;
+        for (x, y) in self.drag_segment(tile_x, tile_y) {
+            painted |= self.place_tile(x, y, sprite);
+        }
+        if painted {
+            self.play_paint_sound();
+        }
+    }
+
+    /// Erases like [`Map::erase_tile`] during a drag, also covering the cells the cursor
+    /// skipped since the previous frame.
+    pub fn erase_stroke(&mut self, tile_x: usize, tile_y: usize) {
+        for (x, y) in self.drag_segment(tile_x, tile_y) {
+            self.erase_tile(x, y, self.apply_to);
+        }
+    }
+
+    /// Cells from the previous drag cell (excluded) to `(tile_x, tile_y)` (included), which
+    /// becomes the new drag cell.
+    fn drag_segment(&mut self, tile_x: usize, tile_y: usize) -> Vec<(usize, usize)> {
+        let cells = match self.drag_cell {
+            Some(previous) if previous != (tile_x, tile_y) => {
+                let mut cells = line_cells(previous, (tile_x, tile_y));
+                cells.remove(0);
+                cells
+            }
+            _ => vec![(tile_x, tile_y)],
+        };
+        self.drag_cell = Some((tile_x, tile_y));
+        cells
+    }
+
+    /// Puts the brush tile on an editable cell, returning `false` when the cell already holds
+    /// exactly that tile or cannot be painted.
+    fn place_tile(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) -> bool {
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return false;
+        };
+        if !self.is_editable(tile_x, tile_y)
+            || self.tiles()[index]
+                .as_ref()
+                .is_some_and(|painted| {
+                    painted.tile_id == sprite.id
+                        && painted.tint == self.brush_tint
+                        && painted.z_priority == self.brush_z_priority
+                        && painted.transform == self.brush_transform
+                })
+        {
+            return false;
+        }
+        self.set_cell(index, Some(self.brush_tile(sprite)));
+        self.note_painted(&sprite.id);
+        true
+    }
+
+    /// Paints a block of tiles, given as `(dx, dy, sprite)` offsets, with its top-left corner
+    /// on `(tile_x, tile_y)` as a single undo step.
+    ///
+    /// Cells falling outside the map or outside the editable area are skipped.
+    pub fn paint_stamp(
+        &mut self, tile_x: usize, tile_y: usize, tiles: &[(usize, usize, &TileSprite)],
+    ) {
+        let mut painted = false;
+        for &(dx, dy, sprite) in tiles {
+            let (x, y) = (tile_x + dx, tile_y + dy);
+            if let Some(index) = self.tile_index(x, y)
+                && self.is_editable(x, y)
+            {
+                self.set_cell(index, Some(self.brush_tile(sprite)));
+                self.note_painted(&sprite.id);
+                painted = true;
+            }
+        }
+        self.history.commit(EditKind::Stamp);
+        if painted {
+            self.play_paint_sound();
+        }
+    }
+
+    /// Returns the id of the tile painted at `(tile_x, tile_y)` on the active layer, if any.
+    pub fn tile_id(&self, tile_x: usize, tile_y: usize) -> Option<&str> {
+        self.tiles()[self.tile_index(tile_x, tile_y)?]
+            .as_ref()
+            .map(|painted| painted.tile_id.as_str())
+    }
+
+    /// Returns the id of the topmost visible tile painted at `(tile_x, tile_y)`, if any.
+    pub fn tile_at(&self, tile_x: usize, tile_y: usize) -> Option<&str> {
+        let index = self.tile_index(tile_x, tile_y)?;
+        self.layers
+            .iter()
+            .rev()
+            .filter(|layer| self.is_shown(layer))
+            .find_map(|layer| layer.tiles[index].as_ref())
+            .map(|painted| painted.tile_id.as_str())
+    }
+
+    /// Name of every layer with a tile painted at `(tile_x, tile_y)` and that tile's id,
+    /// top layer first.
+    pub fn tile_ids_at(&self, tile_x: usize, tile_y: usize) -> Vec<(&str, &str)> {
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return Vec::new();
+        };
+        self.layers
+            .iter()
+            .rev()
+            .filter_map(|layer| {
+                layer.tiles[index]
+                    .as_ref()
+                    .map(|painted| (layer.name.as_str(), painted.tile_id.as_str()))
+            })
+            .collect()
+    }
+
+    /// Returns the metadata of the topmost visible tile painted at `(tile_x, tile_y)`, if any,
+    /// including the properties of its tile type in `catalog`.
+    pub fn get_full_tile_info(
+        &self, tile_x: usize, tile_y: usize, catalog: &AssetCatalog,
+    ) -> Option<TileInfo> {
+        let index = self.tile_index(tile_x, tile_y)?;
+        let (layer, painted) = self
+            .layers
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, layer)| self.is_shown(layer))
+            .find_map(|(layer_index, layer)| {
+                layer.tiles[index]
+                    .as_ref()
+                    .map(|painted| (layer_index, painted))
+            })?;
+        Some(TileInfo {
+            tile_id: painted.tile_id.clone(),
+            display_name: painted.display_name.clone(),
+            layer,
+            position: (tile_x, tile_y),
+            tint: painted.tint,
+            alpha: painted.tint.a,
+            z_priority: painted.z_priority,
+            transform: painted.transform,
+            walkable: self.is_walkable(tile_x, tile_y),
+            properties: painted.properties.clone(),
+            metadata: catalog
+                .sprite_by_id(&painted.tile_id)
+                .map(|sprite| sprite.metadata.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Counts how many cells, across every layer, hold each tile id.
+    pub fn tile_usage_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for painted in self
+            .layers
+            .iter()
+            .flat_map(|layer| layer.tiles.iter().flatten())
+        {
+            *counts
+                .entry(painted.tile_id.clone())
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Clears a tile slot on the layers of `apply_to`.
+    pub fn erase_tile(&mut self, tile_x: usize, tile_y: usize, apply_to: ApplyTo) {
+        let Some(index) = self
+            .tile_index(tile_x, tile_y)
+            .filter(|_| self.in_selection(tile_x, tile_y))
+        else {
+            return;
+        };
+        for layer in self.target_layers(apply_to) {
+            if self.layers[layer].tiles[index].is_some() {
+                self.set_layer_cell(layer, index, None);
+            }
+        }
+    }
+
+    /// Clears every selected cell of the active layer, returning how many tiles were removed.
+    pub fn erase_selection(&mut self) -> usize {
+        if !self.active_layer_editable() {
+            return 0;
+        }
+        let mut erased = 0;
+        for index in 0..self.tiles().len() {
+            let (tile_x, tile_y) = (index % self.map_width_tiles, index / self.map_width_tiles);
+            if self.is_selected(tile_x, tile_y) && self.tiles()[index].is_some() {
+                self.set_cell(index, None);
+                erased += 1;
+            }
+        }
+        self.history
+            .commit(EditKind::EraseSelection);
+        erased
+    }
+
+    pub fn fill_scope(&self) -> FillScope {
+        self.fill_scope
+    }
+
+    pub fn set_fill_scope(&mut self, fill_scope: FillScope) {
+        self.fill_scope = fill_scope;
+    }
+
+    pub fn apply_to(&self) -> ApplyTo {
+        self.apply_to
+    }
+
+    pub fn set_apply_to(&mut self, apply_to: ApplyTo) {
+        self.apply_to = apply_to;
+    }
+
+    /// Applies the fill tool at `(tile_x, tile_y)` according to the current [`FillScope`],
+    /// on the layers of the current [`ApplyTo`].
+    pub fn fill(&mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite) {
+        match self.fill_scope {
+            FillScope::Contiguous => self.flood_fill(tile_x, tile_y, sprite, self.apply_to),
+            FillScope::Global => self.replace_matching(tile_x, tile_y, sprite, self.apply_to),
+        }
+    }
+
+    /// Paints every cell of each layer of `apply_to` holding the same tile as
+    /// `(tile_x, tile_y)` on that layer.
+    fn replace_matching(
+        &mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite, apply_to: ApplyTo,
+    ) {
+        let Some(start) = self.tile_index(tile_x, tile_y) else {
+            return;
+        };
+
+        let replacement = self.brush_tile(sprite);
+        let mut painted = false;
+        for layer in self.target_layers(apply_to) {
+            let target = self.layers[layer].tiles[start]
+                .as_ref()
+                .map(|painted| painted.tile_id.clone());
+            if target.as_deref() == Some(sprite.id.as_str()) {
+                continue;
+            }
+            for index in 0..self.layers[layer].tiles.len() {
+                let (x, y) = (index % self.map_width_tiles, index / self.map_width_tiles);
+                let tile_id = self.layers[layer].tiles[index]
+                    .as_ref()
+                    .map(|painted| painted.tile_id.as_str());
+                if tile_id == target.as_deref() && self.in_selection(x, y) {
+                    self.set_layer_cell(layer, index, Some(replacement.clone()));
+                }
+            }
+            painted = true;
+        }
+        if !painted {
+            return;
+        }
+        self.note_painted(&sprite.id);
+        self.history
+            .commit(EditKind::ReplaceTiles);
+        self.play_paint_sound();
+    }
+
+    /// Repaints every cell of `scope` holding the tile `from` with `to`, as one undo step.
+    /// The cells keep their tint, z-priority, orientation and custom properties.
+    ///
+    /// The active layer scopes search the layers of `apply_to`, while
+    /// [`ReplaceScope::AllLayers`] searches at least every visible, unlocked layer. Object
+    /// layers are skipped; returns the number of replaced cells.
+    pub fn replace_tiles(
+        &mut self, from: &str, to: &TileSprite, scope: ReplaceScope, apply_to: ApplyTo,
+    ) -> usize {
+        if from == to.id {
+            return 0;
+        }
+        let apply_to = match (scope, apply_to) {
+            (ReplaceScope::AllLayers, ApplyTo::ActiveLayer) => ApplyTo::AllUnlockedLayers,
+            (_, apply_to) => apply_to,
+        };
+        let layers = self.target_layers(apply_to);
+        self.commit_stroke();
+        let (texture, source) = to.atlas_region();
+        let mut replaced = 0;
+        for layer in layers {
+            for index in 0..self.layers[layer].tiles.len() {
+                let (x, y) = (index % self.map_width_tiles, index / self.map_width_tiles);
+                if scope == ReplaceScope::Selection && !self.is_selected(x, y) {
+                    continue;
+                }
+                let Some(mut painted) = self.layers[layer].tiles[index]
+                    .clone()
+                    .filter(|painted| painted.tile_id == from)
+                else {
+                    continue;
+                };
+                painted.texture = texture.clone();
+                painted.source = source;
+                painted.tile_id = to.id.clone();
+                painted.display_name = to.name.clone();
+                painted.animation = to.animation_mode.clone();
+                self.set_layer_cell(layer, index, Some(painted));
+                replaced += 1;
+            }
+        }
+        if replaced > 0 {
+            self.note_painted(&to.id);
+        }
+        self.history
+            .commit(EditKind::ReplaceTiles);
+        replaced
+    }
+
+    /// Paints every cell connected (4-way) to `(tile_x, tile_y)` that holds the same tile,
+    /// on each layer of `apply_to`, as one undo step. Each layer fills its own region.
+    ///
+    /// When a selection exists the fill stays inside it.
+    pub fn flood_fill(
+        &mut self, tile_x: usize, tile_y: usize, sprite: &TileSprite, apply_to: ApplyTo,
+    ) {
+        let Some(start) = self.tile_index(tile_x, tile_y) else {
+            return;
+        };
+        if !self.in_selection(tile_x, tile_y) {
+            return;
+        }
+
+        let replacement = self.brush_tile(sprite);
+        let mut painted = false;
+        for layer in self.target_layers(apply_to) {
+            if self.layers[layer].tiles[start]
+                .as_ref()
+                .is_some_and(|painted| painted.tile_id == sprite.id)
+            {
+                continue;
+            }
+            let cells =
+                self.contiguous_cells(layer, tile_x, tile_y, |map, x, y| map.in_selection(x, y));
+            for (x, y) in cells {
+                let index = y * self.map_width_tiles + x;
+                self.set_layer_cell(layer, index, Some(replacement.clone()));
+            }
+            painted = true;
+        }
+        if !painted {
+            return;
+        }
+        self.note_painted(&sprite.id);
+        self.history.commit(EditKind::Fill);
+        self.play_paint_sound();
+    }
+
+    /// Replaces the selection with the painted region connected (4-way) to `(tile_x, tile_y)`
+    /// that holds the same tile. Clicking an empty cell leaves the selection untouched.
+    pub fn smart_select(&mut self, tile_x: usize, tile_y: usize) {
+        let Some(index) = self.tile_index(tile_x, tile_y) else {
+            return;
+        };
+        if self.tiles()[index].is_none() {
+            return;
+        }
+        self.selection = None;
+        self.multi_selection_cells = self
+            .contiguous_cells(self.active_layer, tile_x, tile_y, |_, _, _| true)
+            .into_iter()
+            .collect();
+    }
+
+    pub fn wand_scope(&self) -> FillScope {
+        self.wand_scope
+    }
+
+    pub fn set_wand_scope(&mut self, wand_scope: FillScope) {
+        self.wand_scope = wand_scope;
+    }
+
+    /// Selects the cells of the active layer holding the same tile as `(tile_x, tile_y)`:
+    /// the region connected to it, or every such cell with [`FillScope::Global`]. With `add`
+    /// they join the current selection instead of replacing it. Clicking an empty cell leaves
+    /// the selection untouched.
+    pub fn magic_wand_select(&mut self, tile_x: usize, tile_y: usize, add: bool) {
+        let Some(id) = self
+            .tile_at_active_layer(tile_x, tile_y)
+            .map(str::to_string)
+        else {
+            return;
+        };
+        let cells = match self.wand_scope {
+            FillScope::Contiguous => {
+                self.contiguous_cells(self.active_layer, tile_x, tile_y, |_, _, _| true)
+            }
+            FillScope::Global => self.cells_holding(&HashSet::from([id])),
+        };
+        if !add {
+            self.clear_selection();
+        }
+        self.select_cells(cells);
+    }
+
+    /// Adds every cell of the active layer holding one of the selected tiles to the
+    /// selection, returning the number of selected cells.
+    pub fn select_same(&mut self) -> usize {
+        let ids: HashSet<String> =
+            Selection::new(0, 0, self.map_width_tiles, self.map_height_tiles)
+                .cells()
+                .filter(|&(tile_x, tile_y)| self.is_selected(tile_x, tile_y))
+                .filter_map(|(tile_x, tile_y)| self.tile_at_active_layer(tile_x, tile_y))
+                .map(str::to_string)
+                .collect();
+        let cells = self.cells_holding(&ids);
+        self.select_cells(cells);
+        self.multi_selection_cells.len()
+    }
+
+    /// Id of the tile painted at `(tile_x, tile_y)` on the active layer.
+    fn tile_at_active_layer(&self, tile_x: usize, tile_y: usize) -> Option<&str> {
+        let index = self.tile_index(tile_x, tile_y)?;
+        self.tiles()[index]
+            .as_ref()
+            .map(|painted| painted.tile_id.as_str())
+    }
+
+    /// Cells of the active layer holding one of the tiles `ids`.
+    fn cells_holding(&self, ids: &HashSet<String>) -> Vec<(usize, usize)> {
+        self.tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| {
+                tile.as_ref()
+                    .is_some_and(|painted| ids.contains(&painted.tile_id))
+            })
+            .map(|(index, _)| (index % self.map_width_tiles, index / self.map_width_tiles))
+            .collect()
+    }
+
+    /// Adds `cells` to the selection, folding a rectangular selection into the cell set.
+    fn select_cells(&mut self, cells: Vec<(usize, usize)>) {
+        if let Some(selection) = self.selection.take() {
+            self.multi_selection_cells
+                .extend(selection.cells());
+        }
+        self.multi_selection_cells.extend(cells);
+    }
+
+    /// Replaces the selection with the single cell `(tile_x, tile_y)`.
+    pub fn select_cell(&mut self, tile_x: usize, tile_y: usize) {
+        if self
+            .tile_index(tile_x, tile_y)
+            .is_some()
+        {
+            self.selection = None;
+            self.multi_selection_cells = HashSet::from([(tile_x, tile_y)]);
+        }
+    }
+
+    /// Collects the cells of `layer` connected (4-way) to `(tile_x, tile_y)` holding the same
+    /// tile id, only crossing cells accepted by `include`.
+    fn contiguous_cells(
+        &self, layer: usize, tile_x: usize, tile_y: usize,
+        include: impl Fn(&Self, usize, usize) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let Some(start) = self.tile_index(tile_x, tile_y) else {
+            return Vec::new();
+        };
+        let tiles = &self.layers[layer].tiles;
+        let target = tiles[start]
+            .as_ref()
+            .map(|painted| painted.tile_id.as_str());
+
+        let mut cells = Vec::new();
+        let mut visited = vec![false; tiles.len()];
+        let mut queue = VecDeque::from([(tile_x, tile_y)]);
+        visited[start] = true;
+
+        while let Some((x, y)) = queue.pop_front() {
+            cells.push((x, y));
+            for (nx, ny) in self.neighbors(x, y) {
+                let neighbor = ny * self.map_width_tiles + nx;
+                let neighbor_id = tiles[neighbor]
+                    .as_ref()
+                    .map(|painted| painted.tile_id.as_str());
+                if !visited[neighbor] && neighbor_id == target && include(self, nx, ny) {
+                    visited[neighbor] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Paints `sprite` on every empty cell of `layer`, as one undo step. `region` limits the
+    /// fill to a sub-rectangle, clipped to the map; without one the fill covers the
+    /// selected cells, or the whole map when nothing is selected.
+    ///
+    /// Returns the number of cells that were filled.
+    pub fn auto_fill_empty(
+        &mut self, sprite: &TileSprite, layer: usize, region: Option<Selection>,
+    ) -> usize {
+        if !self.layer_editable(layer) {
+            return 0;
+        }
+        let Some(bounds) = region
+            .unwrap_or(Selection::new(0, 0, self.map_width_tiles, self.map_height_tiles))
+            .clip(self.map_width_tiles, self.map_height_tiles)
+        else {
+            return 0;
+        };
+        let replacement = self.brush_tile(sprite);
+        let mut filled = 0;
+
+        for (tile_x, tile_y) in bounds.cells() {
+            if region.is_none() && !self.in_selection(tile_x, tile_y) {
+                continue;
+            }
+            if let Some(index) = self.tile_index(tile_x, tile_y)
+                && self.layers[layer].tiles[index].is_none()
+            {
+                self.set_layer_cell(layer, index, Some(replacement.clone()));
+                filled += 1;
+            }
+        }
+        self.history.commit(EditKind::FillEmpty);
+
+        if filled > 0 {
+            self.play_paint_sound();
+        }
+        filled
+    }
+
+    /// Returns `true` when the cell is part of the rectangular or multi-cell selection.
+    pub fn is_selected(&self, tile_x: usize, tile_y: usize) -> bool {
+        self.selection
+            .is_some_and(|selection| selection.contains(tile_x, tile_y))
+            || self
+                .multi_selection_cells
+                .contains(&(tile_x, tile_y))
+    }
+
+    fn has_selection(&self) -> bool {
+        self.selection.is_some() || !self.multi_selection_cells.is_empty()
+    }
+
+    /// Returns `true` when edits may touch the cell: the active layer must be visible and
+    /// unlocked, and with a selection only selected cells are editable.
+    fn is_editable(&self, tile_x: usize, tile_y: usize) -> bool {
+        self.active_layer_editable() && self.in_selection(tile_x, tile_y)
+    }
+
+    /// Returns `true` when there is no selection or the cell is selected.
+    fn in_selection(&self, tile_x: usize, tile_y: usize) -> bool {
+        !self.has_selection() || self.is_selected(tile_x, tile_y)
+    }
+
+    /// Indices of the layers `apply_to` lets tile tools edit, bottom first.
+    fn target_layers(&self, apply_to: ApplyTo) -> Vec<usize> {
+        match apply_to {
+            ApplyTo::ActiveLayer => Some(self.active_layer)
+                .filter(|&layer| self.layer_editable(layer))
+                .into_iter()
+                .collect(),
+            ApplyTo::AllLayers => (0..self.layers.len())
+                .filter(|&layer| self.layers[layer].kind == LayerKind::Tiles)
+                .collect(),
+            ApplyTo::AllUnlockedLayers => (0..self.layers.len())
+                .filter(|&layer| self.layer_editable(layer))
+                .collect(),
+        }
+    }
+
+    /// Returns `true` when tile tools may edit the active layer.
+    fn active_layer_editable(&self) -> bool {
+        self.layer_editable(self.active_layer)
+    }
+
+    /// Returns `true` when tile tools may edit the layer at `index`.
+    fn layer_editable(&self, index: usize) -> bool {
+        self.layers
+            .get(index)
+            .is_some_and(|layer| self.is_writable(layer) && layer.kind == LayerKind::Tiles)
+    }
+
+    /// Returns `true` when the object tool may edit the active layer.
+    fn object_layer_editable(&self) -> bool {
+        let layer = &self.layers[self.active_layer];
+        self.is_writable(layer) && layer.is_object_layer()
+    }
+
+    /// Returns `true` when the layer and every group holding it are visible.
+    fn is_shown(&self, layer: &Layer) -> bool {
+        layer.visible
+            && self
+                .groups_of(layer)
+                .all(|group| group.visible)
+    }
+
+    /// Returns `true` when the layer is shown and neither it nor any group holding it is
+    /// locked.
+    fn is_writable(&self, layer: &Layer) -> bool {
+        self.is_shown(layer)
+            && !layer.locked
+            && self
+                .groups_of(layer)
+                .all(|group| !group.locked)
+    }
+
+    /// Product of the opacities of every group holding the layer.
+    fn group_opacity(&self, layer: &Layer) -> f32 {
+        self.groups_of(layer)
+            .map(|group| group.opacity)
+            .product()
+    }
+
+    /// Groups holding the layer, innermost first.
+    fn groups_of(&self, layer: &Layer) -> impl Iterator<Item = &LayerGroup> {
+        self.group_chain(layer.group)
+            .into_iter()
+            .filter_map(|group| self.groups.get(group))
+    }
+
+    /// The selected cell when exactly one cell is selected.
+    pub fn selected_cell(&self) -> Option<(usize, usize)> {
+        match (self.selection, self.multi_selection_cells.len()) {
+            (Some(selection), 0) if selection.width == 1 && selection.height == 1 => {
+                Some((selection.x, selection.y))
+            }
+            (None, 1) => self
+                .multi_selection_cells
+                .iter()
+                .next()
+                .copied(),
+            _ => None,
+        }
+    }
+
+    /// Replaces the selection with every cell of the map that is not currently selected.
+    pub fn invert_selection(&mut self) {
+        let inverted = Selection::new(0, 0, self.map_width_tiles, self.map_height_tiles)
+            .cells()
+            .filter(|&(tile_x, tile_y)| !self.is_selected(tile_x, tile_y))
+            .collect();
+        self.selection = None;
+        self.multi_selection_cells = inverted;
+    }
+
+    /// Deselects every cell.
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+        self.multi_selection_cells.clear();
+    }
+
+    /// Returns the in-bounds orthogonal neighbors of a cell.
+    fn neighbors(&self, tile_x: usize, tile_y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.map_width_tiles, self.map_height_tiles);
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(move |(dx, dy): (isize, isize)| {
+                let x = tile_x.checked_add_signed(dx)?;
+                let y = tile_y.checked_add_signed(dy)?;
+                (x < width && y < height).then_some((x, y))
+            })
+    }
+
+    /// Remembers that the brush painted `tile_id`, for the palette's recently used tiles.
+    fn note_painted(&mut self, tile_id: &str) {
+        if !self
+            .painted_ids
+            .iter()
+            .any(|id| id == tile_id)
+        {
+            self.painted_ids
+                .push(tile_id.to_string());
+        }
+    }
+
+    /// Takes the ids of the brush tiles painted since the previous call, in painting order.
+    pub fn take_painted_ids(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.painted_ids)
+    }
+
+    fn brush_tile(&self, sprite: &TileSprite) -> PaintedTile {
+        let (texture, source) = sprite.atlas_region();
+        PaintedTile {
+            texture: texture.clone(),
+            source,
+            tile_id: sprite.id.clone(),
+            display_name: sprite.name.clone(),
+            tint: self.brush_tint,
+            z_priority: self.brush_z_priority,
+            transform: self.brush_transform,
+            animation: sprite.animation_mode.clone(),
+            properties: Properties::new(),
+        }
+    }
+
+    fn play_paint_sound(&self) {
+        if let Some(sound) = &self.on_paint_sound {
+            play_sound(
+                sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume: Self::PAINT_SOUND_VOLUME,
+                },
+            );
+        }
+    }
+
+    fn tile_index(&self, tile_x: usize, tile_y: usize) -> Option<usize> {
+        if tile_x >= self.map_width_tiles || tile_y >= self.map_height_tiles {
+            return None;
+        }
+
+        Some(tile_y * self.map_width_tiles + tile_x)
+    }
+
+    /// Size of the area covered by the map, in world units.
+    pub fn grid_size(&self) -> Vec2 {
+        self.projection
+            .world_size(self.dimensions(), self.tile_dimensions.into())
+            .into()
+    }
+
+    /// Writes the current map state to disk as JSON, RON or binary.
+    ///
+    /// The encoding is picked from the file extension through the [`FormatRegistry`]. On
+    /// success `path` becomes the map's file path and the map is no longer dirty.
+    pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), io::Error> {
+        let path = path.as_ref();
+        self.write_snapshot(path)?;
+        self.file_path = Some(path.to_path_buf());
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Writes the map like [`Map::save_to_file`] but keeps its file path and dirty state,
+    /// e.g. for autosaves.
+    pub fn write_snapshot(&self, path: &Path) -> Result<(), io::Error> {
+        self.export().write(path, &self.formats)
+    }
+
+    /// Marks a map restored from an autosave as an unsaved edit of `file_path`.
+    pub fn mark_recovered(&mut self, file_path: Option<PathBuf>) {
+        self.file_path = file_path;
+        self.mark_edited();
+    }
+
+    /// Extension suggested for new map files.
+    pub fn default_file_extension() -> &'static str {
+        "fgt"
+    }
+
+    /// Returns the format used for `path`, based on its extension.
+    pub fn format_for(&self, path: &Path) -> Option<MapFormat> {
+        self.formats.format_for(path)
+    }
+
+    /// Associates an extra file extension with a map format.
+    pub fn register_file_extension(&mut self, extension: &str, format: MapFormat) {
+        self.formats.register(extension, format);
+    }
+
+    /// Selects how tile arrays are laid out when the map is saved as JSON.
+    pub fn set_tile_encoding(&mut self, encoding: TileEncoding) {
+        self.tile_encoding = encoding;
+    }
+
+    /// Loads map data from disk and rebuilds the internal tile buffers.
+    ///
+    /// The decoder is picked from the file extension through the [`FormatRegistry`]; Tiled
+    /// `.tmx`/`.tmj` maps are imported with [`Map::import_tiled`] and keep no file path, so
+    /// saving asks for a native map file instead of overwriting them.
+    pub fn load_from_file<P: AsRef<Path>>(
+        &mut self, path: P, catalog: &AssetCatalog,
+    ) -> Result<(), MapLoadError> {
+        let path = path.as_ref();
+        if tiled::is_tiled_file(path) {
+            return self.import_tiled(path, catalog);
+        }
+        let data = MapData::read(path, &self.formats)?;
+        self.load_data(data, catalog)?;
+        self.file_path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Replaces the map with `data`, resolving its tile ids against the catalog.
+    ///
+    /// Group layers become [`LayerGroup`]s nested like in the file. Like
+    /// [`Map::replace_layers`], the undo history is cleared and the map forgets its file
+    /// path.
+    pub fn load_data(
+        &mut self, data: MapData, catalog: &AssetCatalog,
+    ) -> Result<(), MapLoadError> {
+        data.validate()?;
+        let tile_size = Size {
+            width: data.tile_width,
+            height: data.tile_height,
+        };
+        let cell_count = data.width * data.height;
+        let mut layers = Vec::new();
+        let mut groups = Vec::new();
+        load_layer_tree(data.layers, None, cell_count, catalog, &mut layers, &mut groups)?;
+        self.replace_layers((data.width, data.height), tile_size, layers)?;
+        self.groups = groups;
+        self.remove_empty_groups();
+        for (collider, is_walkable) in self
+            .colliders
+            .iter_mut()
+            .zip(data.collision)
+        {
+            collider.is_walkable = is_walkable;
+        }
+        self.properties = data.properties;
+        self.projection = data.projection;
+        self.reference_texture = data
+            .reference
+            .as_ref()
+            .and_then(|reference| {
+                load_layer_image(&reference.path)
+                    .inspect_err(|err| eprintln!("[map] Could not load reference image: {err}"))
+                    .ok()
+            });
+        self.reference = data.reference;
+        self.camera_controller
+            .set_center(self.grid_size());
+        Ok(())
+    }
+
+    /// Replaces the whole layer stack, e.g. with layers read by an importer.
+    ///
+    /// Every layer must hold `map_size.0 * map_size.1` cells and is left ungrouped. The top
+    /// layer becomes active, the undo history is cleared and the map forgets its file path.
+    pub fn replace_layers(
+        &mut self, map_size: (usize, usize), tile_size: Size, layers: Vec<Layer>,
+    ) -> Result<(), MapLoadError> {
+        let cell_count = map_size.0 * map_size.1;
+        if let Some(layer) = layers
+            .iter()
+            .find(|layer| layer.tiles.len() != cell_count)
+        {
+            return Err(MapLoadError::TileCountMismatch {
+                expected: cell_count,
+                found: layer.tiles.len(),
+            });
+        }
+        let layers = if layers.is_empty() {
+            vec![Layer::new("Layer 1", cell_count)]
+        } else {
+            layers
+        };
+
+        self.map_width_tiles = map_size.0;
+        self.map_height_tiles = map_size.1;
+        self.tile_dimensions = tile_size;
+        self.colliders = vec![Collider { is_walkable: true }; cell_count];
+        self.active_layer = layers.len() - 1;
+        self.layers = layers;
+        self.groups = Vec::new();
+        self.properties = Properties::new();
+        self.reference = None;
+        self.reference_texture = None;
+        self.file_path = None;
+        self.dirty = false;
+        self.revision += 1;
+
+        self.history.clear();
+        self.clear_selection();
+        self.selected_object = None;
+        self.shape_drag = None;
+        self.paste_origin = None;
+        self.camera_controller
+            .set_center(self.grid_size());
+
+        Ok(())
+    }
+
+    /// Renames every painted tile, on every layer, whose id appears in `migration` (old id → new id).
+    ///
+    /// Each renamed cell takes the texture, name and animation of its new id in `catalog`,
+    /// keeping its tint, z-priority, orientation and properties. Returns the number of tiles
+    /// that were rewritten and the new ids missing from `catalog`, whose cells are left
+    /// unchanged.
+    pub fn apply_id_migration(
+        &mut self, migration: &HashMap<String, String>, catalog: &AssetCatalog,
+    ) -> (usize, BTreeSet<String>) {
+        let mut replaced = 0;
+        let mut missing = BTreeSet::new();
+        for layer in 0..self.layers.len() {
+            for index in 0..self.layers[layer].tiles.len() {
+                let Some(old) = &self.layers[layer].tiles[index] else {
+                    continue;
+                };
+                let Some(new_id) = migration.get(&old.tile_id) else {
+                    continue;
+                };
+                let Some(sprite) = catalog.sprite_by_id(new_id) else {
+                    missing.insert(new_id.clone());
+                    continue;
+                };
+                let painted = PaintedTile {
+                    tile_id: new_id.clone(),
+                    tint: old.tint,
+                    z_priority: old.z_priority,
+                    transform: old.transform,
+                    properties: old.properties.clone(),
+                    ..plain_tile(sprite)
+                };
+                self.set_layer_cell(layer, index, Some(painted));
+                replaced += 1;
+            }
+        }
+        self.history
+            .commit(EditKind::MigrateIds);
+        (replaced, missing)
+    }
+
+    /// Replaces every cell of the active tile layer with the tiles `ids`, row by row, as one
+    /// undo step; the new tiles have no tint, z-priority or orientation.
+    ///
+    /// Returns `false` without changes while the active layer is hidden, locked or not a
+    /// tile layer. Fails without changes when `ids` does not cover the map exactly or names a
+    /// tile missing from the catalog.
+    pub fn set_active_layer_tiles(
+        &mut self, ids: &[Option<String>], catalog: &AssetCatalog,
+    ) -> Result<bool, MapLoadError> {
+        self.set_layer_tiles(self.active_layer, ids, catalog, EditKind::ImportLayer)
+    }
+
+    /// Like [`Map::set_active_layer_tiles`] for the tiles of a procedural generator painted
+    /// on `layer`, recorded as a [`EditKind::Generate`] step.
+    pub fn generate_layer_tiles(
+        &mut self, layer: usize, ids: &[Option<String>], catalog: &AssetCatalog,
+    ) -> Result<bool, MapLoadError> {
+        self.set_layer_tiles(layer, ids, catalog, EditKind::Generate)
+    }
+
+    fn set_layer_tiles(
+        &mut self, layer: usize, ids: &[Option<String>], catalog: &AssetCatalog, kind: EditKind,
+    ) -> Result<bool, MapLoadError> {
+        if ids.len() != self.tiles().len() {
+            return Err(MapLoadError::TileCountMismatch {
+                expected: self.tiles().len(),
+                found: ids.len(),
+            });
+        }
+        if !self.layer_editable(layer) {
+            return Ok(false);
+        }
+        let tiles = ids
+            .iter()
+            .map(|id| {
+                id.as_ref()
+                    .map(|id| {
+                        catalog
+                            .sprite_by_id(id)
+                            .map(plain_tile)
+                            .ok_or_else(|| MapLoadError::UnknownTile(id.clone()))
+                    })
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, MapLoadError>>()?;
+        self.commit_stroke();
+        for (index, tile) in tiles.into_iter().enumerate() {
+            self.set_layer_cell(layer, index, tile);
+        }
+        self.history.commit(kind);
+        Ok(true)
+    }
+
+    /// Shows the tiles `ids`, row by row, as translucent previews over the map until
+    /// [`Map::clear_generated_preview`]. Ids missing from the catalog are left out.
+    pub fn preview_generated(&mut self, ids: &[Option<String>], catalog: &AssetCatalog) {
+        self.generated_preview = ids
+            .iter()
+            .map(|id| {
+                id.as_deref()
+                    .and_then(|id| catalog.sprite_by_id(id))
+                    .map(plain_tile)
+            })
+            .collect();
+    }
+
+    pub fn clear_generated_preview(&mut self) {
+        self.generated_preview.clear();
+    }
+
+    /// Points every painted tile at the catalog's current sprite with the same id, keeping its
+    /// tint, z-priority and orientation. Called after [`AssetCatalog::hot_reload`].
+    ///
+    /// Tiles whose id no longer exists keep drawing their old texture and are counted in the
+    /// returned value. Undo history is not rewritten, so undoing restores the old textures.
+    pub fn refresh_tiles(&mut self, catalog: &AssetCatalog) -> usize {
+        let mut missing = 0;
+        for layer in &mut self.layers {
+            for painted in layer.tiles.iter_mut().flatten() {
+                let Some(sprite) = catalog.sprite_by_id(&painted.tile_id) else {
+                    missing += 1;
+                    continue;
+                };
+                let (texture, source) = sprite.atlas_region();
+                painted.texture = texture.clone();
+                painted.source = source;
+                painted.display_name = sprite.name.clone();
+                painted.animation = sprite.animation_mode.clone();
+            }
+        }
+        self.tile_sequence.refresh(catalog);
+        self.random_brush.refresh(catalog);
+        self.revision += 1;
+        missing
+    }
+
+    /// Reads an old → new tile id mapping from a JSON object file.
+    pub fn load_id_migration_from_json(path: &Path) -> Result<HashMap<String, String>, io::Error> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Tile usage report of the map against the spritesheets of `catalog`, with the memory
+    /// of the editor's per-tile textures and layer images added to the estimates.
+    pub fn stats(&self, catalog: &AssetCatalog) -> MapStats {
+        let mut stats = MapStats::new(&self.export());
+        stats.add_catalog(catalog.sheets());
+        let texture_bytes =
+            |texture: &Texture2D| texture.width() as usize * texture.height() as usize * 4;
+        let tile_bytes = catalog
+            .categories()
+            .iter()
+            .flat_map(|category| category.tiles())
+            .map(|sprite| texture_bytes(&sprite.texture))
+            .sum();
+        let image_bytes = self
+            .layers
+            .iter()
+            .filter_map(|layer| layer.image.as_ref())
+            .map(texture_bytes)
+            .sum();
+        stats
+            .memory
+            .push(("Tile textures".to_string(), tile_bytes));
+        stats
+            .memory
+            .push(("Layer images".to_string(), image_bytes));
+        stats
+    }
+
+    /// Snapshot of the map as saved to disk, each run of adjacent layers of a group nested
+    /// in a group layer, and nested groups in their parent's group layer.
+    pub fn export(&self) -> MapData {
+        let mut data = MapData::new(
+            self.map_width_tiles,
+            self.map_height_tiles,
+            (self.tile_dimensions.width, self.tile_dimensions.height),
+        );
+        // Group layers being filled, outermost first, with the index of their group.
+        let mut open: Vec<(usize, LayerData)> = Vec::new();
+        let close = |open: &mut Vec<(usize, LayerData)>, top: &mut Vec<LayerData>| {
+            if let Some((_, closed)) = open.pop() {
+                match open.last_mut() {
+                    Some((_, parent)) => parent.layers.push(closed),
+                    None => top.push(closed),
+                }
+            }
+        };
+        for layer in &self.layers {
+            let mut chain = self.group_chain(layer.group);
+            chain.reverse();
+            let shared = open
+                .iter()
+                .zip(&chain)
+                .take_while(|((open, _), group)| open == *group)
+                .count();
+            while open.len() > shared {
+                close(&mut open, &mut data.layers);
+            }
+            for &index in &chain[shared..] {
+                let group = &self.groups[index];
+                let saved = LayerData {
+                    visible: group.visible,
+                    locked: group.locked,
+                    opacity: group.opacity,
+                    collapsed: group.collapsed,
+                    properties: group.properties.clone(),
+                    ..LayerData::group(&group.name, Vec::new())
+                };
+                open.push((index, saved));
+            }
+            match open.last_mut() {
+                Some((_, group)) => group.layers.push(layer.export()),
+                None => data.layers.push(layer.export()),
+            }
+        }
+        while !open.is_empty() {
+            close(&mut open, &mut data.layers);
+        }
+        data.properties = self.properties.clone();
+        data.reference = self.reference.clone();
+        data.projection = self.projection;
+        data.encoding = self.tile_encoding;
+        data.collision = if self
+            .colliders
+            .iter()
+            .all(|collider| collider.is_walkable)
+        {
+            Vec::new()
+        } else {
+            self.colliders
+                .iter()
+                .map(|collider| collider.is_walkable)
+                .collect()
+        };
+        data
+    }
+}
+
+/// Appends the layers of `data` to `layers` and its group layers to `groups`, nested in
+/// the group at `parent`.
+fn load_layer_tree(
+    data: Vec<LayerData>, parent: Option<usize>, cell_count: usize, catalog: &AssetCatalog,
+    layers: &mut Vec<Layer>, groups: &mut Vec<LayerGroup>,
+) -> Result<(), MapLoadError> {
+    for layer in data {
+        if layer.kind != LayerKind::Group {
+            layers.push(Layer {
+                group: parent,
+                ..Layer::from_data(layer, cell_count, catalog)?
+            });
+            continue;
+        }
+        groups.push(LayerGroup {
+            name: layer.name,
+            visible: layer.visible,
+            locked: layer.locked,
+            opacity: layer.opacity,
+            collapsed: layer.collapsed,
+            properties: layer.properties,
+            parent,
+        });
+        let group = Some(groups.len() - 1);
+        load_layer_tree(layer.layers, group, cell_count, catalog, layers, groups)?;
+    }
+    Ok(())
+}
+
+/// Cells covered by a rectangle or line drag.
+fn shape_cells(drag: &ShapeDrag) -> Vec<(usize, usize)> {
+    match drag.mode {
+        PaintMode::Line => line_cells(drag.start, drag.end),
+        _ => Selection::from_corners(drag.start, drag.end)
+            .cells()
+            .collect(),
+    }
+}
+
+/// Untinted, unrotated tile drawing `sprite`.
+fn plain_tile(sprite: &TileSprite) -> PaintedTile {
+    let (texture, source) = sprite.atlas_region();
+    PaintedTile {
+        texture: texture.clone(),
+        source,
+        tile_id: sprite.id.clone(),
+        display_name: sprite.name.clone(),
+        tint: WHITE,
+        z_priority: 0,
+        transform: TileTransform::default(),
+        animation: sprite.animation_mode.clone(),
+        properties: Properties::new(),
+    }
+}
+
+/// Reads and uploads the image of an image layer or of the reference image.
+fn load_layer_image(path: &str) -> Result<Texture2D, io::Error> {
+    let bytes = fs::read(path)?;
+    let image = Image::from_file_with_format(&bytes, None)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {err}")))?;
+    Ok(Texture2D::from_image(&image))
+}
+
+/// Converts a raw dimension into an integral number of tiles.
+fn dimension_to_tiles(value: f32) -> usize {
+    value.max(1.0).round() as usize
+}
+
+/// Drops the alpha of a layer tint for the map file.
+fn color_rgb(color: Color) -> [f32; 3] {
+    [color.r, color.g, color.b]
+}
+
+/// Rebuilds an opaque layer tint read from a map file.
+fn rgb_color([r, g, b]: [f32; 3]) -> Color {
+    Color::new(r, g, b, 1.0)
+}
+
+impl Layer {
+    fn export(&self) -> LayerData {
+        if self.is_object_layer() {
+            return LayerData {
+                visible: self.visible,
+                locked: self.locked,
+                opacity: self.opacity,
+                tint_color: self.tint_color.map(color_rgb),
+                properties: self.properties.clone(),
+                ..LayerData::object_layer(&self.name, self.objects.clone())
+            };
+        }
+        if self.is_image_layer() {
+            return LayerData {
+                visible: self.visible,
+                locked: self.locked,
+                opacity: self.opacity,
+                tint_color: self.tint_color.map(color_rgb),
+                properties: self.properties.clone(),
+                image: self.image_path.clone(),
+                ..LayerData::image_layer(&self.name, "", self.parallax.into())
+            };
+        }
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref()
+                    .map(|painted| painted.tile_id.clone())
+            })
+            .collect();
+
+        let tints: Vec<Option<[f32; 4]>> = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref()
+                    .filter(|painted| painted.tint != WHITE)
+                    .map(|painted| painted.tint.into())
+            })
+            .collect();
+        let tints = if tints.iter().all(Option::is_none) {
+            Vec::new()
+        } else {
+            tints
+        };
+
+        let z_priorities: Vec<i32> = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref()
+                    .map_or(0, |painted| painted.z_priority)
+            })
+            .collect();
+        let z_priorities = if z_priorities.iter().all(|&z| z == 0) {
+            Vec::new()
+        } else {
+            z_priorities
+        };
+
+        let transforms: Vec<TileTransform> = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref()
+                    .map_or(TileTransform::default(), |painted| painted.transform)
+            })
+            .collect();
+        let transforms = if transforms
+            .iter()
+            .all(|transform| transform.is_identity())
+        {
+            Vec::new()
+        } else {
+            transforms
+        };
+
+        let cell_properties: Vec<Properties> = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref()
+                    .map(|painted| painted.properties.clone())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let cell_properties = if cell_properties
+            .iter()
+            .all(Properties::is_empty)
+        {
+            Vec::new()
+        } else {
+            cell_properties
+        };
+
+        LayerData {
+            visible: self.visible,
+            locked: self.locked,
+            opacity: self.opacity,
+            tint_color: self.tint_color.map(color_rgb),
+            tints,
+            z_priorities,
+            transforms,
+            properties: self.properties.clone(),
+            cell_properties,
+            ..LayerData::new(&self.name, tiles)
+        }
+    }
+
+    /// Resolves the saved tile ids of `data` against the catalog. Object and image layers
+    /// get `cell_count` empty cells; an unreadable image is reported and left undrawn.
+    fn from_data(
+        data: LayerData, cell_count: usize, catalog: &AssetCatalog,
+    ) -> Result<Self, MapLoadError> {
+        if data.kind == LayerKind::Objects {
+            return Ok(Layer {
+                visible: data.visible,
+                locked: data.locked,
+                opacity: data.opacity,
+                tint_color: data.tint_color.map(rgb_color),
+                properties: data.properties,
+                objects: data.objects,
+                ..Layer::new_objects(data.name, cell_count)
+            });
+        }
+        if data.kind == LayerKind::Image {
+            let image = data.image.as_deref().and_then(|path| {
+                load_layer_image(path)
+                    .inspect_err(|err| eprintln!("[map] Could not load layer image: {err}"))
+                    .ok()
+            });
+            return Ok(Layer {
+                visible: data.visible,
+                locked: data.locked,
+                opacity: data.opacity,
+                tint_color: data.tint_color.map(rgb_color),
+                properties: data.properties,
+                parallax: data.parallax.into(),
+                image_path: data.image,
+                image,
+                ..Layer::new_image(data.name, cell_count)
+            });
+        }
+        let tiles = data
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(index, maybe_id)| match maybe_id {
+                Some(id) => {
+                    let sprite = catalog
+                        .sprite_by_id(id)
+                        .ok_or_else(|| MapLoadError::UnknownTile(id.clone()))?;
+                    let (texture, source) = sprite.atlas_region();
+                    Ok(Some(PaintedTile {
+                        texture: texture.clone(),
+                        source,
+                        tile_id: sprite.id.clone(),
+                        display_name: sprite.name.clone(),
+                        tint: data
+                            .tint(index)
+                            .map(Color::from)
+                            .unwrap_or(WHITE),
+                        z_priority: data.z_priority(index),
+                        transform: data.transform(index),
+                        animation: sprite.animation_mode.clone(),
+                        properties: data
+                            .cell_properties(index)
+                            .cloned()
+                            .unwrap_or_default(),
+                    }))
+                }
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<_>, MapLoadError>>()?;
+
+        Ok(Layer {
+            visible: data.visible,
+            locked: data.locked,
+            opacity: data.opacity,
+            tint_color: data.tint_color.map(rgb_color),
+            properties: data.properties,
+            tiles,
+            ..Layer::new(data.name, 0)
+        })
+    }
 }
@@ -1,2 +1,7 @@
+pub mod autosave;
+pub mod grid;
+#[allow(clippy::module_inception)]
 pub mod map;
-pub mod tile;
+pub mod tabs;
+
+pub use forgetile_core::map::{format, object, projection, property, reference, stats, tile};
@@ -0,0 +1,102 @@
+use crate::core::map::map::Map;
+use std::path::Path;
+
+/// Maps open side by side, one per tab, with one of them active.
+///
+/// Every map keeps its own camera, history and dirty flag; the tool and brush state follows
+/// the active tab, so switching tabs keeps the current tool.
+pub struct MapTabs {
+    maps: Vec<Map>,
+    active: usize,
+}
+
+impl MapTabs {
+    pub fn new(map: Map) -> Self {
+        Self { maps: vec![map], active: 0 }
+    }
+
+    pub fn maps(&self) -> &[Map] {
+        &self.maps
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Map> {
+        self.maps.iter_mut()
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> &Map {
+        &self.maps[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Map {
+        &mut self.maps[self.active]
+    }
+
+    /// Index of the tab showing the map file at `path`, if any.
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        self.maps
+            .iter()
+            .position(|map| map.file_path() == Some(path))
+    }
+
+    /// Makes tab `index` active; out of range indices are ignored.
+    pub fn select(&mut self, index: usize) {
+        if index == self.active || index >= self.maps.len() {
+            return;
+        }
+        let (previous, next) = pair_mut(&mut self.maps, self.active, index);
+        next.carry_tools_from(previous);
+        self.active = index;
+    }
+
+    /// Activates the next tab, wrapping around after the last one.
+    pub fn select_next(&mut self) {
+        self.select((self.active + 1) % self.maps.len());
+    }
+
+    /// Activates the previous tab, wrapping around before the first one.
+    pub fn select_previous(&mut self) {
+        self.select((self.active + self.maps.len() - 1) % self.maps.len());
+    }
+
+    /// Adds `map` as a new tab after the last one and activates it.
+    pub fn open(&mut self, map: Map) {
+        self.maps.push(map);
+        self.active = self.maps.len() - 1;
+    }
+
+    /// Replaces the map of the active tab.
+    pub fn replace_active(&mut self, map: Map) {
+        self.maps[self.active] = map;
+    }
+
+    /// Closes tab `index`, activating its right neighbour (or the new last tab) when it was
+    /// active. The last remaining tab cannot be closed; returns `false` then.
+    pub fn close(&mut self, index: usize) -> bool {
+        if self.maps.len() < 2 || index >= self.maps.len() {
+            return false;
+        }
+        let closed = self.maps.remove(index);
+        if index < self.active {
+            self.active -= 1;
+        } else if index == self.active {
+            self.active = index.min(self.maps.len() - 1);
+            self.maps[self.active].carry_tools_from(&closed);
+        }
+        true
+    }
+}
+
+/// Mutable references to two distinct elements of `items`.
+fn pair_mut<T>(items: &mut [T], first: usize, second: usize) -> (&mut T, &mut T) {
+    if first < second {
+        let (head, tail) = items.split_at_mut(second);
+        (&mut head[first], &mut tail[0])
+    } else {
+        let (head, tail) = items.split_at_mut(first);
+        (&mut tail[0], &mut head[second])
+    }
+}
@@ -1,3 +1,8 @@
 pub mod assets;
 pub mod camera;
+pub mod config;
+pub mod history;
+pub mod interop;
 pub mod map;
+pub mod project;
+pub mod tools;
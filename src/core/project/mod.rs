@@ -0,0 +1,238 @@
+use crate::core::assets::TextureFilter;
+use crate::core::config::AppConfig;
+use crate::core::map::format::MapFormat;
+use crate::core::map::grid::GridStyle;
+use crate::core::map::tile::Size;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A `.forgetile` project: the spritesheet directory and tileset settings shared by a set of
+/// map files, and the editor preferences used with them.
+///
+/// Paths are stored relative to the project file, so a project folder can be moved or
+/// checked into version control as a whole. Per-spritesheet settings stay in the
+/// `.tileset.json` sidecars inside the assets directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    /// File the project was read from or last written to.
+    #[serde(skip)]
+    path: PathBuf,
+    /// Project format revision the file was written with; see [`Project::VERSION`].
+    pub version: u32,
+    /// Spritesheet directory, relative to the project file.
+    pub assets_root: PathBuf,
+    /// Further spritesheet directories, loaded after `assets_root`; relative to the project
+    /// file when inside its folder.
+    pub extra_asset_dirs: Vec<PathBuf>,
+    /// Size, in pixels, spritesheets are sliced into unless their sidecar sets another one.
+    pub tile_size: [u32; 2],
+    /// Texture filter for tiles whose category has no `category.json` override.
+    pub texture_filter: TextureFilter,
+    /// Map files of the project, relative to the project file.
+    pub maps: Vec<PathBuf>,
+    /// Editor preferences applied while the project is open.
+    pub preferences: ProjectPreferences,
+}
+
+/// The [`AppConfig`] settings a project carries, so everyone working on it saves maps the
+/// same way.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectPreferences {
+    /// Extra map file extensions (without the dot) and the format they are read as.
+    pub map_extensions: BTreeMap<String, MapFormat>,
+    /// Save JSON maps with run-length encoded tile arrays.
+    pub rle_json_tiles: bool,
+    pub grid: GridStyle,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::new(),
+            version: Self::VERSION,
+            assets_root: PathBuf::from("assets"),
+            extra_asset_dirs: Vec::new(),
+            tile_size: [32, 32],
+            texture_filter: TextureFilter::default(),
+            maps: Vec::new(),
+            preferences: ProjectPreferences::default(),
+        }
+    }
+}
+
+impl Project {
+    /// Extension of project files.
+    pub const EXTENSION: &'static str = "forgetile";
+    /// Project format revision written by this build.
+    pub const VERSION: u32 = 1;
+
+    /// Creates a project saved at `path` over the spritesheets in `asset_dirs`, taking its
+    /// tileset settings and preferences from `config`. Without directories, the project
+    /// uses an `assets` folder next to the project file.
+    pub fn new(path: &Path, asset_dirs: &[PathBuf], tile_size: Size, config: &AppConfig) -> Self {
+        let mut project = Self {
+            path: path.to_path_buf(),
+            tile_size: [
+                tile_size.width.round() as u32,
+                tile_size.height.round() as u32,
+            ],
+            ..Self::default()
+        };
+        project.set_asset_dirs(asset_dirs);
+        project.capture(config);
+        project
+    }
+
+    /// Whether `path` has the project file extension.
+    pub fn is_project_file(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(Self::EXTENSION))
+    }
+
+    /// Reads the project file at `path`.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] when the file is not a valid project or was
+    /// written by a newer version.
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        let text = fs::read_to_string(path)?;
+        let mut project: Project = serde_json::from_str(&text)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if project.version > Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "project version {} is newer than the supported version {}",
+                    project.version,
+                    Self::VERSION
+                ),
+            ));
+        }
+        project.version = Self::VERSION;
+        project.path = path.to_path_buf();
+        Ok(project)
+    }
+
+    /// Writes the project back to the file it was read from or created at.
+    pub fn save(&self) -> Result<(), io::Error> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&self.path, json)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Project file name without its extension.
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Spritesheet directories in load order, resolved against the project file.
+    pub fn asset_dirs(&self) -> Vec<PathBuf> {
+        std::iter::once(&self.assets_root)
+            .chain(&self.extra_asset_dirs)
+            .map(|directory| self.resolve(directory))
+            .collect()
+    }
+
+    /// Replaces the spritesheet directories; the first one becomes `assets_root`. An empty
+    /// list is ignored, since a project always has an assets root.
+    pub fn set_asset_dirs(&mut self, directories: &[PathBuf]) {
+        let Some((first, rest)) = directories.split_first() else {
+            return;
+        };
+        self.assets_root = self.relative(first);
+        self.extra_asset_dirs = rest
+            .iter()
+            .map(|directory| self.relative(directory))
+            .collect();
+    }
+
+    /// Default tile size of the project's spritesheets and new maps.
+    pub fn tile_size(&self) -> Size {
+        Size {
+            width: self.tile_size[0].max(1) as f32,
+            height: self.tile_size[1].max(1) as f32,
+        }
+    }
+
+    /// Map files of the project, resolved against the project file.
+    pub fn map_paths(&self) -> Vec<PathBuf> {
+        self.maps
+            .iter()
+            .map(|map| self.resolve(map))
+            .collect()
+    }
+
+    /// Adds the map file at `path`, returning `false` when the project already lists it.
+    pub fn add_map(&mut self, path: &Path) -> bool {
+        let relative = self.relative(path);
+        if self.maps.contains(&relative) {
+            return false;
+        }
+        self.maps.push(relative);
+        true
+    }
+
+    /// Removes the map listed at `index`; the file itself is kept.
+    pub fn remove_map(&mut self, index: usize) {
+        if index < self.maps.len() {
+            self.maps.remove(index);
+        }
+    }
+
+    /// Overrides the preferences of `config` with the project's.
+    pub fn apply_to(&self, config: &mut AppConfig) {
+        config.default_filter_mode = self.texture_filter;
+        config.map_extensions = self.preferences.map_extensions.clone();
+        config.rle_json_tiles = self.preferences.rle_json_tiles;
+        config.grid = self.preferences.grid;
+    }
+
+    /// Takes the project's preferences from `config`, e.g. before saving it.
+    pub fn capture(&mut self, config: &AppConfig) {
+        self.texture_filter = config.default_filter_mode;
+        self.preferences = ProjectPreferences {
+            map_extensions: config.map_extensions.clone(),
+            rle_json_tiles: config.rle_json_tiles,
+            grid: config.grid,
+        };
+    }
+
+    fn directory(&self) -> &Path {
+        self.path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.directory().join(path)
+    }
+
+    /// `path` relative to the project directory, or unchanged when it lies elsewhere.
+    fn relative(&self, path: &Path) -> PathBuf {
+        let absolute =
+            |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let (path, directory) = (absolute(path), absolute(self.directory()));
+        match path.strip_prefix(&directory) {
+            Ok(relative)
+                if relative
+                    .components()
+                    .all(|part| matches!(part, Component::Normal(_))) =>
+            {
+                relative.to_path_buf()
+            }
+            _ => path,
+        }
+    }
+}
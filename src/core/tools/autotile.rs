@@ -0,0 +1,95 @@
+use crate::core::assets::{AssetCategory, TileSprite};
+
+/// Metadata key holding the neighbor mask a terrain tile is drawn for.
+pub const TERRAIN_MASK_KEY: &str = "terrain_mask";
+
+/// Neighbor offsets of a terrain mask, clockwise from north; offset `i` is bit `1 << i`
+/// (N = 1, NE = 2, E = 4, SE = 8, S = 16, SW = 32, W = 64, NW = 128).
+pub const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Bits of the four edge neighbors (N, E, S, W).
+const EDGE_BITS: u8 = 0b0101_0101;
+
+/// Tiles of a terrain category, each drawn for one arrangement of same-terrain neighbors.
+///
+/// A tile's `terrain_mask` property lists, as a decimal bit mask over
+/// [`NEIGHBOR_OFFSETS`], the neighbors that belong to the same terrain. Corner bits only
+/// count when both adjacent edges are set, so a 16-tile (edges only) set and a 47-tile
+/// (blob) set both work.
+pub struct TerrainSet<'a> {
+    variants: Vec<(u8, &'a TileSprite)>,
+}
+
+impl<'a> TerrainSet<'a> {
+    /// Builds the set of a category marked as terrain in its `category.json`.
+    ///
+    /// Tiles without a valid `terrain_mask` are left out; returns `None` when no tile is left.
+    pub fn from_category(category: &'a AssetCategory) -> Option<Self> {
+        if !category.terrain {
+            return None;
+        }
+        let variants: Vec<(u8, &TileSprite)> = category
+            .tiles()
+            .filter_map(|tile| {
+                let mask = tile
+                    .metadata
+                    .get(TERRAIN_MASK_KEY)?
+                    .trim()
+                    .parse::<u8>()
+                    .ok()?;
+                Some((reduce_mask(mask), tile))
+            })
+            .collect();
+        (!variants.is_empty()).then_some(Self { variants })
+    }
+
+    /// Returns `true` when `tile_id` is one of the terrain's tiles.
+    pub fn contains(&self, tile_id: &str) -> bool {
+        self.variants
+            .iter()
+            .any(|(_, tile)| tile.id == tile_id)
+    }
+
+    /// Picks the tile for a cell whose same-terrain neighbors form `mask`.
+    ///
+    /// Uses the tile declared for exactly that arrangement, or else the one agreeing on
+    /// the most neighbors; ties go to the first tile of the category.
+    pub fn variant(&self, mask: u8) -> &'a TileSprite {
+        let mask = reduce_mask(mask);
+        let mut best = self.variants[0];
+        let mut best_score = 0;
+        for &(variant_mask, tile) in &self.variants {
+            if variant_mask == mask {
+                return tile;
+            }
+            let score = (!(variant_mask ^ mask)).count_ones();
+            if score > best_score {
+                best = (variant_mask, tile);
+                best_score = score;
+            }
+        }
+        best.1
+    }
+}
+
+/// Clears the corner bits whose two adjacent edge bits are not both set.
+fn reduce_mask(mask: u8) -> u8 {
+    let mut reduced = mask & EDGE_BITS;
+    for corner in [1, 3, 5, 7] {
+        let before = 1u8 << (corner - 1);
+        let after = 1u8 << ((corner + 1) % 8);
+        if mask & (1 << corner) != 0 && mask & before != 0 && mask & after != 0 {
+            reduced |= 1 << corner;
+        }
+    }
+    reduced
+}
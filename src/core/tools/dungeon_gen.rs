@@ -0,0 +1,231 @@
+/// Procedural dungeon of rectangular rooms joined by corridors, laid out by binary space
+/// partitioning: the map is split in two again and again, one room is carved in each final
+/// part and the two halves of every split are connected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DungeonGenerator {
+    pub seed: u32,
+    /// Times the map is split; up to `2^depth` rooms when the map is large enough.
+    pub depth: u32,
+    /// Smallest room side in cells.
+    pub min_room_size: usize,
+    /// Corridor thickness in cells.
+    pub corridor_width: usize,
+    /// Tile painted on rooms and corridors; empty leaves them empty.
+    pub floor_tile: String,
+    /// Tile painted on the cells around rooms and corridors; empty leaves them empty.
+    pub wall_tile: String,
+}
+
+impl Default for DungeonGenerator {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            depth: 4,
+            min_room_size: 4,
+            corridor_width: 1,
+            floor_tile: String::new(),
+            wall_tile: String::new(),
+        }
+    }
+}
+
+/// Cell kind of a generated dungeon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DungeonCell {
+    Empty,
+    Floor,
+    Wall,
+}
+
+/// Rectangle of cells, `x`/`y` being its top-left cell.
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Area {
+    fn center(&self) -> (usize, usize) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+impl DungeonGenerator {
+    /// Most splits offered by the generator dialog.
+    pub const MAX_DEPTH: u32 = 8;
+    /// Widest corridor offered by the generator dialog.
+    pub const MAX_CORRIDOR_WIDTH: usize = 3;
+
+    /// Cell kinds of a `width` x `height` map, row by row.
+    pub fn cells(&self, width: usize, height: usize) -> Vec<DungeonCell> {
+        let mut cells = vec![DungeonCell::Empty; width * height];
+        let mut rng = Rng(u64::from(self.seed) ^ 0x9e37_79b9_7f4a_7c15);
+        let whole = Area { x: 0, y: 0, width, height };
+        self.split(whole, self.depth, &mut rng, &mut cells, width);
+
+        for index in 0..cells.len() {
+            if cells[index] != DungeonCell::Empty {
+                continue;
+            }
+            let (x, y) = (index % width, index / width);
+            let next_to_floor = (y.saturating_sub(1)..=(y + 1).min(height - 1)).any(|ny| {
+                (x.saturating_sub(1)..=(x + 1).min(width - 1))
+                    .any(|nx| cells[ny * width + nx] == DungeonCell::Floor)
+            });
+            if next_to_floor {
+                cells[index] = DungeonCell::Wall;
+            }
+        }
+        cells
+    }
+
+    /// Tile id of every cell of a `width` x `height` map, row by row.
+    pub fn generate(&self, width: usize, height: usize) -> Vec<Option<String>> {
+        let tile = |id: &str| Some(id.to_string()).filter(|id| !id.is_empty());
+        self.cells(width, height)
+            .into_iter()
+            .map(|cell| match cell {
+                DungeonCell::Empty => None,
+                DungeonCell::Floor => tile(&self.floor_tile),
+                DungeonCell::Wall => tile(&self.wall_tile),
+            })
+            .collect()
+    }
+
+    /// Carves the rooms of `area` and the corridors between them, returning the center of
+    /// one of its rooms to connect it to its sibling, or `None` when no room fit.
+    fn split(
+        &self, area: Area, depth: u32, rng: &mut Rng, cells: &mut [DungeonCell], map_width: usize,
+    ) -> Option<(usize, usize)> {
+        // Rooms keep a one-cell margin for their walls.
+        let min_part = self.min_room_size.max(1) + 2;
+        let can_split_x = area.width >= min_part * 2;
+        let can_split_y = area.height >= min_part * 2;
+        if depth == 0 || !(can_split_x || can_split_y) {
+            return self.carve_room(area, rng, cells, map_width);
+        }
+
+        let split_x = match (can_split_x, can_split_y) {
+            (true, false) => true,
+            (false, true) => false,
+            _ if area.width * 4 >= area.height * 5 => true,
+            _ if area.height * 4 >= area.width * 5 => false,
+            _ => rng.below(2) == 0,
+        };
+        let (first, second) = if split_x {
+            let cut = min_part + rng.below(area.width - min_part * 2 + 1);
+            (
+                Area { width: cut, ..area },
+                Area {
+                    x: area.x + cut,
+                    width: area.width - cut,
+                    ..area
+                },
+            )
+        } else {
+            let cut = min_part + rng.below(area.height - min_part * 2 + 1);
+            (
+                Area { height: cut, ..area },
+                Area {
+                    y: area.y + cut,
+                    height: area.height - cut,
+                    ..area
+                },
+            )
+        };
+
+        let first = self.split(first, depth - 1, rng, cells, map_width);
+        let second = self.split(second, depth - 1, rng, cells, map_width);
+        match (first, second) {
+            (Some(from), Some(to)) => {
+                self.carve_corridor(from, to, rng, cells, map_width);
+                Some(if rng.below(2) == 0 {
+                    from
+                } else {
+                    to
+                })
+            }
+            (from, to) => from.or(to),
+        }
+    }
+
+    fn carve_room(
+        &self, area: Area, rng: &mut Rng, cells: &mut [DungeonCell], map_width: usize,
+    ) -> Option<(usize, usize)> {
+        let min_size = self.min_room_size.max(1);
+        let (max_width, max_height) =
+            (area.width.saturating_sub(2), area.height.saturating_sub(2));
+        if max_width < min_size || max_height < min_size {
+            return None;
+        }
+        let width = min_size + rng.below(max_width - min_size + 1);
+        let height = min_size + rng.below(max_height - min_size + 1);
+        let room = Area {
+            x: area.x + 1 + rng.below(max_width - width + 1),
+            y: area.y + 1 + rng.below(max_height - height + 1),
+            width,
+            height,
+        };
+        fill(cells, map_width, room);
+        Some(room.center())
+    }
+
+    /// Joins `from` and `to` with an L-shaped corridor, bending at a random corner.
+    fn carve_corridor(
+        &self, from: (usize, usize), to: (usize, usize), rng: &mut Rng, cells: &mut [DungeonCell],
+        map_width: usize,
+    ) {
+        let map_height = cells.len() / map_width;
+        let thickness = self
+            .corridor_width
+            .clamp(1, Self::MAX_CORRIDOR_WIDTH);
+        let corner = if rng.below(2) == 0 {
+            (to.0, from.1)
+        } else {
+            (from.0, to.1)
+        };
+        for ((x0, y0), (x1, y1)) in [(from, corner), (corner, to)] {
+            let (x, y) = (x0.min(x1), y0.min(y1));
+            let area = Area {
+                x,
+                y,
+                width: (x0.max(x1) - x + thickness).min(map_width - x),
+                height: (y0.max(y1) - y + thickness).min(map_height - y),
+            };
+            fill(cells, map_width, area);
+        }
+    }
+}
+
+fn fill(cells: &mut [DungeonCell], map_width: usize, area: Area) {
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            cells[y * map_width + x] = DungeonCell::Floor;
+        }
+    }
+}
+
+/// Small seeded SplitMix64 generator, so a seed always rebuilds the same dungeon.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Random value in `0..bound`; `0` when `bound` is `0`.
+    fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next() % bound as u64) as usize
+    }
+}
@@ -0,0 +1,371 @@
+pub mod autotile;
+pub mod dungeon_gen;
+pub mod noise;
+pub mod perlin;
+pub mod terrain_gen;
+
+use crate::core::assets::{AssetCatalog, TileSprite};
+
+/// Editing tool applied when the left mouse button is used on the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaintMode {
+    /// Paints the selected sprite on every hovered cell.
+    #[default]
+    Paint,
+    /// Clears every hovered cell.
+    Erase,
+    /// Flood-fills the contiguous region under the cursor with the selected sprite.
+    Fill,
+    /// Paints the tiles of the [`TileSequence`] in order, one per cell.
+    Sequence,
+    /// Paints a tile picked at random from the weighted [`RandomBrush`], one per cell.
+    Random,
+    /// Clicks select cells instead of editing them.
+    Select,
+    /// Paints tiles chosen by the [`noise::NoiseStamp`] rules, one per cell.
+    NoiseStamp,
+    /// Press-drag-release fills the rectangle spanned by the drag.
+    Rectangle,
+    /// Press-drag-release paints a straight line between the drag's end points.
+    Line,
+    /// Dragging selects the rectangular block spanned by the drag.
+    Marquee,
+    /// Clicking selects the connected cells holding the clicked tile, or every such cell of
+    /// the layer; Shift+click adds to the selection.
+    MagicWand,
+    /// Paints the selected category's [`autotile::TerrainSet`], picking each tile from its
+    /// neighbors.
+    Terrain,
+    /// Clicking toggles whether cells can be walked on; dragging applies the same state.
+    Collision,
+    /// Clicking an object layer picks the object under the cursor, or places a new one;
+    /// dragging moves it.
+    Object,
+}
+
+impl PaintMode {
+    /// Every mode, in the order shown by the tools panel.
+    pub const ALL: [PaintMode; 14] = [
+        PaintMode::Paint,
+        PaintMode::Erase,
+        PaintMode::Fill,
+        PaintMode::Sequence,
+        PaintMode::Random,
+        PaintMode::Select,
+        PaintMode::NoiseStamp,
+        PaintMode::Rectangle,
+        PaintMode::Line,
+        PaintMode::Marquee,
+        PaintMode::MagicWand,
+        PaintMode::Terrain,
+        PaintMode::Collision,
+        PaintMode::Object,
+    ];
+
+    /// Stable name used for display and for persisting the mode in the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            PaintMode::Paint => "Paint",
+            PaintMode::Erase => "Erase",
+            PaintMode::Fill => "Fill",
+            PaintMode::Sequence => "Sequence",
+            PaintMode::Random => "Random",
+            PaintMode::Select => "Select",
+            PaintMode::NoiseStamp => "Noise Stamp",
+            PaintMode::Rectangle => "Rectangle",
+            PaintMode::Line => "Line",
+            PaintMode::Marquee => "Marquee",
+            PaintMode::MagicWand => "Magic Wand",
+            PaintMode::Terrain => "Terrain",
+            PaintMode::Collision => "Collision",
+            PaintMode::Object => "Object",
+        }
+    }
+
+    /// Parses a name produced by [`PaintMode::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+/// Which cells the fill tool repaints and the magic wand selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillScope {
+    /// Only the cells connected (4-way) to the clicked one that hold the same tile.
+    #[default]
+    Contiguous,
+    /// Every cell of the layer holding the same tile as the clicked one.
+    Global,
+}
+
+/// Cells searched by [`Map::replace_tiles`](crate::core::map::map::Map::replace_tiles).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplaceScope {
+    /// Every cell of the active layer.
+    #[default]
+    ActiveLayer,
+    /// Every cell of every visible, unlocked tile layer.
+    AllLayers,
+    /// The selected cells of the active layer.
+    Selection,
+}
+
+impl ReplaceScope {
+    pub const ALL: [ReplaceScope; 3] = [
+        ReplaceScope::ActiveLayer,
+        ReplaceScope::AllLayers,
+        ReplaceScope::Selection,
+    ];
+}
+
+/// Layers edited by the erase and fill tools and by
+/// [`Map::replace_tiles`](crate::core::map::map::Map::replace_tiles).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApplyTo {
+    /// Only the active layer, when it is visible and unlocked.
+    #[default]
+    ActiveLayer,
+    /// Every tile layer, hidden and locked ones included.
+    AllLayers,
+    /// Every visible tile layer that neither it nor its groups lock.
+    AllUnlockedLayers,
+}
+
+/// Rectangular block of tiles, in tile coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Selection {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Smallest selection containing both corners.
+    pub fn from_corners(a: (usize, usize), b: (usize, usize)) -> Self {
+        let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+        Self::new(x, y, a.0.abs_diff(b.0) + 1, a.1.abs_diff(b.1) + 1)
+    }
+
+    /// Returns `true` when `(tile_x, tile_y)` lies inside the selection.
+    pub fn contains(&self, tile_x: usize, tile_y: usize) -> bool {
+        tile_x >= self.x
+            && tile_y >= self.y
+            && tile_x < self.x.saturating_add(self.width)
+            && tile_y < self.y.saturating_add(self.height)
+    }
+
+    /// Iterates every cell of the selection in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + use<> {
+        let Selection { x, y, width, height } = *self;
+        (y..y.saturating_add(height))
+            .flat_map(move |row| (x..x.saturating_add(width)).map(move |col| (col, row)))
+    }
+
+    /// Part of the selection inside a map of `width` × `height` cells, if any.
+    pub fn clip(&self, width: usize, height: usize) -> Option<Self> {
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(width);
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(height);
+        (self.x < right && self.y < bottom)
+            .then(|| Self::new(self.x, self.y, right - self.x, bottom - self.y))
+    }
+}
+
+/// Rectangular block of copied cells, row-major; `None` marks a cell left out of the copy.
+#[derive(Clone)]
+pub struct TileClipboard<T> {
+    width: usize,
+    cells: Vec<Option<T>>,
+}
+
+impl<T> TileClipboard<T> {
+    /// Wraps the row-major cells of a block `width` cells wide.
+    pub fn new(width: usize, cells: Vec<Option<T>>) -> Self {
+        Self { width: width.max(1), cells }
+    }
+
+    /// Iterates the copied cells as `(dx, dy, value)`, offsets from the block's top-left corner.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, cell)| {
+                cell.as_ref()
+                    .map(|value| (index % width, index / width, value))
+            })
+    }
+}
+
+/// Cells on the straight line from `start` to `end`, both included, using Bresenham stepping.
+pub fn line_cells(start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (start.0 as isize, start.1 as isize);
+    let (end_x, end_y) = (end.0 as isize, end.1 as isize);
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x {
+        1
+    } else {
+        -1
+    };
+    let step_y = if y < end_y {
+        1
+    } else {
+        -1
+    };
+    let mut error = dx + dy;
+    let mut cells = Vec::new();
+
+    loop {
+        cells.push((x as usize, y as usize));
+        if x == end_x && y == end_y {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Ordered list of sprites painted one after another by the sequence brush.
+#[derive(Clone, Default)]
+pub struct TileSequence {
+    pub tiles: Vec<TileSprite>,
+    pub current: usize,
+}
+
+impl TileSequence {
+    /// Returns the sprite to paint next and advances the cursor, wrapping around.
+    pub fn next_sprite(&mut self) -> Option<TileSprite> {
+        if self.tiles.is_empty() {
+            return None;
+        }
+        let sprite = self.tiles[self.current % self.tiles.len()].clone();
+        self.current = (self.current + 1) % self.tiles.len();
+        Some(sprite)
+    }
+
+    /// Restarts the sequence from its first tile.
+    pub fn reset(&mut self) {
+        self.current = 0;
+    }
+
+    /// Replaces each sprite with the catalog's current sprite of the same id, dropping the
+    /// ones that no longer exist.
+    pub fn refresh(&mut self, catalog: &AssetCatalog) {
+        self.tiles = self
+            .tiles
+            .iter()
+            .filter_map(|tile| catalog.sprite_by_id(&tile.id).cloned())
+            .collect();
+        self.reset();
+    }
+
+    pub fn push(&mut self, sprite: TileSprite) {
+        self.tiles.push(sprite);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.tiles.len() {
+            self.tiles.remove(index);
+            self.reset();
+        }
+    }
+}
+
+/// Sprite of the [`RandomBrush`] with its relative chance of being picked.
+#[derive(Clone)]
+pub struct WeightedTile {
+    pub sprite: TileSprite,
+    pub weight: u32,
+}
+
+/// Set of sprites painted in random order by the random brush, e.g. grass variants, each
+/// picked with a probability proportional to its weight.
+#[derive(Clone, Default)]
+pub struct RandomBrush {
+    pub tiles: Vec<WeightedTile>,
+}
+
+impl RandomBrush {
+    /// Sum of the weights of every tile.
+    pub fn total_weight(&self) -> u32 {
+        self.tiles
+            .iter()
+            .map(|tile| tile.weight)
+            .sum()
+    }
+
+    /// Picks a sprite at random according to the weights; `None` while every weight is `0`.
+    pub fn pick(&self) -> Option<&TileSprite> {
+        let total = self.total_weight();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = macroquad::rand::gen_range(0, total);
+        self.tiles
+            .iter()
+            .find(|tile| {
+                if roll < tile.weight {
+                    return true;
+                }
+                roll -= tile.weight;
+                false
+            })
+            .map(|tile| &tile.sprite)
+    }
+
+    /// Adds `sprite` with weight `1`, or raises its weight by one when it is already in the
+    /// set.
+    pub fn push(&mut self, sprite: TileSprite) {
+        match self
+            .tiles
+            .iter_mut()
+            .find(|tile| tile.sprite.id == sprite.id)
+        {
+            Some(tile) => tile.weight += 1,
+            None => self
+                .tiles
+                .push(WeightedTile { sprite, weight: 1 }),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.tiles.len() {
+            self.tiles.remove(index);
+        }
+    }
+
+    /// Replaces each sprite with the catalog's current sprite of the same id, dropping the
+    /// ones that no longer exist.
+    pub fn refresh(&mut self, catalog: &AssetCatalog) {
+        self.tiles = self
+            .tiles
+            .iter()
+            .filter_map(|tile| {
+                let sprite = catalog
+                    .sprite_by_id(&tile.sprite.id)?
+                    .clone();
+                Some(WeightedTile { sprite, weight: tile.weight })
+            })
+            .collect();
+    }
+}
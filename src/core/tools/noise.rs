@@ -0,0 +1,146 @@
+use crate::core::assets::{AssetCatalog, TileSprite};
+use crate::core::map::map::Map;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Brush choosing each painted tile from 2D value noise and the tiles around it.
+///
+/// Rules are read from a JSON file such as:
+///
+/// ```json
+/// {
+///   "scale": 6.0,
+///   "rules": [
+///     { "noise": [0.0, 0.4], "tiles": ["water_a", "water_b"] },
+///     { "noise": [0.4, 1.0], "neighbors": ["water_a"], "min_matches": 1, "tiles": ["shore"] },
+///     { "noise": [0.4, 1.0], "tiles": ["grass"] }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NoiseStamp {
+    /// Noise feature size in cells; larger values give broader patches.
+    pub scale: f32,
+    pub seed: u32,
+    /// Decision table, evaluated for every painted cell.
+    pub rules: Vec<NoiseRule>,
+}
+
+/// Row of the [`NoiseStamp`] decision table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NoiseRule {
+    /// Inclusive `[min, max]` noise range, within `0.0..=1.0`, where the rule applies.
+    #[serde(default = "NoiseRule::full_range")]
+    pub noise: [f32; 2],
+    /// Tile ids this rule prefers around the cell; each N/S/E/W neighbor holding one scores a point.
+    #[serde(default)]
+    pub neighbors: Vec<String>,
+    /// Matching neighbors required for the rule to apply.
+    #[serde(default)]
+    pub min_matches: usize,
+    /// Candidate tile ids, picked by where the noise value falls within `noise`.
+    pub tiles: Vec<String>,
+}
+
+impl Default for NoiseStamp {
+    fn default() -> Self {
+        Self { scale: 8.0, seed: 0, rules: Vec::new() }
+    }
+}
+
+impl NoiseStamp {
+    /// Rule file read from the working directory.
+    pub const RULES_FILE: &'static str = "noise_stamp.json";
+
+    pub fn load_from_json(path: &Path) -> Result<Self, io::Error> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(io::Error::other)
+    }
+
+    /// Picks the tile for `(x, y)` from the noise value there and the ids of its four neighbors.
+    ///
+    /// Among the rules whose range contains the noise value and whose `min_matches` is met,
+    /// the one with the most matching neighbors wins; ties go to the earliest rule.
+    pub fn pick<'a>(
+        &self, x: usize, y: usize, map: &Map, catalog: &'a AssetCatalog,
+    ) -> Option<&'a TileSprite> {
+        let value = self.noise(x, y);
+        let neighbor_ids: Vec<&str> = [
+            y.checked_sub(1).map(|north| (x, north)),
+            Some((x, y + 1)),
+            Some((x + 1, y)),
+            x.checked_sub(1).map(|west| (west, y)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|(nx, ny)| map.tile_id(nx, ny))
+        .collect();
+
+        let mut best: Option<(&NoiseRule, usize)> = None;
+        for rule in &self.rules {
+            let [min, max] = rule.noise;
+            if value < min || value > max || rule.tiles.is_empty() {
+                continue;
+            }
+            let matches = neighbor_ids
+                .iter()
+                .filter(|id| {
+                    rule.neighbors
+                        .iter()
+                        .any(|wanted| wanted == *id)
+                })
+                .count();
+            if matches >= rule.min_matches && best.is_none_or(|(_, score)| matches > score) {
+                best = Some((rule, matches));
+            }
+        }
+
+        let (rule, _) = best?;
+        let [min, max] = rule.noise;
+        let position = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.0
+        };
+        let index = ((position * rule.tiles.len() as f32) as usize).min(rule.tiles.len() - 1);
+        catalog.sprite_by_id(&rule.tiles[index])
+    }
+
+    /// Smooth value noise in `0.0..=1.0`, sampled at the cell center.
+    fn noise(&self, x: usize, y: usize) -> f32 {
+        let scale = self.scale.max(1.0);
+        let sample_x = (x as f32 + 0.5) / scale;
+        let sample_y = (y as f32 + 0.5) / scale;
+        let (cell_x, cell_y) = (sample_x.floor(), sample_y.floor());
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(sample_x - cell_x), smooth(sample_y - cell_y));
+        let (cell_x, cell_y) = (cell_x as i32, cell_y as i32);
+
+        let corner = |dx: i32, dy: i32| lattice_value(cell_x + dx, cell_y + dy, self.seed);
+        let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * tx;
+        let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * tx;
+        top + (bottom - top) * ty
+    }
+}
+
+impl NoiseRule {
+    fn full_range() -> [f32; 2] {
+        [0.0, 1.0]
+    }
+}
+
+/// Deterministic pseudo-random value in `0.0..=1.0` for a lattice point.
+fn lattice_value(x: i32, y: i32, seed: u32) -> f32 {
+    let mut hash = (x as u32).wrapping_mul(0x27d4_eb2d)
+        ^ (y as u32).wrapping_mul(0x1656_67b1)
+        ^ seed.wrapping_mul(0x9e37_79b9);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    hash = hash.wrapping_mul(0x297a_2d39);
+    hash ^= hash >> 15;
+    hash as f32 / u32::MAX as f32
+}
@@ -0,0 +1,61 @@
+//! Seeded 2D Perlin gradient noise, for procedural generators.
+
+/// Perlin noise of `(x, y)` in `-1.0..=1.0`, zero on every lattice point.
+pub fn noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (cell_x, cell_y) = (x.floor(), y.floor());
+    let (fx, fy) = (x - cell_x, y - cell_y);
+    let (cell_x, cell_y) = (cell_x as i32, cell_y as i32);
+    let (u, v) = (fade(fx), fade(fy));
+
+    let corner = |dx: i32, dy: i32| {
+        gradient(cell_x + dx, cell_y + dy, seed, fx - dx as f32, fy - dy as f32)
+    };
+    let top = lerp(corner(0, 0), corner(1, 0), u);
+    let bottom = lerp(corner(0, 1), corner(1, 1), u);
+    lerp(top, bottom, v).clamp(-1.0, 1.0)
+}
+
+/// Fractal sum of `octaves` layers of [`noise`], each at twice the frequency and half the
+/// amplitude of the previous one, mapped to `0.0..=1.0`.
+pub fn fractal(x: f32, y: f32, octaves: u32, seed: u32) -> f32 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+    for octave in 0..octaves.max(1) {
+        sum += noise(x * frequency, y * frequency, seed.wrapping_add(octave)) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    (sum / total * 0.5 + 0.5).clamp(0.0, 1.0)
+}
+
+/// Dot product of the lattice point's pseudo-random gradient with the offset `(dx, dy)`.
+fn gradient(x: i32, y: i32, seed: u32, dx: f32, dy: f32) -> f32 {
+    match hash(x, y, seed) & 7 {
+        0 => dx + dy,
+        1 => dx - dy,
+        2 => -dx + dy,
+        3 => -dx - dy,
+        4 => dx,
+        5 => -dx,
+        6 => dy,
+        _ => -dy,
+    }
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut hash = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0x5bd1_e995);
+    hash ^ (hash >> 15)
+}
+
+/// Quintic ease curve, so the noise has continuous slope across cells.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
@@ -0,0 +1,78 @@
+use crate::core::tools::perlin;
+
+/// Procedural terrain painted from fractal Perlin noise, one tile per noise band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainGenerator {
+    pub seed: u32,
+    /// Noise feature size in cells; larger values give broader landmasses.
+    pub scale: f32,
+    /// Noise layers summed for detail, `1` giving the smoothest terrain.
+    pub octaves: u32,
+    /// Bands from the lowest noise values up.
+    pub bands: Vec<TerrainBand>,
+}
+
+/// Range of noise values painted with one tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainBand {
+    /// Label shown in the generator dialog, e.g. "Water".
+    pub name: String,
+    /// Noise values up to this one, in `0.0..=1.0`, fall in the band unless a lower band
+    /// already took them.
+    pub threshold: f32,
+    /// Tile painted in the band; empty leaves its cells empty.
+    pub tile_id: String,
+}
+
+impl Default for TerrainGenerator {
+    fn default() -> Self {
+        let band = |name: &str, threshold: f32| TerrainBand {
+            name: name.to_string(),
+            threshold,
+            tile_id: String::new(),
+        };
+        Self {
+            seed: 0,
+            scale: 16.0,
+            octaves: 4,
+            bands: vec![
+                band("Water", 0.42),
+                band("Sand", 0.47),
+                band("Grass", 0.6),
+                band("Rock", 1.0),
+            ],
+        }
+    }
+}
+
+impl TerrainGenerator {
+    /// Most octaves offered by the generator dialog.
+    pub const MAX_OCTAVES: u32 = 8;
+
+    /// Noise value of the cell `(x, y)` in `0.0..=1.0`.
+    pub fn value(&self, x: usize, y: usize) -> f32 {
+        let scale = self.scale.max(1.0);
+        perlin::fractal(x as f32 / scale, y as f32 / scale, self.octaves, self.seed)
+    }
+
+    /// Tile id of the band the cell `(x, y)` falls in; `None` for bands without a tile and
+    /// values above every threshold.
+    pub fn tile_id(&self, x: usize, y: usize) -> Option<&str> {
+        let value = self.value(x, y);
+        self.bands
+            .iter()
+            .find(|band| value <= band.threshold)
+            .map(|band| band.tile_id.as_str())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Tile id of every cell of a `width` x `height` map, row by row.
+    pub fn generate(&self, width: usize, height: usize) -> Vec<Option<String>> {
+        (0..width * height)
+            .map(|index| {
+                self.tile_id(index % width, index / width)
+                    .map(str::to_string)
+            })
+            .collect()
+    }
+}
@@ -1,18 +1,78 @@
-use crate::core::assets::{AssetCatalog, AssetCategory, TileSprite};
+use crate::core::assets::AssetCatalog;
+use crate::core::assets::loader::AssetLoader;
+use crate::core::config::AppConfig;
+use crate::core::config::dock::DockPanel;
+use crate::core::interop::tiled;
+use crate::core::map::autosave::Autosave;
 use crate::core::map::map::{Map, MapLoadError};
+use crate::core::map::tabs::MapTabs;
 use crate::core::map::tile::Size;
+use crate::core::project::Project;
+use crate::core::tools::PaintMode;
+use crate::core::tools::autotile::TerrainSet;
+use crate::core::tools::noise::NoiseStamp;
+use crate::ui::asset_dirs::AssetDirsDialog;
+use crate::ui::custom_properties::PropertiesPanel;
+use crate::ui::dock::Dock;
+use crate::ui::dungeon_generator::DungeonGeneratorDialog;
+use crate::ui::file_dialog;
+use crate::ui::file_menu::FileMenu;
+use crate::ui::generate_menu::{GenerateMenu, GeneratorAction};
+use crate::ui::history::HistoryPanel;
+use crate::ui::i18n;
+use crate::ui::inspector::TileInspectorPanel;
+use crate::ui::layers::LayersPanel;
+use crate::ui::minimap::Minimap;
+use crate::ui::new_map::NewMapDialog;
+use crate::ui::objects::ObjectPanel;
+use crate::ui::overlay::OverlayText;
+use crate::ui::palette::{PalettePanel, PanelActions};
+use crate::ui::preferences::PreferencesDialog;
+use crate::ui::project_menu::ProjectMenu;
+use crate::ui::properties::TilePropertyEditor;
+use crate::ui::random_brush::RandomBrushEditor;
+use crate::ui::recovery::{RecoveryChoice, RecoveryDialog};
+use crate::ui::reference_image::ReferenceImagePanel;
+use crate::ui::replace_tiles::ReplaceTilesDialog;
+use crate::ui::resize_map::{ResizeMapDialog, ResizeMapRequest};
+use crate::ui::search::QuickSearch;
+use crate::ui::sequence::SequenceEditor;
+use crate::ui::statistics::StatisticsPanel;
+use crate::ui::status_bar::StatusBar;
+use crate::ui::tabs::{TabBar, tab_label};
+use crate::ui::terrain_generator::TerrainGeneratorDialog;
+use crate::ui::theme::UiSkin;
+use crate::ui::tool_options::ToolOptionsPanel;
+use crate::ui::tools::ToolsPanel;
+use crate::ui::view_settings::ViewSettingsPanel;
 use image::imageops::FilterType;
-use macroquad::color::{BLACK, DARKGRAY, WHITE};
-use macroquad::input::{MouseButton, is_mouse_button_down, mouse_position};
-use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::audio::{Sound, load_sound};
+use macroquad::color::{BLACK, DARKGRAY};
+use macroquad::input::{
+    KeyCode, MouseButton, get_dropped_files, is_key_down, is_key_pressed, is_mouse_button_down,
+    is_mouse_button_pressed, is_mouse_button_released, is_quit_requested, mouse_position,
+    prevent_quit,
+};
+use macroquad::math::Vec2;
 use macroquad::miniquad::conf::Icon;
-use macroquad::prelude::{Camera2D, clear_background};
-use macroquad::text::draw_text;
-use macroquad::ui::{Ui, hash, root_ui, widgets};
-use macroquad::window::{Conf, next_frame, screen_height};
+use macroquad::miniquad::date;
+use macroquad::prelude::clear_background;
+use macroquad::rand;
+use macroquad::time::get_time;
+use macroquad::window::{Conf, next_frame};
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::path::{Path, PathBuf};
 
 mod core;
+mod ui;
+
+/// Size, in tiles, of the empty map shown at startup; "New map" picks another one.
+const DEFAULT_MAP_SIZE: Size = Size { width: 20.0, height: 15.0 };
+/// Tile size used to slice the asset catalog and lay out the startup map.
+const DEFAULT_TILE_SIZE: Size = Size { width: 32.0, height: 32.0 };
+/// Time spent slicing spritesheets each frame while the asset catalog loads.
+const ASSET_LOAD_BUDGET_SECS: f64 = 0.008;
 
 fn window_conf() -> Conf {
     Conf {
@@ -25,203 +85,1238 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let map_size = Size { width: 20.0, height: 15.0 };
-    let tile_size = Size { width: 32.0, height: 32.0 };
-    let mut map = Map::new(map_size, tile_size);
-    let asset_catalog = AssetCatalog::load(tile_size).await;
+    let mut config = AppConfig::load();
+    let mut project = reopen_project(&mut config);
+    let tile_size = project
+        .as_ref()
+        .map_or(DEFAULT_TILE_SIZE, Project::tile_size);
+    let mut map = Map::new(DEFAULT_MAP_SIZE, tile_size);
+    // The palette starts empty and fills once `asset_loader` has sliced every spritesheet.
+    let mut asset_catalog = AssetCatalog::empty(tile_size, config.default_filter_mode);
+    let mut asset_loader = Some(AssetLoader::new(
+        &asset_dirs(project.as_ref(), &config),
+        tile_size,
+        config.default_filter_mode,
+    ));
+    // Maps resolve their tiles against the catalog, so files opened while it loads wait.
+    let mut deferred_opens: Vec<PathBuf> = Vec::new();
+    let mut session_restored = false;
+    map.get_camera_controller_mut()
+        .pan_axis_lock = config.pan_axis_lock;
+    map.get_camera_controller_mut()
+        .smooth_motion = config.smooth_camera;
+    map.get_camera_controller_mut()
+        .wheel_zoom_sensitivity = config.wheel_zoom_sensitivity;
+    map.get_camera_controller_mut()
+        .wheel_pan_sensitivity = config.wheel_pan_sensitivity;
+    map.set_paint_cooldown(config.paint_cooldown_tiles);
+    map.set_tile_encoding(config.tile_encoding());
+    map.set_grid_style(config.grid);
+    for (extension, format) in &config.map_extensions {
+        map.register_file_extension(extension, *format);
+    }
+    if Path::new(NoiseStamp::RULES_FILE).exists() {
+        load_noise_stamp(&mut map);
+    }
     let mut palette_panel = PalettePanel::new(tile_size);
+    let mut preferences_dialog = PreferencesDialog::new();
+    let mut new_map_dialog = NewMapDialog::new();
+    let mut resize_map_dialog = ResizeMapDialog::new();
+    let mut replace_tiles_dialog = ReplaceTilesDialog::new();
+    let mut asset_dirs_dialog = AssetDirsDialog::new();
+    let mut terrain_dialog = TerrainGeneratorDialog::new();
+    let mut dungeon_dialog = DungeonGeneratorDialog::new();
+    let mut recovery_dialog = RecoveryDialog::new(Autosave::pending_recovery());
+    let mut autosave = Autosave::new(config.autosave_interval_secs, get_time());
+    let mut tools_panel = ToolsPanel::new();
+    let mut tool_options_panel = ToolOptionsPanel::new();
+    let mut inspector_panel = TileInspectorPanel::new();
+    let mut layers_panel = LayersPanel::new();
+    let mut object_panel = ObjectPanel::new();
+    let mut properties_panel = PropertiesPanel::new();
+    let mut history_panel = HistoryPanel::new();
+    let mut view_settings_panel = ViewSettingsPanel::new();
+    let mut reference_image_panel = ReferenceImagePanel::new();
+    let mut statistics_panel = StatisticsPanel::new();
+    let mut status_bar = StatusBar::new();
+    let mut minimap = Minimap::new();
+    let mut dock = Dock::new(config.dock);
+    dock.set_scale(config.ui_scale());
+    let mut sequence_editor = SequenceEditor::new();
+    let mut random_brush_editor = RandomBrushEditor::new();
+    let mut property_editor = TilePropertyEditor::new();
+    let mut quick_search = QuickSearch::new();
+    let mut file_menu = FileMenu::new();
+    let mut generate_menu = GenerateMenu::new();
+    let mut project_menu = ProjectMenu::new();
+    let mut right_click = RightClick::default();
+    prevent_quit();
+    rand::srand((date::now() * 1000.0) as u64);
+    let overlay_text = OverlayText::load(&config).await;
+    i18n::set_language(config.language);
+    let mut ui_skin = UiSkin::new();
+    ui_skin.apply(&config);
+    if let Some(path) = &config.paint_sound_path {
+        map.set_paint_sound(load_paint_sound(path).await);
+    }
+    let mut tabs = MapTabs::new(map);
+    let mut tab_bar = TabBar::new();
+    // Map file shown in the palette's path field, following the active tab.
+    let mut shown_path: Option<PathBuf> = None;
 
     loop {
         clear_background(BLACK);
+        let tab_actions = tab_bar.draw(&tabs);
+        let tab_before = tabs.active_index();
+        if let Some(index) = tab_actions.selected {
+            tabs.select(index);
+        }
+        if let Some(index) = tab_actions.closed {
+            tabs.close(index);
+        }
+        if tab_actions.new_tab_requested {
+            let map = tabs.active().new_tab();
+            tabs.open(map);
+        }
+        if ctrl_down() && is_key_pressed(KeyCode::Tab) {
+            if shift_down() {
+                tabs.select_previous();
+            } else {
+                tabs.select_next();
+            }
+        }
+        if tabs.active_index() != tab_before || tab_actions.closed.is_some() {
+            minimap.invalidate();
+        }
+        let map = tabs.active_mut();
+        // Opened at the end of the frame, once `map` is no longer borrowed.
+        let mut open_requests: Vec<PathBuf> = Vec::new();
+        let mut restored_tabs: Vec<Map> = Vec::new();
+        let dialog_open = preferences_dialog.is_open()
+            || property_editor.is_open()
+            || new_map_dialog.is_open()
+            || resize_map_dialog.is_open()
+            || replace_tiles_dialog.is_open()
+            || asset_dirs_dialog.is_open()
+            || terrain_dialog.is_open()
+            || dungeon_dialog.is_open()
+            || recovery_dialog.is_open();
+        map.get_camera_controller_mut()
+            .keyboard_input_enabled = !dialog_open && !quick_search.is_open();
 
-        draw_text("ForgeTile!", 20.0, 20.0, 30.0, DARKGRAY);
+        overlay_text.draw(&window_title(map), 20.0, 20.0, 2.5, DARKGRAY);
 
-        let camera: Camera2D = map.draw();
-        let zoom = map
-            .get_camera_controller()
-            .get_current_zoom();
+        map.draw(&overlay_text);
+        dock.reserve_below(dock.side(DockPanel::Palette), tool_options_panel.height());
+        dock.arrange(|panel| match panel {
+            DockPanel::Layers => layers_panel.is_visible(),
+            DockPanel::Properties => properties_panel.is_visible(),
+            DockPanel::History => history_panel.is_visible(),
+            DockPanel::Palette | DockPanel::Minimap => true,
+        });
+        minimap.draw(map, dock.rect(DockPanel::Minimap));
+        dock.draw(&overlay_text);
+        status_bar.draw(map, &overlay_text, asset_loader.as_ref());
+        quick_search.draw(&overlay_text, &asset_catalog, &palette_panel);
 
-        draw_text(&format!("Zoom: {:.1}", zoom), 10.0, 20.0, 20.0, WHITE);
+        if ctrl_down() && is_key_pressed(KeyCode::O) {
+            map.toggle_tile_outlines();
+        }
+        if ctrl_down() && is_key_pressed(KeyCode::G) {
+            view_settings_panel.toggle();
+        }
+        if ctrl_down() && is_key_pressed(KeyCode::H) {
+            history_panel.toggle();
+        }
+        if ctrl_down() && shift_down() && is_key_pressed(KeyCode::I) {
+            map.invert_selection();
+        }
+        // The palette, layers, object and properties panels host text fields, so their
+        // keystrokes must not reach the hotkeys.
+        let over_palette = palette_panel.pointer_over_ui()
+            || layers_panel.pointer_over_ui()
+            || object_panel.pointer_over_ui()
+            || properties_panel.pointer_over_ui();
+        let was_searching = quick_search.is_open();
+        quick_search.update(
+            &asset_catalog,
+            &mut palette_panel,
+            !dialog_open && !ctrl_down() && !over_palette && map.paint_mode() == PaintMode::Select,
+        );
+        let typing = dialog_open || quick_search.is_open() || over_palette;
+        if !typing && is_key_pressed(KeyCode::Delete) && !map.remove_selected_object() {
+            map.erase_selection();
+        }
+        if !was_searching && is_key_pressed(KeyCode::Escape) {
+            map.clear_selection();
+            map.stop_playback();
+            map.cancel_shape();
+            map.cancel_paste();
+        }
+        if ctrl_down() && !typing && is_key_pressed(KeyCode::C) {
+            let copied = map.copy_selection();
+            if copied > 0 {
+                println!("Copied {copied} tiles");
+            }
+        }
+        if ctrl_down() && !typing && is_key_pressed(KeyCode::V) {
+            let (tile_x, tile_y) = map.hovered_tile().unwrap_or((0, 0));
+            map.start_paste(tile_x, tile_y);
+        }
+        if ctrl_down() && !typing && is_key_pressed(KeyCode::Z) {
+            if shift_down() {
+                map.redo();
+            } else {
+                map.undo();
+            }
+        }
+        if !ctrl_down() && !typing {
+            if !alt_down() && is_key_pressed(KeyCode::I) {
+                inspector_panel.toggle();
+            }
+            if is_key_pressed(KeyCode::L) {
+                layers_panel.toggle();
+            }
+            if is_key_pressed(KeyCode::P) {
+                properties_panel.toggle();
+            }
+            if is_key_pressed(KeyCode::B) {
+                map.set_paint_mode(PaintMode::Paint);
+            } else if is_key_pressed(KeyCode::E) {
+                map.set_paint_mode(PaintMode::Erase);
+            } else if is_key_pressed(KeyCode::G) {
+                map.set_paint_mode(PaintMode::Fill);
+            } else if is_key_pressed(KeyCode::M) {
+                map.set_paint_mode(PaintMode::Marquee);
+            }
+            let transform = map.brush_transform();
+            if is_key_pressed(KeyCode::R) {
+                map.set_brush_transform(transform.rotated_clockwise());
+            } else if is_key_pressed(KeyCode::H) {
+                map.set_brush_transform(transform.flipped_horizontally());
+            } else if is_key_pressed(KeyCode::V) {
+                map.set_brush_transform(transform.flipped_vertically());
+            }
+        }
+
+        let panel_actions: PanelActions =
+            palette_panel.draw(&asset_catalog, dock.rect(DockPanel::Palette));
+        let file_menu_actions = file_menu.draw(&config.recent_files);
+        let generate_menu_actions = generate_menu.draw();
+        let project_menu_actions = project_menu.draw(project.as_ref());
+        let tools_actions = tools_panel.draw(map);
+        let tool_options_actions =
+            tool_options_panel.draw(map, dock.reserved_position(tool_options_panel.width()));
+        if tool_options_actions.sequence_editor_requested {
+            sequence_editor.toggle();
+        }
+        if tool_options_actions.random_brush_editor_requested {
+            random_brush_editor.toggle();
+        }
+        if tool_options_actions.noise_rules_reload_requested {
+            load_noise_stamp(map);
+        }
+        sequence_editor
+            .draw(map.tile_sequence_mut(), palette_panel.selected_sprite(&asset_catalog));
+        random_brush_editor
+            .draw(map.random_brush_mut(), palette_panel.selected_sprite(&asset_catalog));
+        let hovered_info = map
+            .hovered_tile()
+            .and_then(|(tile_x, tile_y)| map.get_full_tile_info(tile_x, tile_y, &asset_catalog));
+        inspector_panel.draw(hovered_info.as_ref());
+        layers_panel.draw(map, dock.rect(DockPanel::Layers));
+        object_panel.draw(map);
+        properties_panel.draw(map, dock.rect(DockPanel::Properties));
+        history_panel.draw(map, dock.rect(DockPanel::History));
+        if panel_actions.view_settings_requested {
+            view_settings_panel.toggle();
+        }
+        let view_settings_actions = view_settings_panel.draw(map.grid_style());
+        if view_settings_actions.reference_image_requested {
+            reference_image_panel.toggle();
+        }
+        reference_image_panel.draw(map);
+        if panel_actions.statistics_requested {
+            statistics_panel.toggle();
+        }
+        if statistics_panel.draw(map, &asset_catalog)
+            && let Some(stats) = statistics_panel.stats()
+        {
+            let map_path = Path::new(palette_panel.map_path());
+            let path = map_path.with_file_name(format!(
+                "{}_stats.csv",
+                map_path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ));
+            match stats.write_csv(&path) {
+                Ok(()) => println!("{} exported!", path.display()),
+                Err(err) => eprintln!("Error exporting statistics: {err}"),
+            }
+        }
+        if ctrl_down()
+            && is_key_pressed(KeyCode::Enter)
+            && let Some(sprite) = hovered_info
+                .as_ref()
+                .and_then(|info| asset_catalog.sprite_by_id(&info.tile_id))
+                .or_else(|| palette_panel.highlighted_sprite(&asset_catalog))
+                .or_else(|| palette_panel.selected_sprite(&asset_catalog))
+        {
+            property_editor.open_for(sprite);
+        }
+        property_editor.draw(&mut asset_catalog);
+        let preferences_actions = preferences_dialog.draw();
+        let new_map_request = new_map_dialog.draw();
+        let resize_map_request = resize_map_dialog.draw();
+        let replace_tiles_request =
+            replace_tiles_dialog.draw(palette_panel.selected_sprite(&asset_catalog));
+        let terrain_action =
+            terrain_dialog.draw(map, palette_panel.selected_sprite(&asset_catalog));
+        let dungeon_action =
+            dungeon_dialog.draw(map, palette_panel.selected_sprite(&asset_catalog));
+        let asset_dirs_request = asset_dirs_dialog.draw(&asset_catalog);
+        // Restoring the autosave resolves its tiles, so it is offered once the catalog loaded.
+        let recovery_choice = if asset_loader.is_none() {
+            recovery_dialog.draw()
+        } else {
+            None
+        };
 
-        let panel_actions: PanelActions = palette_panel.draw(&asset_catalog);
+        let pointer_over_ui = palette_panel.pointer_over_ui()
+            || file_menu.pointer_over_ui()
+            || generate_menu.pointer_over_ui()
+            || project_menu.pointer_over_ui()
+            || tab_bar.pointer_over_ui()
+            || tools_panel.pointer_over_ui()
+            || tool_options_panel.pointer_over_ui()
+            || inspector_panel.pointer_over_ui()
+            || layers_panel.pointer_over_ui()
+            || object_panel.pointer_over_ui()
+            || properties_panel.pointer_over_ui()
+            || history_panel.pointer_over_ui()
+            || view_settings_panel.pointer_over_ui()
+            || reference_image_panel.pointer_over_ui()
+            || statistics_panel.pointer_over_ui()
+            || sequence_editor.pointer_over_ui()
+            || random_brush_editor.pointer_over_ui()
+            || property_editor.pointer_over_ui()
+            || preferences_dialog.pointer_over_ui()
+            || new_map_dialog.pointer_over_ui()
+            || resize_map_dialog.pointer_over_ui()
+            || replace_tiles_dialog.pointer_over_ui()
+            || asset_dirs_dialog.pointer_over_ui()
+            || terrain_dialog.pointer_over_ui()
+            || dungeon_dialog.pointer_over_ui()
+            || recovery_dialog.pointer_over_ui()
+            || minimap.pointer_over_ui()
+            || dock.pointer_over_ui()
+            || status_bar.pointer_over_ui();
+        // Applied by the next frame's camera update, so scrolling a panel never zooms.
+        map.get_camera_controller_mut()
+            .mouse_wheel_enabled = !dialog_open && !pointer_over_ui;
+        if !pointer_over_ui
+            && !palette_panel.is_dragging()
+            && let Some((tile_x, tile_y)) = map.hovered_tile()
+        {
+            let sprite = palette_panel.selected_sprite(&asset_catalog);
+            if replace_tiles_dialog.is_picking() {
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    replace_tiles_dialog.pick_source(map.tile_at(tile_x, tile_y));
+                }
+            } else if map.is_pasting() {
+                map.move_paste(tile_x, tile_y);
+                if is_mouse_button_released(MouseButton::Left) {
+                    map.commit_paste();
+                }
+            } else if ctrl_down() {
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    map.set_paint_mode(PaintMode::Select);
+                    map.smart_select(tile_x, tile_y);
+                }
+            } else if alt_down() {
+                // Eyedropper: the tile under the cursor becomes the brush.
+                if (is_mouse_button_pressed(MouseButton::Left) || is_key_pressed(KeyCode::I))
+                    && let Some(id) = map.tile_at(tile_x, tile_y)
+                    && !palette_panel.pick_tile(&asset_catalog, id)
+                {
+                    println!("Tile {id} is not in the catalog");
+                }
+            } else {
+                match map.paint_mode() {
+                    PaintMode::Paint => {
+                        let stamp = palette_panel.stamp(&asset_catalog);
+                        if !stamp.is_empty() {
+                            if is_mouse_button_pressed(MouseButton::Left) {
+                                map.paint_stamp(tile_x, tile_y, &stamp);
+                            }
+                        } else if is_mouse_button_down(MouseButton::Left)
+                            && let Some(sprite) = sprite
+                        {
+                            map.paint_stroke(tile_x, tile_y, sprite);
+                        }
+                    }
+                    PaintMode::Erase => {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            map.erase_stroke(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::Fill => {
+                        if is_mouse_button_pressed(MouseButton::Left)
+                            && let Some(sprite) = sprite
+                        {
+                            map.fill(tile_x, tile_y, sprite);
+                        }
+                    }
+                    PaintMode::Sequence => {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            map.paint_sequence_tile(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::Random => {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            map.paint_random_tile(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::Select => {
+                        if is_mouse_button_pressed(MouseButton::Left) {
+                            map.select_cell(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::NoiseStamp => {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            map.paint_noise_tile(tile_x, tile_y, &asset_catalog);
+                        }
+                    }
+                    PaintMode::Rectangle | PaintMode::Line => {
+                        if is_mouse_button_pressed(MouseButton::Left)
+                            && let Some(sprite) = sprite
+                        {
+                            map.begin_shape(tile_x, tile_y, sprite);
+                        }
+                        map.update_shape(tile_x, tile_y);
+                    }
+                    PaintMode::Collision => {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            map.paint_collision(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::Terrain => {
+                        if is_mouse_button_down(MouseButton::Left)
+                            && let Some(terrain) = asset_catalog
+                                .category(palette_panel.selected_category())
+                                .and_then(TerrainSet::from_category)
+                        {
+                            map.paint_terrain(tile_x, tile_y, &terrain);
+                        }
+                    }
+                    PaintMode::MagicWand => {
+                        if is_mouse_button_pressed(MouseButton::Left) {
+                            map.magic_wand_select(tile_x, tile_y, shift_down());
+                        }
+                    }
+                    PaintMode::Marquee => {
+                        if is_mouse_button_pressed(MouseButton::Left) {
+                            map.begin_marquee(tile_x, tile_y);
+                        } else if is_mouse_button_down(MouseButton::Left) {
+                            map.update_marquee(tile_x, tile_y);
+                        }
+                    }
+                    PaintMode::Object => {
+                        if is_mouse_button_pressed(MouseButton::Left) {
+                            map.pick_or_place_object();
+                        } else if is_mouse_button_down(MouseButton::Left) {
+                            map.drag_object();
+                        }
+                    }
+                }
+            }
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            map.finish_shape();
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            map.end_stroke();
+            // Read once the stroke is over, so a whole drag counts as one use of its tiles.
+            palette_panel.record_painted(map.take_painted_ids());
+        }
+        if let Some(index) = panel_actions.tile_dropped
+            && !pointer_over_ui
+            && let Some((tile_x, tile_y)) = map.hovered_tile()
+            && let Some(sprite) = palette_panel.tile_sprite(&asset_catalog, index)
+        {
+            map.paint_tile(tile_x, tile_y, sprite);
+        }
+        if right_click.update()
+            && config.secondary_brush_enabled
+            && !pointer_over_ui
+            && let (Some((tile_x, tile_y)), Some(sprite)) =
+                (map.hovered_tile(), palette_panel.secondary_sprite(&asset_catalog))
+        {
+            map.paint_tile(tile_x, tile_y, sprite);
+        }
 
-        if is_mouse_button_down(MouseButton::Left) && !palette_panel.pointer_over_ui() {
-            if let (Some((tile_x, tile_y)), Some(sprite)) =
-                (map.hovered_tile(&camera), palette_panel.selected_sprite(&asset_catalog))
+        if let Some(request) = tools_actions.fill_empty_requested {
+            match palette_panel.selected_sprite(&asset_catalog) {
+                Some(sprite) => {
+                    let filled = map.auto_fill_empty(sprite, request.layer, request.region);
+                    println!("Filled {filled} empty cells");
+                }
+                None => eprintln!("Select a tile before filling empty cells"),
+            }
+        }
+        match recovery_choice {
+            Some(RecoveryChoice::Restore(recovery)) => {
+                // An untouched untitled tab takes the autosave, otherwise it gets a new tab.
+                let reuse_tab = map.file_path().is_none() && !map.is_dirty();
+                let mut restored = map.new_tab();
+                let target = if reuse_tab {
+                    &mut *map
+                } else {
+                    &mut restored
+                };
+                match Autosave::restore(recovery, target, &asset_catalog) {
+                    Ok(()) => {
+                        println!("[autosave] Unsaved edits restored");
+                        if !reuse_tab {
+                            restored_tabs.push(restored);
+                        }
+                    }
+                    Err(err) => eprintln!("[autosave] Could not restore the autosave: {err}"),
+                }
+            }
+            Some(RecoveryChoice::Discard(recovery)) => Autosave::discard(&recovery),
+            None => {}
+        }
+        if panel_actions.save_requested || panel_actions.save_as_requested {
+            let path = match map.file_path() {
+                Some(path) if !panel_actions.save_as_requested => Some(path.to_path_buf()),
+                _ => choose_map_path(&palette_panel, file_dialog::pick_save_path),
+            };
+            if let Some(path) = path {
+                match map.save_to_file(&path) {
+                    Ok(_) => {
+                        println!("{} saved!", path.display());
+                        remember_recent_file(&mut config, &path);
+                    }
+                    Err(err) => eprintln!("Error saving map: {err}"),
+                }
+            }
+        }
+        if panel_actions.load_requested
+            && let Some(path) = choose_map_path(&palette_panel, file_dialog::pick_open_path)
+        {
+            open_requests.push(path);
+        }
+        open_requests.extend(file_menu_actions.open_requested);
+        if file_menu_actions.clear_recent_requested {
+            config.recent_files.clear();
+            if let Err(err) = config.save() {
+                eprintln!("Error saving recent files: {err}");
+            }
+        }
+        if panel_actions.tiled_export_requested {
+            let map_path = Path::new(palette_panel.map_path());
+            let path = match map_path
+                .extension()
+                .and_then(|ext| ext.to_str())
             {
-                map.paint_tile(tile_x, tile_y, sprite);
+                Some("tmj") => map_path.to_path_buf(),
+                _ => map_path.with_extension("tmx"),
+            };
+            match map.export_tiled(&path, &asset_catalog) {
+                Ok(0) => println!("{} exported!", path.display()),
+                Ok(unresolved) => println!(
+                    "{} exported, {unresolved} tiles missing from the catalog left empty",
+                    path.display()
+                ),
+                Err(err) => eprintln!("Error exporting to Tiled: {err}"),
+            }
+        }
+        if let Some(scale) = panel_actions.image_export_requested {
+            let path = Path::new(palette_panel.map_path()).with_extension("png");
+            match map.export_image(&path, scale) {
+                Ok((width, height)) => {
+                    println!("{} exported ({width}x{height} pixels)!", path.display())
+                }
+                Err(err) => eprintln!("Error exporting image: {err}"),
+            }
+        }
+        if panel_actions.csv_export_requested {
+            match map.export_csv_layers(
+                palette_panel.map_path(),
+                &asset_catalog,
+                palette_panel.csv_indices(),
+            ) {
+                Ok((written, unresolved)) => {
+                    for path in &written {
+                        println!("{} exported!", path.display());
+                    }
+                    if unresolved > 0 {
+                        println!("{unresolved} tiles missing from the catalog left empty");
+                    }
+                }
+                Err(err) => eprintln!("Error exporting CSV: {err}"),
+            }
+        }
+        if panel_actions.csv_import_requested {
+            let suggested = Path::new(palette_panel.map_path()).with_extension("csv");
+            let picked = file_dialog::pick_open_path(&suggested).unwrap_or_else(|err| {
+                eprintln!("[dialog] No native file dialog ({err}), using {}", suggested.display());
+                Some(suggested)
+            });
+            if let Some(path) = picked {
+                match map.import_csv_layer(&path, &asset_catalog, palette_panel.csv_indices()) {
+                    Ok(true) => println!("{} loaded into the active layer!", path.display()),
+                    Ok(false) => eprintln!("The active layer is hidden, locked or holds objects"),
+                    Err(err) => eprintln!("Error importing {}: {err}", path.display()),
+                }
+            }
+        }
+        open_requests.extend(
+            get_dropped_files()
+                .into_iter()
+                .filter_map(|dropped| dropped.path),
+        );
+        if panel_actions.migration_requested {
+            match Map::load_id_migration_from_json(Path::new("id_migration.json")) {
+                Ok(migration) => {
+                    let (replaced, missing) = map.apply_id_migration(&migration, &asset_catalog);
+                    println!("id_migration.json applied: {replaced} tiles renamed");
+                    for id in missing {
+                        eprintln!("id_migration.json: {id} is not in the catalog, tiles kept");
+                    }
+                }
+                Err(err) => eprintln!("Error reading id_migration.json: {err}"),
             }
         }
+        if panel_actions.preferences_requested {
+            preferences_dialog.open(&config);
+        }
+        if panel_actions.new_map_requested {
+            new_map_dialog.open(map.dimensions(), map.tile_size(), map.projection());
+        }
+        if let Some(request) = new_map_request {
+            map.reset(request.map_size, request.tile_size, request.projection);
+        }
+        if panel_actions.resize_map_requested {
+            resize_map_dialog.open(map.dimensions());
+        }
+        if panel_actions.replace_tiles_requested {
+            replace_tiles_dialog.open(palette_panel.selected_sprite(&asset_catalog));
+        }
+        if let Some(request) = replace_tiles_request {
+            match asset_catalog.sprite_by_id(&request.to) {
+                Some(sprite) => {
+                    let replaced =
+                        map.replace_tiles(&request.from, sprite, request.scope, map.apply_to());
+                    println!("Replaced {replaced} {} tiles with {}", request.from, request.to);
+                }
+                None => eprintln!("Tile {} is not in the catalog", request.to),
+            }
+        }
+        match resize_map_request {
+            Some(ResizeMapRequest::Resize { width, height, anchor }) => {
+                map.resize(width, height, anchor);
+                println!("Map resized to {width}x{height}");
+            }
+            Some(ResizeMapRequest::CropToContent) => match map.crop_to_content() {
+                Some(region) => println!(
+                    "Map cropped to {}x{} (from column {}, row {})",
+                    region.width, region.height, region.x, region.y
+                ),
+                None => println!("Nothing to crop"),
+            },
+            None => {}
+        }
+        if generate_menu_actions.terrain_requested {
+            dungeon_dialog.close();
+            terrain_dialog.open(map.active_layer());
+        }
+        if generate_menu_actions.dungeon_requested {
+            terrain_dialog.close();
+            dungeon_dialog.open(map.active_layer());
+        }
+        for action in [terrain_action, dungeon_action]
+            .into_iter()
+            .flatten()
+        {
+            apply_generator_action(map, action, &asset_catalog);
+        }
+        hot_reload_assets(&mut asset_catalog, &mut tabs, &mut palette_panel).await;
+        if let Some(loader) = asset_loader.as_mut()
+            && loader
+                .step(ASSET_LOAD_BUDGET_SECS)
+                .await
+            && let Some(loader) = asset_loader.take()
+        {
+            switch_catalog(
+                &mut asset_catalog,
+                loader.finish(),
+                &mut tabs,
+                &mut palette_panel,
+                &config,
+            );
+            if !session_restored {
+                restore_session(&config, tabs.active_mut(), &mut palette_panel, &asset_catalog);
+                session_restored = true;
+            }
+            open_requests.append(&mut deferred_opens);
+        }
+        if preferences_actions.compact_requested {
+            compact_catalog(&mut asset_catalog, &tabs, &mut palette_panel);
+        }
+        if let Some(threshold) = preferences_actions.collision_detection_requested {
+            let (walkable, solid) = asset_catalog.auto_detect_collision(threshold);
+            println!("Collision detected: {walkable} walkable, {solid} solid tiles");
+        }
+        if let Some(mut new_config) = preferences_actions.applied {
+            // The draft was copied when the dialog opened; keep files used since then.
+            new_config.recent_files = config.recent_files.clone();
+            new_config.grid = config.grid;
+            new_config.asset_dirs = config.asset_dirs.clone();
+            new_config.last_project_path = config.last_project_path.clone();
+            if new_config.paint_sound_path != config.paint_sound_path {
+                let sound = match &new_config.paint_sound_path {
+                    Some(path) => load_paint_sound(path).await,
+                    None => None,
+                };
+                for map in tabs.iter_mut() {
+                    map.set_paint_sound(sound.clone());
+                }
+            }
+            for map in tabs.iter_mut() {
+                map.get_camera_controller_mut()
+                    .pan_axis_lock = new_config.pan_axis_lock;
+                map.get_camera_controller_mut()
+                    .smooth_motion = new_config.smooth_camera;
+                map.get_camera_controller_mut()
+                    .wheel_zoom_sensitivity = new_config.wheel_zoom_sensitivity;
+                map.get_camera_controller_mut()
+                    .wheel_pan_sensitivity = new_config.wheel_pan_sensitivity;
+                map.set_paint_cooldown(new_config.paint_cooldown_tiles);
+                map.set_tile_encoding(new_config.tile_encoding());
+            }
+            i18n::set_language(new_config.language);
+            if new_config.ui_theme != config.ui_theme
+                || new_config.ui_scale() != config.ui_scale()
+                || new_config.ui_font_path != config.ui_font_path
+            {
+                ui_skin.apply(&new_config);
+                dock.set_scale(new_config.ui_scale());
+            }
+            if new_config.default_filter_mode != config.default_filter_mode {
+                asset_catalog.set_default_filter_mode(new_config.default_filter_mode);
+            }
+            config = new_config;
+            if let Err(err) = config.save() {
+                eprintln!("Error saving preferences: {err}");
+            }
+        }
+
+        if let Some(style) = view_settings_actions.grid_style {
+            for map in tabs.iter_mut() {
+                map.set_grid_style(style);
+            }
+            config.grid = style;
+        }
+
+        if project_menu_actions.new_requested
+            && let Some(path) = choose_project_path(file_dialog::pick_save_path)
+        {
+            let path = path.with_extension(Project::EXTENSION);
+            let roots: Vec<PathBuf> = asset_catalog
+                .roots()
+                .map(Path::to_path_buf)
+                .collect();
+            let mut created = Project::new(&path, &roots, tabs.active().tile_size(), &config);
+            for map_path in tabs
+                .maps()
+                .iter()
+                .filter_map(Map::file_path)
+            {
+                created.add_map(map_path);
+            }
+            match created.save() {
+                Ok(()) => {
+                    println!("Project {} created", path.display());
+                    config.last_project_path = Some(path);
+                    save_config(&config);
+                    project = Some(created);
+                }
+                Err(err) => eprintln!("Error creating project: {err}"),
+            }
+        }
+        if project_menu_actions.open_requested
+            && let Some(path) = choose_project_path(file_dialog::pick_open_path)
+        {
+            open_requests.push(path);
+        }
+        open_requests.extend(project_menu_actions.map_requested);
+        if let Some(open) = project.as_mut() {
+            let mut changed = false;
+            if project_menu_actions.add_map_requested {
+                match tabs.active().file_path() {
+                    Some(path) => changed = open.add_map(path),
+                    None => eprintln!("Save the map before adding it to the project"),
+                }
+            }
+            if let Some(index) = project_menu_actions.remove_map_requested {
+                open.remove_map(index);
+                changed = true;
+            }
+            if project_menu_actions.save_requested {
+                open.capture(&config);
+                changed = true;
+            }
+            if changed {
+                match open.save() {
+                    Ok(()) => println!("Project {} saved", open.path().display()),
+                    Err(err) => eprintln!("Error saving project: {err}"),
+                }
+            }
+        }
+        if project_menu_actions.asset_dirs_requested {
+            asset_dirs_dialog.open(asset_dirs(project.as_ref(), &config));
+        }
+        if let Some(directories) = asset_dirs_request {
+            match project.as_mut() {
+                Some(project) => {
+                    project.set_asset_dirs(&directories);
+                    if let Err(err) = project.save() {
+                        eprintln!("Error saving project: {err}");
+                    }
+                }
+                None => {
+                    config.asset_dirs = directories;
+                    save_config(&config);
+                }
+            }
+            asset_loader = Some(AssetLoader::new(
+                &asset_dirs(project.as_ref(), &config),
+                tabs.active().tile_size(),
+                config.default_filter_mode,
+            ));
+        }
+        if project_menu_actions.close_requested && project.take().is_some() {
+            config.last_project_path = None;
+            save_config(&config);
+            asset_loader = Some(AssetLoader::new(
+                &config.asset_dirs,
+                DEFAULT_TILE_SIZE,
+                config.default_filter_mode,
+            ));
+        }
 
-        if panel_actions.save_requested {
-            match map.save_to_file("map.json") {
-                Ok(_) => println!("map.json saved!"),
-                Err(err) => eprintln!("Error saving map: {err}"),
+        for map in restored_tabs {
+            tabs.open(map);
+        }
+        if !recovery_dialog.is_open() {
+            autosave.update(tabs.maps(), get_time());
+        }
+        if !open_requests.is_empty() {
+            for path in open_requests {
+                if Project::is_project_file(&path) {
+                    if let Some(opened) = open_project(&path, &mut tabs, &mut config) {
+                        asset_loader = Some(AssetLoader::new(
+                            &opened.asset_dirs(),
+                            opened.tile_size(),
+                            config.default_filter_mode,
+                        ));
+                        deferred_opens.extend(opened.map_paths().into_iter().take(1));
+                        project = Some(opened);
+                    }
+                } else if asset_loader.is_some() {
+                    deferred_opens.push(path);
+                } else {
+                    open_in_tab(&mut tabs, &path, &asset_catalog, &mut config);
+                }
+            }
+            minimap.invalidate();
+        }
+        if tabs.active().file_path() != shown_path.as_deref() {
+            shown_path = tabs
+                .active()
+                .file_path()
+                .map(Path::to_path_buf);
+            if let Some(path) = &shown_path {
+                palette_panel.set_map_path(path);
             }
         }
-        if panel_actions.load_requested {
-            log_map_load_result(map.load_from_file("map.json", &asset_catalog));
+
+        if is_quit_requested() {
+            // Unsaved edits of every tab stay recoverable on the next start; unanswered
+            // recoveries are kept.
+            if !recovery_dialog.is_open() {
+                autosave.write(tabs.maps());
+            }
+            // Quitting before the first load finished keeps the previous session.
+            if session_restored {
+                store_session(&mut config, tabs.active(), &palette_panel, &asset_catalog);
+            }
+            config.dock = *dock.layout();
+            if let Err(err) = config.save() {
+                eprintln!("Error saving session: {err}");
+            }
+            break;
         }
 
         next_frame().await;
     }
 }
 
-struct PalettePanel {
-    selected_category: usize,
-    selected_tile: Option<usize>,
-    preview_columns: usize,
-    tile_preview_size: f32,
-    button_padding: f32,
-    grid_origin: Vec2,
-    window_position: Vec2,
-    pointer_over_ui: bool,
+/// Tells a short right click apart from a right-button pan drag.
+#[derive(Default)]
+struct RightClick {
+    press_position: Option<Vec2>,
+    dragged: bool,
 }
 
-impl PalettePanel {
-    fn new(tile_size: Size) -> Self {
-        Self {
-            selected_category: 0,
-            selected_tile: None,
-            preview_columns: 3,
-            tile_preview_size: tile_size.width.max(8.0),
-            button_padding: 6.0,
-            grid_origin: vec2(10.0, 110.0),
-            window_position: vec2(20.0, 80.0),
-            pointer_over_ui: false,
+impl RightClick {
+    /// Cursor travel, in pixels, after which a right press counts as a drag.
+    const DRAG_THRESHOLD: f32 = 3.0;
+
+    /// Returns `true` on the frame a right click is released without having dragged.
+    fn update(&mut self) -> bool {
+        let mouse = Vec2::from(mouse_position());
+        if is_mouse_button_pressed(MouseButton::Right) {
+            self.press_position = Some(mouse);
+            self.dragged = false;
+        }
+        if let Some(start) = self.press_position
+            && start.distance(mouse) > Self::DRAG_THRESHOLD
+        {
+            self.dragged = true;
         }
+        if is_mouse_button_released(MouseButton::Right) {
+            return self.press_position.take().is_some() && !self.dragged;
+        }
+        false
     }
+}
 
-    fn draw(&mut self, catalog: &AssetCatalog) -> PanelActions {
-        let mut actions = PanelActions::default();
-        self.ensure_selection_bounds(catalog);
-        let panel_height = (screen_height() - 60.0).max(260.0);
-        let panel_size = vec2(280.0, panel_height);
-        let position = self.window_position;
-        let rect = Rect::new(position.x, position.y, panel_size.x, panel_size.y);
+/// Re-applies the tool state recorded by [`store_session`] in the previous run and, when
+/// enabled, reopens its map with the same camera position and zoom.
+fn restore_session(
+    config: &AppConfig, map: &mut Map, palette_panel: &mut PalettePanel, catalog: &AssetCatalog,
+) {
+    if config.reopen_last_session
+        && let Some(path) = &config.last_map_path
+    {
+        match map.load_from_file(path, catalog) {
+            Ok(()) => {
+                let [x, y] = config.last_camera_center;
+                map.get_camera_controller_mut()
+                    .set_view(Vec2::new(x, y), config.last_zoom);
+                println!("Reopened {}", path.display());
+            }
+            Err(err) => eprintln!("Could not reopen {}: {err}", path.display()),
+        }
+    }
+    map.set_paint_mode(PaintMode::from_name(&config.last_paint_mode).unwrap_or_default());
+    map.set_brush_tint(config.last_tint.into());
+    palette_panel.restore_selection(
+        catalog,
+        config.last_selected_category_index,
+        config.last_selected_tile_id.as_deref(),
+    );
+    palette_panel.restore_tile_usage(&config.recent_tiles, &config.tile_paint_counts);
+}
 
-        root_ui().window(hash!("palette_window"), position, panel_size, |ui| {
-            ui.label(None, "Tile Palette");
+fn store_session(
+    config: &mut AppConfig, map: &Map, palette_panel: &PalettePanel, catalog: &AssetCatalog,
+) {
+    config.last_paint_mode = map.paint_mode().name().to_string();
+    config.last_tint = map.brush_tint().into();
+    config.last_map_path = map.file_path().map(Path::to_path_buf);
+    let camera = map.get_camera_controller();
+    config.last_camera_center = Vec2::from(camera.screen_center.clone()).into();
+    config.last_zoom = camera.get_current_zoom();
+    config.last_selected_category_index = palette_panel.selected_category();
+    config.last_selected_tile_id = palette_panel
+        .selected_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    config.recent_tiles = palette_panel.recent_tiles().to_vec();
+    config.tile_paint_counts = palette_panel.paint_counts().clone();
+}
 
-            if catalog.is_empty() {
-                ui.separator();
-                ui.label(None, "No asset tiles were found.");
-                ui.label(None, "Add an `assets` folder next to the executable.");
-                return;
-            }
+/// Reloads changed asset categories, then re-resolves painted tiles and the palette
+/// selection by id since reloaded sprites get new textures and palette indices.
+async fn hot_reload_assets(
+    catalog: &mut AssetCatalog, tabs: &mut MapTabs, palette_panel: &mut PalettePanel,
+) {
+    let selected_id = palette_panel
+        .selected_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    let secondary_id = palette_panel
+        .secondary_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    if !catalog.hot_reload(get_time()).await {
+        return;
+    }
 
-            let category_labels: Vec<&str> = catalog
-                .categories()
-                .iter()
-                .map(|category| category.name.as_str())
-                .collect();
+    palette_panel.restore_selection(
+        catalog,
+        palette_panel.selected_category(),
+        selected_id.as_deref(),
+    );
+    palette_panel.restore_secondary(catalog, secondary_id.as_deref());
 
-            ui.combo_box(
-                hash!("palette_categories"),
-                "Categories",
-                &category_labels,
-                &mut self.selected_category,
-            );
-            ui.separator();
+    let missing: usize = tabs
+        .iter_mut()
+        .map(|map| map.refresh_tiles(catalog))
+        .sum();
+    if missing > 0 {
+        println!("[assets] {missing} painted tiles reference ids that are no longer available");
+    }
+}
 
-            if let Some(category) = catalog.category(self.selected_category) {
-                if category.tiles.is_empty() {
-                    ui.label(None, "No tiles in this category yet.");
-                } else {
-                    ui.label(None, "Pick a tile, then left click on the grid to paint.");
-                    if let Some(index) = self.selected_tile {
-                        if let Some(tile) = category.tiles.get(index) {
-                            ui.label(None, &format!("Selected: {}", tile.name));
-                        }
-                    }
-                    self.draw_tile_grid(ui, category);
-                }
-            }
+/// Frees every palette sprite that is neither painted on an open map nor used by a brush.
+fn compact_catalog(catalog: &mut AssetCatalog, tabs: &MapTabs, palette_panel: &mut PalettePanel) {
+    let selected_id = palette_panel
+        .selected_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    let secondary_id = palette_panel
+        .secondary_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
 
-            ui.separator();
-            if ui.button(None, "Salvar mapa (JSON)") {
-                actions.save_requested = true;
-            }
-            if ui.button(None, "Carregar mapa (JSON)") {
-                actions.load_requested = true;
-            }
-        });
+    let mut used_ids: HashSet<String> = tabs
+        .maps()
+        .iter()
+        .flat_map(|map| map.tile_usage_counts().into_keys())
+        .collect();
+    used_ids.extend(selected_id.clone());
+    used_ids.extend(secondary_id.clone());
+
+    let freed = catalog.compact(&used_ids);
+    palette_panel.restore_selection(catalog, 0, selected_id.as_deref());
+    palette_panel.restore_secondary(catalog, secondary_id.as_deref());
+    println!("Catalog compacted: {freed} unused sprites freed");
+}
 
-        let (mouse_x, mouse_y) = mouse_position();
-        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
-        actions
+/// Spritesheet directories to load: the open project's, else the ones set in the
+/// configuration.
+fn asset_dirs(project: Option<&Project>, config: &AppConfig) -> Vec<PathBuf> {
+    match project {
+        Some(project) => project.asset_dirs(),
+        None => config.asset_dirs.clone(),
     }
+}
 
-    fn draw_tile_grid(&mut self, ui: &mut Ui, category: &AssetCategory) {
-        let columns = self.preview_columns.max(1);
-        let button_edge = self.tile_preview_size + self.button_padding;
-        let mut x = self.grid_origin.x;
-        let mut y = self.grid_origin.y;
+/// Reopens the project of the previous session, applying its preferences to `config`.
+fn reopen_project(config: &mut AppConfig) -> Option<Project> {
+    let path = config
+        .last_project_path
+        .clone()
+        .filter(|_| config.reopen_last_session)?;
+    match Project::load(&path) {
+        Ok(project) => {
+            project.apply_to(config);
+            println!("Reopened project {}", path.display());
+            Some(project)
+        }
+        Err(err) => {
+            eprintln!("Could not reopen project {}: {err}", path.display());
+            None
+        }
+    }
+}
 
-        for (index, tile) in category.tiles.iter().enumerate() {
-            let pressed = widgets::Button::new(tile.texture.clone())
-                .position(vec2(x, y))
-                .size(vec2(button_edge, button_edge))
-                .selected(self.selected_tile == Some(index))
-                .ui(ui);
+/// Opens the project file at `path`: its preferences replace the current ones and an
+/// untitled map without edits takes its tile size. The caller reloads the catalog from its
+/// asset directories before opening its maps.
+fn open_project(path: &Path, tabs: &mut MapTabs, config: &mut AppConfig) -> Option<Project> {
+    let project = match Project::load(path) {
+        Ok(project) => project,
+        Err(err) => {
+            eprintln!("Error opening project {}: {err}", path.display());
+            return None;
+        }
+    };
+    project.apply_to(config);
+    config.last_project_path = Some(path.to_path_buf());
+    save_config(config);
 
-            if pressed {
-                self.selected_tile = Some(index);
-            }
+    let tile_size = project.tile_size();
+    let active = tabs.active_mut();
+    if active.file_path().is_none() && !active.is_dirty() {
+        let (width, height) = active.dimensions();
+        let map_size = Size {
+            width: width as f32,
+            height: height as f32,
+        };
+        active.reset(map_size, tile_size, active.projection());
+    }
+    println!("Project {} opened", path.display());
+    Some(project)
+}
 
-            x += button_edge + self.button_padding;
-            if (index + 1) % columns == 0 {
-                x = self.grid_origin.x;
-                y += button_edge + self.button_padding;
-            }
+/// Replaces `catalog` with `loaded` and points the open maps, the palette selection and the
+/// map settings of `config` at it.
+fn switch_catalog(
+    catalog: &mut AssetCatalog, loaded: AssetCatalog, tabs: &mut MapTabs,
+    palette_panel: &mut PalettePanel, config: &AppConfig,
+) {
+    let selected_id = palette_panel
+        .selected_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    let secondary_id = palette_panel
+        .secondary_sprite(catalog)
+        .map(|sprite| sprite.id.clone());
+    *catalog = loaded;
+    palette_panel.restore_selection(catalog, 0, selected_id.as_deref());
+    palette_panel.restore_secondary(catalog, secondary_id.as_deref());
+    for map in tabs.iter_mut() {
+        map.set_tile_encoding(config.tile_encoding());
+        map.set_grid_style(config.grid);
+        for (extension, format) in &config.map_extensions {
+            map.register_file_extension(extension, *format);
         }
+        map.refresh_tiles(catalog);
     }
+}
 
-    fn pointer_over_ui(&self) -> bool {
-        self.pointer_over_ui
+/// Asks for a project file with the native `pick` dialog, suggesting one in the working
+/// directory; returns `None` when cancelled or when no dialog helper is available.
+fn choose_project_path(pick: fn(&Path) -> std::io::Result<Option<PathBuf>>) -> Option<PathBuf> {
+    let suggested = Path::new("project").with_extension(Project::EXTENSION);
+    pick(&suggested).unwrap_or_else(|err| {
+        eprintln!("[dialog] No native file dialog ({err})");
+        None
+    })
+}
+
+fn save_config(config: &AppConfig) {
+    if let Err(err) = config.save() {
+        eprintln!("Error saving preferences: {err}");
     }
+}
 
-    fn selected_sprite<'a>(&self, catalog: &'a AssetCatalog) -> Option<&'a TileSprite> {
-        let category = catalog.category(self.selected_category)?;
-        let index = self.selected_tile?;
-        category.tiles.get(index)
+/// Records `path` in the recent files and saves the configuration right away, so the list
+/// survives a crash.
+fn remember_recent_file(config: &mut AppConfig, path: &Path) {
+    config.add_recent_file(path);
+    if let Err(err) = config.save() {
+        eprintln!("Error saving recent files: {err}");
     }
+}
 
-    fn ensure_selection_bounds(&mut self, catalog: &AssetCatalog) {
-        let category_count = catalog.categories().len();
-        if category_count == 0 {
-            self.selected_category = 0;
-            self.selected_tile = None;
-            return;
-        }
+fn ctrl_down() -> bool {
+    is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+}
 
-        if self.selected_category >= category_count {
-            self.selected_category = 0;
-            self.selected_tile = None;
-        }
+fn shift_down() -> bool {
+    is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+}
 
-        if let Some(category) = catalog.category(self.selected_category) {
-            if let Some(index) = self.selected_tile {
-                if index >= category.tiles.len() {
-                    self.selected_tile = None;
-                }
+fn alt_down() -> bool {
+    is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt)
+}
+
+/// Opens the map file at `path` in a new tab, or switches to the tab already showing it.
+///
+/// Only registered map formats and Tiled maps are opened. An untitled tab without edits is
+/// replaced instead of being kept next to the opened map.
+fn open_in_tab(tabs: &mut MapTabs, path: &Path, catalog: &AssetCatalog, config: &mut AppConfig) {
+    if let Some(index) = tabs.position(path) {
+        tabs.select(index);
+        return;
+    }
+    let active = tabs.active();
+    if active.format_for(path).is_none() && !tiled::is_tiled_file(path) {
+        eprintln!("Ignoring {:?}: not a map file", path);
+        return;
+    }
+
+    let mut map = active.new_tab();
+    let result = map.load_from_file(path, catalog);
+    let loaded = result.is_ok();
+    log_map_load_result(path, result);
+    if !loaded {
+        return;
+    }
+    remember_recent_file(config, path);
+    if active.file_path().is_none() && !active.is_dirty() {
+        tabs.replace_active(map);
+    } else {
+        tabs.open(map);
+    }
+}
+
+/// Applies the outcome of a generator dialog to `map`.
+fn apply_generator_action(map: &mut Map, action: GeneratorAction, catalog: &AssetCatalog) {
+    match action {
+        GeneratorAction::Preview(tiles) => map.preview_generated(&tiles, catalog),
+        GeneratorAction::Generate { layer, tiles } => {
+            map.clear_generated_preview();
+            match map.generate_layer_tiles(layer, &tiles, catalog) {
+                Ok(true) => println!("Layer generated"),
+                Ok(false) => println!("Cannot generate: layer hidden, locked or not a tile layer"),
+                Err(err) => eprintln!("Could not generate layer: {err}"),
             }
-        } else {
-            self.selected_tile = None;
         }
+        GeneratorAction::Close => map.clear_generated_preview(),
     }
 }
 
-#[derive(Default)]
-struct PanelActions {
-    save_requested: bool,
-    load_requested: bool,
+fn load_noise_stamp(map: &mut Map) {
+    match NoiseStamp::load_from_json(Path::new(NoiseStamp::RULES_FILE)) {
+        Ok(noise_stamp) => {
+            println!(
+                "[noise] Loaded {} rules from {}",
+                noise_stamp.rules.len(),
+                NoiseStamp::RULES_FILE
+            );
+            map.set_noise_stamp(noise_stamp);
+        }
+        Err(err) => eprintln!("[noise] Could not read {}: {err}", NoiseStamp::RULES_FILE),
+    }
+}
+
+/// Title shown above the canvas: the map's file name, `*` marking unsaved edits.
+///
+/// miniquad cannot retitle the window after startup, so the title is drawn as overlay text.
+fn window_title(map: &Map) -> String {
+    format!("ForgeTile - {}", tab_label(map))
+}
+
+/// Asks for a map file with the native `pick` dialog, suggesting the palette's map file.
+///
+/// Falls back to the palette path when no dialog helper is available; returns `None` when
+/// the dialog is cancelled.
+fn choose_map_path(
+    palette_panel: &PalettePanel, pick: fn(&Path) -> std::io::Result<Option<PathBuf>>,
+) -> Option<PathBuf> {
+    let suggested = Path::new(palette_panel.map_path());
+    match pick(suggested) {
+        Ok(picked) => picked,
+        Err(err) => {
+            eprintln!("[dialog] No native file dialog ({err}), using {}", suggested.display());
+            Some(suggested.to_path_buf())
+        }
+    }
 }
 
-fn log_map_load_result(result: Result<(), MapLoadError>) {
+fn log_map_load_result(path: &Path, result: Result<(), MapLoadError>) {
     match result {
-        Ok(_) => println!("Mapa carregado de map.json"),
-        Err(err) => eprintln!("Erro ao carregar mapa: {err}"),
+        Ok(_) => println!("Map loaded from {}", path.display()),
+        Err(err) => eprintln!("Error loading map: {err}"),
+    }
+}
+
+async fn load_paint_sound(path: &Path) -> Option<Sound> {
+    if !cfg!(feature = "audio") {
+        eprintln!("[audio] Paint sound ignored: build with `--features audio` to enable it.");
+        return None;
+    }
+
+    let path_str = path.to_str()?;
+    match load_sound(path_str).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            eprintln!("[audio] Failed to load paint sound {:?}: {err}", path);
+            None
+        }
     }
 }
 
@@ -0,0 +1,149 @@
+use crate::core::assets::{AssetCatalog, AssetDirStatus};
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{Layout, hash, root_ui, widgets};
+use macroquad::window::screen_width;
+use std::path::{Path, PathBuf};
+
+/// Dialog editing the directories the palette loads spritesheets from, with how each one
+/// loaded. The list is applied all at once, reloading the catalog.
+pub struct AssetDirsDialog {
+    open: bool,
+    /// Directories being edited, in load order.
+    directories: Vec<PathBuf>,
+    /// Path typed for the next directory to add.
+    new_directory: String,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl AssetDirsDialog {
+    /// Height of the scrolling directory list.
+    const LIST_HEIGHT: f32 = 150.0;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            directories: Vec::new(),
+            new_directory: String::new(),
+            size: vec2(460.0, 300.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog on the directories currently in use; an empty list stands for the
+    /// `assets` folder found next to the executable.
+    pub fn open(&mut self, directories: Vec<PathBuf>) {
+        self.directories = directories;
+        self.new_directory.clear();
+        self.open = true;
+    }
+
+    /// Draws the dialog, returning the edited directory list when it is applied.
+    pub fn draw(&mut self, catalog: &AssetCatalog) -> Option<Vec<PathBuf>> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let (mut add, mut apply, mut close) = (false, false, false);
+        let (mut raise, mut remove) = (None, None);
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let statuses = catalog.directory_status();
+        let directories = &self.directories;
+        let new_directory = &mut self.new_directory;
+
+        root_ui().window(hash!("asset_dirs_window"), position, self.size, |ui| {
+            ui.label(None, tr("asset_dirs.title"));
+            ui.separator();
+            widgets::Group::new(
+                hash!("asset_dirs_list"),
+                vec2(self.size.x - 20.0, Self::LIST_HEIGHT),
+            )
+            .layout(Layout::Vertical)
+            .ui(ui, |ui| {
+                if directories.is_empty() {
+                    ui.label(None, tr("asset_dirs.default"));
+                    for (path, status) in &statuses {
+                        ui.label(None, &row_label(path, Some(*status)));
+                    }
+                }
+                for (index, directory) in directories.iter().enumerate() {
+                    let status = statuses
+                        .iter()
+                        .find(|(path, _)| path == directory)
+                        .map(|(_, status)| *status);
+                    ui.label(None, &row_label(directory, status));
+                    ui.same_line(0.0);
+                    if index > 0 && ui.button(None, "^") {
+                        raise = Some(index);
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(None, "x") {
+                        remove = Some(index);
+                    }
+                }
+            });
+            widgets::InputText::new(hash!("asset_dirs_new"))
+                .label(tr("asset_dirs.directory"))
+                .size(vec2(300.0, 19.0))
+                .ratio(0.75)
+                .ui(ui, new_directory);
+            ui.same_line(0.0);
+            add = ui.button(None, tr("asset_dirs.add"));
+
+            ui.separator();
+            apply = ui.button(None, tr("common.apply"));
+            ui.same_line(0.0);
+            close = ui.button(None, tr("common.cancel"));
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if let Some(index) = raise {
+            self.directories.swap(index - 1, index);
+        }
+        if let Some(index) = remove {
+            self.directories.remove(index);
+        }
+        let typed = self.new_directory.trim();
+        if add && !typed.is_empty() {
+            let directory = PathBuf::from(typed);
+            if !self.directories.contains(&directory) {
+                self.directories.push(directory);
+            }
+            self.new_directory.clear();
+        }
+        if apply || close {
+            self.open = false;
+        }
+        apply.then(|| self.directories.clone())
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Directory followed by how it loaded; `None` for a directory added since the catalog
+/// was last loaded.
+fn row_label(path: &Path, status: Option<AssetDirStatus>) -> String {
+    let status = match status {
+        Some(AssetDirStatus::Missing) => tr("asset_dirs.missing").to_string(),
+        Some(AssetDirStatus::Loaded { categories, tiles }) => trf(
+            "asset_dirs.loaded",
+            &[
+                ("categories", &categories),
+                ("tiles", &tiles),
+            ],
+        ),
+        None => tr("asset_dirs.pending").to_string(),
+    };
+    format!("{} - {status}", path.display())
+}
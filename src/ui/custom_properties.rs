@@ -0,0 +1,248 @@
+use crate::core::map::map::Map;
+use crate::core::map::property::{Properties, PropertyType};
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// What the custom properties window edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropertyTarget {
+    #[default]
+    Map,
+    /// The active layer.
+    Layer,
+    /// The single selected cell of the active layer.
+    Cell,
+}
+
+impl PropertyTarget {
+    const ALL: [PropertyTarget; 3] = [
+        PropertyTarget::Map,
+        PropertyTarget::Layer,
+        PropertyTarget::Cell,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            PropertyTarget::Map => tr("custom_properties.map"),
+            PropertyTarget::Layer => tr("custom_properties.layer"),
+            PropertyTarget::Cell => tr("custom_properties.cell"),
+        }
+    }
+}
+
+/// Exact owner of the properties shown, so the rows reload when it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    Map,
+    Layer(usize),
+    Cell(usize, usize, usize),
+}
+
+/// Window (`P`) editing the typed custom properties of the map, the active layer or the
+/// selected cell.
+pub struct PropertiesPanel {
+    visible: bool,
+    target: PropertyTarget,
+    /// Properties as last loaded into or applied from the rows.
+    shown: Option<(Owner, Properties)>,
+    /// Editable `(key, type index, value)` rows; rows with an empty key or a value that
+    /// does not parse as their type are not applied.
+    rows: Vec<(String, usize, String)>,
+    pointer_over_ui: bool,
+}
+
+impl PropertiesPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            target: PropertyTarget::default(),
+            shown: None,
+            rows: Vec::new(),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Draws the window inside `area`, applying every change to the target as it is typed.
+    pub fn draw(&mut self, map: &mut Map, area: Option<Rect>) {
+        let Some(rect) = area.filter(|_| self.visible) else {
+            self.pointer_over_ui = false;
+            return;
+        };
+
+        let current = Self::current(map, self.target);
+        match &current {
+            Some(current) if self.shown.as_ref() != Some(current) => self.load(current),
+            None => self.shown = None,
+            _ => {}
+        }
+
+        let mut target = self.target;
+        let mut remove_index = None;
+
+        widgets::Window::new(hash!("custom_properties_window"), rect.point(), rect.size())
+            .titlebar(false)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                for (index, option) in PropertyTarget::ALL
+                    .into_iter()
+                    .enumerate()
+                {
+                    if index > 0 {
+                        ui.same_line(0.0);
+                    }
+                    let label = if option == self.target {
+                        format!("> {}", option.name())
+                    } else {
+                        option.name().to_string()
+                    };
+                    if ui.button(None, label.as_str()) {
+                        target = option;
+                    }
+                }
+                ui.separator();
+
+                let Some((owner, _)) = current else {
+                    ui.label(None, tr("custom_properties.select_cell"));
+                    return;
+                };
+                ui.label(
+                    None,
+                    &match owner {
+                        Owner::Map => tr("custom_properties.whole_map").to_string(),
+                        Owner::Layer(layer) => {
+                            trf("custom_properties.layer_owner", &[("layer", &layer)])
+                        }
+                        Owner::Cell(layer, x, y) => trf(
+                            "custom_properties.cell_owner",
+                            &[("x", &x), ("y", &y), ("layer", &layer)],
+                        ),
+                    },
+                );
+                ui.label(None, tr("common.key"));
+                ui.same_line(125.0);
+                ui.label(None, tr("common.type"));
+                ui.same_line(200.0);
+                ui.label(None, tr("common.value"));
+                for (index, (key, kind, value)) in self.rows.iter_mut().enumerate() {
+                    widgets::InputText::new(hash!("custom_property_key", index))
+                        .size(vec2(110.0, 19.0))
+                        .ratio(1.0)
+                        .ui(ui, key);
+                    ui.same_line(125.0);
+                    // Clicking the type cycles through the types.
+                    if ui.button(None, PropertyType::ALL[*kind].name()) {
+                        *kind = (*kind + 1) % PropertyType::ALL.len();
+                    }
+                    ui.same_line(200.0);
+                    widgets::InputText::new(hash!("custom_property_value", index))
+                        .size(vec2(120.0, 19.0))
+                        .ratio(1.0)
+                        .ui(ui, value);
+                    ui.same_line(325.0);
+                    if PropertyType::ALL[*kind]
+                        .parse(value)
+                        .is_none()
+                    {
+                        ui.label(None, "?");
+                        ui.same_line(340.0);
+                    }
+                    if ui.button(None, "x") {
+                        remove_index = Some(index);
+                    }
+                }
+                if ui.button(None, tr("common.add_property")) {
+                    self.rows
+                        .push((String::new(), 0, String::new()));
+                }
+            });
+
+        if let Some(index) = remove_index {
+            self.rows.remove(index);
+        }
+        if let Some((owner, properties)) = &current {
+            let edited = self.edited();
+            if edited != *properties {
+                Self::apply(map, *owner, edited.clone());
+            }
+            self.shown = Some((*owner, edited));
+        }
+        if target != self.target {
+            self.target = target;
+            self.shown = None;
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    /// Owner and properties `target` currently refers to; `None` for [`PropertyTarget::Cell`]
+    /// without a single selected painted cell.
+    fn current(map: &Map, target: PropertyTarget) -> Option<(Owner, Properties)> {
+        let layer = map.active_layer();
+        match target {
+            PropertyTarget::Map => Some((Owner::Map, map.properties().clone())),
+            PropertyTarget::Layer => {
+                Some((Owner::Layer(layer), map.layers()[layer].properties.clone()))
+            }
+            PropertyTarget::Cell => {
+                let (x, y) = map.selected_cell()?;
+                let properties = map.cell_properties(x, y)?.clone();
+                Some((Owner::Cell(layer, x, y), properties))
+            }
+        }
+    }
+
+    fn load(&mut self, (owner, properties): &(Owner, Properties)) {
+        self.rows = properties
+            .iter()
+            .map(|(key, value)| {
+                let kind = PropertyType::ALL
+                    .iter()
+                    .position(|kind| *kind == value.kind())
+                    .unwrap_or(0);
+                (key.clone(), kind, value.to_string())
+            })
+            .collect();
+        self.shown = Some((*owner, properties.clone()));
+    }
+
+    /// Properties described by the valid rows.
+    fn edited(&self) -> Properties {
+        self.rows
+            .iter()
+            .filter(|(key, _, _)| !key.trim().is_empty())
+            .filter_map(|(key, kind, value)| {
+                let value = PropertyType::ALL[*kind].parse(value)?;
+                Some((key.trim().to_string(), value))
+            })
+            .collect()
+    }
+
+    fn apply(map: &mut Map, owner: Owner, properties: Properties) {
+        match owner {
+            Owner::Map => map.set_properties(properties),
+            Owner::Layer(layer) => {
+                if let Some(layer) = map.layer_mut(layer) {
+                    layer.properties = properties;
+                }
+            }
+            Owner::Cell(_, x, y) => {
+                map.set_cell_properties(x, y, properties);
+            }
+        }
+    }
+}
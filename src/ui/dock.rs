@@ -0,0 +1,331 @@
+use crate::core::config::dock::{DockLayout, DockPanel, DockSide};
+use crate::ui::i18n::tr;
+use crate::ui::overlay::OverlayText;
+use crate::ui::status_bar::StatusBar;
+use macroquad::color::{Color, LIGHTGRAY, WHITE};
+use macroquad::input::{
+    MouseButton, is_mouse_button_down, is_mouse_button_pressed, mouse_position,
+};
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::shapes::{draw_rectangle, draw_rectangle_lines};
+use macroquad::window::{screen_height, screen_width};
+
+/// Panel edge dragged to resize it.
+#[derive(Debug, Clone, Copy)]
+enum ResizeEdge {
+    /// The edge facing the middle of the window.
+    Inner,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ResizeDrag {
+    panel: DockPanel,
+    edge: ResizeEdge,
+    start_mouse: Vec2,
+    start_size: f32,
+}
+
+/// Stacks the dockable panels along the left and right window edges.
+///
+/// Every panel gets a header with buttons to collapse it and to move it to the other side;
+/// dragging its inner or bottom edge resizes it. When a side runs out of room its panels
+/// shrink proportionally.
+pub struct Dock {
+    layout: DockLayout,
+    /// `(panel, header, body)` of every shown panel; the body is `None` when collapsed.
+    frames: Vec<(DockPanel, Rect, Option<Rect>)>,
+    /// Height kept free below the panels of one side.
+    reserved: Option<(DockSide, f32)>,
+    /// Top edge of the reserved space.
+    reserved_top: f32,
+    resize: Option<ResizeDrag>,
+    /// UI scale applied to the headers, buttons and grips.
+    scale: f32,
+    pointer_over_ui: bool,
+}
+
+impl Dock {
+    /// Top edge of the first panel of each side, below the menu and tab bar.
+    const TOP: f32 = 80.0;
+    /// Distance kept from the left and right window edges.
+    const MARGIN: f32 = 20.0;
+    const GAP: f32 = 10.0;
+    const HEADER_HEIGHT: f32 = 20.0;
+    const BUTTON_SIZE: f32 = 16.0;
+    /// Thickness of the draggable band along the resizable edges.
+    const GRIP: f32 = 6.0;
+    const MIN_WIDTH: f32 = 160.0;
+    const MAX_WIDTH: f32 = 520.0;
+    const MIN_HEIGHT: f32 = 60.0;
+    const MAX_HEIGHT: f32 = 1200.0;
+
+    pub fn new(layout: DockLayout) -> Self {
+        Self {
+            layout,
+            frames: Vec::new(),
+            reserved: None,
+            reserved_top: Self::TOP,
+            resize: None,
+            scale: 1.0,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Sets the UI scale of the panel headers, see [`AppConfig::ui_scale`].
+    ///
+    /// [`AppConfig::ui_scale`]: crate::core::config::AppConfig::ui_scale
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    /// Current placement of every panel, as saved in the config.
+    pub fn layout(&self) -> &DockLayout {
+        &self.layout
+    }
+
+    /// Side `panel` is docked on.
+    pub fn side(&self, panel: DockPanel) -> DockSide {
+        self.layout.placement(panel).side
+    }
+
+    /// Keeps `height` pixels free below the panels docked on `side`, for a window that
+    /// follows them (the tool options).
+    pub fn reserve_below(&mut self, side: DockSide, height: f32) {
+        self.reserved = Some((side, height));
+    }
+
+    /// Top-left corner of the space kept by [`Dock::reserve_below`] for a `width` wide window.
+    pub fn reserved_position(&self, width: f32) -> Vec2 {
+        let side = self
+            .reserved
+            .map_or(DockSide::Left, |(side, _)| side);
+        vec2(Self::column_x(side, width), self.reserved_top)
+    }
+
+    /// Screen x of a `width` wide panel docked on `side`.
+    fn column_x(side: DockSide, width: f32) -> f32 {
+        match side {
+            DockSide::Left => Self::MARGIN,
+            DockSide::Right => (screen_width() - width - Self::MARGIN).max(0.0),
+        }
+    }
+
+    /// Lays out this frame's headers and bodies of the panels for which `shown` is true.
+    pub fn arrange(&mut self, shown: impl Fn(DockPanel) -> bool) {
+        self.frames.clear();
+        for side in [DockSide::Left, DockSide::Right] {
+            let panels: Vec<DockPanel> = DockPanel::ALL
+                .into_iter()
+                .filter(|&panel| shown(panel) && self.side(panel) == side)
+                .collect();
+            let reserved = match self.reserved {
+                Some((reserved_side, height)) if reserved_side == side => height + Self::GAP,
+                _ => 0.0,
+            };
+            let bottom = screen_height() - StatusBar::HEIGHT - Self::GAP - reserved;
+            let header_height = Self::HEADER_HEIGHT * self.scale;
+            let headers = panels.len() as f32 * (header_height + Self::GAP);
+            let wanted: f32 = panels
+                .iter()
+                .map(|&panel| self.layout.placement(panel))
+                .filter(|placement| !placement.collapsed)
+                .map(|placement| placement.height)
+                .sum();
+            let room = (bottom - Self::TOP - headers).max(0.0);
+            let scale = if wanted > room {
+                room / wanted
+            } else {
+                1.0
+            };
+
+            let mut y = Self::TOP;
+            for panel in panels {
+                let placement = *self.layout.placement(panel);
+                let x = Self::column_x(side, placement.width);
+                let header = Rect::new(x, y, placement.width, header_height);
+                y += header_height;
+                let body = (!placement.collapsed).then(|| {
+                    let height = (placement.height * scale).max(Self::MIN_HEIGHT);
+                    let body = Rect::new(x, y, placement.width, height);
+                    y += height;
+                    body
+                });
+                y += Self::GAP;
+                self.frames.push((panel, header, body));
+            }
+            if matches!(self.reserved, Some((reserved_side, _)) if reserved_side == side) {
+                self.reserved_top = y;
+            }
+        }
+    }
+
+    /// Screen area of a panel's contents, or `None` when it is hidden or collapsed.
+    pub fn rect(&self, panel: DockPanel) -> Option<Rect> {
+        self.frames
+            .iter()
+            .find(|(shown, _, _)| *shown == panel)
+            .and_then(|(_, _, body)| *body)
+    }
+
+    /// Draws the panel headers and handles their buttons and the resize drags.
+    pub fn draw(&mut self, overlay_text: &OverlayText) {
+        let mouse = Vec2::from(mouse_position());
+        let pressed = is_mouse_button_pressed(MouseButton::Left);
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.resize = None;
+        }
+        if let Some(drag) = self.resize {
+            let placement = self.layout.placement_mut(drag.panel);
+            let delta = mouse - drag.start_mouse;
+            match drag.edge {
+                ResizeEdge::Inner => {
+                    let grow = match placement.side {
+                        DockSide::Left => delta.x,
+                        DockSide::Right => -delta.x,
+                    };
+                    placement.width =
+                        (drag.start_size + grow).clamp(Self::MIN_WIDTH, Self::MAX_WIDTH);
+                }
+                ResizeEdge::Bottom => {
+                    placement.height =
+                        (drag.start_size + delta.y).clamp(Self::MIN_HEIGHT, Self::MAX_HEIGHT);
+                }
+            }
+        }
+
+        let scale = self.scale;
+        let button_size = Self::BUTTON_SIZE * scale;
+        let grip = Self::GRIP * scale;
+        let mut pointer_over_ui = self.resize.is_some();
+        let mut collapse_toggled = None;
+        let mut side_switched = None;
+        for &(panel, header, body) in &self.frames {
+            let placement = self.layout.placement(panel);
+            draw_rectangle(
+                header.x,
+                header.y,
+                header.w,
+                header.h,
+                Color { r: 0.15, g: 0.15, b: 0.18, a: 0.95 },
+            );
+            overlay_text.draw(
+                panel_title(panel),
+                header.x + 6.0 * scale,
+                header.y + 14.0 * scale,
+                1.2 * scale,
+                LIGHTGRAY,
+            );
+
+            let collapse_button = Rect::new(
+                header.right() - button_size - 2.0 * scale,
+                header.y + 2.0 * scale,
+                button_size,
+                button_size,
+            );
+            let side_button = Rect::new(
+                collapse_button.x - button_size - 4.0 * scale,
+                collapse_button.y,
+                button_size,
+                button_size,
+            );
+            let collapse_label = if placement.collapsed {
+                "+"
+            } else {
+                "-"
+            };
+            let side_label = match placement.side {
+                DockSide::Left => ">",
+                DockSide::Right => "<",
+            };
+            for (button, label) in [
+                (collapse_button, collapse_label),
+                (side_button, side_label),
+            ] {
+                draw_header_button(overlay_text, button, label, button.contains(mouse), scale);
+            }
+            pointer_over_ui |= header.contains(mouse);
+            if pressed && collapse_button.contains(mouse) {
+                collapse_toggled = Some(panel);
+            } else if pressed && side_button.contains(mouse) {
+                side_switched = Some(panel);
+            }
+
+            let Some(body) = body else {
+                continue;
+            };
+            let inner_grip = match placement.side {
+                DockSide::Left => Rect::new(body.right(), body.y, grip, body.h),
+                DockSide::Right => Rect::new(body.x - grip, body.y, grip, body.h),
+            };
+            let bottom_grip = Rect::new(body.x, body.bottom(), body.w, grip);
+            for (grip_rect, edge, start_size) in [
+                (inner_grip, ResizeEdge::Inner, placement.width),
+                (bottom_grip, ResizeEdge::Bottom, placement.height),
+            ] {
+                if !grip_rect.contains(mouse) {
+                    continue;
+                }
+                pointer_over_ui = true;
+                draw_rectangle(
+                    grip_rect.x,
+                    grip_rect.y,
+                    grip_rect.w,
+                    grip_rect.h,
+                    Color { a: 0.4, ..WHITE },
+                );
+                if pressed && self.resize.is_none() {
+                    self.resize = Some(ResizeDrag {
+                        panel,
+                        edge,
+                        start_mouse: mouse,
+                        // Resizing starts from the size shown, which may have been shrunk.
+                        start_size: match edge {
+                            ResizeEdge::Inner => start_size,
+                            ResizeEdge::Bottom => body.h,
+                        },
+                    });
+                }
+            }
+        }
+
+        if let Some(panel) = collapse_toggled {
+            let placement = self.layout.placement_mut(panel);
+            placement.collapsed = !placement.collapsed;
+        }
+        if let Some(panel) = side_switched {
+            let placement = self.layout.placement_mut(panel);
+            placement.side = placement.side.opposite();
+        }
+        self.pointer_over_ui = pointer_over_ui;
+    }
+
+    /// Returns `true` while the cursor is over a header or a resize grip, or resizing.
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Title shown in the panel's dock header.
+fn panel_title(panel: DockPanel) -> &'static str {
+    match panel {
+        DockPanel::Palette => tr("dock.palette"),
+        DockPanel::Layers => tr("dock.layers"),
+        DockPanel::Properties => tr("dock.properties"),
+        DockPanel::History => tr("dock.history"),
+        DockPanel::Minimap => tr("dock.minimap"),
+    }
+}
+
+fn draw_header_button(
+    overlay_text: &OverlayText, rect: Rect, label: &str, hovered: bool, scale: f32,
+) {
+    let shade = if hovered {
+        0.35
+    } else {
+        0.25
+    };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::new(shade, shade, shade + 0.03, 1.0));
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, Color { a: 0.5, ..WHITE });
+    overlay_text.draw(label, rect.x + 4.0 * scale, rect.y + 12.0 * scale, 1.2 * scale, WHITE);
+}
@@ -0,0 +1,171 @@
+use crate::core::assets::TileSprite;
+use crate::core::map::map::Map;
+use crate::core::tools::dungeon_gen::DungeonGenerator;
+use crate::ui::generate_menu::GeneratorAction;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::rand;
+use macroquad::ui::{hash, root_ui, widgets};
+use macroquad::window::screen_width;
+
+/// Dialog filling a tile layer with rooms and corridors, previewed on the map while it is
+/// open.
+pub struct DungeonGeneratorDialog {
+    open: bool,
+    generator: DungeonGenerator,
+    seed_input: String,
+    layer: usize,
+    /// Generator the map preview was last computed from.
+    previewed: Option<DungeonGenerator>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl DungeonGeneratorDialog {
+    /// Room sides offered by the minimum room size slider.
+    const ROOM_SIZES: std::ops::Range<f32> = 2.0..16.0;
+
+    pub fn new() -> Self {
+        let generator = DungeonGenerator::default();
+        Self {
+            open: false,
+            seed_input: generator.seed.to_string(),
+            generator,
+            layer: 0,
+            previewed: None,
+            size: vec2(360.0, 300.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog targeting `active_layer`; the settings of the last use are kept.
+    pub fn open(&mut self, active_layer: usize) {
+        self.layer = active_layer;
+        self.previewed = None;
+        self.open = true;
+    }
+
+    /// Closes the dialog without generating, e.g. when another generator is opened.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Draws the dialog; `selected` is the palette tile offered for the floor and walls.
+    pub fn draw(&mut self, map: &Map, selected: Option<&TileSprite>) -> Option<GeneratorAction> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let (mut generate, mut close, mut reroll) = (false, false, false);
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let layer_names: Vec<&str> = map
+            .layers()
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect();
+        self.layer = self
+            .layer
+            .min(layer_names.len().saturating_sub(1));
+        let mut depth = self.generator.depth as f32;
+        let mut min_room_size = self.generator.min_room_size as f32;
+        let mut corridor_width = self.generator.corridor_width as f32;
+        let generator = &mut self.generator;
+        let (seed_input, layer) = (&mut self.seed_input, &mut self.layer);
+
+        root_ui().window(hash!("dungeon_generator_window"), position, self.size, |ui| {
+            ui.label(None, tr("dungeon.title"));
+            ui.separator();
+            ui.input_text(hash!("dungeon_seed"), tr("generator.seed"), seed_input);
+            ui.same_line(0.0);
+            reroll = ui.button(None, tr("generator.random_seed"));
+            ui.slider(
+                hash!("dungeon_depth"),
+                tr("dungeon.depth"),
+                1.0..DungeonGenerator::MAX_DEPTH as f32,
+                &mut depth,
+            );
+            ui.slider(
+                hash!("dungeon_min_room_size"),
+                tr("dungeon.min_room_size"),
+                Self::ROOM_SIZES,
+                &mut min_room_size,
+            );
+            ui.slider(
+                hash!("dungeon_corridor_width"),
+                tr("dungeon.corridor_width"),
+                1.0..DungeonGenerator::MAX_CORRIDOR_WIDTH as f32,
+                &mut corridor_width,
+            );
+            ui.combo_box(hash!("dungeon_layer"), tr("generator.layer"), &layer_names, layer);
+
+            let tiles = [
+                (tr("dungeon.floor"), &mut generator.floor_tile),
+                (tr("dungeon.wall"), &mut generator.wall_tile),
+            ];
+            for (index, (label, tile_id)) in tiles.into_iter().enumerate() {
+                ui.separator();
+                widgets::InputText::new(hash!("dungeon_tile", index))
+                    .label(label)
+                    .size(vec2(200.0, 19.0))
+                    .ratio(0.6)
+                    .ui(ui, tile_id);
+                if let Some(sprite) = selected {
+                    ui.same_line(0.0);
+                    if ui.button(None, tr("generator.use_selected")) {
+                        *tile_id = sprite.id.clone();
+                    }
+                }
+            }
+            if selected.is_none() {
+                ui.label(None, tr("common.select_palette_tile"));
+            }
+
+            ui.separator();
+            generate = ui.button(None, tr("generator.generate"));
+            ui.same_line(0.0);
+            close = ui.button(None, tr("common.cancel"));
+        });
+
+        if reroll {
+            self.generator.seed = rand::rand();
+            self.seed_input = self.generator.seed.to_string();
+        } else if let Ok(seed) = self.seed_input.trim().parse() {
+            self.generator.seed = seed;
+        }
+        self.generator.depth = (depth.round() as u32).clamp(1, DungeonGenerator::MAX_DEPTH);
+        self.generator.min_room_size = min_room_size.round().max(1.0) as usize;
+        self.generator.corridor_width =
+            (corridor_width.round() as usize).clamp(1, DungeonGenerator::MAX_CORRIDOR_WIDTH);
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        let (width, height) = map.dimensions();
+        if generate {
+            self.open = false;
+            return Some(GeneratorAction::Generate {
+                layer: self.layer,
+                tiles: self.generator.generate(width, height),
+            });
+        }
+        if close {
+            self.open = false;
+            return Some(GeneratorAction::Close);
+        }
+        if self.previewed.as_ref() == Some(&self.generator) {
+            return None;
+        }
+        self.previewed = Some(self.generator.clone());
+        Some(GeneratorAction::Preview(self.generator.generate(width, height)))
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,130 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Which file picker to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialogKind {
+    Open,
+    Save,
+}
+
+/// Asks for an existing map file, starting next to `current`.
+///
+/// The native picker is run through the platform's dialog helper: `zenity` or `kdialog` on
+/// Linux, AppleScript on macOS and PowerShell on Windows. Returns `Ok(None)` when the user
+/// cancels and an error when no helper could be started.
+pub fn pick_open_path(current: &Path) -> io::Result<Option<PathBuf>> {
+    pick(current, DialogKind::Open)
+}
+
+/// Asks where to save the map, suggesting `current`. See [`pick_open_path`].
+pub fn pick_save_path(current: &Path) -> io::Result<Option<PathBuf>> {
+    pick(current, DialogKind::Save)
+}
+
+fn pick(current: &Path, kind: DialogKind) -> io::Result<Option<PathBuf>> {
+    let current = std::path::absolute(current).unwrap_or_else(|_| current.to_path_buf());
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no file dialog helper found");
+
+    for mut command in helper_commands(&current, kind) {
+        match command.output() {
+            Ok(output) if output.status.success() => {
+                let picked = String::from_utf8_lossy(&output.stdout)
+                    .trim()
+                    .to_string();
+                return Ok((!picked.is_empty()).then(|| PathBuf::from(picked)));
+            }
+            // The helper ran and the dialog was dismissed.
+            Ok(_) => return Ok(None),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
+/// Dialog helpers to try in order, each printing the chosen path on stdout.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn helper_commands(current: &Path, kind: DialogKind) -> Vec<Command> {
+    let mut zenity = Command::new("zenity");
+    zenity
+        .arg("--file-selection")
+        .arg(format!("--filename={}", current.display()));
+    if kind == DialogKind::Save {
+        zenity.arg("--save");
+    }
+
+    let mut kdialog = Command::new("kdialog");
+    kdialog
+        .arg(match kind {
+            DialogKind::Open => "--getopenfilename",
+            DialogKind::Save => "--getsavefilename",
+        })
+        .arg(current);
+
+    vec![zenity, kdialog]
+}
+
+#[cfg(target_os = "macos")]
+fn helper_commands(current: &Path, kind: DialogKind) -> Vec<Command> {
+    let quote = |value: &str| {
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+    };
+    let directory = current
+        .parent()
+        .map(|parent| quote(&parent.to_string_lossy()))
+        .unwrap_or_default();
+    let script = match kind {
+        DialogKind::Open => format!(
+            "POSIX path of (choose file with prompt \"Open map\" \
+             default location POSIX file \"{directory}\")"
+        ),
+        DialogKind::Save => format!(
+            "POSIX path of (choose file name with prompt \"Save map\" \
+             default name \"{}\" default location POSIX file \"{directory}\")",
+            quote(
+                &current
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            )
+        ),
+    };
+
+    let mut osascript = Command::new("osascript");
+    osascript.arg("-e").arg(script);
+    vec![osascript]
+}
+
+#[cfg(windows)]
+fn helper_commands(current: &Path, kind: DialogKind) -> Vec<Command> {
+    let quote = |value: &str| value.replace('\'', "''");
+    let dialog = match kind {
+        DialogKind::Open => "OpenFileDialog",
+        DialogKind::Save => "SaveFileDialog",
+    };
+    let directory = current
+        .parent()
+        .map(|parent| quote(&parent.to_string_lossy()))
+        .unwrap_or_default();
+    let file_name = quote(
+        &current
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy(),
+    );
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; \
+         $dialog = New-Object System.Windows.Forms.{dialog}; \
+         $dialog.InitialDirectory = '{directory}'; $dialog.FileName = '{file_name}'; \
+         if ($dialog.ShowDialog() -eq 'OK') {{ $dialog.FileName }}"
+    );
+
+    let mut powershell = Command::new("powershell");
+    powershell
+        .args(["-NoProfile", "-Command"])
+        .arg(script);
+    vec![powershell]
+}
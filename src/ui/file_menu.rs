@@ -0,0 +1,99 @@
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use std::path::{Path, PathBuf};
+
+/// Collapsible "File" window at the top of the screen listing recently used map files.
+pub struct FileMenu {
+    expanded: bool,
+    position: Vec2,
+    width: f32,
+    pointer_over_ui: bool,
+}
+
+impl FileMenu {
+    /// Height of the collapsed window and of each extra row.
+    const ROW_HEIGHT: f32 = 24.0;
+
+    pub fn new() -> Self {
+        Self {
+            expanded: false,
+            position: vec2(540.0, 4.0),
+            width: 300.0,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the menu; picking an entry collapses it.
+    pub fn draw(&mut self, recent_files: &[PathBuf]) -> FileMenuActions {
+        let mut actions = FileMenuActions::default();
+        let rows = if self.expanded {
+            recent_files.len().max(1) + 2
+        } else {
+            0
+        };
+        let size = vec2(self.width, Self::ROW_HEIGHT * (rows as f32 + 1.5));
+        let rect = Rect::new(self.position.x, self.position.y, size.x, size.y);
+        let mut toggle = false;
+
+        root_ui().window(hash!("file_menu_window"), self.position, size, |ui| {
+            let label = if self.expanded {
+                tr("file_menu.expanded")
+            } else {
+                tr("file_menu.collapsed")
+            };
+            toggle = ui.button(None, label);
+            if !self.expanded {
+                return;
+            }
+
+            ui.label(None, tr("file_menu.recent_files"));
+            if recent_files.is_empty() {
+                ui.label(None, tr("file_menu.none_yet"));
+            }
+            for path in recent_files {
+                if ui.button(None, recent_label(path).as_str()) {
+                    actions.open_requested = Some(path.clone());
+                }
+            }
+            if !recent_files.is_empty() && ui.button(None, tr("file_menu.clear_recent")) {
+                actions.clear_recent_requested = true;
+            }
+        });
+
+        if toggle || actions.open_requested.is_some() || actions.clear_recent_requested {
+            self.expanded = toggle && !self.expanded;
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the file menu during the current frame.
+#[derive(Default)]
+pub struct FileMenuActions {
+    /// Recent map file to open.
+    pub open_requested: Option<PathBuf>,
+    pub clear_recent_requested: bool,
+}
+
+/// File name followed by its folder, so maps with the same name stay distinguishable.
+fn recent_label(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    match path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        Some(parent) => format!("{name} ({})", parent.display()),
+        None => name,
+    }
+}
@@ -0,0 +1,87 @@
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+
+/// Collapsible "Generate" window at the top of the screen opening the procedural
+/// generators.
+pub struct GenerateMenu {
+    expanded: bool,
+    position: Vec2,
+    width: f32,
+    pointer_over_ui: bool,
+}
+
+impl GenerateMenu {
+    /// Height of the collapsed window and of each extra row.
+    const ROW_HEIGHT: f32 = 24.0;
+
+    pub fn new() -> Self {
+        Self {
+            expanded: false,
+            position: vec2(420.0, 4.0),
+            width: 115.0,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the menu; picking an entry collapses it.
+    pub fn draw(&mut self) -> GenerateMenuActions {
+        let mut actions = GenerateMenuActions::default();
+        let rows = if self.expanded {
+            2
+        } else {
+            0
+        };
+        let size = vec2(self.width, Self::ROW_HEIGHT * (rows as f32 + 1.5));
+        let rect = Rect::new(self.position.x, self.position.y, size.x, size.y);
+        let mut toggle = false;
+
+        root_ui().window(hash!("generate_menu_window"), self.position, size, |ui| {
+            let label = if self.expanded {
+                tr("generate_menu.expanded")
+            } else {
+                tr("generate_menu.collapsed")
+            };
+            toggle = ui.button(None, label);
+            if !self.expanded {
+                return;
+            }
+            actions.terrain_requested = ui.button(None, tr("generate_menu.terrain"));
+            actions.dungeon_requested = ui.button(None, tr("generate_menu.dungeon"));
+        });
+
+        if toggle || actions.terrain_requested || actions.dungeon_requested {
+            self.expanded = toggle && !self.expanded;
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the generate menu during the current frame.
+#[derive(Default)]
+pub struct GenerateMenuActions {
+    /// Open the noise terrain generator.
+    pub terrain_requested: bool,
+    /// Open the room and corridor generator.
+    pub dungeon_requested: bool,
+}
+
+/// Outcome of one frame of a generator dialog.
+pub enum GeneratorAction {
+    /// Show these tiles, row by row, as the map preview.
+    Preview(Vec<Option<String>>),
+    /// Paint `tiles` on `layer` as one undo step; the dialog closed.
+    Generate {
+        layer: usize,
+        tiles: Vec<Option<String>>,
+    },
+    /// The dialog closed without generating.
+    Close,
+}
@@ -0,0 +1,94 @@
+use crate::core::history::{EditKind, StepSummary};
+use crate::core::map::map::Map;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// Window listing the undo steps of the map, newest first; clicking one undoes or redoes
+/// every step up to it.
+pub struct HistoryPanel {
+    visible: bool,
+    pointer_over_ui: bool,
+}
+
+impl HistoryPanel {
+    pub fn new() -> Self {
+        Self { visible: false, pointer_over_ui: false }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Draws the step list inside `area` and jumps `map` to the clicked step.
+    pub fn draw(&mut self, map: &mut Map, area: Option<Rect>) {
+        let Some(rect) = area.filter(|_| self.visible) else {
+            self.pointer_over_ui = false;
+            return;
+        };
+
+        let steps = map.history_steps();
+        let position = map.history_position();
+        let mut selected = None;
+
+        widgets::Window::new(hash!("history_window"), rect.point(), rect.size())
+            .titlebar(false)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                // Entry `n` is the state after the first `n` steps; entry 0 is the oldest kept.
+                for entry in (0..=steps.len()).rev() {
+                    let description = match entry {
+                        0 => tr("history.start").to_string(),
+                        _ => describe(steps[entry - 1]),
+                    };
+                    let label = if entry == position {
+                        format!("> {description}")
+                    } else if entry > position {
+                        format!("{description} {}", tr("history.undone"))
+                    } else {
+                        description
+                    };
+                    if ui.button(None, label.as_str()) {
+                        selected = Some(entry);
+                    }
+                }
+            });
+
+        if let Some(entry) = selected {
+            map.jump_to_history(entry);
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Translated description of a step, e.g. "Paint 14 tiles".
+fn describe(step: StepSummary) -> String {
+    let key = match step.kind {
+        EditKind::Paint => "history.paint",
+        EditKind::Erase => "history.erase",
+        EditKind::Stamp => "history.stamp",
+        EditKind::Shape => "history.shape",
+        EditKind::Paste => "history.paste",
+        EditKind::EraseSelection => "history.erase_selection",
+        EditKind::ReplaceTiles => "history.replace_tiles",
+        EditKind::Fill => "history.fill",
+        EditKind::FillEmpty => "history.fill_empty",
+        EditKind::ImportLayer => "history.import_layer",
+        EditKind::Generate => "history.generate",
+        EditKind::MigrateIds => "history.migrate_ids",
+        EditKind::CellProperties => "history.cell_properties",
+        EditKind::Resize => "history.resize",
+        EditKind::Crop => "history.crop",
+    };
+    trf(key, &[("count", &step.cells)])
+}
@@ -0,0 +1,71 @@
+//! Translated UI strings.
+//!
+//! Every language has a `<code>.lang` file next to this module, embedded in the binary. Each
+//! line holds `key = text`; blank lines and lines starting with `#` are ignored. Texts may
+//! contain `{name}` placeholders filled by [`trf`]. Keys missing from a language fall back to
+//! the English text, and keys missing from English are shown as-is.
+
+use crate::core::config::language::Language;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+type Table = HashMap<&'static str, &'static str>;
+
+thread_local! {
+    static ACTIVE: Cell<Language> = const { Cell::new(Language::English) };
+}
+
+/// Switches every later lookup to `language`.
+pub fn set_language(language: Language) {
+    ACTIVE.with(|active| active.set(language));
+}
+
+/// Language used by [`tr`] and [`trf`].
+pub fn language() -> Language {
+    ACTIVE.with(Cell::get)
+}
+
+/// Text of `key` in the active language.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(key).unwrap_or(key)
+}
+
+/// Text of `key` in the active language with its `{name}` placeholders replaced by `args`.
+pub fn trf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(tr(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+fn lookup(key: &str) -> Option<&'static str> {
+    table(language())
+        .get(key)
+        .or_else(|| table(Language::English).get(key))
+        .copied()
+}
+
+fn table(language: Language) -> &'static Table {
+    static ENGLISH: OnceLock<Table> = OnceLock::new();
+    static BRAZILIAN_PORTUGUESE: OnceLock<Table> = OnceLock::new();
+    match language {
+        Language::English => ENGLISH.get_or_init(|| parse(include_str!("en.lang"))),
+        Language::BrazilianPortuguese => {
+            BRAZILIAN_PORTUGUESE.get_or_init(|| parse(include_str!("pt-BR.lang")))
+        }
+    }
+}
+
+fn parse(source: &'static str) -> Table {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, text) = line.split_once('=')?;
+            Some((key.trim(), text.trim()))
+        })
+        .collect()
+}
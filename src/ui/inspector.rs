@@ -0,0 +1,110 @@
+use crate::core::map::map::TileInfo;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+
+/// Screen-space window listing the metadata of the hovered tile.
+pub struct TileInspectorPanel {
+    visible: bool,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl TileInspectorPanel {
+    const BASE_HEIGHT: f32 = 230.0;
+    const ROW_HEIGHT: f32 = 20.0;
+
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            size: vec2(260.0, Self::BASE_HEIGHT),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Draws the inspector for `info`; must run after the world camera has been reset.
+    pub fn draw(&mut self, info: Option<&TileInfo>) {
+        if !self.visible {
+            self.pointer_over_ui = false;
+            return;
+        }
+
+        // The window grows by a row for each property listed under the fixed fields.
+        let rows = info.map_or(0, |info| {
+            let headers =
+                usize::from(!info.properties.is_empty()) + usize::from(!info.metadata.is_empty());
+            headers + info.properties.len() + info.metadata.len()
+        });
+        self.size.y = Self::BASE_HEIGHT + rows as f32 * Self::ROW_HEIGHT;
+        let position = vec2((screen_width() - self.size.x - 20.0).max(0.0), 420.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("tile_inspector_window"), position, self.size, |ui| {
+            ui.label(None, tr("inspector.title"));
+            ui.separator();
+
+            let Some(info) = info else {
+                ui.label(None, tr("inspector.no_tile"));
+                return;
+            };
+
+            let tint = info.tint;
+            ui.label(None, &trf("inspector.id", &[("id", &info.tile_id)]));
+            ui.label(None, &trf("inspector.name", &[("name", &info.display_name)]));
+            ui.label(None, &trf("inspector.layer", &[("layer", &info.layer)]));
+            ui.label(
+                None,
+                &trf(
+                    "inspector.position",
+                    &[
+                        ("x", &info.position.0),
+                        ("y", &info.position.1),
+                    ],
+                ),
+            );
+            let tint = format!("{:.2} {:.2} {:.2}", tint.r, tint.g, tint.b);
+            ui.label(None, &trf("inspector.tint", &[("tint", &tint)]));
+            ui.label(None, &trf("inspector.alpha", &[("alpha", &format!("{:.2}", info.alpha))]));
+            ui.label(None, &trf("inspector.z_priority", &[("z", &info.z_priority)]));
+            ui.label(
+                None,
+                &trf("inspector.orientation", &[("orientation", &info.transform.label())]),
+            );
+            ui.label(
+                None,
+                if info.walkable {
+                    tr("inspector.walkable")
+                } else {
+                    tr("inspector.blocked")
+                },
+            );
+            if !info.properties.is_empty() {
+                ui.separator();
+                ui.label(None, tr("inspector.properties"));
+                for (key, value) in &info.properties {
+                    ui.label(None, &format!("  {key} = {value}"));
+                }
+            }
+            if !info.metadata.is_empty() {
+                ui.separator();
+                ui.label(None, tr("inspector.metadata"));
+                for (key, value) in &info.metadata {
+                    ui.label(None, &format!("  {key} = {value}"));
+                }
+            }
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,354 @@
+use crate::core::map::map::Map;
+use crate::ui::i18n::tr;
+use macroquad::color::{Color, WHITE};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// Window listing the map layers, top layer first, with visibility and lock toggles, and
+/// the opacity and tint of the active layer. Grouped layers are indented under a header
+/// row per group, nested groups further in; the header toggles the group, folds its rows
+/// away and moves the active layer into it. The name and opacity of the active layer's
+/// innermost group are edited below the list. Image layers also get their image file and
+/// parallax factors.
+pub struct LayersPanel {
+    visible: bool,
+    pointer_over_ui: bool,
+    /// Image path typed for the active image layer.
+    image_path: String,
+    /// Image layer `image_path` was last filled from.
+    image_path_layer: Option<usize>,
+}
+
+impl LayersPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            pointer_over_ui: false,
+            image_path: String::new(),
+            image_path_layer: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Draws the layer list inside `area` and applies the edits made in it to `map`.
+    pub fn draw(&mut self, map: &mut Map, area: Option<Rect>) {
+        let Some(rect) = area.filter(|_| self.visible) else {
+            self.pointer_over_ui = false;
+            return;
+        };
+
+        let active = map.active_layer();
+        let markers: Vec<&str> = map
+            .layers()
+            .iter()
+            .map(|layer| {
+                if layer.is_object_layer() {
+                    " [obj]"
+                } else if layer.is_image_layer() {
+                    " [img]"
+                } else {
+                    ""
+                }
+            })
+            .collect();
+        let image_layer = map
+            .layers()
+            .get(active)
+            .filter(|layer| layer.is_image_layer());
+        let mut parallax = image_layer.map(|layer| layer.parallax);
+        let image_path_layer = image_layer.map(|_| active);
+        if self.image_path_layer != image_path_layer {
+            self.image_path = image_layer
+                .and_then(|layer| layer.image_path())
+                .unwrap_or_default()
+                .to_string();
+            self.image_path_layer = image_path_layer;
+        }
+        let old_parallax = parallax;
+        // Groups holding each layer, outermost first.
+        let layer_groups: Vec<Vec<usize>> = map
+            .layers()
+            .iter()
+            .map(|layer| {
+                let mut chain: Vec<usize> = std::iter::successors(layer.group(), |&group| {
+                    map.groups()
+                        .get(group)
+                        .and_then(|group| group.parent())
+                })
+                .take(map.groups().len())
+                .collect();
+                chain.reverse();
+                chain
+            })
+            .collect();
+        let active_group = map
+            .layers()
+            .get(active)
+            .and_then(|layer| layer.group());
+        let mut groups: Vec<(String, bool, bool, f32, bool)> = map
+            .groups()
+            .iter()
+            .map(|group| {
+                (group.name.clone(), group.visible, group.locked, group.opacity, group.collapsed)
+            })
+            .collect();
+        let mut move_to_group = None;
+        let mut rows: Vec<(String, bool, bool)> = map
+            .layers()
+            .iter()
+            .map(|layer| (layer.name.clone(), layer.visible, layer.locked))
+            .collect();
+        let (mut opacity, mut tinted, mut tint) = map
+            .layers()
+            .get(active)
+            .map(|layer| {
+                let tint = layer.tint_color.unwrap_or(WHITE);
+                (layer.opacity, layer.tint_color.is_some(), [tint.r, tint.g, tint.b])
+            })
+            .unwrap_or((1.0, false, [1.0; 3]));
+        let style = (opacity, tinted, tint);
+        let mut selected = None;
+        let (mut add, mut add_objects, mut remove, mut move_up, mut move_down) =
+            (false, false, false, false, false);
+        let (mut add_image, mut load_image, mut group_layer, mut ungroup_layer) =
+            (false, false, false, false);
+        let image_path = &mut self.image_path;
+
+        widgets::Window::new(hash!("layers_window"), rect.point(), rect.size())
+            .titlebar(false)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                for (index, (name, visible, locked)) in rows.iter_mut().enumerate().rev() {
+                    let chain = &layer_groups[index];
+                    let shared = layer_groups
+                        .get(index + 1)
+                        .map_or(0, |above| {
+                            above
+                                .iter()
+                                .zip(chain)
+                                .take_while(|(above, group)| above == group)
+                                .count()
+                        });
+                    for (depth, &group) in chain.iter().enumerate().skip(shared) {
+                        let folded = chain[..depth].iter().any(|&outer| {
+                            groups
+                                .get(outer)
+                                .is_some_and(|outer| outer.4)
+                        });
+                        let Some((group_name, group_visible, group_locked, _, collapsed)) = groups
+                            .get_mut(group)
+                            .filter(|_| !folded)
+                        else {
+                            continue;
+                        };
+                        ui.checkbox(
+                            hash!("group_visible", group),
+                            tr("layers.show"),
+                            group_visible,
+                        );
+                        ui.same_line(70.0);
+                        ui.checkbox(hash!("group_locked", group), tr("layers.lock"), group_locked);
+                        ui.same_line(140.0);
+                        let indent = "   ".repeat(depth);
+                        let fold = if *collapsed {
+                            ">"
+                        } else {
+                            "v"
+                        };
+                        if ui.button(None, format!("{indent}{fold} [{group_name}]").as_str()) {
+                            *collapsed = !*collapsed;
+                        }
+                        if active_group != Some(group) {
+                            ui.same_line(0.0);
+                            if ui.button(None, tr("layers.move_here")) {
+                                move_to_group = Some(group);
+                            }
+                        }
+                    }
+                    let hidden = chain.iter().any(|&group| {
+                        groups
+                            .get(group)
+                            .is_some_and(|group| group.4)
+                    });
+                    if hidden {
+                        continue;
+                    }
+                    ui.checkbox(hash!("layer_visible", index), tr("layers.show"), visible);
+                    ui.same_line(70.0);
+                    ui.checkbox(hash!("layer_locked", index), tr("layers.lock"), locked);
+                    ui.same_line(140.0);
+                    let marker = markers[index];
+                    let indent = "   ".repeat(chain.len());
+                    let label = if index == active {
+                        format!("{indent}> {name}{marker}")
+                    } else {
+                        format!("{indent}{name}{marker}")
+                    };
+                    if ui.button(None, label.as_str()) {
+                        selected = Some(index);
+                    }
+                }
+
+                ui.separator();
+                if let Some((name, _, _)) = rows.get_mut(active) {
+                    widgets::InputText::new(hash!("layer_name"))
+                        .label(tr("common.name"))
+                        .size(vec2(180.0, 19.0))
+                        .ratio(1.0)
+                        .ui(ui, name);
+                }
+                ui.slider(hash!("layer_opacity"), tr("common.opacity"), 0.0..1.0, &mut opacity);
+                ui.checkbox(hash!("layer_tinted"), tr("layers.tint"), &mut tinted);
+                if tinted {
+                    ui.slider(hash!("layer_tint_r"), "R", 0.0..1.0, &mut tint[0]);
+                    ui.slider(hash!("layer_tint_g"), "G", 0.0..1.0, &mut tint[1]);
+                    ui.slider(hash!("layer_tint_b"), "B", 0.0..1.0, &mut tint[2]);
+                }
+                if let Some(parallax) = parallax.as_mut() {
+                    widgets::InputText::new(hash!("layer_image"))
+                        .label(tr("layers.image"))
+                        .size(vec2(180.0, 19.0))
+                        .ratio(1.0)
+                        .ui(ui, image_path);
+                    load_image = ui.button(None, tr("layers.load_image"));
+                    ui.slider(
+                        hash!("layer_parallax_x"),
+                        tr("layers.parallax_x"),
+                        0.0..1.0,
+                        &mut parallax.x,
+                    );
+                    ui.slider(
+                        hash!("layer_parallax_y"),
+                        tr("layers.parallax_y"),
+                        0.0..1.0,
+                        &mut parallax.y,
+                    );
+                }
+                if let Some((name, _, _, opacity, _)) =
+                    active_group.and_then(|group| groups.get_mut(group))
+                {
+                    ui.separator();
+                    widgets::InputText::new(hash!("group_name"))
+                        .label(tr("layers.group_name"))
+                        .size(vec2(180.0, 19.0))
+                        .ratio(1.0)
+                        .ui(ui, name);
+                    ui.slider(
+                        hash!("group_opacity"),
+                        tr("layers.group_opacity"),
+                        0.0..1.0,
+                        opacity,
+                    );
+                }
+                add = ui.button(None, tr("layers.add"));
+                ui.same_line(0.0);
+                add_objects = ui.button(None, tr("layers.add_objects"));
+                ui.same_line(0.0);
+                add_image = ui.button(None, tr("layers.add_image"));
+                ui.same_line(0.0);
+                remove = ui.button(None, tr("layers.remove"));
+                ui.same_line(0.0);
+                move_up = ui.button(None, tr("layers.up"));
+                ui.same_line(0.0);
+                move_down = ui.button(None, tr("layers.down"));
+                ui.same_line(0.0);
+                group_layer = ui.button(None, tr("layers.group"));
+                if active_group.is_some() {
+                    ui.same_line(0.0);
+                    ungroup_layer = ui.button(None, tr("layers.ungroup"));
+                }
+            });
+
+        for (index, (name, visible, locked, opacity, collapsed)) in groups.into_iter().enumerate()
+        {
+            let unchanged = map
+                .groups()
+                .get(index)
+                .is_none_or(|group| {
+                    group.name == name
+                        && group.visible == visible
+                        && group.locked == locked
+                        && group.opacity == opacity
+                        && group.collapsed == collapsed
+                });
+            if unchanged {
+                continue;
+            }
+            if let Some(group) = map.group_mut(index) {
+                group.name = name;
+                group.visible = visible;
+                group.locked = locked;
+                group.opacity = opacity;
+                group.collapsed = collapsed;
+            }
+        }
+
+        for (index, (name, visible, locked)) in rows.into_iter().enumerate() {
+            let unchanged = map
+                .layers()
+                .get(index)
+                .is_none_or(|layer| {
+                    layer.name == name && layer.visible == visible && layer.locked == locked
+                });
+            if unchanged {
+                continue;
+            }
+            if let Some(layer) = map.layer_mut(index) {
+                layer.name = name;
+                layer.visible = visible;
+                layer.locked = locked;
+            }
+        }
+        if (opacity, tinted, tint) != style
+            && let Some(layer) = map.layer_mut(active)
+        {
+            layer.opacity = opacity;
+            layer.tint_color = tinted.then(|| Color::new(tint[0], tint[1], tint[2], 1.0));
+        }
+        if parallax != old_parallax
+            && let (Some(parallax), Some(layer)) = (parallax, map.layer_mut(active))
+        {
+            layer.parallax = parallax;
+        }
+        if load_image && let Err(err) = map.set_layer_image(active, self.image_path.trim()) {
+            eprintln!("Could not load layer image: {err}");
+        }
+        if let Some(index) = selected {
+            map.set_active_layer(index);
+        }
+        if add {
+            map.add_layer();
+        } else if add_objects {
+            map.add_object_layer();
+        } else if add_image {
+            map.add_image_layer();
+        } else if remove {
+            map.remove_layer(active);
+        } else if move_up {
+            map.move_layer(active, true);
+        } else if move_down {
+            map.move_layer(active, false);
+        } else if group_layer {
+            map.group_active_layer();
+        } else if ungroup_layer {
+            map.ungroup_active_layer();
+        } else if let Some(group) = move_to_group {
+            map.add_layer_to_group(active, group);
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
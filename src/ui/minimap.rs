@@ -0,0 +1,138 @@
+use crate::core::map::map::Map;
+use macroquad::color::{Color, WHITE, YELLOW};
+use macroquad::input::{
+    MouseButton, is_mouse_button_down, is_mouse_button_pressed, mouse_position,
+};
+use macroquad::math::{Rect, Vec2};
+use macroquad::shapes::{draw_rectangle, draw_rectangle_lines};
+use macroquad::texture::{
+    DrawTextureParams, FilterMode, RenderTarget, draw_texture_ex, render_target,
+};
+
+/// Low-resolution overview of the whole map, drawn in its dock area.
+///
+/// The map is rendered into a cached render target that is refreshed only when
+/// [`Map::revision`] changes. Clicking or dragging on the minimap recenters the camera.
+pub struct Minimap {
+    target: Option<RenderTarget>,
+    rendered_revision: Option<u64>,
+    dragging: bool,
+    pointer_over_ui: bool,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            rendered_revision: None,
+            dragging: false,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the minimap, fitted inside `area`, with the current viewport outlined and
+    /// handles click-to-jump; a collapsed minimap (`None`) draws nothing.
+    pub fn draw(&mut self, map: &mut Map, area: Option<Rect>) {
+        let Some(area) = area else {
+            self.pointer_over_ui = false;
+            self.dragging = false;
+            return;
+        };
+        let grid_size = map.grid_size();
+        let scale = (area.w / grid_size.x).min(area.h / grid_size.y);
+        let size = (grid_size * scale)
+            .max(Vec2::ONE)
+            .round();
+        let rect = Rect::new(
+            area.x + ((area.w - size.x) / 2.0).round(),
+            area.y + ((area.h - size.y) / 2.0).round(),
+            size.x,
+            size.y,
+        );
+
+        let target = self.render(map, size);
+        draw_rectangle(area.x, area.y, area.w, area.h, Color { r: 0.0, g: 0.0, b: 0.0, a: 0.6 });
+        draw_texture_ex(
+            &target.texture,
+            rect.x,
+            rect.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(size),
+                // Render targets are stored bottom row first.
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, WHITE);
+
+        let camera = map.get_camera_controller();
+        let view_size = camera.get_view_size() * scale;
+        let view_center = Vec2::from(camera.screen_center.clone()) * scale;
+        let view = Rect::new(
+            rect.x + view_center.x - view_size.x / 2.0,
+            rect.y + view_center.y - view_size.y / 2.0,
+            view_size.x,
+            view_size.y,
+        )
+        .intersect(rect);
+        if let Some(view) = view {
+            draw_rectangle_lines(view.x, view.y, view.w, view.h, 1.0, YELLOW);
+        }
+
+        let mouse = Vec2::from(mouse_position());
+        self.pointer_over_ui = rect.contains(mouse);
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.dragging = self.pointer_over_ui;
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.dragging = false;
+        }
+        if self.dragging {
+            let target = ((mouse - rect.point()) / scale).clamp(Vec2::ZERO, grid_size);
+            map.get_camera_controller_mut()
+                .jump_to(target);
+            self.pointer_over_ui = true;
+        }
+    }
+
+    /// Returns `true` while the cursor is over the minimap or dragging on it.
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    /// Forces a redraw on the next frame, e.g. after switching to another map whose
+    /// revision counter may match the cached one.
+    pub fn invalidate(&mut self) {
+        self.rendered_revision = None;
+    }
+
+    /// Returns the cached render of the map, redrawing it when the map or size changed.
+    fn render(&mut self, map: &Map, size: Vec2) -> RenderTarget {
+        let stale_size = self
+            .target
+            .as_ref()
+            .is_none_or(|target| {
+                target.texture.width() != size.x || target.texture.height() != size.y
+            });
+        if stale_size {
+            self.target = None;
+            self.rendered_revision = None;
+        }
+        let target = self
+            .target
+            .get_or_insert_with(|| {
+                let target = render_target(size.x as u32, size.y as u32);
+                target
+                    .texture
+                    .set_filter(FilterMode::Linear);
+                target
+            })
+            .clone();
+        if self.rendered_revision != Some(map.revision()) {
+            map.draw_overview(&target);
+            self.rendered_revision = Some(map.revision());
+        }
+        target
+    }
+}
@@ -0,0 +1,34 @@
+pub mod asset_dirs;
+pub mod custom_properties;
+pub mod dock;
+pub mod dungeon_generator;
+pub mod file_dialog;
+pub mod file_menu;
+pub mod generate_menu;
+pub mod history;
+pub mod i18n;
+pub mod inspector;
+pub mod layers;
+pub mod minimap;
+pub mod new_map;
+pub mod objects;
+pub mod overlay;
+pub mod palette;
+pub mod preferences;
+pub mod project_menu;
+pub mod properties;
+pub mod random_brush;
+pub mod recovery;
+pub mod reference_image;
+pub mod replace_tiles;
+pub mod resize_map;
+pub mod search;
+pub mod sequence;
+pub mod statistics;
+pub mod status_bar;
+pub mod tabs;
+pub mod terrain_generator;
+pub mod theme;
+pub mod tool_options;
+pub mod tools;
+pub mod view_settings;
@@ -0,0 +1,198 @@
+use crate::core::map::projection::MapProjection;
+use crate::core::map::tile::Size;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+
+/// Dialog asking for the grid and tile dimensions of a new, empty map.
+pub struct NewMapDialog {
+    open: bool,
+    width_input: String,
+    height_input: String,
+    tile_width_input: String,
+    tile_height_input: String,
+    projection_index: usize,
+    error: Option<String>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+/// Dimensions confirmed in the [`NewMapDialog`].
+pub struct NewMapRequest {
+    /// Map size in tiles.
+    pub map_size: Size,
+    /// Tile size in pixels.
+    pub tile_size: Size,
+    pub projection: MapProjection,
+}
+
+impl NewMapDialog {
+    /// Largest accepted map side, in tiles.
+    const MAX_MAP_TILES: u32 = 1024;
+    /// Accepted tile side range, in pixels.
+    const TILE_PIXELS: std::ops::RangeInclusive<u32> = 4..=256;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            width_input: String::new(),
+            height_input: String::new(),
+            tile_width_input: String::new(),
+            tile_height_input: String::new(),
+            projection_index: 0,
+            error: None,
+            size: vec2(300.0, 250.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog prefilled with the current map's dimensions and projection.
+    pub fn open(&mut self, map_size: (usize, usize), tile_size: Size, projection: MapProjection) {
+        self.width_input = map_size.0.to_string();
+        self.height_input = map_size.1.to_string();
+        self.tile_width_input = tile_size.width.to_string();
+        self.tile_height_input = tile_size.height.to_string();
+        self.projection_index = MapProjection::ALL
+            .iter()
+            .position(|option| *option == projection)
+            .unwrap_or(0);
+        self.error = None;
+        self.open = true;
+    }
+
+    /// Draws the dialog, returning the requested dimensions once "Create" is pressed
+    /// with valid values.
+    pub fn draw(&mut self) -> Option<NewMapRequest> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let mut create = false;
+        let mut close = false;
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("new_map_window"), position, self.size, |ui| {
+            ui.label(None, tr("new_map.title"));
+            ui.separator();
+            ui.input_text(hash!("new_map_width"), tr("common.width_tiles"), &mut self.width_input);
+            ui.input_text(
+                hash!("new_map_height"),
+                tr("common.height_tiles"),
+                &mut self.height_input,
+            );
+            ui.input_text(
+                hash!("new_map_tile_width"),
+                tr("new_map.tile_width_px"),
+                &mut self.tile_width_input,
+            );
+            ui.input_text(
+                hash!("new_map_tile_height"),
+                tr("new_map.tile_height_px"),
+                &mut self.tile_height_input,
+            );
+            let projection_labels: Vec<&str> = MapProjection::ALL
+                .iter()
+                .map(|projection| projection.name())
+                .collect();
+            ui.combo_box(
+                hash!("new_map_projection"),
+                tr("new_map.projection"),
+                &projection_labels,
+                &mut self.projection_index,
+            );
+            if let Some(error) = &self.error {
+                ui.label(None, error);
+            }
+
+            ui.separator();
+            ui.label(None, tr("new_map.discard_warning"));
+            if ui.button(None, tr("new_map.create")) {
+                create = true;
+            }
+            if ui.button(None, tr("common.cancel")) {
+                close = true;
+            }
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        let mut request = None;
+        if create {
+            match self.parse() {
+                Ok(parsed) => {
+                    request = Some(parsed);
+                    close = true;
+                }
+                Err(error) => self.error = Some(error),
+            }
+        }
+        if close {
+            self.open = false;
+        }
+        request
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    fn parse(&self) -> Result<NewMapRequest, String> {
+        let map_side = |input: &str, label: &str| {
+            input
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|value| (1..=Self::MAX_MAP_TILES).contains(value))
+                .map(|value| value as f32)
+                .ok_or_else(|| {
+                    trf(
+                        "common.size_out_of_range",
+                        &[
+                            ("label", &label),
+                            ("min", &1),
+                            ("max", &Self::MAX_MAP_TILES),
+                        ],
+                    )
+                })
+        };
+        let tile_side = |input: &str, label: &str| {
+            input
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|value| Self::TILE_PIXELS.contains(value))
+                .map(|value| value as f32)
+                .ok_or_else(|| {
+                    trf(
+                        "common.size_out_of_range",
+                        &[
+                            ("label", &label),
+                            ("min", Self::TILE_PIXELS.start()),
+                            ("max", Self::TILE_PIXELS.end()),
+                        ],
+                    )
+                })
+        };
+
+        Ok(NewMapRequest {
+            map_size: Size {
+                width: map_side(&self.width_input, tr("common.width"))?,
+                height: map_side(&self.height_input, tr("common.height"))?,
+            },
+            tile_size: Size {
+                width: tile_side(&self.tile_width_input, tr("new_map.tile_width"))?,
+                height: tile_side(&self.tile_height_input, tr("new_map.tile_height"))?,
+            },
+            projection: MapProjection::ALL[self.projection_index],
+        })
+    }
+}
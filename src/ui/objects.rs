@@ -0,0 +1,174 @@
+use crate::core::map::map::Map;
+use crate::core::map::object::MapObject;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{Ui, hash, root_ui, widgets};
+use macroquad::window::screen_width;
+
+/// Window editing the object selected with the object tool, shown while one is selected.
+pub struct ObjectPanel {
+    /// Object as last loaded into or applied from the fields; the fields reload when the map's
+    /// copy differs, e.g. after the object was dragged or another one was picked.
+    shown: Option<MapObject>,
+    name: String,
+    kind: String,
+    /// Position and size as typed; fields that do not parse keep the previous value.
+    geometry: [String; 4],
+    /// Editable `(key, value)` rows; rows with an empty key are ignored.
+    rows: Vec<(String, String)>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl ObjectPanel {
+    pub fn new() -> Self {
+        Self {
+            shown: None,
+            name: String::new(),
+            kind: String::new(),
+            geometry: Default::default(),
+            rows: Vec::new(),
+            size: vec2(300.0, 330.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the panel, applying every change to the selected object as it is typed.
+    pub fn draw(&mut self, map: &mut Map) {
+        let Some(object) = map.selected_object().cloned() else {
+            self.shown = None;
+            self.pointer_over_ui = false;
+            return;
+        };
+        if self.shown.as_ref() != Some(&object) {
+            self.load(&object);
+        }
+
+        let mut remove_index = None;
+        let mut delete = false;
+        let position = vec2((screen_width() - self.size.x - 20.0).max(0.0), 420.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("object_window"), position, self.size, |ui| {
+            ui.label(None, tr("objects.title"));
+            ui.separator();
+            text_field(ui, hash!("object_name"), tr("common.name"), &mut self.name);
+            text_field(ui, hash!("object_type"), tr("common.type"), &mut self.kind);
+            for (index, label) in [
+                "X",
+                "Y",
+                tr("common.width"),
+                tr("common.height"),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                text_field(ui, hash!("object_geometry", index), label, &mut self.geometry[index]);
+            }
+
+            ui.separator();
+            ui.label(None, tr("common.key"));
+            ui.same_line(125.0);
+            ui.label(None, tr("common.value"));
+            for (index, (key, value)) in self.rows.iter_mut().enumerate() {
+                widgets::InputText::new(hash!("object_property_key", index))
+                    .size(vec2(110.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, key);
+                ui.same_line(125.0);
+                widgets::InputText::new(hash!("object_property_value", index))
+                    .size(vec2(130.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, value);
+                ui.same_line(265.0);
+                if ui.button(None, "x") {
+                    remove_index = Some(index);
+                }
+            }
+            if ui.button(None, tr("common.add_property")) {
+                self.rows
+                    .push((String::new(), String::new()));
+            }
+
+            ui.separator();
+            delete = ui.button(None, tr("objects.delete"));
+        });
+
+        if let Some(index) = remove_index {
+            self.rows.remove(index);
+        }
+        if delete {
+            map.remove_selected_object();
+            self.shown = None;
+        } else {
+            let edited = self.edited(&object);
+            map.set_selected_object(edited.clone());
+            self.shown = Some(edited);
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    fn load(&mut self, object: &MapObject) {
+        self.name = object.name.clone();
+        self.kind = object.kind.clone();
+        self.geometry = [
+            object.x,
+            object.y,
+            object.width,
+            object.height,
+        ]
+        .map(|value| value.to_string());
+        self.rows = object
+            .properties
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        self.shown = Some(object.clone());
+    }
+
+    /// `object` with the values of the fields applied.
+    fn edited(&self, object: &MapObject) -> MapObject {
+        let parse = |index: usize, old: f32| {
+            self.geometry[index]
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .unwrap_or(old)
+        };
+        let positive = |index: usize, old: f32| {
+            Some(parse(index, old))
+                .filter(|&value| value > 0.0)
+                .unwrap_or(old)
+        };
+        MapObject {
+            name: self.name.clone(),
+            kind: self.kind.trim().to_string(),
+            x: parse(0, object.x),
+            y: parse(1, object.y),
+            width: positive(2, object.width),
+            height: positive(3, object.height),
+            properties: self
+                .rows
+                .iter()
+                .filter(|(key, _)| !key.trim().is_empty())
+                .map(|(key, value)| (key.trim().to_string(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+fn text_field(ui: &mut Ui, id: u64, label: &str, value: &mut String) {
+    widgets::InputText::new(id)
+        .label(label)
+        .size(vec2(180.0, 19.0))
+        .ratio(1.0)
+        .ui(ui, value);
+}
@@ -0,0 +1,73 @@
+use crate::core::config::AppConfig;
+use macroquad::color::Color;
+use macroquad::text::{Font, TextParams, camera_font_scale, draw_text_ex, load_ttf_font};
+
+/// Font settings shared by every piece of text drawn directly over the viewport.
+pub struct OverlayText {
+    font: Option<Font>,
+    base_size: u16,
+}
+
+impl OverlayText {
+    /// Loads the configured TTF font, falling back to macroquad's built-in font.
+    pub async fn load(config: &AppConfig) -> Self {
+        let font = match &config.overlay_font_path {
+            Some(path) => match load_ttf_font(&path.to_string_lossy()).await {
+                Ok(font) => Some(font),
+                Err(err) => {
+                    eprintln!("[overlay] Failed to load font {:?}: {err}", path);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Self {
+            font,
+            base_size: config.overlay_font_size.max(1),
+        }
+    }
+
+    /// Draws `text` with its baseline at `(x, y)`, sized as a multiple of the base font size.
+    pub fn draw(&self, text: &str, x: f32, y: f32, scale: f32, color: Color) {
+        let font_size = (self.base_size as f32 * scale)
+            .round()
+            .max(1.0) as u16;
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: self.font.as_ref(),
+                font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draws `text` under the active world camera, `scale` times the base size in world
+    /// units, so the label grows and shrinks with the zoom.
+    pub fn draw_world(&self, text: &str, x: f32, y: f32, scale: f32, color: Color) {
+        let (font_size, font_scale, font_scale_aspect) =
+            camera_font_scale(self.base_size as f32 * scale);
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: self.font.as_ref(),
+                font_size,
+                font_scale,
+                font_scale_aspect,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Height in world units of text drawn by [`OverlayText::draw_world`] at `scale`.
+    pub fn world_height(&self, scale: f32) -> f32 {
+        self.base_size as f32 * scale
+    }
+}
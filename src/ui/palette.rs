@@ -0,0 +1,826 @@
+use crate::core::assets::{AssetCatalog, TileSprite};
+use crate::core::map::map::Map;
+use crate::core::map::tile::Size;
+use crate::core::tools::Selection;
+use crate::ui::i18n::{tr, trf};
+use macroquad::color::Color;
+use macroquad::input::{
+    KeyCode, MouseButton, is_key_down, is_mouse_button_down, is_mouse_button_pressed,
+    mouse_position,
+};
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::texture::{DrawTextureParams, draw_texture_ex};
+use macroquad::ui::{Layout, Ui, hash, root_ui, widgets};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Left-side window listing asset categories as a collapsible tree and their tiles.
+pub struct PalettePanel {
+    selected_category: usize,
+    /// Sub-category filter of the grid: `0` shows every tile, `n` the `n - 1`th sub-category
+    /// and the ones nested in it.
+    selected_sub_category: usize,
+    /// Tree nodes whose nested groups are shown, keyed by path, e.g. `Exterior/Trees`.
+    expanded_nodes: HashSet<String>,
+    selected_tile: Option<usize>,
+    /// Opposite corner, picked with `Shift+click`, of a multi-tile stamp anchored at the
+    /// selected tile.
+    stamp_corner: Option<usize>,
+    /// `(category, tile)` painted by the right mouse button when the secondary brush is on.
+    secondary_tile: Option<(usize, usize)>,
+    /// Tile of the selected category being dragged from the grid onto the canvas.
+    drag_from_palette: Option<usize>,
+    /// Tile of the selected category under the cursor.
+    hovered_tile: Option<usize>,
+    /// Text typed in the filter box; when set, the grid lists the tiles of every category
+    /// whose name fuzzy-matches it.
+    tile_filter: String,
+    /// Ids of the most recently painted tiles, most recent first.
+    recent_tiles: Vec<String>,
+    /// Number of paint actions that used each tile id.
+    paint_counts: BTreeMap<String, u32>,
+    /// `(category, tile)` outlined by the quick search.
+    highlighted_tile: Option<(usize, usize)>,
+    /// Tiles per grid row, lowered while wider previews would not fit the panel.
+    preview_columns: usize,
+    /// Edge of a tile preview in the grid, in screen pixels.
+    tile_preview_size: f32,
+    button_padding: f32,
+    /// Offset of the first tile inside the scrollable grid area.
+    grid_origin: Vec2,
+    /// Screen area given to the panel by the dock this frame.
+    rect: Rect,
+    /// Current map file, also suggested by the file dialogs.
+    map_path: String,
+    /// Image pixels per map pixel used by "Export image".
+    image_scale: f32,
+    /// CSV exports and imports use catalog tile indices instead of tile ids.
+    csv_indices: bool,
+    pointer_over_ui: bool,
+}
+
+impl PalettePanel {
+    /// Approximate height of the widgets above and below the tile grid.
+    const GRID_CHROME_HEIGHT: f32 = 610.0;
+    /// Smallest height of the tile grid, which scrolls when its tiles do not fit.
+    const MIN_GRID_HEIGHT: f32 = 120.0;
+    const MIN_PREVIEW_SIZE: f32 = 16.0;
+    const MAX_PREVIEW_SIZE: f32 = 96.0;
+    const MAX_COLUMNS: usize = 12;
+    /// Tiles shown in each row of the quick-access strip.
+    const QUICK_TILES: usize = 8;
+    /// Edge of a quick-access tile button, in screen pixels.
+    const QUICK_TILE_SIZE: f32 = 24.0;
+    /// Tallest the category tree gets before it scrolls.
+    const TREE_HEIGHT: f32 = 120.0;
+    const TREE_ROW_HEIGHT: f32 = 20.0;
+    /// Indentation of each nesting level of the category tree.
+    const TREE_INDENT: f32 = 12.0;
+
+    pub fn new(tile_size: Size) -> Self {
+        Self {
+            selected_category: 0,
+            selected_sub_category: 0,
+            expanded_nodes: HashSet::new(),
+            selected_tile: None,
+            stamp_corner: None,
+            secondary_tile: None,
+            drag_from_palette: None,
+            hovered_tile: None,
+            tile_filter: String::new(),
+            recent_tiles: Vec::new(),
+            paint_counts: BTreeMap::new(),
+            highlighted_tile: None,
+            preview_columns: 3,
+            tile_preview_size: tile_size
+                .width
+                .clamp(Self::MIN_PREVIEW_SIZE, Self::MAX_PREVIEW_SIZE),
+            button_padding: 6.0,
+            grid_origin: vec2(4.0, 4.0),
+            rect: Rect::new(0.0, 0.0, 280.0, 600.0),
+            map_path: format!("map.{}", Map::default_file_extension()),
+            image_scale: 1.0,
+            csv_indices: false,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the panel inside `area`; a collapsed panel (`None`) only finishes tile drags.
+    pub fn draw(&mut self, catalog: &AssetCatalog, area: Option<Rect>) -> PanelActions {
+        let mut actions = PanelActions::default();
+        self.ensure_selection_bounds(catalog);
+        self.hovered_tile = None;
+        let Some(rect) = area else {
+            self.pointer_over_ui = false;
+            self.update_drag(catalog, &mut actions);
+            return actions;
+        };
+        self.rect = rect;
+
+        widgets::Window::new(hash!("palette_window"), rect.point(), rect.size())
+            .titlebar(false)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                if catalog.is_empty() {
+                    ui.label(None, tr("palette.no_assets"));
+                    ui.label(None, tr("palette.add_assets"));
+                    return;
+                }
+
+                self.draw_quick_strip(ui, catalog);
+
+                widgets::InputText::new(hash!("palette_tile_filter"))
+                    .label(tr("palette.filter"))
+                    .size(vec2(190.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, &mut self.tile_filter);
+
+                self.draw_category_tree(ui, catalog);
+                ui.separator();
+
+                if let Some(category) = catalog.category(self.selected_category) {
+                    if category.is_empty() && self.tile_filter.trim().is_empty() {
+                        ui.label(None, tr("palette.empty_category"));
+                    } else {
+                        ui.label(None, tr("palette.hint"));
+                        if let Some(index) = self.selected_tile
+                            && let Some(tile) = category.tile(index)
+                        {
+                            ui.label(None, &trf("palette.selected", &[("tile", &tile.name)]));
+                        }
+                        let stamp = self.stamp(catalog);
+                        if let Some((width, height)) = stamp_size(&stamp) {
+                            ui.label(
+                                None,
+                                &trf("palette.stamp", &[("width", &width), ("height", &height)]),
+                            );
+                        }
+                        if let Some(tile) = self.secondary_sprite(catalog) {
+                            ui.label(None, &trf("palette.secondary", &[("tile", &tile.name)]));
+                        }
+                        let stamp_ids: Vec<String> = stamp
+                            .iter()
+                            .map(|(_, _, sprite)| sprite.id.clone())
+                            .collect();
+                        self.draw_grid_options(ui);
+                        self.draw_tile_grid(ui, catalog, &stamp_ids);
+                    }
+                }
+
+                ui.separator();
+                widgets::InputText::new(hash!("palette_map_path"))
+                    .label(tr("palette.map_file"))
+                    .size(vec2(190.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, &mut self.map_path);
+                if ui.button(None, tr("palette.new_map")) {
+                    actions.new_map_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.resize_map")) {
+                    actions.resize_map_requested = true;
+                }
+                if ui.button(None, tr("palette.save_map")) {
+                    actions.save_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.save_as")) {
+                    actions.save_as_requested = true;
+                }
+                if ui.button(None, tr("palette.load_map")) {
+                    actions.load_requested = true;
+                }
+                if ui.button(None, tr("palette.export_tiled")) {
+                    actions.tiled_export_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.export_image")) {
+                    actions.image_export_requested =
+                        Some(self.image_scale.round().max(1.0) as u32);
+                }
+                ui.slider(
+                    hash!("palette_image_scale"),
+                    tr("palette.image_scale"),
+                    1.0..8.0,
+                    &mut self.image_scale,
+                );
+                self.image_scale = self.image_scale.round();
+                if ui.button(None, tr("palette.export_csv")) {
+                    actions.csv_export_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.import_csv")) {
+                    actions.csv_import_requested = true;
+                }
+                ui.checkbox(
+                    hash!("palette_csv_indices"),
+                    tr("palette.csv_indices"),
+                    &mut self.csv_indices,
+                );
+                if ui.button(None, tr("palette.apply_migration")) {
+                    actions.migration_requested = true;
+                }
+                if ui.button(None, tr("palette.preferences")) {
+                    actions.preferences_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.view_settings")) {
+                    actions.view_settings_requested = true;
+                }
+                if ui.button(None, tr("palette.statistics")) {
+                    actions.statistics_requested = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, tr("palette.replace_tiles")) {
+                    actions.replace_tiles_requested = true;
+                }
+            });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        self.update_drag(catalog, &mut actions);
+        actions
+    }
+
+    /// Returns `true` when CSV grids hold catalog tile indices instead of tile ids.
+    pub fn csv_indices(&self) -> bool {
+        self.csv_indices
+    }
+
+    /// Map file path typed in the panel; the extension selects the format.
+    pub fn map_path(&self) -> &str {
+        self.map_path.trim()
+    }
+
+    /// Shows `path` in the map file field, e.g. after a dialog picked it.
+    pub fn set_map_path(&mut self, path: &Path) {
+        self.map_path = path.display().to_string();
+    }
+
+    /// Starts a drag on a grid tile and reports the drop once the button is released off the panel.
+    fn update_drag(&mut self, catalog: &AssetCatalog, actions: &mut PanelActions) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.drag_from_palette = self.hovered_tile;
+        }
+        let Some(index) = self.drag_from_palette else {
+            return;
+        };
+
+        if is_mouse_button_down(MouseButton::Left) {
+            if !self.pointer_over_ui
+                && let Some(sprite) = self.dragged_sprite(catalog)
+            {
+                let (mouse_x, mouse_y) = mouse_position();
+                let half = self.tile_preview_size / 2.0;
+                draw_texture_ex(
+                    &sprite.texture,
+                    mouse_x - half,
+                    mouse_y - half,
+                    Color::new(1.0, 1.0, 1.0, 0.6),
+                    DrawTextureParams {
+                        dest_size: Some(vec2(self.tile_preview_size, self.tile_preview_size)),
+                        ..Default::default()
+                    },
+                );
+            }
+        } else {
+            self.drag_from_palette = None;
+            if !self.pointer_over_ui {
+                actions.tile_dropped = Some(index);
+            }
+        }
+    }
+
+    /// Rows of recently and frequently painted tiles; clicking one makes it the brush.
+    fn draw_quick_strip(&mut self, ui: &mut Ui, catalog: &AssetCatalog) {
+        let frequent = self.frequent_tiles();
+        let recent = self.recent_tiles.clone();
+        for (label, ids) in [
+            (tr("palette.recent"), recent),
+            (tr("palette.frequent"), frequent),
+        ] {
+            let sprites: Vec<&TileSprite> = ids
+                .iter()
+                .filter_map(|id| catalog.sprite_by_id(id))
+                .collect();
+            if sprites.is_empty() {
+                continue;
+            }
+            ui.label(None, label);
+            for sprite in sprites {
+                ui.same_line(0.0);
+                let selected = self
+                    .selected_sprite(catalog)
+                    .is_some_and(|selected| selected.id == sprite.id);
+                if widgets::Button::new(sprite.texture.clone())
+                    .size(vec2(Self::QUICK_TILE_SIZE, Self::QUICK_TILE_SIZE))
+                    .selected(selected)
+                    .ui(ui)
+                {
+                    self.pick_tile(catalog, &sprite.id);
+                }
+            }
+        }
+    }
+
+    /// Ids of the most painted tiles, most painted first.
+    fn frequent_tiles(&self) -> Vec<String> {
+        let mut counts: Vec<(&String, &u32)> = self.paint_counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        counts
+            .into_iter()
+            .take(Self::QUICK_TILES)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Counts one paint action for each of `ids` and moves them to the front of the
+    /// recently used tiles.
+    pub fn record_painted(&mut self, ids: Vec<String>) {
+        for id in ids {
+            *self
+                .paint_counts
+                .entry(id.clone())
+                .or_default() += 1;
+            self.recent_tiles
+                .retain(|recent| *recent != id);
+            self.recent_tiles.insert(0, id);
+        }
+        self.recent_tiles
+            .truncate(Self::QUICK_TILES);
+    }
+
+    /// Restores the recently used tiles and paint counts of a previous session.
+    pub fn restore_tile_usage(&mut self, recent: &[String], counts: &BTreeMap<String, u32>) {
+        self.recent_tiles = recent
+            .iter()
+            .take(Self::QUICK_TILES)
+            .cloned()
+            .collect();
+        self.paint_counts = counts.clone();
+    }
+
+    /// Ids of the most recently painted tiles, most recent first.
+    pub fn recent_tiles(&self) -> &[String] {
+        &self.recent_tiles
+    }
+
+    /// Number of paint actions that used each tile id.
+    pub fn paint_counts(&self) -> &BTreeMap<String, u32> {
+        &self.paint_counts
+    }
+
+    /// Preview size and column count sliders shown above the tile grid.
+    fn draw_grid_options(&mut self, ui: &mut Ui) {
+        ui.slider(
+            hash!("palette_preview_size"),
+            tr("palette.preview_size"),
+            Self::MIN_PREVIEW_SIZE..Self::MAX_PREVIEW_SIZE,
+            &mut self.tile_preview_size,
+        );
+        self.tile_preview_size = self.tile_preview_size.round();
+        let mut columns = self.preview_columns as f32;
+        ui.slider(
+            hash!("palette_columns"),
+            tr("palette.columns"),
+            1.0..Self::MAX_COLUMNS as f32,
+            &mut columns,
+        );
+        self.preview_columns = (columns.round() as usize).min(self.max_columns());
+    }
+
+    /// Most columns of the current preview size that fit the panel width.
+    fn max_columns(&self) -> usize {
+        let slot = self.tile_preview_size + 2.0 * self.button_padding;
+        let width = self.rect.w - 2.0 * self.grid_origin.x - 20.0;
+        ((width / slot) as usize).clamp(1, Self::MAX_COLUMNS)
+    }
+
+    /// Draws every category and, below expanded nodes, their nested sub-categories.
+    /// Clicking a name shows its tiles along with those of the groups nested in it.
+    fn draw_category_tree(&mut self, ui: &mut Ui, catalog: &AssetCatalog) {
+        let mut rows = Vec::new();
+        for (category_index, category) in catalog.categories().iter().enumerate() {
+            let expanded = self
+                .expanded_nodes
+                .contains(&category.name);
+            rows.push(TreeRow {
+                category: category_index,
+                sub_category: 0,
+                depth: 0,
+                label: format!("{} ({})", category.name, category.tile_count()),
+                key: category.name.clone(),
+                foldable: !category.sub_categories.is_empty(),
+                expanded,
+            });
+            if !expanded {
+                continue;
+            }
+            // Depth of the collapsed node whose nested groups are being skipped.
+            let mut collapsed_depth = None;
+            for (sub_index, sub) in category
+                .sub_categories
+                .iter()
+                .enumerate()
+            {
+                if collapsed_depth.is_some_and(|depth| sub.depth > depth) {
+                    continue;
+                }
+                let key = format!("{}/{}", category.name, category.sub_category_path(sub_index));
+                let expanded = self.expanded_nodes.contains(&key);
+                collapsed_depth = (!expanded).then_some(sub.depth);
+                rows.push(TreeRow {
+                    category: category_index,
+                    sub_category: sub_index + 1,
+                    depth: sub.depth,
+                    label: format!("{} ({})", sub.name, category.sub_tree_range(sub_index).len()),
+                    key,
+                    foldable: category.has_nested(sub_index),
+                    expanded,
+                });
+            }
+        }
+
+        let height = (rows.len() as f32 * Self::TREE_ROW_HEIGHT + 4.0).min(Self::TREE_HEIGHT);
+        widgets::Group::new(hash!("palette_category_tree"), vec2(self.rect.w - 20.0, height))
+            .layout(Layout::Vertical)
+            .ui(ui, |ui| {
+                for (row_index, row) in rows.iter().enumerate() {
+                    let x = 4.0 + row.depth as f32 * Self::TREE_INDENT;
+                    let y = 2.0 + row_index as f32 * Self::TREE_ROW_HEIGHT;
+                    if row.foldable
+                        && widgets::Button::new(if row.expanded {
+                            "-"
+                        } else {
+                            "+"
+                        })
+                        .position(vec2(x, y))
+                        .size(vec2(16.0, 16.0))
+                        .ui(ui)
+                        && !self.expanded_nodes.remove(&row.key)
+                    {
+                        self.expanded_nodes
+                            .insert(row.key.clone());
+                    }
+                    let selected = row.category == self.selected_category
+                        && row.sub_category == self.selected_sub_category;
+                    if widgets::Button::new(row.label.as_str())
+                        .position(vec2(x + 20.0, y))
+                        .selected(selected)
+                        .ui(ui)
+                    {
+                        self.selected_category = row.category;
+                        self.selected_sub_category = row.sub_category;
+                    }
+                }
+            });
+
+        if let Some(category) = catalog.category(self.selected_category)
+            && self.selected_sub_category > 0
+        {
+            let path = category.sub_category_path(self.selected_sub_category - 1);
+            ui.label(
+                None,
+                &trf("palette.showing", &[("path", &format!("{}/{path}", category.name))]),
+            );
+        }
+    }
+
+    /// Draws the tiles of the selected (sub-)category, or the tiles matching the filter,
+    /// in a scrollable area.
+    fn draw_tile_grid(&mut self, ui: &mut Ui, catalog: &AssetCatalog, stamp_ids: &[String]) {
+        let query = self.tile_filter.trim();
+        let tiles: Vec<(usize, usize, &TileSprite)> = if query.is_empty() {
+            let Some(category) = catalog.category(self.selected_category) else {
+                return;
+            };
+            let visible = match self.selected_sub_category {
+                0 => 0..category.tile_count(),
+                sub => category.sub_tree_range(sub - 1),
+            };
+            category
+                .tiles()
+                .enumerate()
+                .skip(visible.start)
+                .take(visible.len())
+                .map(|(index, tile)| (self.selected_category, index, tile))
+                .collect()
+        } else {
+            let tiles: Vec<_> = catalog
+                .categories()
+                .iter()
+                .enumerate()
+                .flat_map(|(category_index, category)| {
+                    category
+                        .tiles()
+                        .enumerate()
+                        .map(move |(index, tile)| (category_index, index, tile))
+                })
+                .filter(|(_, _, tile)| fuzzy_match(&tile.name, query))
+                .collect();
+            ui.label(None, &trf("palette.matching_tiles", &[("count", &tiles.len())]));
+            tiles
+        };
+
+        let columns = self.preview_columns.max(1);
+        let button_edge = self.tile_preview_size + self.button_padding;
+        let rows = tiles.len().div_ceil(columns);
+        let content_height =
+            rows as f32 * (button_edge + self.button_padding) + 2.0 * self.grid_origin.y;
+        let grid_height = content_height
+            .min((self.rect.h - Self::GRID_CHROME_HEIGHT).max(Self::MIN_GRID_HEIGHT));
+
+        widgets::Group::new(hash!("palette_tile_grid"), vec2(self.rect.w - 20.0, grid_height))
+            .layout(Layout::Vertical)
+            .ui(ui, |ui| self.draw_tile_buttons(ui, &tiles, stamp_ids));
+    }
+
+    /// Draws `(category, index, sprite)` tiles as buttons, handling selection clicks.
+    fn draw_tile_buttons(
+        &mut self, ui: &mut Ui, tiles: &[(usize, usize, &TileSprite)], stamp_ids: &[String],
+    ) {
+        let columns = self.preview_columns.max(1);
+        let button_edge = self.tile_preview_size + self.button_padding;
+        let mut x = self.grid_origin.x;
+        let mut y = self.grid_origin.y;
+        for (slot, &(category, index, tile)) in tiles.iter().enumerate() {
+            let in_selected_category = category == self.selected_category;
+            let pressed = widgets::Button::new(tile.texture.clone())
+                .position(vec2(x, y))
+                .size(vec2(button_edge, button_edge))
+                .selected(
+                    (in_selected_category && self.selected_tile == Some(index))
+                        || self.highlighted_tile == Some((category, index))
+                        || stamp_ids.contains(&tile.id),
+                )
+                .ui(ui);
+
+            if pressed {
+                if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+                    self.secondary_tile = Some((category, index));
+                } else if (is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift))
+                    && in_selected_category
+                    && self.selected_tile.is_some()
+                {
+                    self.stamp_corner = Some(index);
+                } else {
+                    if !in_selected_category {
+                        self.show_category(category);
+                    }
+                    self.selected_tile = Some(index);
+                    self.stamp_corner = None;
+                }
+            }
+            // Checked after the click, so a press that switched category can start a drag.
+            if ui.last_item_hovered() && category == self.selected_category {
+                self.hovered_tile = Some(index);
+            }
+
+            x += button_edge + self.button_padding;
+            if (slot + 1) % columns == 0 {
+                x = self.grid_origin.x;
+                y += button_edge + self.button_padding;
+            }
+        }
+    }
+
+    /// Restores a previous session's selection, locating the tile by id across categories.
+    pub fn restore_selection(
+        &mut self, catalog: &AssetCatalog, category_index: usize, tile_id: Option<&str>,
+    ) {
+        self.selected_category = category_index;
+        self.selected_sub_category = 0;
+        self.selected_tile = None;
+        self.stamp_corner = None;
+
+        if let Some((category, tile)) = tile_id.and_then(|id| find_tile(catalog, id)) {
+            self.selected_category = category;
+            self.selected_tile = Some(tile);
+        }
+
+        self.ensure_selection_bounds(catalog);
+    }
+
+    /// Makes the tile with the given id the brush, switching to its category; returns
+    /// `false`, leaving the selection untouched, when no category holds it.
+    pub fn pick_tile(&mut self, catalog: &AssetCatalog, tile_id: &str) -> bool {
+        let Some((category, tile)) = find_tile(catalog, tile_id) else {
+            return false;
+        };
+        self.selected_category = category;
+        self.selected_sub_category = 0;
+        self.selected_tile = Some(tile);
+        self.stamp_corner = None;
+        self.highlighted_tile = None;
+        true
+    }
+
+    /// Highlights the tile with the given id, switching to its category; `None` clears it.
+    pub fn set_highlight_id(&mut self, catalog: &AssetCatalog, tile_id: Option<&str>) {
+        self.highlighted_tile = tile_id.and_then(|id| find_tile(catalog, id));
+        if let Some((category, _)) = self.highlighted_tile {
+            self.selected_category = category;
+            self.selected_sub_category = 0;
+        }
+    }
+
+    /// Returns the sprite highlighted by the quick search.
+    pub fn highlighted_sprite<'a>(&self, catalog: &'a AssetCatalog) -> Option<&'a TileSprite> {
+        let (category, index) = self.highlighted_tile?;
+        catalog.category(category)?.tile(index)
+    }
+
+    /// Switches the palette to another category without changing the selected tile.
+    pub fn show_category(&mut self, category: usize) {
+        self.selected_category = category;
+        self.selected_sub_category = 0;
+    }
+
+    /// Re-locates the secondary brush tile by id after the catalog layout changed.
+    pub fn restore_secondary(&mut self, catalog: &AssetCatalog, tile_id: Option<&str>) {
+        self.secondary_tile = tile_id.and_then(|id| find_tile(catalog, id));
+    }
+
+    pub fn selected_category(&self) -> usize {
+        self.selected_category
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    pub fn selected_sprite<'a>(&self, catalog: &'a AssetCatalog) -> Option<&'a TileSprite> {
+        let category = catalog.category(self.selected_category)?;
+        let index = self.selected_tile?;
+        category.tile(index)
+    }
+
+    /// Tiles of the multi-tile stamp as `(dx, dy, sprite)` offsets from its top-left corner.
+    ///
+    /// The stamp covers the spritesheet rectangle between the selected tile and the
+    /// `Shift+click`ed corner; it is empty unless both come from the same sheet and span
+    /// more than one tile.
+    pub fn stamp<'a>(&self, catalog: &'a AssetCatalog) -> Vec<(usize, usize, &'a TileSprite)> {
+        let Some(category) = catalog.category(self.selected_category) else {
+            return Vec::new();
+        };
+        let (Some(anchor), Some(corner)) = (
+            self.selected_tile
+                .and_then(|index| category.tile(index)),
+            self.stamp_corner
+                .and_then(|index| category.tile(index)),
+        ) else {
+            return Vec::new();
+        };
+        let (anchor_slot, corner_slot) = (anchor.sheet_slot(), corner.sheet_slot());
+        if anchor_slot.path != corner_slot.path || anchor_slot.index == corner_slot.index {
+            return Vec::new();
+        }
+
+        let columns = anchor_slot.columns.max(1);
+        let cell = |index: usize| (index % columns, index / columns);
+        let area = Selection::from_corners(cell(anchor_slot.index), cell(corner_slot.index));
+        area.cells()
+            .filter_map(|(column, row)| {
+                catalog
+                    .sprite_at(anchor_slot.path, row * columns + column)
+                    .map(|sprite| (column - area.x, row - area.y, sprite))
+            })
+            .collect()
+    }
+
+    /// Returns true while a tile is being dragged from the grid.
+    pub fn is_dragging(&self) -> bool {
+        self.drag_from_palette.is_some()
+    }
+
+    /// Returns the sprite currently being dragged from the grid.
+    pub fn dragged_sprite<'a>(&self, catalog: &'a AssetCatalog) -> Option<&'a TileSprite> {
+        self.tile_sprite(catalog, self.drag_from_palette?)
+    }
+
+    /// Returns a tile of the selected category by its grid index.
+    pub fn tile_sprite<'a>(
+        &self, catalog: &'a AssetCatalog, index: usize,
+    ) -> Option<&'a TileSprite> {
+        catalog
+            .category(self.selected_category)?
+            .tile(index)
+    }
+
+    /// Returns the sprite picked with `Ctrl+click` for the secondary brush.
+    pub fn secondary_sprite<'a>(&self, catalog: &'a AssetCatalog) -> Option<&'a TileSprite> {
+        let (category, index) = self.secondary_tile?;
+        catalog.category(category)?.tile(index)
+    }
+
+    fn ensure_selection_bounds(&mut self, catalog: &AssetCatalog) {
+        let category_count = catalog.categories().len();
+        if category_count == 0 {
+            self.selected_category = 0;
+            self.selected_tile = None;
+            return;
+        }
+
+        if self.selected_category >= category_count {
+            self.selected_category = 0;
+            self.selected_tile = None;
+        }
+
+        if let Some(category) = catalog.category(self.selected_category) {
+            if let Some(index) = self.selected_tile
+                && index >= category.tile_count()
+            {
+                self.selected_tile = None;
+            }
+            if self.selected_sub_category > category.sub_categories.len() {
+                self.selected_sub_category = 0;
+            }
+        } else {
+            self.selected_tile = None;
+        }
+    }
+}
+
+/// Width and height, in tiles, of a non-empty stamp.
+fn stamp_size(stamp: &[(usize, usize, &TileSprite)]) -> Option<(usize, usize)> {
+    let width = stamp
+        .iter()
+        .map(|&(dx, _, _)| dx + 1)
+        .max()?;
+    let height = stamp
+        .iter()
+        .map(|&(_, dy, _)| dy + 1)
+        .max()?;
+    Some((width, height))
+}
+
+/// Returns `true` when the characters of `query` appear in `name` in order, ignoring case
+/// (e.g. `"gwl"` matches `"grass_wall"`).
+fn fuzzy_match(name: &str, query: &str) -> bool {
+    let mut name_chars = name
+        .chars()
+        .flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|wanted| name_chars.any(|c| c == wanted))
+}
+
+/// Returns the `(category, tile)` indices of the sprite with the given id.
+fn find_tile(catalog: &AssetCatalog, id: &str) -> Option<(usize, usize)> {
+    catalog
+        .categories()
+        .iter()
+        .enumerate()
+        .find_map(|(category_index, category)| {
+            category
+                .tiles()
+                .position(|tile| tile.id == id)
+                .map(|tile_index| (category_index, tile_index))
+        })
+}
+
+/// One visible node of the category tree.
+struct TreeRow {
+    category: usize,
+    /// Same meaning as [`PalettePanel::selected_sub_category`].
+    sub_category: usize,
+    depth: usize,
+    label: String,
+    /// Path of the node in [`PalettePanel::expanded_nodes`].
+    key: String,
+    /// Set when other groups are nested in the node.
+    foldable: bool,
+    expanded: bool,
+}
+
+/// Requests raised by the palette buttons during the current frame.
+#[derive(Default)]
+pub struct PanelActions {
+    /// Save to the map's file, asking for one when the map has none yet.
+    pub save_requested: bool,
+    /// Ask for a new file and save the map there.
+    pub save_as_requested: bool,
+    pub load_requested: bool,
+    pub migration_requested: bool,
+    pub preferences_requested: bool,
+    pub view_settings_requested: bool,
+    /// Open or close the map statistics report.
+    pub statistics_requested: bool,
+    pub new_map_requested: bool,
+    pub resize_map_requested: bool,
+    /// Open the find and replace dialog for tile ids.
+    pub replace_tiles_requested: bool,
+    /// Export to Tiled next to the map file (`.tmj` when the map file ends in it, else `.tmx`).
+    pub tiled_export_requested: bool,
+    /// Render the whole map to a PNG next to the map file at this scale.
+    pub image_export_requested: Option<u32>,
+    /// Write every tile layer as a CSV grid next to the map file.
+    pub csv_export_requested: bool,
+    /// Ask for a CSV grid and load it into the active layer.
+    pub csv_import_requested: bool,
+    /// Grid index (in the selected category) of a tile dropped outside the panel.
+    pub tile_dropped: Option<usize>,
+}
@@ -0,0 +1,251 @@
+use crate::core::assets::TextureFilter;
+use crate::core::camera::PanAxisLock;
+use crate::core::config::AppConfig;
+use crate::core::config::language::Language;
+use crate::core::config::theme::UiTheme;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+use std::path::PathBuf;
+
+/// Modal-less window editing a draft copy of the [`AppConfig`].
+pub struct PreferencesDialog {
+    open: bool,
+    draft: AppConfig,
+    paint_sound_input: String,
+    ui_font_input: String,
+    /// Brightness below which "Auto-detect collision" marks a tile as solid.
+    collision_threshold: f32,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl PreferencesDialog {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            draft: AppConfig::default(),
+            paint_sound_input: String::new(),
+            ui_font_input: String::new(),
+            collision_threshold: 96.0,
+            size: vec2(360.0, 640.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog with a fresh draft of the current configuration.
+    pub fn open(&mut self, config: &AppConfig) {
+        self.draft = config.clone();
+        self.paint_sound_input = path_to_input(&config.paint_sound_path);
+        self.ui_font_input = path_to_input(&config.ui_font_path);
+        self.open = true;
+    }
+
+    /// Draws the dialog, reporting the edited configuration once the user applies it.
+    pub fn draw(&mut self) -> PreferencesActions {
+        let mut actions = PreferencesActions::default();
+        if !self.open {
+            self.pointer_over_ui = false;
+            return actions;
+        }
+
+        let mut close = false;
+        let position = vec2((screen_width() - self.size.x - 20.0).max(0.0), 80.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("preferences_window"), position, self.size, |ui| {
+            ui.label(None, tr("preferences.title"));
+            ui.separator();
+
+            ui.label(None, tr("preferences.paint_sound"));
+            ui.input_text(hash!("paint_sound_path"), "", &mut self.paint_sound_input);
+            if ui.button(None, tr("preferences.clear_sound")) {
+                self.paint_sound_input.clear();
+            }
+
+            ui.separator();
+            ui.checkbox(
+                hash!("secondary_brush"),
+                tr("preferences.secondary_brush"),
+                &mut self.draft.secondary_brush_enabled,
+            );
+            ui.checkbox(
+                hash!("reopen_last_session"),
+                tr("preferences.reopen_last_session"),
+                &mut self.draft.reopen_last_session,
+            );
+            ui.checkbox(
+                hash!("rle_json_tiles"),
+                tr("preferences.rle_json_tiles"),
+                &mut self.draft.rle_json_tiles,
+            );
+            ui.slider(
+                hash!("paint_cooldown"),
+                tr("preferences.paint_cooldown"),
+                0.0..2.0,
+                &mut self.draft.paint_cooldown_tiles,
+            );
+
+            ui.separator();
+            ui.label(None, tr("preferences.camera_panning"));
+            let mut lock_x = self.draft.pan_axis_lock == PanAxisLock::LockX;
+            let mut lock_y = self.draft.pan_axis_lock == PanAxisLock::LockY;
+            ui.checkbox(hash!("pan_lock_x"), tr("preferences.lock_x"), &mut lock_x);
+            ui.checkbox(hash!("pan_lock_y"), tr("preferences.lock_y"), &mut lock_y);
+            self.draft.pan_axis_lock = match (lock_x, lock_y) {
+                (true, true) if self.draft.pan_axis_lock == PanAxisLock::LockX => {
+                    PanAxisLock::LockY
+                }
+                (true, _) => PanAxisLock::LockX,
+                (false, true) => PanAxisLock::LockY,
+                (false, false) => PanAxisLock::None,
+            };
+            ui.checkbox(
+                hash!("smooth_camera"),
+                tr("preferences.smooth_camera"),
+                &mut self.draft.smooth_camera,
+            );
+            ui.slider(
+                hash!("wheel_zoom_sensitivity"),
+                tr("preferences.wheel_zoom_sensitivity"),
+                0.25..3.0,
+                &mut self.draft.wheel_zoom_sensitivity,
+            );
+            ui.slider(
+                hash!("wheel_pan_sensitivity"),
+                tr("preferences.wheel_pan_sensitivity"),
+                0.25..3.0,
+                &mut self.draft.wheel_pan_sensitivity,
+            );
+
+            ui.separator();
+            ui.label(None, tr("preferences.appearance"));
+            let language_labels: Vec<&str> = Language::ALL
+                .iter()
+                .map(|language| language.name())
+                .collect();
+            let mut language_index = Language::ALL
+                .iter()
+                .position(|language| *language == self.draft.language)
+                .unwrap_or(0);
+            ui.combo_box(
+                hash!("language"),
+                tr("preferences.language"),
+                &language_labels,
+                &mut language_index,
+            );
+            self.draft.language = Language::ALL[language_index];
+            let theme_labels: Vec<&str> = UiTheme::ALL
+                .iter()
+                .map(|&theme| theme_name(theme))
+                .collect();
+            let mut theme_index = UiTheme::ALL
+                .iter()
+                .position(|theme| *theme == self.draft.ui_theme)
+                .unwrap_or(0);
+            ui.combo_box(
+                hash!("ui_theme"),
+                tr("preferences.theme"),
+                &theme_labels,
+                &mut theme_index,
+            );
+            self.draft.ui_theme = UiTheme::ALL[theme_index];
+            ui.slider(
+                hash!("ui_scale"),
+                tr("preferences.ui_scale"),
+                AppConfig::MIN_UI_SCALE..AppConfig::MAX_UI_SCALE,
+                &mut self.draft.ui_scale,
+            );
+            ui.label(None, tr("preferences.ui_font"));
+            ui.input_text(hash!("ui_font_path"), "", &mut self.ui_font_input);
+
+            ui.separator();
+            let filter_labels: Vec<&str> = TextureFilter::ALL
+                .iter()
+                .map(|filter| filter.name())
+                .collect();
+            let mut filter_index = TextureFilter::ALL
+                .iter()
+                .position(|filter| *filter == self.draft.default_filter_mode)
+                .unwrap_or(0);
+            ui.combo_box(
+                hash!("default_filter"),
+                tr("preferences.texture_filter"),
+                &filter_labels,
+                &mut filter_index,
+            );
+            self.draft.default_filter_mode = TextureFilter::ALL[filter_index];
+
+            if ui.button(None, tr("preferences.compact_catalog")) {
+                actions.compact_requested = true;
+            }
+            ui.slider(
+                hash!("collision_threshold"),
+                tr("preferences.collision_threshold"),
+                0.0..255.0,
+                &mut self.collision_threshold,
+            );
+            if ui.button(None, tr("preferences.detect_collision")) {
+                actions.collision_detection_requested =
+                    Some(self.collision_threshold.round() as u8);
+            }
+
+            ui.separator();
+            if ui.button(None, tr("common.apply")) {
+                self.draft.paint_sound_path = input_to_path(&self.paint_sound_input);
+                self.draft.ui_font_path = input_to_path(&self.ui_font_input);
+                actions.applied = Some(self.draft.clone());
+                close = true;
+            }
+            if ui.button(None, tr("common.cancel")) {
+                close = true;
+            }
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if close {
+            self.open = false;
+        }
+        actions
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the preferences dialog during the current frame.
+#[derive(Default)]
+pub struct PreferencesActions {
+    /// Configuration confirmed with "Apply".
+    pub applied: Option<AppConfig>,
+    /// Free palette sprites that the map does not use.
+    pub compact_requested: bool,
+    /// Mark tiles walkable or solid using this brightness threshold.
+    pub collision_detection_requested: Option<u8>,
+}
+
+fn theme_name(theme: UiTheme) -> &'static str {
+    match theme {
+        UiTheme::Light => tr("preferences.theme_light"),
+        UiTheme::Dark => tr("preferences.theme_dark"),
+    }
+}
+
+fn path_to_input(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+fn input_to_path(input: &str) -> Option<PathBuf> {
+    let trimmed = input.trim();
+    (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
+}
@@ -0,0 +1,134 @@
+use crate::core::project::Project;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use std::path::PathBuf;
+
+/// Collapsible "Project" window at the top of the screen creating, opening and saving
+/// `.forgetile` projects, listing the maps of the open one and opening the asset
+/// directories dialog.
+pub struct ProjectMenu {
+    expanded: bool,
+    position: Vec2,
+    /// Width of the collapsed window; the expanded one is wider to fit map names.
+    width: f32,
+    expanded_width: f32,
+    pointer_over_ui: bool,
+}
+
+impl ProjectMenu {
+    /// Height of the collapsed window and of each extra row.
+    const ROW_HEIGHT: f32 = 24.0;
+
+    pub fn new() -> Self {
+        Self {
+            expanded: false,
+            position: vec2(300.0, 4.0),
+            width: 115.0,
+            expanded_width: 260.0,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the menu for the open `project`, if any; picking an entry collapses it.
+    pub fn draw(&mut self, project: Option<&Project>) -> ProjectMenuActions {
+        let mut actions = ProjectMenuActions::default();
+        let rows = match (self.expanded, project) {
+            (false, _) => 0,
+            (true, None) => 3,
+            (true, Some(project)) => project.maps.len().max(1) + 7,
+        };
+        let width = if self.expanded {
+            self.expanded_width
+        } else {
+            self.width
+        };
+        let size = vec2(width, Self::ROW_HEIGHT * (rows as f32 + 1.5));
+        let rect = Rect::new(self.position.x, self.position.y, size.x, size.y);
+        let mut toggle = false;
+
+        root_ui().window(hash!("project_menu_window"), self.position, size, |ui| {
+            let label = if self.expanded {
+                tr("project_menu.expanded")
+            } else {
+                tr("project_menu.collapsed")
+            };
+            toggle = ui.button(None, label);
+            if !self.expanded {
+                return;
+            }
+
+            actions.new_requested = ui.button(None, tr("project_menu.new"));
+            actions.open_requested = ui.button(None, tr("project_menu.open"));
+            actions.asset_dirs_requested = ui.button(None, tr("project_menu.asset_dirs"));
+            let Some(project) = project else {
+                return;
+            };
+            ui.separator();
+            ui.label(None, &trf("project_menu.maps", &[("name", &project.name())]));
+            if project.maps.is_empty() {
+                ui.label(None, tr("file_menu.none_yet"));
+            }
+            for (index, (map, path)) in project
+                .maps
+                .iter()
+                .zip(project.map_paths())
+                .enumerate()
+            {
+                if ui.button(None, map.display().to_string().as_str()) {
+                    actions.map_requested = Some(path);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "x") {
+                    actions.remove_map_requested = Some(index);
+                }
+            }
+            actions.add_map_requested = ui.button(None, tr("project_menu.add_map"));
+            actions.save_requested = ui.button(None, tr("project_menu.save"));
+            actions.close_requested = ui.button(None, tr("project_menu.close"));
+        });
+
+        if toggle || actions.any() {
+            self.expanded = toggle && !self.expanded;
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the project menu during the current frame.
+#[derive(Default)]
+pub struct ProjectMenuActions {
+    /// Create a project file over the current assets directory.
+    pub new_requested: bool,
+    pub open_requested: bool,
+    /// Edit the asset directories of the project, or of the editor without a project.
+    pub asset_dirs_requested: bool,
+    /// Add the active map file to the open project.
+    pub add_map_requested: bool,
+    pub save_requested: bool,
+    pub close_requested: bool,
+    /// Map file of the project to open.
+    pub map_requested: Option<PathBuf>,
+    /// Position of the map to drop from the project's list.
+    pub remove_map_requested: Option<usize>,
+}
+
+impl ProjectMenuActions {
+    fn any(&self) -> bool {
+        self.new_requested
+            || self.open_requested
+            || self.asset_dirs_requested
+            || self.add_map_requested
+            || self.save_requested
+            || self.close_requested
+            || self.map_requested.is_some()
+            || self.remove_map_requested.is_some()
+    }
+}
@@ -0,0 +1,144 @@
+use crate::core::assets::{AssetCatalog, TileSprite};
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+use macroquad::window::screen_width;
+use std::collections::BTreeMap;
+
+/// Popup editing the key/value properties of a tile type (`Ctrl+Enter`).
+pub struct TilePropertyEditor {
+    open: bool,
+    tile_id: Option<String>,
+    tile_name: String,
+    /// Editable `(key, value)` rows; rows with an empty key are ignored.
+    rows: Vec<(String, String)>,
+    write_sidecar: bool,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl TilePropertyEditor {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            tile_id: None,
+            tile_name: String::new(),
+            rows: Vec::new(),
+            write_sidecar: false,
+            size: vec2(340.0, 300.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the editor on the properties of `sprite`.
+    pub fn open_for(&mut self, sprite: &TileSprite) {
+        self.tile_id = Some(sprite.id.clone());
+        self.tile_name = sprite.name.clone();
+        self.rows = sprite
+            .metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        self.open = true;
+    }
+
+    /// Draws the editor, applying every change to the catalog as it is typed.
+    pub fn draw(&mut self, catalog: &mut AssetCatalog) {
+        let Some(id) = self
+            .tile_id
+            .clone()
+            .filter(|_| self.open)
+        else {
+            self.pointer_over_ui = false;
+            return;
+        };
+
+        let mut close = false;
+        let mut remove_index = None;
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("tile_properties_window"), position, self.size, |ui| {
+            ui.label(None, &trf("properties.title", &[("tile", &self.tile_name)]));
+            ui.separator();
+
+            ui.label(None, tr("common.key"));
+            ui.same_line(145.0);
+            ui.label(None, tr("common.value"));
+            for (index, (key, value)) in self.rows.iter_mut().enumerate() {
+                widgets::InputText::new(hash!("property_key", index))
+                    .size(vec2(130.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, key);
+                ui.same_line(145.0);
+                widgets::InputText::new(hash!("property_value", index))
+                    .size(vec2(150.0, 19.0))
+                    .ratio(1.0)
+                    .ui(ui, value);
+                ui.same_line(305.0);
+                if ui.button(None, "x") {
+                    remove_index = Some(index);
+                }
+            }
+            if ui.button(None, tr("common.add_property")) {
+                self.rows
+                    .push((String::new(), String::new()));
+            }
+
+            ui.separator();
+            ui.checkbox(
+                hash!("properties_write_sidecar"),
+                tr("properties.save_tileset"),
+                &mut self.write_sidecar,
+            );
+            if ui.button(None, tr("common.close")) {
+                close = true;
+            }
+        });
+
+        if let Some(index) = remove_index {
+            self.rows.remove(index);
+        }
+        let changed = self.apply(catalog, &id);
+        if self.write_sidecar
+            && (changed || close)
+            && let Err(err) = catalog.save_tile_metadata(&id)
+        {
+            eprintln!("[assets] Could not save properties of {id}: {err}");
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if close {
+            self.open = false;
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    /// Writes the edited rows back to the sprite, returning `true` when they differed.
+    fn apply(&self, catalog: &mut AssetCatalog, id: &str) -> bool {
+        let metadata: BTreeMap<String, String> = self
+            .rows
+            .iter()
+            .filter(|(key, _)| !key.trim().is_empty())
+            .map(|(key, value)| (key.trim().to_string(), value.clone()))
+            .collect();
+        if catalog
+            .sprite_by_id(id)
+            .is_none_or(|sprite| sprite.metadata == metadata)
+        {
+            return false;
+        }
+
+        catalog.set_tile_metadata(id, metadata);
+        true
+    }
+}
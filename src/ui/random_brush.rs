@@ -0,0 +1,102 @@
+use crate::core::assets::TileSprite;
+use crate::core::tools::RandomBrush;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// Popup listing the tiles of the random brush with their weights.
+pub struct RandomBrushEditor {
+    open: bool,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl RandomBrushEditor {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            window_position: vec2(540.0, 80.0),
+            size: vec2(300.0, 300.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Draws the editor; `selected` is the palette tile offered by the "Add" button.
+    pub fn draw(&mut self, brush: &mut RandomBrush, selected: Option<&TileSprite>) {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return;
+        }
+
+        let mut close = false;
+        let mut remove_index = None;
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let total = brush.total_weight();
+
+        root_ui().window(hash!("random_brush_window"), position, self.size, |ui| {
+            ui.label(None, tr("random_brush.title"));
+            ui.separator();
+
+            match selected {
+                Some(sprite) => {
+                    if ui.button(None, tr("common.add_selected_tile")) {
+                        brush.push(sprite.clone());
+                    }
+                }
+                None => ui.label(None, tr("common.select_palette_tile")),
+            }
+            if ui.button(None, tr("common.close")) {
+                close = true;
+            }
+            ui.separator();
+
+            if brush.tiles.is_empty() {
+                ui.label(None, tr("random_brush.empty"));
+            }
+            for (index, tile) in brush.tiles.iter_mut().enumerate() {
+                widgets::Texture::new(tile.sprite.texture.clone())
+                    .size(20.0, 20.0)
+                    .ui(ui);
+                ui.same_line(30.0);
+                let share = if total > 0 {
+                    tile.weight as f32 * 100.0 / total as f32
+                } else {
+                    0.0
+                };
+                ui.label(None, &format!("{} x{} ({share:.0}%)", tile.sprite.name, tile.weight));
+                ui.same_line(200.0);
+                if ui.button(None, "-") {
+                    tile.weight = tile.weight.saturating_sub(1);
+                }
+                ui.same_line(220.0);
+                if ui.button(None, "+") {
+                    tile.weight += 1;
+                }
+                ui.same_line(250.0);
+                if ui.button(None, "x") {
+                    remove_index = Some(index);
+                }
+            }
+        });
+
+        if let Some(index) = remove_index {
+            brush.remove(index);
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if close {
+            self.open = false;
+        }
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,84 @@
+use crate::core::map::autosave::Recovery;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+use std::collections::VecDeque;
+
+/// Startup dialog offering to restore the autosaves newer than the last explicit save, one
+/// after the other.
+pub struct RecoveryDialog {
+    recoveries: VecDeque<Recovery>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+/// Answer given in the [`RecoveryDialog`].
+pub enum RecoveryChoice {
+    Restore(Recovery),
+    Discard(Recovery),
+}
+
+impl RecoveryDialog {
+    /// Creates the dialog, open while some of `recoveries` are unanswered.
+    pub fn new(recoveries: Vec<Recovery>) -> Self {
+        Self {
+            recoveries: recoveries.into(),
+            size: vec2(320.0, 150.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the dialog, returning the user's choice once a button is pressed.
+    pub fn draw(&mut self) -> Option<RecoveryChoice> {
+        let Some(recovery) = self.recoveries.front() else {
+            self.pointer_over_ui = false;
+            return None;
+        };
+
+        let source = recovery
+            .source
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| tr("recovery.unsaved_map").to_string());
+        let mut restore = false;
+        let mut discard = false;
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("recovery_window"), position, self.size, |ui| {
+            ui.label(None, tr("recovery.title"));
+            ui.separator();
+            ui.label(None, tr("recovery.found"));
+            ui.label(None, &source);
+            ui.separator();
+            restore = ui.button(None, tr("recovery.restore"));
+            ui.same_line(0.0);
+            discard = ui.button(None, tr("recovery.discard"));
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        if restore {
+            self.recoveries
+                .pop_front()
+                .map(RecoveryChoice::Restore)
+        } else if discard {
+            self.recoveries
+                .pop_front()
+                .map(RecoveryChoice::Discard)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.recoveries.is_empty()
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
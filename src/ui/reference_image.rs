@@ -0,0 +1,141 @@
+use crate::core::map::map::Map;
+use crate::core::map::reference::ReferenceImage;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// Window loading a tracing image over the map and adjusting its offset (in tiles), scale
+/// and opacity. The image is only drawn in the editor and never painted into a layer.
+pub struct ReferenceImagePanel {
+    open: bool,
+    /// Image path typed by the user.
+    path: String,
+    /// Reference image `path` was last filled from.
+    path_source: Option<String>,
+    error: Option<String>,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl ReferenceImagePanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            path: String::new(),
+            path_source: None,
+            error: None,
+            window_position: vec2(540.0, 310.0),
+            size: vec2(300.0, 230.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.error = None;
+    }
+
+    /// Draws the panel and applies the edits made in it to the reference image of `map`.
+    pub fn draw(&mut self, map: &mut Map) {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return;
+        }
+
+        let current = map.reference_image().cloned();
+        let source = current
+            .as_ref()
+            .map(|reference| reference.path.clone());
+        if self.path_source != source {
+            self.path = source.clone().unwrap_or_default();
+            self.path_source = source;
+        }
+
+        let mut draft = current.clone();
+        let (width, height) = map.dimensions();
+        let missing = current.is_some() && !map.reference_image_loaded();
+        let (mut load, mut remove, mut close) = (false, false, false);
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let (path, error) = (&mut self.path, &self.error);
+
+        root_ui().window(hash!("reference_image_window"), position, self.size, |ui| {
+            ui.label(None, tr("reference_image.title"));
+            ui.separator();
+            widgets::InputText::new(hash!("reference_image_path"))
+                .label(tr("layers.image"))
+                .size(vec2(200.0, 19.0))
+                .ratio(0.8)
+                .ui(ui, path);
+            load = ui.button(None, tr("layers.load_image"));
+            if let Some(reference) = draft.as_mut() {
+                ui.same_line(0.0);
+                remove = ui.button(None, tr("reference_image.remove"));
+                ui.checkbox(
+                    hash!("reference_image_visible"),
+                    tr("layers.show"),
+                    &mut reference.visible,
+                );
+                ui.slider(
+                    hash!("reference_image_offset_x"),
+                    tr("reference_image.offset_x"),
+                    -(width as f32)..width as f32,
+                    &mut reference.offset[0],
+                );
+                ui.slider(
+                    hash!("reference_image_offset_y"),
+                    tr("reference_image.offset_y"),
+                    -(height as f32)..height as f32,
+                    &mut reference.offset[1],
+                );
+                let (min_scale, max_scale) = ReferenceImage::SCALE_RANGE;
+                ui.slider(
+                    hash!("reference_image_scale"),
+                    tr("reference_image.scale"),
+                    min_scale..max_scale,
+                    &mut reference.scale,
+                );
+                ui.slider(
+                    hash!("reference_image_opacity"),
+                    tr("common.opacity"),
+                    0.0..1.0,
+                    &mut reference.opacity,
+                );
+                if missing {
+                    ui.label(None, tr("reference_image.missing"));
+                }
+            } else {
+                ui.label(None, tr("reference_image.hint"));
+            }
+            if let Some(error) = error {
+                ui.label(None, error);
+            }
+            ui.separator();
+            close = ui.button(None, tr("common.close"));
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if let Some(draft) = draft.filter(|draft| Some(draft) != current.as_ref()) {
+            map.set_reference_image_settings(draft);
+        }
+        if remove {
+            map.clear_reference_image();
+            self.error = None;
+        } else if load {
+            self.error = map
+                .load_reference_image(self.path.trim())
+                .err()
+                .map(|err| trf("reference_image.load_failed", &[("error", &err)]));
+        }
+        if close {
+            self.open = false;
+        }
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,163 @@
+use crate::core::assets::TileSprite;
+use crate::core::tools::ReplaceScope;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+use macroquad::window::screen_width;
+
+/// Dialog replacing one tile id with another across the active layer, every layer or the
+/// selection. The source tile can be picked by clicking it on the map.
+pub struct ReplaceTilesDialog {
+    open: bool,
+    source: String,
+    target: String,
+    scope: usize,
+    /// Set while the next click on the map picks the source tile.
+    picking: bool,
+    error: Option<String>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+/// Replacement confirmed in the [`ReplaceTilesDialog`].
+pub struct ReplaceTilesRequest {
+    pub from: String,
+    pub to: String,
+    pub scope: ReplaceScope,
+}
+
+impl ReplaceTilesDialog {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            source: String::new(),
+            target: String::new(),
+            scope: 0,
+            picking: false,
+            error: None,
+            size: vec2(340.0, 230.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog with the palette selection, if any, as the target tile.
+    pub fn open(&mut self, selected: Option<&TileSprite>) {
+        if let Some(sprite) = selected {
+            self.target = sprite.id.clone();
+        }
+        self.error = None;
+        self.picking = false;
+        self.open = true;
+    }
+
+    /// Returns `true` while the next click on the map should be passed to
+    /// [`ReplaceTilesDialog::pick_source`].
+    pub fn is_picking(&self) -> bool {
+        self.open && self.picking
+    }
+
+    /// Uses `id`, the tile clicked on the map, as the source tile.
+    pub fn pick_source(&mut self, id: Option<&str>) {
+        match id {
+            Some(id) => {
+                self.source = id.to_string();
+                self.error = None;
+            }
+            None => self.error = Some(tr("replace_tiles.empty_cell").to_string()),
+        }
+        self.picking = false;
+    }
+
+    /// Draws the dialog; `selected` is the palette tile offered for both fields.
+    pub fn draw(&mut self, selected: Option<&TileSprite>) -> Option<ReplaceTilesRequest> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let (mut replace, mut close) = (false, false);
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let scope_labels = [
+            tr("replace_tiles.active_layer"),
+            tr("replace_tiles.all_layers"),
+            tr("replace_tiles.selection"),
+        ];
+
+        root_ui().window(hash!("replace_tiles_window"), position, self.size, |ui| {
+            ui.label(None, tr("replace_tiles.title"));
+            ui.separator();
+            let fields = [
+                (tr("replace_tiles.find"), &mut self.source),
+                (tr("replace_tiles.replace_with"), &mut self.target),
+            ];
+            for (index, (label, id)) in fields.into_iter().enumerate() {
+                widgets::InputText::new(hash!("replace_tiles_id", index))
+                    .label(label)
+                    .size(vec2(220.0, 19.0))
+                    .ratio(0.6)
+                    .ui(ui, id);
+                if let Some(sprite) = selected {
+                    ui.same_line(0.0);
+                    if ui.button(None, tr("generator.use_selected")) {
+                        *id = sprite.id.clone();
+                    }
+                }
+            }
+            let pick_label = if self.picking {
+                tr("replace_tiles.picking")
+            } else {
+                tr("replace_tiles.pick")
+            };
+            if ui.button(None, pick_label) {
+                self.picking = !self.picking;
+            }
+            ui.combo_box(
+                hash!("replace_tiles_scope"),
+                tr("replace_tiles.scope"),
+                &scope_labels,
+                &mut self.scope,
+            );
+            if let Some(error) = &self.error {
+                ui.label(None, error);
+            }
+
+            ui.separator();
+            replace = ui.button(None, tr("replace_tiles.replace"));
+            ui.same_line(0.0);
+            close = ui.button(None, tr("common.cancel"));
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        let mut request = None;
+        let (from, to) = (self.source.trim(), self.target.trim());
+        if replace && (from.is_empty() || to.is_empty()) {
+            self.error = Some(tr("replace_tiles.missing_ids").to_string());
+        } else if replace {
+            request = Some(ReplaceTilesRequest {
+                from: from.to_string(),
+                to: to.to_string(),
+                scope: ReplaceScope::ALL[self
+                    .scope
+                    .min(ReplaceScope::ALL.len() - 1)],
+            });
+            close = true;
+        }
+        if close {
+            self.open = false;
+            self.picking = false;
+        }
+        request
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
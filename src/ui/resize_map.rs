@@ -0,0 +1,163 @@
+use crate::core::map::map::ResizeAnchor;
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+
+/// Dialog resizing the current map or cropping it to its painted content.
+pub struct ResizeMapDialog {
+    open: bool,
+    width_input: String,
+    height_input: String,
+    centered: bool,
+    error: Option<String>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+/// Operation confirmed in the [`ResizeMapDialog`].
+pub enum ResizeMapRequest {
+    /// Resize the grid to `width` x `height` tiles.
+    Resize {
+        width: usize,
+        height: usize,
+        anchor: ResizeAnchor,
+    },
+    /// Trim empty rows and columns from every edge.
+    CropToContent,
+}
+
+impl ResizeMapDialog {
+    /// Largest accepted map side, in tiles.
+    const MAX_MAP_TILES: usize = 1024;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            width_input: String::new(),
+            height_input: String::new(),
+            centered: false,
+            error: None,
+            size: vec2(300.0, 210.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog prefilled with the current map size.
+    pub fn open(&mut self, map_size: (usize, usize)) {
+        self.width_input = map_size.0.to_string();
+        self.height_input = map_size.1.to_string();
+        self.error = None;
+        self.open = true;
+    }
+
+    /// Draws the dialog, returning the chosen operation once a button is pressed with
+    /// valid values.
+    pub fn draw(&mut self) -> Option<ResizeMapRequest> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let mut resize = false;
+        let mut crop = false;
+        let mut close = false;
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("resize_map_window"), position, self.size, |ui| {
+            ui.label(None, tr("resize_map.title"));
+            ui.separator();
+            ui.input_text(
+                hash!("resize_map_width"),
+                tr("common.width_tiles"),
+                &mut self.width_input,
+            );
+            ui.input_text(
+                hash!("resize_map_height"),
+                tr("common.height_tiles"),
+                &mut self.height_input,
+            );
+            ui.checkbox(
+                hash!("resize_map_centered"),
+                tr("resize_map.keep_centered"),
+                &mut self.centered,
+            );
+            if let Some(error) = &self.error {
+                ui.label(None, error);
+            }
+
+            ui.separator();
+            if ui.button(None, tr("resize_map.resize")) {
+                resize = true;
+            }
+            ui.same_line(0.0);
+            if ui.button(None, tr("resize_map.crop")) {
+                crop = true;
+            }
+            if ui.button(None, tr("common.cancel")) {
+                close = true;
+            }
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        let mut request = None;
+        if crop {
+            request = Some(ResizeMapRequest::CropToContent);
+            close = true;
+        } else if resize {
+            match self.parse() {
+                Ok(parsed) => {
+                    request = Some(parsed);
+                    close = true;
+                }
+                Err(error) => self.error = Some(error),
+            }
+        }
+        if close {
+            self.open = false;
+        }
+        request
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+
+    fn parse(&self) -> Result<ResizeMapRequest, String> {
+        let map_side = |input: &str, label: &str| {
+            input
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|value| (1..=Self::MAX_MAP_TILES).contains(value))
+                .ok_or_else(|| {
+                    trf(
+                        "common.size_out_of_range",
+                        &[
+                            ("label", &label),
+                            ("min", &1),
+                            ("max", &Self::MAX_MAP_TILES),
+                        ],
+                    )
+                })
+        };
+
+        Ok(ResizeMapRequest::Resize {
+            width: map_side(&self.width_input, tr("common.width"))?,
+            height: map_side(&self.height_input, tr("common.height"))?,
+            anchor: if self.centered {
+                ResizeAnchor::Center
+            } else {
+                ResizeAnchor::TopLeft
+            },
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use crate::core::assets::AssetCatalog;
+use crate::ui::i18n::{tr, trf};
+use crate::ui::overlay::OverlayText;
+use crate::ui::palette::PalettePanel;
+use macroquad::color::{Color, WHITE};
+use macroquad::input::{KeyCode, get_char_pressed, is_key_pressed};
+use macroquad::shapes::draw_rectangle;
+use macroquad::window::screen_width;
+
+/// Translucent overlay filtering the palette by tile name as the user types.
+pub struct QuickSearch {
+    open: bool,
+    query: String,
+    /// Palette category shown before the search started, restored on `Escape`.
+    previous_category: usize,
+}
+
+impl QuickSearch {
+    const WIDTH: f32 = 360.0;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            previous_category: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handles typed characters; the overlay opens on an alphanumeric key when `can_open`.
+    ///
+    /// `Enter` selects the highlighted tile and `Escape` cancels the search.
+    pub fn update(&mut self, catalog: &AssetCatalog, palette: &mut PalettePanel, can_open: bool) {
+        let mut typed = Vec::new();
+        while let Some(character) = get_char_pressed() {
+            typed.push(character);
+        }
+        typed.reverse();
+
+        if !self.open {
+            match typed
+                .iter()
+                .find(|character| character.is_alphanumeric())
+            {
+                Some(&first) if can_open => {
+                    self.open = true;
+                    self.query = first.to_string();
+                    self.previous_category = palette.selected_category();
+                }
+                _ => return,
+            }
+        } else {
+            self.query.extend(
+                typed
+                    .into_iter()
+                    .filter(|character| !character.is_control()),
+            );
+            if is_key_pressed(KeyCode::Backspace) {
+                self.query.pop();
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            palette.set_highlight_id(catalog, None);
+            palette.show_category(self.previous_category);
+            self.close();
+            return;
+        }
+
+        let found = first_match(catalog, &self.query);
+        palette.set_highlight_id(catalog, found.as_deref());
+        if is_key_pressed(KeyCode::Enter) {
+            if let Some(id) = found.as_deref() {
+                palette.restore_selection(catalog, palette.selected_category(), Some(id));
+            }
+            palette.set_highlight_id(catalog, None);
+            self.close();
+        }
+    }
+
+    /// Draws the overlay near the top of the screen while it is open.
+    pub fn draw(&self, text: &OverlayText, catalog: &AssetCatalog, palette: &PalettePanel) {
+        if !self.open {
+            return;
+        }
+
+        let x = ((screen_width() - Self::WIDTH) / 2.0).max(0.0);
+        let y = 40.0;
+        draw_rectangle(x, y, Self::WIDTH, 52.0, Color::new(0.0, 0.0, 0.0, 0.65));
+        text.draw(
+            &trf("search.prompt", &[("query", &self.query)]),
+            x + 10.0,
+            y + 20.0,
+            1.5,
+            WHITE,
+        );
+        let result = match palette.highlighted_sprite(catalog) {
+            Some(sprite) => trf("search.match", &[("tile", &sprite.name)]),
+            None => tr("search.no_match").to_string(),
+        };
+        text.draw(&result, x + 10.0, y + 42.0, 1.2, WHITE);
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.query.clear();
+    }
+}
+
+/// Returns the id of the first tile, in palette order, whose name contains `query`.
+fn first_match(catalog: &AssetCatalog, query: &str) -> Option<String> {
+    let query = query.to_lowercase();
+    catalog
+        .categories()
+        .iter()
+        .flat_map(|category| category.tiles())
+        .find(|sprite| {
+            sprite
+                .name
+                .to_lowercase()
+                .contains(&query)
+        })
+        .map(|sprite| sprite.id.clone())
+}
@@ -0,0 +1,88 @@
+use crate::core::assets::TileSprite;
+use crate::core::tools::TileSequence;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui, widgets};
+
+/// Popup listing the tiles of the sequence brush, with add/remove controls.
+pub struct SequenceEditor {
+    open: bool,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl SequenceEditor {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            window_position: vec2(540.0, 80.0),
+            size: vec2(240.0, 300.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Draws the editor; `selected` is the palette tile offered by the "Add" button.
+    pub fn draw(&mut self, sequence: &mut TileSequence, selected: Option<&TileSprite>) {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return;
+        }
+
+        let mut close = false;
+        let mut remove_index = None;
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("sequence_window"), position, self.size, |ui| {
+            ui.label(None, tr("sequence.title"));
+            ui.separator();
+
+            match selected {
+                Some(sprite) => {
+                    if ui.button(None, tr("common.add_selected_tile")) {
+                        sequence.push(sprite.clone());
+                    }
+                }
+                None => ui.label(None, tr("common.select_palette_tile")),
+            }
+            if ui.button(None, tr("common.close")) {
+                close = true;
+            }
+            ui.separator();
+
+            if sequence.tiles.is_empty() {
+                ui.label(None, tr("sequence.empty"));
+            }
+            for (index, tile) in sequence.tiles.iter().enumerate() {
+                widgets::Texture::new(tile.texture.clone())
+                    .size(20.0, 20.0)
+                    .ui(ui);
+                ui.same_line(30.0);
+                ui.label(None, &format!("{}. {}", index + 1, tile.name));
+                ui.same_line(190.0);
+                if ui.button(None, "x") {
+                    remove_index = Some(index);
+                }
+            }
+        });
+
+        if let Some(index) = remove_index {
+            sequence.remove(index);
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if close {
+            self.open = false;
+        }
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
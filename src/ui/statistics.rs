@@ -0,0 +1,132 @@
+use crate::core::assets::AssetCatalog;
+use crate::core::map::map::Map;
+use crate::core::map::stats::MapStats;
+use crate::ui::i18n::{tr, trf};
+use forgetile_core::map::LayerKind;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{Layout, hash, root_ui, widgets};
+
+/// Popup reporting the painted cells of every tile layer, the tile ids in use, the tileset
+/// tiles painted nowhere and the estimated memory of the map. The report is taken when the
+/// panel opens and on "Refresh", not every frame.
+pub struct StatisticsPanel {
+    open: bool,
+    stats: Option<MapStats>,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl StatisticsPanel {
+    /// Height of the scrolling tile lists.
+    const LIST_HEIGHT: f32 = 110.0;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            stats: None,
+            window_position: vec2(300.0, 80.0),
+            size: vec2(340.0, 480.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.stats = None;
+    }
+
+    /// Draws the report of `map`, returning `true` when a CSV export was requested.
+    pub fn draw(&mut self, map: &Map, catalog: &AssetCatalog) -> bool {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return false;
+        }
+
+        let stats = self
+            .stats
+            .get_or_insert_with(|| map.stats(catalog));
+        let (mut refresh, mut export, mut close) = (false, false, false);
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let list_width = self.size.x - 20.0;
+
+        root_ui().window(hash!("statistics_window"), position, self.size, |ui| {
+            ui.label(None, tr("statistics.title"));
+            refresh = ui.button(None, tr("statistics.refresh"));
+            ui.same_line(0.0);
+            export = ui.button(None, tr("statistics.export_csv"));
+            ui.same_line(0.0);
+            close = ui.button(None, tr("common.close"));
+            ui.separator();
+
+            for layer in stats
+                .layers
+                .iter()
+                .filter(|layer| layer.kind == LayerKind::Tiles)
+            {
+                ui.label(
+                    None,
+                    &trf(
+                        "statistics.layer_fill",
+                        &[
+                            ("name", &layer.name),
+                            ("count", &layer.painted),
+                            ("percent", &format!("{:.1}", stats.fill(layer) * 100.0)),
+                        ],
+                    ),
+                );
+            }
+
+            ui.separator();
+            ui.label(None, &trf("statistics.used_tiles", &[("count", &stats.tile_usage.len())]));
+            widgets::Group::new(hash!("statistics_usage"), vec2(list_width, Self::LIST_HEIGHT))
+                .layout(Layout::Vertical)
+                .ui(ui, |ui| {
+                    for (id, count) in &stats.tile_usage {
+                        ui.label(None, &format!("{count:>6}  {id}"));
+                    }
+                });
+            ui.label(
+                None,
+                &trf("statistics.unused_tiles", &[("count", &stats.unused_tiles.len())]),
+            );
+            widgets::Group::new(hash!("statistics_unused"), vec2(list_width, Self::LIST_HEIGHT))
+                .layout(Layout::Vertical)
+                .ui(ui, |ui| {
+                    for id in &stats.unused_tiles {
+                        ui.label(None, id);
+                    }
+                });
+
+            ui.separator();
+            for (name, bytes) in &stats.memory {
+                ui.label(None, &format!("{name}: {} KiB", bytes.div_ceil(1024)));
+            }
+            ui.label(
+                None,
+                &trf("statistics.total_memory", &[("kib", &stats.total_memory().div_ceil(1024))]),
+            );
+        });
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if refresh {
+            self.stats = None;
+        }
+        if close {
+            self.open = false;
+        }
+        export
+    }
+
+    /// The report shown by the panel, taken when it last opened or refreshed.
+    pub fn stats(&self) -> Option<&MapStats> {
+        self.stats.as_ref()
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,88 @@
+use crate::core::assets::loader::AssetLoader;
+use crate::core::map::map::Map;
+use crate::ui::i18n::trf;
+use crate::ui::overlay::OverlayText;
+use crate::ui::tools::tool_name;
+use macroquad::color::{Color, DARKGRAY, LIGHTGRAY, SKYBLUE};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, vec2};
+use macroquad::shapes::draw_rectangle;
+use macroquad::window::{screen_height, screen_width};
+
+/// Strip along the bottom of the window with the hovered cell, the tile ids under the
+/// cursor on every layer, the current tool, zoom and map size. While assets load, their
+/// progress is shown on the right.
+pub struct StatusBar {
+    pointer_over_ui: bool,
+}
+
+impl StatusBar {
+    /// Height of the strip, in screen pixels.
+    pub const HEIGHT: f32 = 22.0;
+    /// Width of the asset loading progress bar, in screen pixels.
+    const PROGRESS_WIDTH: f32 = 160.0;
+
+    pub fn new() -> Self {
+        Self { pointer_over_ui: false }
+    }
+
+    pub fn draw(&mut self, map: &Map, overlay_text: &OverlayText, loader: Option<&AssetLoader>) {
+        let rect = Rect::new(0.0, screen_height() - Self::HEIGHT, screen_width(), Self::HEIGHT);
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color { r: 0.1, g: 0.1, b: 0.12, a: 0.9 });
+
+        let (width, height) = map.dimensions();
+        let zoom = map
+            .get_camera_controller()
+            .get_current_zoom();
+        let mut sections = Vec::new();
+        match map.hovered_tile() {
+            Some((tile_x, tile_y)) => {
+                sections.push(format!("x: {tile_x}, y: {tile_y}"));
+                let tiles: Vec<String> = map
+                    .tile_ids_at(tile_x, tile_y)
+                    .into_iter()
+                    .map(|(layer, id)| format!("{layer}: {id}"))
+                    .collect();
+                if !tiles.is_empty() {
+                    sections.push(tiles.join(", "));
+                }
+            }
+            None => sections.push("x: -, y: -".to_string()),
+        }
+        sections.push(trf("status_bar.tool", &[("tool", &tool_name(map.paint_mode()))]));
+        sections.push(trf("status_bar.zoom", &[("percent", &format!("{:.0}", zoom * 100.0))]));
+        sections.push(trf("status_bar.map", &[("width", &width), ("height", &height)]));
+
+        if let Some(loader) = loader {
+            let (loaded, total) = loader.progress();
+            let mut section =
+                trf("status_bar.loading_assets", &[("loaded", &loaded), ("total", &total)]);
+            if let Some(name) = loader
+                .current()
+                .and_then(|path| path.file_name())
+            {
+                section.push_str(&format!(" ({})", name.to_string_lossy()));
+            }
+            sections.push(section);
+
+            let bar = Rect::new(
+                rect.right() - Self::PROGRESS_WIDTH - 8.0,
+                rect.y + 6.0,
+                Self::PROGRESS_WIDTH,
+                rect.h - 12.0,
+            );
+            draw_rectangle(bar.x, bar.y, bar.w, bar.h, DARKGRAY);
+            let done = loaded as f32 / total.max(1) as f32;
+            draw_rectangle(bar.x, bar.y, bar.w * done, bar.h, SKYBLUE);
+        }
+
+        overlay_text.draw(&sections.join("  |  "), 8.0, rect.y + 16.0, 1.2, LIGHTGRAY);
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
@@ -0,0 +1,107 @@
+use crate::core::map::map::Map;
+use crate::core::map::tabs::MapTabs;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+use macroquad::window::screen_width;
+use std::path::Path;
+
+/// Strip across the top of the viewport with one button per open map.
+///
+/// Closing a tab with unsaved edits takes a second click on its `x?` button.
+pub struct TabBar {
+    position: Vec2,
+    height: f32,
+    /// Tab whose close button was clicked once while it had unsaved edits.
+    close_armed: Option<usize>,
+    pointer_over_ui: bool,
+}
+
+impl TabBar {
+    pub fn new() -> Self {
+        Self {
+            position: vec2(20.0, 44.0),
+            height: 32.0,
+            close_armed: None,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the tabs, reporting the tab clicked, closed or added this frame.
+    pub fn draw(&mut self, tabs: &MapTabs) -> TabBarActions {
+        let mut actions = TabBarActions::default();
+        let size = vec2((screen_width() - self.position.x - 20.0).max(200.0), self.height);
+        let rect = Rect::new(self.position.x, self.position.y, size.x, size.y);
+
+        root_ui().window(hash!("tab_bar_window"), self.position, size, |ui| {
+            for (index, map) in tabs.maps().iter().enumerate() {
+                if index > 0 {
+                    ui.same_line(0.0);
+                }
+                let label = if index == tabs.active_index() {
+                    format!("> {}", tab_label(map))
+                } else {
+                    tab_label(map)
+                };
+                if ui.button(None, label.as_str()) {
+                    actions.selected = Some(index);
+                }
+                if tabs.maps().len() > 1 {
+                    ui.same_line(0.0);
+                    let close_label = if self.close_armed == Some(index) {
+                        "x?"
+                    } else {
+                        "x"
+                    };
+                    if ui.button(None, close_label) {
+                        if map.is_dirty() && self.close_armed != Some(index) {
+                            self.close_armed = Some(index);
+                        } else {
+                            actions.closed = Some(index);
+                        }
+                    }
+                }
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "+") {
+                actions.new_tab_requested = true;
+            }
+        });
+
+        if actions.selected.is_some() || actions.closed.is_some() || actions.new_tab_requested {
+            self.close_armed = None;
+        }
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the tab bar during the current frame.
+#[derive(Default)]
+pub struct TabBarActions {
+    pub selected: Option<usize>,
+    pub closed: Option<usize>,
+    /// Open an empty map in a new tab.
+    pub new_tab_requested: bool,
+}
+
+/// File name of the map, or "Untitled", followed by `*` while it has unsaved edits.
+pub fn tab_label(map: &Map) -> String {
+    let name = map
+        .file_path()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| tr("tabs.untitled").to_string());
+    let dirty = if map.is_dirty() {
+        "*"
+    } else {
+        ""
+    };
+    format!("{name}{dirty}")
+}
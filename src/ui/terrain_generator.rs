@@ -0,0 +1,159 @@
+use crate::core::assets::TileSprite;
+use crate::core::map::map::Map;
+use crate::core::tools::terrain_gen::TerrainGenerator;
+use crate::ui::generate_menu::GeneratorAction;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::rand;
+use macroquad::ui::{hash, root_ui, widgets};
+use macroquad::window::screen_width;
+
+/// Dialog filling a tile layer with noise terrain, previewed on the map while it is open.
+pub struct TerrainGeneratorDialog {
+    open: bool,
+    generator: TerrainGenerator,
+    seed_input: String,
+    layer: usize,
+    /// Generator the map preview was last computed from.
+    previewed: Option<TerrainGenerator>,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl TerrainGeneratorDialog {
+    pub fn new() -> Self {
+        let generator = TerrainGenerator::default();
+        Self {
+            open: false,
+            seed_input: generator.seed.to_string(),
+            generator,
+            layer: 0,
+            previewed: None,
+            size: vec2(360.0, 380.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Opens the dialog targeting `active_layer`; the band settings of the last use are kept.
+    pub fn open(&mut self, active_layer: usize) {
+        self.layer = active_layer;
+        self.previewed = None;
+        self.open = true;
+    }
+
+    /// Closes the dialog without generating, e.g. when another generator is opened.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Draws the dialog; `selected` is the palette tile offered for each band.
+    pub fn draw(&mut self, map: &Map, selected: Option<&TileSprite>) -> Option<GeneratorAction> {
+        if !self.open {
+            self.pointer_over_ui = false;
+            return None;
+        }
+
+        let (mut generate, mut close, mut reroll) = (false, false, false);
+        let position = vec2(((screen_width() - self.size.x) / 2.0).max(0.0), 100.0);
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+        let layer_names: Vec<&str> = map
+            .layers()
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect();
+        self.layer = self
+            .layer
+            .min(layer_names.len().saturating_sub(1));
+        let mut octaves = self.generator.octaves as f32;
+        let generator = &mut self.generator;
+        let (seed_input, layer) = (&mut self.seed_input, &mut self.layer);
+
+        root_ui().window(hash!("terrain_generator_window"), position, self.size, |ui| {
+            ui.label(None, tr("terrain.title"));
+            ui.separator();
+            ui.input_text(hash!("terrain_seed"), tr("generator.seed"), seed_input);
+            ui.same_line(0.0);
+            reroll = ui.button(None, tr("generator.random_seed"));
+            ui.slider(
+                hash!("terrain_scale"),
+                tr("terrain.scale"),
+                2.0..64.0,
+                &mut generator.scale,
+            );
+            ui.slider(
+                hash!("terrain_octaves"),
+                tr("terrain.octaves"),
+                1.0..TerrainGenerator::MAX_OCTAVES as f32,
+                &mut octaves,
+            );
+            ui.combo_box(hash!("terrain_layer"), tr("generator.layer"), &layer_names, layer);
+
+            for (index, band) in generator.bands.iter_mut().enumerate() {
+                ui.separator();
+                ui.label(None, &band.name);
+                ui.slider(
+                    hash!("terrain_threshold", index),
+                    tr("terrain.threshold"),
+                    0.0..1.0,
+                    &mut band.threshold,
+                );
+                widgets::InputText::new(hash!("terrain_tile", index))
+                    .label(tr("generator.tile"))
+                    .size(vec2(200.0, 19.0))
+                    .ratio(0.7)
+                    .ui(ui, &mut band.tile_id);
+                if let Some(sprite) = selected {
+                    ui.same_line(0.0);
+                    if ui.button(None, tr("generator.use_selected")) {
+                        band.tile_id = sprite.id.clone();
+                    }
+                }
+            }
+            if selected.is_none() {
+                ui.label(None, tr("common.select_palette_tile"));
+            }
+
+            ui.separator();
+            generate = ui.button(None, tr("generator.generate"));
+            ui.same_line(0.0);
+            close = ui.button(None, tr("common.cancel"));
+        });
+
+        if reroll {
+            self.generator.seed = rand::rand();
+            self.seed_input = self.generator.seed.to_string();
+        } else if let Ok(seed) = self.seed_input.trim().parse() {
+            self.generator.seed = seed;
+        }
+        self.generator.octaves = (octaves.round() as u32).clamp(1, TerrainGenerator::MAX_OCTAVES);
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+
+        let (width, height) = map.dimensions();
+        if generate {
+            self.open = false;
+            return Some(GeneratorAction::Generate {
+                layer: self.layer,
+                tiles: self.generator.generate(width, height),
+            });
+        }
+        if close {
+            self.open = false;
+            return Some(GeneratorAction::Close);
+        }
+        if self.previewed.as_ref() == Some(&self.generator) {
+            return None;
+        }
+        self.previewed = Some(self.generator.clone());
+        Some(GeneratorAction::Preview(self.generator.generate(width, height)))
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
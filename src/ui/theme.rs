@@ -0,0 +1,204 @@
+use crate::core::config::AppConfig;
+use crate::core::config::theme::UiTheme;
+use macroquad::color::Color;
+use macroquad::math::RectOffset;
+use macroquad::texture::Image;
+use macroquad::ui::{Skin, Ui, root_ui};
+use std::fs;
+
+/// Macroquad UI skin built from the configured theme, scale and font.
+pub struct UiSkin {
+    /// Whether a skin of ours is on the skin stack, to be replaced by the next one.
+    pushed: bool,
+}
+
+/// Colors of one [`UiTheme`].
+struct ThemeColors {
+    window: Color,
+    border: Color,
+    text: Color,
+    text_inactive: Color,
+    button: Color,
+    button_hovered: Color,
+    button_clicked: Color,
+    field: Color,
+    selected: Color,
+    selected_hovered: Color,
+    scrollbar: Color,
+    group: Color,
+    group_hovered: Color,
+}
+
+impl ThemeColors {
+    fn of(theme: UiTheme) -> Self {
+        match theme {
+            UiTheme::Light => Self {
+                window: Color::from_rgba(238, 238, 238, 255),
+                border: Color::from_rgba(68, 68, 68, 255),
+                text: Color::from_rgba(0, 0, 0, 255),
+                text_inactive: Color::from_rgba(0, 0, 0, 128),
+                button: Color::from_rgba(204, 204, 204, 235),
+                button_hovered: Color::from_rgba(170, 170, 170, 235),
+                button_clicked: Color::from_rgba(187, 187, 187, 255),
+                field: Color::from_rgba(200, 200, 200, 255),
+                selected: Color::from_rgba(128, 128, 128, 255),
+                selected_hovered: Color::from_rgba(140, 140, 140, 255),
+                scrollbar: Color::from_rgba(68, 68, 68, 255),
+                group: Color::from_rgba(34, 34, 34, 68),
+                group_hovered: Color::from_rgba(34, 153, 34, 68),
+            },
+            UiTheme::Dark => Self {
+                window: Color::from_rgba(40, 42, 48, 250),
+                border: Color::from_rgba(20, 20, 24, 255),
+                text: Color::from_rgba(225, 225, 230, 255),
+                text_inactive: Color::from_rgba(225, 225, 230, 110),
+                button: Color::from_rgba(70, 74, 84, 240),
+                button_hovered: Color::from_rgba(92, 98, 112, 240),
+                button_clicked: Color::from_rgba(58, 62, 70, 255),
+                field: Color::from_rgba(60, 63, 72, 255),
+                selected: Color::from_rgba(88, 128, 190, 255),
+                selected_hovered: Color::from_rgba(104, 144, 206, 255),
+                scrollbar: Color::from_rgba(28, 28, 32, 255),
+                group: Color::from_rgba(0, 0, 0, 60),
+                group_hovered: Color::from_rgba(88, 128, 190, 60),
+            },
+        }
+    }
+}
+
+impl UiSkin {
+    pub fn new() -> Self {
+        Self { pushed: false }
+    }
+
+    /// Replaces the current skin with one built from the theme, scale and font of `config`.
+    pub fn apply(&mut self, config: &AppConfig) {
+        let font = config
+            .ui_font_path
+            .as_ref()
+            .and_then(|path| match fs::read(path) {
+                Ok(bytes) => Some(bytes),
+                Err(err) => {
+                    eprintln!("[theme] Failed to read font {:?}: {err}", path);
+                    None
+                }
+            });
+        let mut ui = root_ui();
+        let font = font.filter(|bytes| match ui.style_builder().font(bytes) {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!("[theme] Failed to load font: {err}");
+                false
+            }
+        });
+        let skin = build_skin(&ui, config.ui_theme, config.ui_scale(), font.as_deref());
+        if self.pushed {
+            ui.pop_skin();
+        }
+        ui.push_skin(&skin);
+        self.pushed = true;
+    }
+}
+
+fn build_skin(ui: &Ui, theme: UiTheme, scale: f32, font: Option<&[u8]>) -> Skin {
+    let colors = ThemeColors::of(theme);
+    let font_size = (16.0 * scale).round() as u16;
+    let margin = 2.0 * scale;
+    let style = || {
+        let builder = ui.style_builder().font_size(font_size);
+        match font {
+            // Checked to load in `UiSkin::apply`.
+            Some(bytes) => builder
+                .font(bytes)
+                .unwrap_or_else(|_| ui.style_builder().font_size(font_size)),
+            None => builder,
+        }
+    };
+    let padded = || style().margin(RectOffset::new(margin, margin, margin, margin));
+
+    let (window, border) = (color_bytes(colors.window), color_bytes(colors.border));
+    let window_background: Vec<u8> = (0..9)
+        .flat_map(|pixel| {
+            if pixel == 4 {
+                window
+            } else {
+                border
+            }
+        })
+        .collect();
+
+    Skin {
+        label_style: padded()
+            .text_color(colors.text)
+            .color_inactive(colors.text_inactive)
+            .build(),
+        button_style: padded()
+            .text_color(colors.text)
+            .text_color_hovered(colors.text)
+            .text_color_clicked(colors.text)
+            .color(colors.button)
+            .color_hovered(colors.button_hovered)
+            .color_clicked(colors.button_clicked)
+            .color_selected(colors.selected)
+            .color_selected_hovered(colors.selected_hovered)
+            .build(),
+        tabbar_style: padded()
+            .text_color(colors.text)
+            .color(colors.button)
+            .color_hovered(colors.button_hovered)
+            .color_clicked(colors.button_clicked)
+            .color_selected(colors.selected)
+            .color_selected_hovered(colors.selected_hovered)
+            .build(),
+        window_style: style()
+            .background_margin(RectOffset::new(1.0, 1.0, 1.0, 1.0))
+            .background(Image {
+                bytes: window_background,
+                width: 3,
+                height: 3,
+            })
+            .color_inactive(Color { a: 0.5, ..colors.window })
+            .text_color(colors.text)
+            .build(),
+        window_titlebar_style: style()
+            .text_color(colors.text)
+            .color(colors.border)
+            .build(),
+        editbox_style: style()
+            .text_color(colors.text)
+            .color(colors.field)
+            .color_selected(colors.selected)
+            .build(),
+        scrollbar_style: style().color(colors.scrollbar).build(),
+        scrollbar_handle_style: style()
+            .color(colors.button)
+            .color_inactive(Color { a: 0.5, ..colors.button })
+            .color_hovered(colors.button_hovered)
+            .color_clicked(colors.button_clicked)
+            .build(),
+        checkbox_style: style()
+            .text_color(colors.text)
+            .color(colors.field)
+            .color_hovered(colors.button_hovered)
+            .color_clicked(colors.button_clicked)
+            .color_selected(colors.selected)
+            .color_selected_hovered(colors.selected_hovered)
+            .build(),
+        group_style: style()
+            .color(colors.group)
+            .color_hovered(colors.group_hovered)
+            .color_selected(colors.selected)
+            .color_selected_hovered(colors.group_hovered)
+            .build(),
+        margin,
+        title_height: 14.0 * scale,
+        scroll_width: 10.0 * scale,
+        // The combo box arrow is an image bundled with macroquad's default skin.
+        ..ui.default_skin()
+    }
+}
+
+fn color_bytes(color: Color) -> [u8; 4] {
+    let [r, g, b, a]: [u8; 4] = color.into();
+    [r, g, b, a]
+}
@@ -0,0 +1,229 @@
+use crate::core::map::map::Map;
+use crate::core::tools::noise::NoiseStamp;
+use crate::core::tools::{ApplyTo, FillScope, PaintMode};
+use crate::ui::i18n::{tr, trf};
+use crate::ui::tools::tool_name;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{Ui, hash, root_ui, widgets};
+
+/// Context-sensitive window below the palette showing the settings of the active tool.
+pub struct ToolOptionsPanel {
+    mode: PaintMode,
+    width: f32,
+    pointer_over_ui: bool,
+}
+
+impl ToolOptionsPanel {
+    /// Height of the title row and window padding.
+    const BASE_HEIGHT: f32 = 40.0;
+    /// Height added for every option row.
+    const ROW_HEIGHT: f32 = 24.0;
+
+    pub fn new() -> Self {
+        Self {
+            mode: PaintMode::default(),
+            width: 280.0,
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Window height needed by the options of the active tool.
+    pub fn height(&self) -> f32 {
+        let rows = match self.mode {
+            PaintMode::Paint
+            | PaintMode::Rectangle
+            | PaintMode::Line
+            | PaintMode::Terrain
+            | PaintMode::Collision
+            | PaintMode::Object => 1,
+            PaintMode::Sequence
+            | PaintMode::Random
+            | PaintMode::Select
+            | PaintMode::NoiseStamp
+            | PaintMode::Marquee
+            | PaintMode::Erase => 2,
+            PaintMode::MagicWand => 3,
+            PaintMode::Fill => 4,
+        };
+        Self::BASE_HEIGHT + rows as f32 * Self::ROW_HEIGHT
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Draws the options of the map's active tool with the window's top-left corner at
+    /// `position`.
+    pub fn draw(&mut self, map: &mut Map, position: Vec2) -> ToolOptionsActions {
+        let mut actions = ToolOptionsActions::default();
+        self.mode = map.paint_mode();
+        let size = vec2(self.width, self.height());
+        let rect = Rect::new(position.x, position.y, size.x, size.y);
+        let mut fill_scope = map.fill_scope();
+        let mut wand_scope = map.wand_scope();
+        let mut apply_to = map.apply_to();
+
+        widgets::Window::new(hash!("tool_options_window"), position, size)
+            .titlebar(false)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(None, &trf("tool_options.title", &[("tool", &tool_name(self.mode))]));
+                match self.mode {
+                    PaintMode::Paint => {
+                        ui.label(None, tr("tool_options.none"));
+                    }
+                    PaintMode::Erase => apply_to_checkboxes(ui, &mut apply_to),
+                    PaintMode::Fill => {
+                        let mut contiguous = fill_scope == FillScope::Contiguous;
+                        let mut global = fill_scope == FillScope::Global;
+                        ui.checkbox(
+                            hash!("fill_contiguous"),
+                            tr("tool_options.contiguous"),
+                            &mut contiguous,
+                        );
+                        ui.checkbox(hash!("fill_global"), tr("tool_options.global"), &mut global);
+                        fill_scope = match (contiguous, global) {
+                            (true, true) if fill_scope == FillScope::Contiguous => {
+                                FillScope::Global
+                            }
+                            (true, _) => FillScope::Contiguous,
+                            (false, true) => FillScope::Global,
+                            (false, false) => fill_scope,
+                        };
+                        apply_to_checkboxes(ui, &mut apply_to);
+                    }
+                    PaintMode::Sequence => {
+                        if ui.button(None, tr("tool_options.edit_sequence")) {
+                            actions.sequence_editor_requested = true;
+                        }
+                        if ui.button(None, tr("tool_options.restart_sequence")) {
+                            map.tile_sequence_mut().reset();
+                        }
+                    }
+                    PaintMode::Random => {
+                        let brush = map.random_brush_mut();
+                        ui.label(
+                            None,
+                            &trf(
+                                "tool_options.random_set",
+                                &[
+                                    ("count", &brush.tiles.len()),
+                                    ("weight", &brush.total_weight()),
+                                ],
+                            ),
+                        );
+                        if ui.button(None, tr("tool_options.edit_random_set")) {
+                            actions.random_brush_editor_requested = true;
+                        }
+                    }
+                    PaintMode::Select => {
+                        ui.label(None, tr("tool_options.select_hint"));
+                        if ui.button(None, tr("tool_options.erase_selection")) {
+                            map.erase_selection();
+                        }
+                        ui.same_line(0.0);
+                        if ui.button(None, tr("tool_options.select_same")) {
+                            map.select_same();
+                        }
+                    }
+                    PaintMode::Marquee => {
+                        ui.label(None, tr("tool_options.marquee_hint"));
+                        if ui.button(None, tr("tool_options.copy_selection")) {
+                            map.copy_selection();
+                        }
+                    }
+                    PaintMode::MagicWand => {
+                        ui.label(None, tr("tool_options.magic_wand_hint"));
+                        let mut contiguous = wand_scope == FillScope::Contiguous;
+                        ui.checkbox(
+                            hash!("wand_contiguous"),
+                            tr("tool_options.contiguous"),
+                            &mut contiguous,
+                        );
+                        wand_scope = if contiguous {
+                            FillScope::Contiguous
+                        } else {
+                            FillScope::Global
+                        };
+                        if ui.button(None, tr("tool_options.select_same")) {
+                            map.select_same();
+                        }
+                        ui.same_line(0.0);
+                        if ui.button(None, tr("tool_options.copy_selection")) {
+                            map.copy_selection();
+                        }
+                    }
+                    PaintMode::Collision => {
+                        ui.label(None, tr("tool_options.collision_hint"));
+                    }
+                    PaintMode::Terrain => {
+                        ui.label(None, tr("tool_options.terrain_hint"));
+                    }
+                    PaintMode::Object => {
+                        ui.label(None, tr("tool_options.object_hint"));
+                    }
+                    PaintMode::Rectangle | PaintMode::Line => {
+                        ui.label(None, tr("tool_options.shape_hint"));
+                    }
+                    PaintMode::NoiseStamp => {
+                        ui.label(
+                            None,
+                            &trf(
+                                "tool_options.rules_loaded",
+                                &[("count", &map.noise_stamp().rules.len())],
+                            ),
+                        );
+                        if ui.button(
+                            None,
+                            trf("tool_options.reload", &[("file", &NoiseStamp::RULES_FILE)])
+                                .as_str(),
+                        ) {
+                            actions.noise_rules_reload_requested = true;
+                        }
+                    }
+                }
+            });
+
+        map.set_fill_scope(fill_scope);
+        map.set_wand_scope(wand_scope);
+        map.set_apply_to(apply_to);
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Checkboxes picking the layers the erase and fill tools edit: the active one, every
+/// unlocked one, or every one with locked layers included.
+fn apply_to_checkboxes(ui: &mut Ui, apply_to: &mut ApplyTo) {
+    let mut all_layers = *apply_to != ApplyTo::ActiveLayer;
+    let mut include_locked = *apply_to == ApplyTo::AllLayers;
+    ui.checkbox(hash!("apply_to_all_layers"), tr("tool_options.all_layers"), &mut all_layers);
+    // Only meaningful once every layer is edited, so it is hidden for the active layer.
+    if all_layers {
+        ui.checkbox(
+            hash!("apply_to_locked"),
+            tr("tool_options.include_locked"),
+            &mut include_locked,
+        );
+    }
+    *apply_to = match (all_layers, include_locked) {
+        (false, _) => ApplyTo::ActiveLayer,
+        (true, false) => ApplyTo::AllUnlockedLayers,
+        (true, true) => ApplyTo::AllLayers,
+    };
+}
+
+/// Requests raised by the tool options panel during the current frame.
+#[derive(Default)]
+pub struct ToolOptionsActions {
+    pub sequence_editor_requested: bool,
+    pub random_brush_editor_requested: bool,
+    pub noise_rules_reload_requested: bool,
+}
@@ -0,0 +1,214 @@
+use crate::core::camera::PlaybackDirection;
+use crate::core::map::map::Map;
+use crate::core::tools::{PaintMode, Selection};
+use crate::ui::i18n::{tr, trf};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+
+/// Small window selecting the active tool and the brush tint.
+pub struct ToolsPanel {
+    fill_region_input: String,
+    /// Layer "Fill Empty Cells" paints, following the active layer until another is picked.
+    fill_layer: usize,
+    /// Active layer `fill_layer` was last synced with.
+    fill_layer_synced: Option<usize>,
+    /// Set when the region text could not be parsed.
+    fill_region_invalid: bool,
+    playback_speed: f32,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+impl ToolsPanel {
+    pub fn new() -> Self {
+        Self {
+            fill_region_input: String::new(),
+            fill_layer: 0,
+            fill_layer_synced: None,
+            fill_region_invalid: false,
+            playback_speed: 200.0,
+            window_position: vec2(310.0, 80.0),
+            size: vec2(220.0, 505.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    /// Draws the panel and writes the chosen tool and tint back to the map.
+    pub fn draw(&mut self, map: &mut Map) -> ToolsActions {
+        let mut actions = ToolsActions::default();
+        let labels: Vec<&str> = PaintMode::ALL
+            .iter()
+            .map(|mode| tool_name(*mode))
+            .collect();
+        let mut mode_index = PaintMode::ALL
+            .iter()
+            .position(|mode| *mode == map.paint_mode())
+            .unwrap_or(0);
+        let mut tint: [f32; 4] = map.brush_tint().into();
+        let mut z_priority = map.brush_z_priority() as f32;
+        let playback_labels: Vec<&str> = PlaybackDirection::ALL
+            .iter()
+            .map(|direction| direction.name())
+            .collect();
+        let mut playback_index = PlaybackDirection::ALL
+            .iter()
+            .position(|direction| *direction == map.playback_direction())
+            .unwrap_or(0);
+        let mut toggle_playback = false;
+        if self.fill_layer_synced != Some(map.active_layer()) {
+            self.fill_layer = map.active_layer();
+            self.fill_layer_synced = Some(map.active_layer());
+        }
+        let layer_names: Vec<String> = map
+            .layers()
+            .iter()
+            .map(|layer| layer.name.clone())
+            .collect();
+        let layer_labels: Vec<&str> = layer_names
+            .iter()
+            .map(String::as_str)
+            .collect();
+        self.fill_layer = self
+            .fill_layer
+            .min(layer_labels.len().saturating_sub(1));
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("tools_window"), position, self.size, |ui| {
+            ui.label(None, tr("tools.title"));
+            ui.combo_box(hash!("tools_mode"), tr("tools.tool"), &labels, &mut mode_index);
+            ui.separator();
+
+            ui.label(None, tr("tools.brush_tint"));
+            ui.slider(hash!("tools_tint_r"), "R", 0.0..1.0, &mut tint[0]);
+            ui.slider(hash!("tools_tint_g"), "G", 0.0..1.0, &mut tint[1]);
+            ui.slider(hash!("tools_tint_b"), "B", 0.0..1.0, &mut tint[2]);
+            ui.slider(hash!("tools_tint_a"), "A", 0.0..1.0, &mut tint[3]);
+            if ui.button(None, tr("tools.reset_tint")) {
+                tint = [1.0; 4];
+            }
+            ui.slider(
+                hash!("tools_z_priority"),
+                tr("common.z_priority"),
+                -10.0..10.0,
+                &mut z_priority,
+            );
+            ui.label(
+                None,
+                &trf("tools.orientation", &[("orientation", &map.brush_transform().label())]),
+            );
+
+            ui.separator();
+            ui.label(None, tr("tools.fill_region"));
+            ui.input_text(hash!("tools_fill_region"), "", &mut self.fill_region_input);
+            ui.combo_box(
+                hash!("tools_fill_layer"),
+                tr("tools.fill_layer"),
+                &layer_labels,
+                &mut self.fill_layer,
+            );
+            if self.fill_region_invalid {
+                ui.label(None, tr("tools.invalid_region"));
+            }
+            if ui.button(None, tr("tools.fill_empty")) {
+                let region = parse_region(&self.fill_region_input);
+                self.fill_region_invalid = region.is_none();
+                actions.fill_empty_requested =
+                    region.map(|region| FillEmptyRequest { layer: self.fill_layer, region });
+            }
+
+            ui.separator();
+            ui.label(None, tr("tools.playback"));
+            ui.combo_box(
+                hash!("tools_playback_path"),
+                tr("tools.playback_path"),
+                &playback_labels,
+                &mut playback_index,
+            );
+            ui.slider(
+                hash!("tools_playback_speed"),
+                tr("tools.playback_speed"),
+                20.0..1000.0,
+                &mut self.playback_speed,
+            );
+            let label = if map.is_playback_active() {
+                tr("tools.stop_playback")
+            } else {
+                tr("tools.start_playback")
+            };
+            if ui.button(None, label) {
+                toggle_playback = true;
+            }
+        });
+
+        map.set_paint_mode(PaintMode::ALL[mode_index]);
+        map.set_brush_tint(tint.into());
+        map.set_brush_z_priority(z_priority.round() as i32);
+        map.set_playback_direction(PlaybackDirection::ALL[playback_index]);
+        if toggle_playback {
+            if map.is_playback_active() {
+                map.stop_playback();
+            } else {
+                map.start_playback(self.playback_speed);
+            }
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}
+
+/// Requests raised by the tools panel during the current frame.
+#[derive(Default)]
+pub struct ToolsActions {
+    pub fill_empty_requested: Option<FillEmptyRequest>,
+}
+
+/// Empty cells to fill, as entered in the tools panel.
+pub struct FillEmptyRequest {
+    pub layer: usize,
+    /// Rectangle typed in the region field; `None` fills the selection or the whole map.
+    pub region: Option<Selection>,
+}
+
+/// Parses the `x y w h` region field: `Some(None)` when it is blank, `None` when it is not
+/// four whole numbers.
+fn parse_region(input: &str) -> Option<Option<Selection>> {
+    let values: Vec<usize> = input
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match values[..] {
+        [] => Some(None),
+        [x, y, width, height] => Some(Some(Selection::new(x, y, width, height))),
+        _ => None,
+    }
+}
+
+/// Translated display name of `mode`; [`PaintMode::name`] stays the persisted identifier.
+pub fn tool_name(mode: PaintMode) -> &'static str {
+    match mode {
+        PaintMode::Paint => tr("tool.paint"),
+        PaintMode::Erase => tr("tool.erase"),
+        PaintMode::Fill => tr("tool.fill"),
+        PaintMode::Sequence => tr("tool.sequence"),
+        PaintMode::Random => tr("tool.random"),
+        PaintMode::Select => tr("tool.select"),
+        PaintMode::NoiseStamp => tr("tool.noise_stamp"),
+        PaintMode::Rectangle => tr("tool.rectangle"),
+        PaintMode::Line => tr("tool.line"),
+        PaintMode::Marquee => tr("tool.marquee"),
+        PaintMode::MagicWand => tr("tool.magic_wand"),
+        PaintMode::Terrain => tr("tool.terrain"),
+        PaintMode::Collision => tr("tool.collision"),
+        PaintMode::Object => tr("tool.object"),
+    }
+}
@@ -0,0 +1,94 @@
+use crate::core::map::grid::GridStyle;
+use crate::ui::i18n::tr;
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2, vec2};
+use macroquad::ui::{hash, root_ui};
+
+/// Window with the grid display options: visibility, color, major lines and coordinates.
+/// It also opens the reference image panel.
+pub struct ViewSettingsPanel {
+    open: bool,
+    window_position: Vec2,
+    size: Vec2,
+    pointer_over_ui: bool,
+}
+
+/// Requests raised by the view settings panel during the current frame.
+#[derive(Default)]
+pub struct ViewSettingsActions {
+    /// Edited grid style, when it changed this frame.
+    pub grid_style: Option<GridStyle>,
+    pub reference_image_requested: bool,
+}
+
+impl ViewSettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            window_position: vec2(540.0, 80.0),
+            size: vec2(300.0, 230.0),
+            pointer_over_ui: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Draws the panel for `style`; the returned actions carry the edited style when it
+    /// changed this frame.
+    pub fn draw(&mut self, style: GridStyle) -> ViewSettingsActions {
+        let mut actions = ViewSettingsActions::default();
+        if !self.open {
+            self.pointer_over_ui = false;
+            return actions;
+        }
+
+        let mut draft = style;
+        let mut major_every = draft.major_every as f32;
+        let mut close = false;
+        let position = self.window_position;
+        let rect = Rect::new(position.x, position.y, self.size.x, self.size.y);
+
+        root_ui().window(hash!("view_settings_window"), position, self.size, |ui| {
+            ui.label(None, tr("view_settings.title"));
+            ui.separator();
+            ui.checkbox(hash!("grid_visible"), tr("view_settings.show_grid"), &mut draft.visible);
+            ui.slider(hash!("grid_color_r"), "R", 0.0..1.0, &mut draft.color[0]);
+            ui.slider(hash!("grid_color_g"), "G", 0.0..1.0, &mut draft.color[1]);
+            ui.slider(hash!("grid_color_b"), "B", 0.0..1.0, &mut draft.color[2]);
+            ui.slider(hash!("grid_opacity"), tr("common.opacity"), 0.0..1.0, &mut draft.color[3]);
+            ui.slider(
+                hash!("grid_major_every"),
+                tr("view_settings.major_every"),
+                0.0..32.0,
+                &mut major_every,
+            );
+            ui.checkbox(
+                hash!("grid_coordinates"),
+                tr("view_settings.tile_coordinates"),
+                &mut draft.show_coordinates,
+            );
+            ui.separator();
+            actions.reference_image_requested =
+                ui.button(None, tr("view_settings.reference_image"));
+            ui.same_line(0.0);
+            if ui.button(None, tr("common.close")) {
+                close = true;
+            }
+        });
+        draft.major_every = major_every.round() as u32;
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.pointer_over_ui = rect.contains(vec2(mouse_x, mouse_y));
+        if close {
+            self.open = false;
+        }
+        actions.grid_style = (draft != style).then_some(draft);
+        actions
+    }
+
+    pub fn pointer_over_ui(&self) -> bool {
+        self.pointer_over_ui
+    }
+}